@@ -11,10 +11,10 @@ use clap::Subcommand;
 use serde::Serialize;
 use t_linter_core::{
     DiagnosticEdit, DiagnosticEditRange, FormatError, FormatOptions as CoreFormatOptions,
-    LanguageDetection, LintDiagnostic, LintFileResult, LintRunSummary, LintSeverity,
-    TemplateStringParser, apply_diagnostic_edits, apply_template_edits, file_read_error,
-    format_document_in_file_with_options, format_document_with_options, lint_source,
-    lint_source_with_config, load_project_config_for_path,
+    LanguageDetection, LanguagePlugins, LintDiagnostic, LintFileResult, LintRunSummary,
+    LintSeverity, TemplateStringParser, apply_diagnostic_edits, apply_template_edits,
+    file_read_error, format_document_in_file_with_options, format_document_with_options,
+    lint_source, lint_source_with_config, load_project_config_for_path,
 };
 use tempfile::NamedTempFile;
 
@@ -748,6 +748,10 @@ fn resolve_format_options(
     let config = load_project_config_for_path(path)?;
     Ok(CoreFormatOptions {
         line_length: cli_line_length.or(config.line_length).unwrap_or(80).max(1),
+        css: config.format.css,
+        javascript: config.format.javascript,
+        sql: config.format.sql,
+        plugins: LanguagePlugins::from_config(&config),
     })
 }
 
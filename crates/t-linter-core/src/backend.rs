@@ -8,22 +8,36 @@ use tstring_thtml as backend_thtml;
 use tstring_toml as backend_toml;
 use tstring_yaml as backend_yaml;
 
+use crate::formatting::FormatOptions;
+use crate::project_config::SqlDialect;
+use crate::xml::syntax::XmlDialect;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum TemplateBackend {
     Html,
     Thtml,
     Tdom,
+    Css,
+    Javascript,
+    Graphql,
     Json,
     Sql,
     Yaml,
     Toml,
+    Xml,
+    Svg,
 }
 
 impl TemplateBackend {
     pub(crate) fn for_language(language: &str) -> Option<Self> {
         let language = language.trim();
         match language.len() {
+            2 if language.eq_ignore_ascii_case("js") => Some(Self::Javascript),
+            3 if language.eq_ignore_ascii_case("css") => Some(Self::Css),
+            3 if language.eq_ignore_ascii_case("gql") => Some(Self::Graphql),
             3 if language.eq_ignore_ascii_case("sql") => Some(Self::Sql),
+            3 if language.eq_ignore_ascii_case("svg") => Some(Self::Svg),
+            3 if language.eq_ignore_ascii_case("xml") => Some(Self::Xml),
             3 if language.eq_ignore_ascii_case("yml") => Some(Self::Yaml),
             4 if language.eq_ignore_ascii_case("html") => Some(Self::Html),
             4 if language.eq_ignore_ascii_case("tdom") => Some(Self::Tdom),
@@ -31,6 +45,8 @@ impl TemplateBackend {
             4 if language.eq_ignore_ascii_case("yaml") => Some(Self::Yaml),
             4 if language.eq_ignore_ascii_case("toml") => Some(Self::Toml),
             5 if language.eq_ignore_ascii_case("thtml") => Some(Self::Thtml),
+            7 if language.eq_ignore_ascii_case("graphql") => Some(Self::Graphql),
+            10 if language.eq_ignore_ascii_case("javascript") => Some(Self::Javascript),
             _ => None,
         }
     }
@@ -47,10 +63,18 @@ impl TemplateBackend {
             (Self::Tdom, Some(profile)) if profile.eq_ignore_ascii_case("svg") => {
                 backend_tdom::check_template(input)
             }
+            (Self::Css, None) => crate::css::format::check_template(input),
+            (Self::Javascript, None) => crate::javascript::format::check_template(input),
+            (Self::Graphql, None) => crate::graphql::syntax::check_template(input),
             (Self::Json, None) => backend_json::check_template(input),
-            (Self::Sql, None) => check_sql_template(input),
+            (Self::Sql, None) => check_sql_template(input, SqlDialect::Generic),
+            (Self::Sql, Some(profile)) => {
+                check_sql_template(input, parse_profile::<SqlDialect>(profile)?)
+            }
             (Self::Yaml, None) => backend_yaml::check_template(input),
             (Self::Toml, None) => backend_toml::check_template(input),
+            (Self::Xml, None) => crate::xml::syntax::check_template(input, XmlDialect::Xml),
+            (Self::Svg, None) => crate::xml::syntax::check_template(input, XmlDialect::Svg),
             (Self::Json, Some(profile)) => backend_json::check_template_with_profile(
                 input,
                 parse_profile::<backend_json::JsonProfile>(profile)?,
@@ -63,9 +87,17 @@ impl TemplateBackend {
                 input,
                 parse_profile::<backend_toml::TomlProfile>(profile)?,
             ),
-            (backend @ (Self::Html | Self::Thtml | Self::Tdom | Self::Sql), Some(profile)) => {
-                Err(unsupported_profile_error(backend, profile))
-            }
+            (
+                backend @ (Self::Html
+                | Self::Thtml
+                | Self::Tdom
+                | Self::Css
+                | Self::Javascript
+                | Self::Graphql
+                | Self::Xml
+                | Self::Svg),
+                Some(profile),
+            ) => Err(unsupported_profile_error(backend, profile)),
         }
     }
 
@@ -96,11 +128,34 @@ impl TemplateBackend {
                     parse_profile::<backend_toml::TomlProfile>(profile)?,
                 )
             }
-            (Self::Html | Self::Thtml | Self::Tdom | Self::Sql, None) => Ok(Vec::new()),
+            (
+                Self::Html
+                | Self::Thtml
+                | Self::Tdom
+                | Self::Css
+                | Self::Javascript
+                | Self::Graphql
+                | Self::Sql
+                | Self::Xml
+                | Self::Svg,
+                None,
+            ) => Ok(Vec::new()),
             (Self::Tdom, Some(profile)) if profile.eq_ignore_ascii_case("svg") => Ok(Vec::new()),
-            (backend @ (Self::Html | Self::Thtml | Self::Tdom | Self::Sql), Some(profile)) => {
-                Err(unsupported_profile_error(backend, profile))
+            (Self::Sql, Some(profile)) => {
+                parse_profile::<SqlDialect>(profile)?;
+                Ok(Vec::new())
             }
+            (
+                backend @ (Self::Html
+                | Self::Thtml
+                | Self::Tdom
+                | Self::Css
+                | Self::Javascript
+                | Self::Graphql
+                | Self::Xml
+                | Self::Svg),
+                Some(profile),
+            ) => Err(unsupported_profile_error(backend, profile)),
         }
     }
 
@@ -108,8 +163,9 @@ impl TemplateBackend {
         self,
         input: &TemplateInput,
         profile: Option<&str>,
-        line_length: usize,
+        options: &FormatOptions,
     ) -> BackendResult<String> {
+        let line_length = options.line_length;
         match (self, profile) {
             (Self::Html, None) => backend_html::format_template_with_options(
                 input,
@@ -129,9 +185,22 @@ impl TemplateBackend {
                     &backend_tdom::FormatOptions { line_length },
                 )
             }
+            (Self::Css, None) => {
+                crate::css::format::format_template(input, &options.css, line_length)
+            }
+            (Self::Javascript, None) => {
+                crate::javascript::format::format_template(input, &options.javascript, line_length)
+            }
+            (Self::Graphql, None) => crate::graphql::format::format_template(input, line_length),
             (Self::Json, None) => backend_json::format_template(input),
             (Self::Yaml, None) => backend_yaml::format_template(input),
             (Self::Toml, None) => backend_toml::format_template(input),
+            (Self::Xml, None) => {
+                crate::xml::format::format_template(input, XmlDialect::Xml, line_length)
+            }
+            (Self::Svg, None) => {
+                crate::xml::format::format_template(input, XmlDialect::Svg, line_length)
+            }
             (Self::Json, Some(profile)) => backend_json::format_template_with_profile(
                 input,
                 parse_profile::<backend_json::JsonProfile>(profile)?,
@@ -144,12 +213,22 @@ impl TemplateBackend {
                 input,
                 parse_profile::<backend_toml::TomlProfile>(profile)?,
             ),
-            (Self::Sql, None) => Err(BackendError::semantic(
-                "Formatting is not supported for sql templates.",
-            )),
-            (backend @ (Self::Html | Self::Thtml | Self::Tdom | Self::Sql), Some(profile)) => {
-                Err(unsupported_profile_error(backend, profile))
+            (Self::Sql, None) => format_sql_template(input, options),
+            (Self::Sql, Some(profile)) => {
+                parse_profile::<SqlDialect>(profile)?;
+                format_sql_template(input, options)
             }
+            (
+                backend @ (Self::Html
+                | Self::Thtml
+                | Self::Tdom
+                | Self::Css
+                | Self::Javascript
+                | Self::Graphql
+                | Self::Xml
+                | Self::Svg),
+                Some(profile),
+            ) => Err(unsupported_profile_error(backend, profile)),
         }
     }
 }
@@ -168,10 +247,10 @@ fn unsupported_profile_error(backend: TemplateBackend, profile: &str) -> Backend
     ))
 }
 
-fn check_sql_template(input: &TemplateInput) -> BackendResult<()> {
+fn check_sql_template(input: &TemplateInput, dialect: SqlDialect) -> BackendResult<()> {
     #[cfg(feature = "sql")]
     {
-        let source = sql_template_source(input);
+        let source = crate::sql::dialect::normalize(&sql_template_source(input), dialect).content;
         let mut parser = tree_sitter::Parser::new();
         parser
             .set_language(&tree_sitter_sequel::LANGUAGE.into())
@@ -190,11 +269,25 @@ fn check_sql_template(input: &TemplateInput) -> BackendResult<()> {
     }
     #[cfg(not(feature = "sql"))]
     {
-        let _ = input;
+        let _ = (input, dialect);
         Ok(())
     }
 }
 
+fn format_sql_template(input: &TemplateInput, options: &FormatOptions) -> BackendResult<String> {
+    #[cfg(feature = "sql")]
+    {
+        crate::sql::format::format_template(input, &options.sql, options.line_length)
+    }
+    #[cfg(not(feature = "sql"))]
+    {
+        let _ = (input, options);
+        Err(BackendError::semantic(
+            "Formatting is not supported for sql templates.",
+        ))
+    }
+}
+
 #[cfg(feature = "sql")]
 fn sql_template_source(input: &TemplateInput) -> String {
     use tstring_syntax::TemplateSegment;
@@ -223,10 +316,15 @@ impl TemplateBackend {
             Self::Html => "html",
             Self::Thtml => "thtml",
             Self::Tdom => "tdom",
+            Self::Css => "css",
+            Self::Javascript => "javascript",
+            Self::Graphql => "graphql",
             Self::Json => "json",
             Self::Sql => "sql",
             Self::Yaml => "yaml",
             Self::Toml => "toml",
+            Self::Xml => "xml",
+            Self::Svg => "svg",
         }
     }
 }
@@ -357,4 +455,22 @@ mod tests {
             .check_template(&input, None)
             .expect("sql template parses");
     }
+
+    #[cfg(feature = "sql")]
+    #[test]
+    fn sql_backend_accepts_dialect_profiles() {
+        let input = TemplateInput::from_segments(vec![TemplateSegment::StaticText(
+            "INSERT OR REPLACE INTO users (id) VALUES (:id)".to_string(),
+        )]);
+
+        TemplateBackend::Sql
+            .check_template(&input, Some("sqlite"))
+            .expect("sqlite template parses");
+        assert!(TemplateBackend::Sql.check_template(&input, None).is_err());
+        assert!(
+            TemplateBackend::Sql
+                .check_template(&input, Some("oracle"))
+                .is_err()
+        );
+    }
 }
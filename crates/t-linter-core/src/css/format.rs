@@ -0,0 +1,886 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use tstring_format_doc::{Doc, RenderOptions, render};
+use tstring_syntax::{BackendError, BackendResult, TemplateInput};
+
+use super::properties;
+use crate::project_config::{CssFormatConfig, DeclarationOrder, QuoteStyle};
+use crate::template_text::{self, keep_block_breaks, template_source};
+
+/// Formats a `css` template: one declaration per line inside each block,
+/// normalized spacing around colons, commas, combinators and brackets, and
+/// consistent string quotes. Interpolations are kept byte-for-byte wherever
+/// they appear, and comments are kept verbatim. A single-line template made
+/// only of declarations, such as an inline `style` value, stays on one line.
+pub fn format_template(
+    input: &TemplateInput,
+    config: &CssFormatConfig,
+    line_length: usize,
+) -> BackendResult<String> {
+    let (source, atoms) = template_source(input, "css")?;
+    let tokens = tokenize(&source, &atoms)?;
+    if tokens.is_empty() {
+        return Ok(source);
+    }
+
+    let formatter = CssFormatter {
+        source: &source,
+        atoms: &atoms,
+        tokens,
+        config,
+    };
+    let items = formatter.items(0..formatter.tokens.len());
+    let inline = !source.contains('\n')
+        && items
+            .iter()
+            .all(|item| !matches!(item.kind, ItemKind::Block { .. }));
+    if inline {
+        return Ok(formatter.inline_items(&items));
+    }
+
+    let doc = formatter.item_list(items);
+    let formatted = render(
+        &doc,
+        RenderOptions {
+            line_length,
+            indent_width: config.indent_width,
+        },
+    );
+    Ok(keep_block_breaks(&source, formatted))
+}
+
+/// Checks that strings and comments are terminated and brackets balance.
+pub fn check_template(input: &TemplateInput) -> BackendResult<()> {
+    let (source, atoms) = template_source(input, "css")?;
+    tokenize(&source, &atoms).map(|_| ())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Atom,
+    Quoted,
+    Comment,
+    Open,
+    Close,
+    BlockOpen,
+    BlockClose,
+    Colon,
+    Semicolon,
+    Comma,
+    Delim,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    range: Range<usize>,
+    /// Whether the token directly follows the previous one without whitespace.
+    glued: bool,
+    /// Line breaks in the whitespace before the token.
+    newlines: usize,
+}
+
+/// Splits the template into tokens. An interpolation touching word characters
+/// forms a single word with them, so `{prefix}-color` and `{size}px` stay whole.
+fn tokenize(source: &str, atoms: &BTreeMap<usize, usize>) -> BackendResult<Vec<Token>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut brackets = Vec::new();
+    let mut glued = false;
+    let mut newlines = 0;
+    let mut index = 0;
+
+    // Advances past a delimited body, stepping over interpolations whole so
+    // quotes inside their expressions do not end the string.
+    let scan_until = |mut index: usize, closing: &[u8]| -> Option<usize> {
+        while index < bytes.len() {
+            if let Some(&end) = atoms.get(&index) {
+                index = end;
+                continue;
+            }
+            if bytes[index] == b'\\' && closing.len() == 1 {
+                index += 2;
+                continue;
+            }
+            if bytes[index..].starts_with(closing) {
+                return Some(index + closing.len());
+            }
+            index += 1;
+        }
+        None
+    };
+
+    while index < bytes.len() {
+        let start = index;
+        let byte = bytes[index];
+        let is_atom = atoms.contains_key(&index);
+        if !is_atom && byte.is_ascii_whitespace() {
+            if byte == b'\n' {
+                newlines += 1;
+            }
+            index += 1;
+            glued = false;
+            continue;
+        }
+
+        let kind = if is_atom || is_word_byte(byte) {
+            let mut has_atom = false;
+            while index < bytes.len() {
+                if let Some(&end) = atoms.get(&index) {
+                    index = end;
+                    has_atom = true;
+                } else if bytes[index] == b'\\' {
+                    index = (index + 2).min(bytes.len());
+                } else if is_word_byte(bytes[index]) {
+                    index += 1;
+                } else {
+                    break;
+                }
+            }
+            // An unquoted `url(...)` is a single token: its body may hold
+            // `,`, `;` or `:` that are not punctuation.
+            if source[start..index].eq_ignore_ascii_case("url")
+                && bytes.get(index) == Some(&b'(')
+                && !matches!(
+                    bytes[index + 1..]
+                        .iter()
+                        .find(|byte| !byte.is_ascii_whitespace()),
+                    Some(b'"' | b'\'')
+                )
+            {
+                let end = scan_until(index + 1, b")").ok_or_else(|| unterminated("url"))?;
+                has_atom |= atoms.keys().any(|&offset| (index..end).contains(&offset));
+                index = end;
+            }
+            if has_atom {
+                TokenKind::Atom
+            } else {
+                TokenKind::Word
+            }
+        } else {
+            match byte {
+                b'"' | b'\'' => {
+                    index = scan_until(index + 1, &[byte]).ok_or_else(|| unterminated("string"))?;
+                    TokenKind::Quoted
+                }
+                b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                    index = scan_until(index + 2, b"*/").ok_or_else(|| unterminated("comment"))?;
+                    TokenKind::Comment
+                }
+                b'(' | b'[' | b'{' => {
+                    brackets.push(byte);
+                    index += 1;
+                    if byte == b'{' {
+                        TokenKind::BlockOpen
+                    } else {
+                        TokenKind::Open
+                    }
+                }
+                b')' | b']' | b'}' => {
+                    let expected = match byte {
+                        b')' => b'(',
+                        b']' => b'[',
+                        _ => b'{',
+                    };
+                    if brackets.pop() != Some(expected) {
+                        return Err(unbalanced());
+                    }
+                    index += 1;
+                    if byte == b'}' {
+                        TokenKind::BlockClose
+                    } else {
+                        TokenKind::Close
+                    }
+                }
+                b':' => {
+                    index += 1;
+                    TokenKind::Colon
+                }
+                b';' => {
+                    index += 1;
+                    TokenKind::Semicolon
+                }
+                b',' => {
+                    index += 1;
+                    TokenKind::Comma
+                }
+                _ => {
+                    index += source[index..].chars().next().map_or(1, char::len_utf8);
+                    TokenKind::Delim
+                }
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            range: start..index,
+            glued,
+            newlines,
+        });
+        glued = true;
+        newlines = 0;
+    }
+
+    if !brackets.is_empty() {
+        return Err(unbalanced());
+    }
+    Ok(tokens)
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    template_text::is_word_byte(byte) || matches!(byte, b'-' | b'.' | b'#' | b'%' | b'@')
+}
+
+fn unterminated(what: &str) -> BackendError {
+    template_text::unterminated(what, "css")
+}
+
+fn unbalanced() -> BackendError {
+    template_text::unbalanced("css")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    Selector,
+    AtRule,
+    Value,
+}
+
+#[derive(Debug)]
+enum ItemKind {
+    Comment,
+    /// `property: value`, split at the colon token.
+    Declaration {
+        colon: usize,
+    },
+    /// An at-rule statement such as `@import`, or text the formatter cannot
+    /// classify, such as an interpolation spliced in as a whole declaration.
+    Statement,
+    /// A selector or at-rule prelude followed by a `{ ... }` body.
+    Block {
+        prelude: Range<usize>,
+        body: Vec<Item>,
+    },
+}
+
+#[derive(Debug)]
+struct Item {
+    kind: ItemKind,
+    range: Range<usize>,
+    /// Whether the source ended the item with `;`.
+    terminated: bool,
+    /// Whether a blank line separated the item from the previous one.
+    blank_before: bool,
+    /// A comment on the same line after the item.
+    trailing_comment: Option<usize>,
+}
+
+struct CssFormatter<'a> {
+    source: &'a str,
+    atoms: &'a BTreeMap<usize, usize>,
+    tokens: Vec<Token>,
+    config: &'a CssFormatConfig,
+}
+
+impl CssFormatter<'_> {
+    fn items(&self, range: Range<usize>) -> Vec<Item> {
+        let mut items: Vec<Item> = Vec::new();
+        let mut index = range.start;
+        while index < range.end {
+            let token = &self.tokens[index];
+            let blank_before = token.newlines > 1;
+            if token.kind == TokenKind::Semicolon {
+                index += 1;
+                continue;
+            }
+            if token.kind == TokenKind::Comment {
+                let follows_item = index > range.start
+                    && token.newlines == 0
+                    && self.tokens[index - 1].kind != TokenKind::Comment;
+                if follows_item
+                    && let Some(previous) = items.last_mut()
+                    && previous.trailing_comment.is_none()
+                {
+                    previous.trailing_comment = Some(index);
+                } else {
+                    items.push(Item {
+                        kind: ItemKind::Comment,
+                        range: index..index + 1,
+                        terminated: false,
+                        blank_before,
+                        trailing_comment: None,
+                    });
+                }
+                index += 1;
+                continue;
+            }
+
+            let start = index;
+            let mut depth = 0usize;
+            let mut colon = None;
+            while index < range.end {
+                match self.tokens[index].kind {
+                    TokenKind::Open => depth += 1,
+                    TokenKind::Close => depth = depth.saturating_sub(1),
+                    TokenKind::Colon if depth == 0 && colon.is_none() => colon = Some(index),
+                    TokenKind::Semicolon | TokenKind::BlockOpen | TokenKind::BlockClose
+                        if depth == 0 =>
+                    {
+                        break;
+                    }
+                    _ => {}
+                }
+                index += 1;
+            }
+
+            if self.kind(index) == Some(TokenKind::BlockOpen) {
+                let close = self.matching_close(index);
+                items.push(Item {
+                    kind: ItemKind::Block {
+                        prelude: start..index,
+                        body: self.items(index + 1..close),
+                    },
+                    range: start..close + 1,
+                    terminated: false,
+                    blank_before,
+                    trailing_comment: None,
+                });
+                index = close + 1;
+                continue;
+            }
+
+            let terminated = self.kind(index) == Some(TokenKind::Semicolon);
+            let kind = match colon {
+                Some(colon) if !self.text(start).starts_with('@') => {
+                    ItemKind::Declaration { colon }
+                }
+                _ => ItemKind::Statement,
+            };
+            items.push(Item {
+                kind,
+                range: start..index,
+                terminated,
+                blank_before,
+                trailing_comment: None,
+            });
+            if terminated {
+                index += 1;
+            }
+        }
+        if self.config.declaration_order == DeclarationOrder::Alphabetical {
+            self.sort_declarations(&mut items);
+        }
+        items
+    }
+
+    /// Sorts each run of declarations by property name, ignoring vendor
+    /// prefixes. Comments, blank lines, nested blocks and interpolated
+    /// property names end a run, and the sort is stable so fallbacks such as
+    /// `display: -webkit-box; display: flex` keep their order. A shorthand
+    /// and its longhands also end a run, since `padding-top: 1px; padding: 0`
+    /// means something else when swapped.
+    fn sort_declarations(&self, items: &mut [Item]) {
+        let mut start = 0;
+        while start < items.len() {
+            let mut end = start;
+            let mut names: Vec<String> = Vec::new();
+            while end < items.len() && (end == start || !items[end].blank_before) {
+                let Some(name) = self.sort_key(&items[end]) else {
+                    break;
+                };
+                if names.iter().any(|other| overlaps(other, &name)) {
+                    break;
+                }
+                names.push(name);
+                end += 1;
+            }
+            if end > start + 1 {
+                let blank_before = items[start].blank_before;
+                items[start].blank_before = false;
+                items[start..end].sort_by_cached_key(|item| self.sort_key(item));
+                items[start].blank_before = blank_before;
+            }
+            start = end.max(start + 1);
+        }
+    }
+
+    fn sort_key(&self, item: &Item) -> Option<String> {
+        let ItemKind::Declaration { colon } = item.kind else {
+            return None;
+        };
+        if (item.range.start..colon).any(|index| self.tokens[index].kind == TokenKind::Atom) {
+            return None;
+        }
+        let name = self.inline(item.range.start..colon, Context::Value);
+        let name = name.to_ascii_lowercase();
+        Some(
+            properties::strip_vendor_prefix(&name)
+                .unwrap_or(&name)
+                .to_string(),
+        )
+    }
+
+    fn item_list(&self, items: Vec<Item>) -> Doc {
+        let mut docs = Vec::new();
+        for (position, item) in items.into_iter().enumerate() {
+            if position > 0 {
+                if item.blank_before {
+                    docs.push(Doc::text("\n"));
+                }
+                docs.push(Doc::hard_line());
+            }
+            docs.push(self.item(item));
+        }
+        Doc::concat(docs)
+    }
+
+    fn item(&self, item: Item) -> Doc {
+        let trailing_comment = item.trailing_comment;
+        let doc = match item.kind {
+            ItemKind::Block { prelude, body } => {
+                let head = self.prelude(prelude);
+                if body.is_empty() {
+                    Doc::concat(vec![head, Doc::text(" {{}}")])
+                } else {
+                    Doc::concat(vec![
+                        head,
+                        Doc::text(" {{"),
+                        Doc::concat(vec![Doc::hard_line(), self.item_list(body)]).indent(),
+                        Doc::hard_line(),
+                        Doc::text("}}"),
+                    ])
+                }
+            }
+            ItemKind::Comment => Doc::text(self.token_text(item.range.start)),
+            ItemKind::Statement if self.atoms_only(&item.range) && !item.terminated => {
+                Doc::text(self.inline(item.range.clone(), Context::Value))
+            }
+            kind => Doc::text(format!("{};", self.statement_text(&kind, &item.range))),
+        };
+        match trailing_comment {
+            Some(comment) => Doc::concat(vec![
+                doc,
+                Doc::text(" "),
+                Doc::text(self.token_text(comment)),
+            ]),
+            None => doc,
+        }
+    }
+
+    /// Formats a single-line list of declarations, keeping a final `;` only
+    /// when the source had one.
+    fn inline_items(&self, items: &[Item]) -> String {
+        let mut parts = Vec::new();
+        for (position, item) in items.iter().enumerate() {
+            let is_last = position + 1 == items.len();
+            let mut part = self.statement_text(&item.kind, &item.range);
+            let needs_semicolon = match item.kind {
+                ItemKind::Comment => false,
+                _ if is_last => item.terminated,
+                ItemKind::Statement => item.terminated || !self.atoms_only(&item.range),
+                _ => true,
+            };
+            if needs_semicolon {
+                part.push(';');
+            }
+            if let Some(comment) = item.trailing_comment {
+                part.push(' ');
+                part.push_str(&self.token_text(comment));
+            }
+            parts.push(part);
+        }
+        parts.join(" ")
+    }
+
+    fn statement_text(&self, kind: &ItemKind, range: &Range<usize>) -> String {
+        match kind {
+            ItemKind::Declaration { colon } => {
+                let property = self.inline(range.start..*colon, Context::Value);
+                let value = self.inline(colon + 1..range.end, Context::Value);
+                if value.is_empty() {
+                    format!("{property}:")
+                } else {
+                    format!("{property}: {value}")
+                }
+            }
+            ItemKind::Statement if self.text(range.start).starts_with('@') => {
+                self.inline(range.clone(), Context::AtRule)
+            }
+            _ => self.inline(range.clone(), Context::Value),
+        }
+    }
+
+    /// A selector list breaks one selector per line when it does not fit;
+    /// an at-rule prelude stays on one line.
+    fn prelude(&self, range: Range<usize>) -> Doc {
+        if range.is_empty() {
+            return Doc::text("");
+        }
+        if self.text(range.start).starts_with('@') {
+            return Doc::text(self.inline(range, Context::AtRule));
+        }
+        let mut selectors = Vec::new();
+        let mut start = range.start;
+        let mut depth = 0usize;
+        for index in range.clone() {
+            match self.tokens[index].kind {
+                TokenKind::Open => depth += 1,
+                TokenKind::Close => depth = depth.saturating_sub(1),
+                TokenKind::Comma if depth == 0 => {
+                    selectors.push(start..index);
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        selectors.push(start..range.end);
+
+        let mut docs = Vec::new();
+        for (position, selector) in selectors.into_iter().enumerate() {
+            if position > 0 {
+                docs.push(Doc::text(","));
+                docs.push(Doc::line());
+            }
+            docs.push(Doc::text(self.inline(selector, Context::Selector)));
+        }
+        Doc::concat(docs).group()
+    }
+
+    /// Joins tokens on one line: glued tokens stay glued, other whitespace
+    /// becomes a single space, with fixed spacing around commas, brackets,
+    /// `!important`, selector combinators and media feature colons.
+    fn inline(&self, range: Range<usize>, context: Context) -> String {
+        let mut out = String::new();
+        // For each open bracket, whether it starts a media feature such as
+        // `(min-width: 600px)` rather than a function call.
+        let mut features = Vec::new();
+        for index in range.clone() {
+            if index > range.start && self.space_between(index - 1, index, context, &features) {
+                out.push(' ');
+            }
+            out.push_str(&self.token_text(index));
+            match self.tokens[index].kind {
+                TokenKind::Open => features.push(
+                    context == Context::AtRule
+                        && !(self.tokens[index].glued
+                            && index > range.start
+                            && matches!(
+                                self.tokens[index - 1].kind,
+                                TokenKind::Word | TokenKind::Atom
+                            )),
+                ),
+                TokenKind::Close => {
+                    features.pop();
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn space_between(
+        &self,
+        previous: usize,
+        next: usize,
+        context: Context,
+        features: &[bool],
+    ) -> bool {
+        let previous_kind = self.tokens[previous].kind;
+        let next_kind = self.tokens[next].kind;
+        let is_combinator = |index: usize| {
+            context == Context::Selector
+                && features.is_empty()
+                && self.tokens[index].kind == TokenKind::Delim
+                && matches!(self.text(index), ">" | "+" | "~")
+        };
+        if next_kind == TokenKind::Comma
+            || previous_kind == TokenKind::Open
+            || next_kind == TokenKind::Close
+        {
+            return false;
+        }
+        if previous_kind == TokenKind::Comma || is_combinator(previous) || is_combinator(next) {
+            return true;
+        }
+        if next_kind == TokenKind::Delim && self.text(next) == "!" {
+            return true;
+        }
+        if previous_kind == TokenKind::Delim && self.text(previous) == "!" {
+            return false;
+        }
+        if previous_kind == TokenKind::Colon && features.last() == Some(&true) {
+            return true;
+        }
+        if next_kind == TokenKind::Colon && features.last() == Some(&true) {
+            return false;
+        }
+        !self.tokens[next].glued
+    }
+
+    /// The token's template text, with string quotes switched to the
+    /// configured style when the string contains no quotes, escapes or
+    /// interpolations of its own.
+    fn token_text(&self, index: usize) -> String {
+        let range = self.tokens[index].range.clone();
+        let text = self.template_text(range.clone());
+        if self.tokens[index].kind != TokenKind::Quoted {
+            return text;
+        }
+        let quote = match self.config.quote_style {
+            QuoteStyle::Double => '"',
+            QuoteStyle::Single => '\'',
+            QuoteStyle::Preserve => return text,
+        };
+        let mut offset = range.start + 1;
+        while offset < range.end - 1 {
+            if self.atoms.contains_key(&offset)
+                || matches!(self.source.as_bytes()[offset], b'"' | b'\'' | b'\\')
+            {
+                return text;
+            }
+            offset += 1;
+        }
+        format!("{quote}{}{quote}", &text[1..text.len() - 1])
+    }
+
+    fn template_text(&self, range: Range<usize>) -> String {
+        template_text::template_text(self.source, self.atoms, range)
+    }
+
+    /// Whether the range is only interpolations, such as `{rules}` spliced in
+    /// as whole declarations, which must not gain a `;`.
+    fn atoms_only(&self, range: &Range<usize>) -> bool {
+        range.clone().all(|index| {
+            matches!(
+                self.tokens[index].kind,
+                TokenKind::Atom | TokenKind::Comment
+            )
+        })
+    }
+
+    fn matching_close(&self, open: usize) -> usize {
+        template_text::matching_close(
+            self.tokens.iter().map(|token| token.kind),
+            open,
+            (TokenKind::BlockOpen, TokenKind::BlockClose),
+        )
+        .unwrap_or(self.tokens.len())
+    }
+
+    fn kind(&self, index: usize) -> Option<TokenKind> {
+        self.tokens.get(index).map(|token| token.kind)
+    }
+
+    fn text(&self, index: usize) -> &str {
+        &self.source[self.tokens[index].range.clone()]
+    }
+}
+
+/// Whether one property is a shorthand that sets the other, such as
+/// `padding` and `padding-top` or `inset` and `left`.
+fn overlaps(first: &str, second: &str) -> bool {
+    const SHORTHANDS: &[(&str, &[&str])] = &[
+        ("inset", &["top", "right", "bottom", "left"]),
+        ("gap", &["row-gap", "column-gap"]),
+        ("place-content", &["align-content", "justify-content"]),
+        ("place-items", &["align-items", "justify-items"]),
+        ("place-self", &["align-self", "justify-self"]),
+        ("flex-flow", &["flex-direction", "flex-wrap"]),
+        ("font", &["line-height"]),
+        (
+            "grid-area",
+            &[
+                "grid-row-start",
+                "grid-row-end",
+                "grid-column-start",
+                "grid-column-end",
+            ],
+        ),
+    ];
+    let sets = |shorthand: &str, longhand: &str| {
+        longhand
+            .strip_prefix(shorthand)
+            .is_some_and(|rest| rest.starts_with('-'))
+            || SHORTHANDS
+                .iter()
+                .any(|(name, longhands)| *name == shorthand && longhands.contains(&longhand))
+    };
+    first != second && (sets(first, second) || sets(second, first))
+}
+
+#[cfg(test)]
+mod tests {
+    use tstring_syntax::{TemplateInterpolation, TemplateSegment};
+
+    use super::*;
+
+    fn format_css(segments: &[&str], config: &CssFormatConfig, line_length: usize) -> String {
+        let segments = segments
+            .iter()
+            .enumerate()
+            .map(|(index, text)| match text.strip_prefix('{') {
+                Some(expression) => TemplateSegment::Interpolation(TemplateInterpolation {
+                    expression: expression.trim_end_matches('}').to_string(),
+                    conversion: None,
+                    format_spec: String::new(),
+                    interpolation_index: index,
+                    raw_source: Some(text.to_string()),
+                }),
+                None => TemplateSegment::StaticText(text.to_string()),
+            })
+            .collect();
+        format_template(&TemplateInput::from_segments(segments), config, line_length)
+            .expect("css template formats")
+    }
+
+    #[test]
+    fn formats_one_declaration_per_line_and_keeps_interpolations() {
+        assert_eq!(
+            format_css(
+                &[
+                    "\n.",
+                    "{name}",
+                    ">span,a:hover{color:",
+                    "{color}",
+                    ";margin:0 auto ;font-family:'Inter',sans-serif;",
+                    "{prop}",
+                    "-width:",
+                    "{size}",
+                    "px!important}\n@media screen and (min-width:600px){.b{transform:translate( -50% , -50% )}\n\n\n.c{}}\n",
+                ],
+                &CssFormatConfig::default(),
+                80,
+            ),
+            "
+.{name} > span, a:hover {{
+  color: {color};
+  margin: 0 auto;
+  font-family: \"Inter\", sans-serif;
+  {prop}-width: {size}px !important;
+}}
+@media screen and (min-width: 600px) {{
+  .b {{
+    transform: translate(-50%, -50%);
+  }}
+
+  .c {{}}
+}}
+"
+        );
+    }
+
+    #[test]
+    fn keeps_single_line_declaration_lists_inline_and_breaks_long_selector_lists() {
+        assert_eq!(
+            format_css(
+                &["color:", "{color}", ";margin : 0"],
+                &CssFormatConfig::default(),
+                80
+            ),
+            "color: {color}; margin: 0"
+        );
+        assert_eq!(
+            format_css(
+                &[
+                    ".first-selector,.second-selector{",
+                    "{rules}",
+                    " /* extra */}"
+                ],
+                &CssFormatConfig::default(),
+                20,
+            ),
+            ".first-selector,\n.second-selector {{\n  {rules} /* extra */\n}}"
+        );
+    }
+
+    #[test]
+    fn honours_quote_style_and_declaration_order() {
+        let config = CssFormatConfig {
+            indent_width: 4,
+            quote_style: QuoteStyle::Single,
+            declaration_order: DeclarationOrder::Alphabetical,
+        };
+        assert_eq!(
+            format_css(
+                &[
+                    ".a { z-index: 1; content: \"x\"; quotes: \"it's\"; display: -webkit-box; -webkit-align-items: center; display: flex;\n\n color: red; background: blue }"
+                ],
+                &config,
+                80,
+            ),
+            ".a {{
+    -webkit-align-items: center;
+    content: 'x';
+    display: -webkit-box;
+    display: flex;
+    quotes: \"it's\";
+    z-index: 1;
+
+    background: blue;
+    color: red;
+}}"
+        );
+    }
+
+    #[test]
+    fn keeps_quotes_around_strings_with_interpolations() {
+        assert_eq!(
+            format_css(
+                &[".a{content:'", "{label}", "';quotes:'x'}"],
+                &CssFormatConfig::default(),
+                80,
+            ),
+            ".a {{\n  content: '{label}';\n  quotes: \"x\";\n}}"
+        );
+    }
+
+    #[test]
+    fn keeps_unquoted_urls_whole_and_shorthands_after_their_longhands() {
+        assert_eq!(
+            format_css(
+                &[".a{background:url(data:image/png;base64,AAA=) no-repeat}"],
+                &CssFormatConfig::default(),
+                80,
+            ),
+            ".a {{\n  background: url(data:image/png;base64,AAA=) no-repeat;\n}}"
+        );
+        let config = CssFormatConfig {
+            declaration_order: DeclarationOrder::Alphabetical,
+            ..CssFormatConfig::default()
+        };
+        assert_eq!(
+            format_css(
+                &[
+                    ".a { padding-top: 1px; padding: 0; color: red; border: 0; left: 0; inset: auto }"
+                ],
+                &config,
+                80,
+            ),
+            ".a {{
+  padding-top: 1px;
+  border: 0;
+  color: red;
+  left: 0;
+  padding: 0;
+  inset: auto;
+}}"
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_strings_and_unbalanced_brackets() {
+        for source in [
+            ".a { content: 'open }",
+            ".a { color: red",
+            ".a } {",
+            "/* open",
+        ] {
+            let input =
+                TemplateInput::from_segments(vec![TemplateSegment::StaticText(source.to_string())]);
+            assert!(format_template(&input, &CssFormatConfig::default(), 80).is_err());
+            assert!(check_template(&input).is_err());
+        }
+    }
+}
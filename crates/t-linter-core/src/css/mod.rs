@@ -0,0 +1,3 @@
+pub mod format;
+pub mod properties;
+pub mod rules;
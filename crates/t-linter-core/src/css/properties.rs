@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+const CSS_PROPERTIES: &str = include_str!("manifests/properties.toml");
+const CSS_WIDE_KEYWORDS: &[&str] = &["inherit", "initial", "unset", "revert", "revert-layer"];
+const VENDOR_PREFIXES: &[&str] = &["-webkit-", "-moz-", "-ms-", "-o-"];
+
+#[derive(Debug, Deserialize)]
+pub struct CssDatabase {
+    #[serde(rename = "at-rules")]
+    at_rules: AtRules,
+    colors: Colors,
+    properties: BTreeMap<String, PropertySpec>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AtRules {
+    known: BTreeSet<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Colors {
+    named: BTreeSet<String>,
+}
+
+/// The values a property accepts. A property with neither `accepts` nor
+/// `keywords` accepts anything and its values are not checked.
+#[derive(Debug, Default, Deserialize)]
+pub struct PropertySpec {
+    #[serde(default)]
+    accepts: Vec<ValueKind>,
+    #[serde(default)]
+    keywords: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueKind {
+    Color,
+    Length,
+    Percentage,
+    Number,
+    Integer,
+    Angle,
+    Time,
+}
+
+impl ValueKind {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Color => "color",
+            Self::Length => "length",
+            Self::Percentage => "percentage",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::Angle => "angle",
+            Self::Time => "time",
+        }
+    }
+
+    /// Classifies a dimension unit, `%` included.
+    pub fn for_unit(unit: &str) -> Option<Self> {
+        match unit.to_ascii_lowercase().as_str() {
+            "%" => Some(Self::Percentage),
+            "px" | "em" | "rem" | "ex" | "rex" | "ch" | "rch" | "cap" | "rcap" | "ic" | "ric"
+            | "lh" | "rlh" | "vw" | "vh" | "vi" | "vb" | "vmin" | "vmax" | "svw" | "svh"
+            | "svi" | "svb" | "svmin" | "svmax" | "lvw" | "lvh" | "lvi" | "lvb" | "lvmin"
+            | "lvmax" | "dvw" | "dvh" | "dvi" | "dvb" | "dvmin" | "dvmax" | "cqw" | "cqh"
+            | "cqi" | "cqb" | "cqmin" | "cqmax" | "cm" | "mm" | "q" | "in" | "pt" | "pc" => {
+                Some(Self::Length)
+            }
+            "deg" | "rad" | "grad" | "turn" => Some(Self::Angle),
+            "s" | "ms" => Some(Self::Time),
+            _ => None,
+        }
+    }
+}
+
+pub fn database() -> &'static CssDatabase {
+    static DATABASE: OnceLock<CssDatabase> = OnceLock::new();
+    DATABASE.get_or_init(|| toml::from_str(CSS_PROPERTIES).expect("bundled CSS database parses"))
+}
+
+/// Strips a `-webkit-`-style prefix, returning `None` for unprefixed names.
+pub fn strip_vendor_prefix(name: &str) -> Option<&str> {
+    VENDOR_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+}
+
+impl CssDatabase {
+    pub fn property(&self, name: &str) -> Option<&PropertySpec> {
+        self.properties.get(name)
+    }
+
+    pub fn property_names(&self) -> impl Iterator<Item = &str> {
+        self.properties.keys().map(String::as_str)
+    }
+
+    pub fn is_known_at_rule(&self, name: &str) -> bool {
+        self.at_rules.known.contains(name)
+    }
+
+    pub fn at_rule_names(&self) -> impl Iterator<Item = &str> {
+        self.at_rules.known.iter().map(String::as_str)
+    }
+
+    /// Keywords valid for `spec`, named colors included when it takes a color.
+    pub fn keywords<'a>(&'a self, spec: &'a PropertySpec) -> impl Iterator<Item = &'a str> {
+        let colors = spec
+            .accepts(ValueKind::Color)
+            .then(|| self.colors.named.iter().map(String::as_str))
+            .into_iter()
+            .flatten();
+        spec.keywords.iter().map(String::as_str).chain(colors)
+    }
+
+    pub fn accepts_keyword(&self, spec: &PropertySpec, keyword: &str) -> bool {
+        let keyword = keyword.to_ascii_lowercase();
+        CSS_WIDE_KEYWORDS.contains(&keyword.as_str())
+            || self.keywords(spec).any(|candidate| candidate == keyword)
+    }
+}
+
+impl PropertySpec {
+    pub fn is_checked(&self) -> bool {
+        !self.accepts.is_empty() || !self.keywords.is_empty()
+    }
+
+    pub fn accepts(&self, kind: ValueKind) -> bool {
+        self.accepts.contains(&kind)
+            || (kind == ValueKind::Integer && self.accepts.contains(&ValueKind::Number))
+    }
+
+    pub fn describe(&self) -> String {
+        let mut expected = self
+            .accepts
+            .iter()
+            .map(|kind| kind.describe())
+            .collect::<Vec<_>>();
+        if !self.keywords.is_empty() {
+            expected.push("keyword");
+        }
+        match expected.split_last() {
+            Some((last, [])) => last.to_string(),
+            Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+            None => "any value".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_database_loads_properties_and_value_grammars() {
+        let database = database();
+
+        let display = database.property("display").unwrap();
+        assert!(display.is_checked());
+        assert!(database.accepts_keyword(display, "FLEX"));
+        assert!(database.accepts_keyword(display, "inherit"));
+        assert!(!database.accepts_keyword(display, "flexx"));
+
+        let color = database.property("color").unwrap();
+        assert!(database.accepts_keyword(color, "rebeccapurple"));
+        assert_eq!(color.describe(), "color");
+        assert_eq!(
+            database.property("opacity").unwrap().describe(),
+            "number or percentage"
+        );
+
+        assert!(
+            !database
+                .property("grid-template-areas")
+                .unwrap()
+                .is_checked()
+        );
+        assert!(database.is_known_at_rule("container"));
+        assert_eq!(strip_vendor_prefix("-webkit-keyframes"), Some("keyframes"));
+        assert_eq!(ValueKind::for_unit("PX"), Some(ValueKind::Length));
+        assert_eq!(ValueKind::for_unit("fr"), None);
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeSet, HashMap};
+
+use tree_sitter::Node;
+
+use super::properties::{self, CssDatabase, PropertySpec, ValueKind};
+use crate::lint::{EmbeddedDocument, LintDiagnostic, LintSeverity, closest_key};
+
+const RULE_UNKNOWN_PROPERTY: &str = "css-unknown-property";
+const RULE_INVALID_VALUE: &str = "css-invalid-value";
+const RULE_DUPLICATE_PROPERTY: &str = "css-duplicate-property";
+const RULE_UNKNOWN_AT_RULE: &str = "css-unknown-at-rule";
+const RULE_EMPTY_RULESET: &str = "css-empty-ruleset";
+
+/// Reports unknown properties and at-rules, invalid values for properties
+/// with a value grammar, duplicate properties and empty rulesets. Anything
+/// touching an interpolation is treated as a wildcard.
+pub fn lint_rules(document: &EmbeddedDocument<'_>) -> Vec<LintDiagnostic> {
+    let database = properties::database();
+    let mut linter = CssLinter {
+        document,
+        database,
+        diagnostics: Vec::new(),
+    };
+    linter.visit(document.tree.root_node());
+    linter.diagnostics
+}
+
+struct CssLinter<'a> {
+    document: &'a EmbeddedDocument<'a>,
+    database: &'static CssDatabase,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl CssLinter<'_> {
+    fn visit(&mut self, node: Node<'_>) {
+        if node.is_error() {
+            return;
+        }
+        match node.kind() {
+            "stylesheet" | "block" => self.check_duplicate_properties(node),
+            "declaration" => self.check_declaration(node),
+            "rule_set" => self.check_empty_ruleset(node),
+            "at_rule" | "postcss_statement" | "keyframes_statement" => self.check_at_rule(node),
+            _ => {}
+        }
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.visit(child);
+        }
+    }
+
+    fn check_declaration(&mut self, declaration: Node<'_>) {
+        let Some(property) = self.property_name(declaration) else {
+            return;
+        };
+        let name = property.to_ascii_lowercase();
+        if name.starts_with("--") || properties::strip_vendor_prefix(&name).is_some() {
+            return;
+        }
+        let Some(spec) = self.database.property(&name) else {
+            let mut message = format!("Unknown CSS property '{property}'.");
+            if let Some(suggestion) = closest_key(&name, self.database.property_names()) {
+                message.push_str(&format!(" Did you mean '{suggestion}'?"));
+            }
+            let node = named_child(declaration, "property_name").unwrap_or(declaration);
+            self.report(node, RULE_UNKNOWN_PROPERTY, LintSeverity::Error, message);
+            return;
+        };
+        if !spec.is_checked() {
+            return;
+        }
+
+        for value in value_nodes(declaration) {
+            if self.touches_interpolation(value) || self.value_matches(spec, value) {
+                continue;
+            }
+            let text = self.text(value);
+            let mut message = format!("Invalid value '{text}' for CSS property '{name}'");
+            if value.kind() == "plain_value" {
+                message.push('.');
+                let lowered = text.to_ascii_lowercase();
+                if let Some(suggestion) = closest_key(&lowered, self.database.keywords(spec)) {
+                    message.push_str(&format!(" Did you mean '{suggestion}'?"));
+                }
+            } else {
+                message.push_str(&format!("; expected {}.", spec.describe()));
+            }
+            self.report(value, RULE_INVALID_VALUE, LintSeverity::Error, message);
+        }
+    }
+
+    fn value_matches(&self, spec: &PropertySpec, value: Node<'_>) -> bool {
+        match value.kind() {
+            "plain_value" => {
+                let keyword = self.text(value);
+                properties::strip_vendor_prefix(&keyword.to_ascii_lowercase()).is_some()
+                    || self.database.accepts_keyword(spec, keyword)
+            }
+            "color_value" => spec.accepts(ValueKind::Color),
+            "string_value" => false,
+            "integer_value" | "float_value" => {
+                let Some(unit) = named_child(value, "unit") else {
+                    let is_zero = self.text(value).parse::<f64>() == Ok(0.0);
+                    return spec.accepts(ValueKind::Number)
+                        || (value.kind() == "integer_value" && spec.accepts(ValueKind::Integer))
+                        || (is_zero
+                            && (spec.accepts(ValueKind::Length)
+                                || spec.accepts(ValueKind::Angle)));
+                };
+                ValueKind::for_unit(self.text(unit)).is_some_and(|kind| spec.accepts(kind))
+            }
+            // Functions such as `var()` and `calc()`, and compound values,
+            // are not evaluated.
+            _ => true,
+        }
+    }
+
+    fn check_duplicate_properties(&mut self, container: Node<'_>) {
+        let mut seen = HashMap::<String, (String, ValueFeatures)>::new();
+        let mut previous = None::<String>;
+        let mut cursor = container.walk();
+        for declaration in container.named_children(&mut cursor) {
+            if declaration.kind() != "declaration" {
+                if !matches!(declaration.kind(), "comment" | "js_comment") {
+                    previous = None;
+                }
+                continue;
+            }
+            let Some(property) = self.property_name(declaration) else {
+                previous = None;
+                continue;
+            };
+            let name = property.to_ascii_lowercase();
+            let value = value_nodes(declaration)
+                .map(|value| self.text(value))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let features = self.value_features(declaration);
+            // `width: 100px; width: calc(...)` back-to-back is a deliberate
+            // fallback for browsers without the newer syntax, but
+            // `color: red; color: blue` is a plain override.
+            let is_fallback = previous.as_deref() == Some(name.as_str())
+                && seen.get(&name).is_some_and(|(seen_value, seen_features)| {
+                    *seen_value != value && features.enhances(seen_features)
+                });
+            if seen.contains_key(&name) && !is_fallback {
+                let node = named_child(declaration, "property_name").unwrap_or(declaration);
+                self.report(
+                    node,
+                    RULE_DUPLICATE_PROPERTY,
+                    LintSeverity::Warning,
+                    format!("CSS property '{name}' is already declared in this block."),
+                );
+            }
+            seen.insert(name.clone(), (value, features));
+            previous = Some(name);
+        }
+    }
+
+    fn value_features(&self, declaration: Node<'_>) -> ValueFeatures {
+        let mut features = ValueFeatures::default();
+        let mut stack = value_nodes(declaration).collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            match node.kind() {
+                "function_name" => {
+                    features
+                        .functions
+                        .insert(self.text(node).to_ascii_lowercase());
+                }
+                "unit" => {
+                    features.units.insert(self.text(node).to_ascii_lowercase());
+                }
+                "plain_value" => {
+                    features.prefixed |=
+                        properties::strip_vendor_prefix(&self.text(node).to_ascii_lowercase())
+                            .is_some();
+                }
+                _ => {}
+            }
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+        }
+        features
+    }
+
+    fn check_empty_ruleset(&mut self, rule_set: Node<'_>) {
+        let Some(block) = named_child(rule_set, "block") else {
+            return;
+        };
+        let mut cursor = block.walk();
+        let is_empty = block
+            .named_children(&mut cursor)
+            .all(|child| matches!(child.kind(), "comment" | "js_comment"));
+        if !is_empty || self.touches_interpolation(block) {
+            return;
+        }
+        let selectors = named_child(rule_set, "selectors").unwrap_or(rule_set);
+        let message = format!("CSS ruleset '{}' is empty.", self.text(selectors));
+        self.report(
+            selectors,
+            RULE_EMPTY_RULESET,
+            LintSeverity::Warning,
+            message,
+        );
+    }
+
+    fn check_at_rule(&mut self, statement: Node<'_>) {
+        let Some(keyword) = named_child(statement, "at_keyword") else {
+            return;
+        };
+        if self.touches_interpolation(keyword) {
+            return;
+        }
+        let text = self.text(keyword);
+        let name = text.trim_start_matches('@').to_ascii_lowercase();
+        let unprefixed = properties::strip_vendor_prefix(&name).unwrap_or(&name);
+        if self.database.is_known_at_rule(unprefixed) {
+            return;
+        }
+        let mut message = format!("Unknown CSS at-rule '{text}'.");
+        if let Some(suggestion) = closest_key(unprefixed, self.database.at_rule_names()) {
+            message.push_str(&format!(" Did you mean '@{suggestion}'?"));
+        }
+        self.report(keyword, RULE_UNKNOWN_AT_RULE, LintSeverity::Error, message);
+    }
+
+    fn property_name(&self, declaration: Node<'_>) -> Option<&str> {
+        let property = named_child(declaration, "property_name")?;
+        (!self.touches_interpolation(property)).then(|| self.text(property))
+    }
+
+    fn text(&self, node: Node<'_>) -> &str {
+        self.document.text(node)
+    }
+
+    fn touches_interpolation(&self, node: Node<'_>) -> bool {
+        self.document.touches_interpolation(node)
+    }
+
+    fn report(&mut self, node: Node<'_>, rule: &str, severity: LintSeverity, message: String) {
+        let diagnostic = self.document.diagnostic(node, rule, severity, message);
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+/// The functions, units, and vendor-prefixed keywords a declaration value
+/// uses, which tell a progressive-enhancement fallback from an override.
+#[derive(Default)]
+struct ValueFeatures {
+    functions: BTreeSet<String>,
+    units: BTreeSet<String>,
+    prefixed: bool,
+}
+
+impl ValueFeatures {
+    /// Whether this value, declared right after `earlier`, adds syntax older
+    /// browsers may not support, as in `display: -webkit-box; display: flex`
+    /// or `height: 100vh; height: 100dvh`. A unitless `0` is not a new unit.
+    fn enhances(&self, earlier: &Self) -> bool {
+        ["var", "calc", "env"]
+            .iter()
+            .any(|function| self.functions.contains(*function))
+            || self.prefixed
+            || earlier.prefixed
+            || self.functions != earlier.functions
+            || (!self.units.is_empty() && !earlier.units.is_empty() && self.units != earlier.units)
+    }
+}
+
+fn named_child<'tree>(node: Node<'tree>, kind: &str) -> Option<Node<'tree>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .find(|child| child.kind() == kind)
+}
+
+fn value_nodes<'tree>(declaration: Node<'tree>) -> impl Iterator<Item = Node<'tree>> {
+    let mut cursor = declaration.walk();
+    declaration
+        .named_children(&mut cursor)
+        .filter(|child| {
+            !matches!(
+                child.kind(),
+                "property_name" | "important" | "comment" | "js_comment"
+            )
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::lint_source;
+
+    fn css_diagnostics(css: &str) -> Vec<(String, String)> {
+        let source = format!(
+            "from typing import Annotated\nfrom string.templatelib import Template\n\nsize = 1\nstyles: Annotated[Template, \"css\"] = t\"\"\"{css}\"\"\"\n"
+        );
+        lint_source(std::path::Path::new("styles.py"), &source)
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn reports_unknown_properties_and_invalid_values() {
+        assert_eq!(
+            css_diagnostics(".a {{ colr: red; display: flexx; opacity: 10px; color: #fff; }}"),
+            vec![
+                (
+                    "css-unknown-property".to_string(),
+                    "Unknown CSS property 'colr'. Did you mean 'color'?".to_string()
+                ),
+                (
+                    "css-invalid-value".to_string(),
+                    "Invalid value 'flexx' for CSS property 'display'. Did you mean 'flex'?"
+                        .to_string()
+                ),
+                (
+                    "css-invalid-value".to_string(),
+                    "Invalid value '10px' for CSS property 'opacity'; expected number or percentage."
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn treats_interpolations_custom_properties_and_functions_as_wildcards() {
+        assert!(
+            css_diagnostics(
+                ".a {{ width: {size}px; margin: 0 auto; --brand: whatever; -webkit-box-flex: 1; color: var(--brand); z-index: {size}; }}"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn reports_duplicates_unknown_at_rules_and_empty_rulesets() {
+        let rules = css_diagnostics(
+            "@tailwind base; .a {{ color: red; margin: 0; color: blue; }} .b {{ display: -webkit-box; display: flex; height: 100vh; height: 100dvh; width: 100px; width: calc(100% - 1px); }} .c {{ /* todo */ }} @-webkit-keyframes spin {{ from {{ opacity: 0; }} }} @media print {{ .d {{ color: red; color: blue; }} }} .e {{ padding: 0; padding: 1px; }}",
+        )
+        .into_iter()
+        .map(|(rule, _)| rule)
+        .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![
+                "css-unknown-at-rule",
+                "css-duplicate-property",
+                "css-empty-ruleset",
+                "css-duplicate-property",
+                "css-duplicate-property"
+            ]
+        );
+    }
+}
@@ -5,16 +5,28 @@ use tstring_syntax::BackendError;
 
 use crate::backend::TemplateBackend;
 use crate::lint::DiagnosticEdit;
+use crate::plugin::LanguagePlugins;
+use crate::project_config::{CssFormatConfig, JavascriptFormatConfig, SqlFormatConfig};
 use crate::{Location, TemplateStringInfo, TemplateStringParser};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FormatOptions {
     pub line_length: usize,
+    pub css: CssFormatConfig,
+    pub javascript: JavascriptFormatConfig,
+    pub sql: SqlFormatConfig,
+    pub plugins: LanguagePlugins,
 }
 
 impl Default for FormatOptions {
     fn default() -> Self {
-        Self { line_length: 80 }
+        Self {
+            line_length: 80,
+            css: CssFormatConfig::default(),
+            javascript: JavascriptFormatConfig::default(),
+            sql: SqlFormatConfig::default(),
+            plugins: LanguagePlugins::default(),
+        }
     }
 }
 
@@ -210,14 +222,37 @@ fn format_template_edit(
     options: &FormatOptions,
 ) -> Option<Result<TemplateEdit>> {
     let language = template.language.as_deref()?.to_ascii_lowercase();
-    let input = template.to_template_input();
     let line_length = options.line_length.max(1);
 
-    let backend = TemplateBackend::for_language(&language)?;
-    if backend == TemplateBackend::Sql {
-        return None;
+    if let Some(formatted) = options.plugins.format_template(template, line_length) {
+        return Some(
+            formatted
+                .map(|content| TemplateEdit {
+                    location: template.formatting_location(&content).clone(),
+                    replacement: template.formatted_literal(&content),
+                })
+                .map_err(|error| {
+                    FormatError {
+                        message: format!("{error:#}"),
+                        language: Some(language),
+                        location: Some(template.location.clone()),
+                    }
+                    .into()
+                }),
+        );
     }
-    let formatted = backend.format_template(&input, template.profile.as_deref(), line_length);
+
+    let input = template.to_template_input();
+    let options = FormatOptions {
+        line_length,
+        css: options.css,
+        javascript: options.javascript,
+        sql: options.sql,
+        plugins: LanguagePlugins::default(),
+    };
+
+    let backend = TemplateBackend::for_language(&language)?;
+    let formatted = backend.format_template(&input, template.profile.as_deref(), &options);
 
     Some(
         formatted
@@ -475,8 +510,14 @@ from string.templatelib import Template
 markup: Annotated[Template, "html"] = t'<div data-a="12345" data-b="67890"></div>'
 "#;
 
-        let edits = format_document_with_options(source, &FormatOptions { line_length: 20 })
-            .expect("expected format success");
+        let edits = format_document_with_options(
+            source,
+            &FormatOptions {
+                line_length: 20,
+                ..FormatOptions::default()
+            },
+        )
+        .expect("expected format success");
 
         assert_eq!(edits.len(), 1);
         assert_eq!(
@@ -502,7 +543,10 @@ markup: Annotated[Template, "html"] = t'<div data-a="12345" data-b="67890"></div
                 end_line: 5,
                 end_column: 55,
             },
-            &FormatOptions { line_length: 20 },
+            &FormatOptions {
+                line_length: 20,
+                ..FormatOptions::default()
+            },
         )
         .expect("expected range format success");
 
@@ -0,0 +1,311 @@
+//! Tree-sitter grammars loaded from shared libraries at runtime, configured
+//! under `[tool.t-linter.grammars.<language>]`, so templates in languages the
+//! binary does not bundle can be checked for syntax errors and highlighted.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use anyhow::{Context, Result, bail};
+use libloading::{Library, Symbol};
+use tree_sitter::{LANGUAGE_VERSION, Language, MIN_COMPATIBLE_LANGUAGE_VERSION};
+use tree_sitter_language::LanguageFn;
+
+use crate::lint::{LintDiagnostic, LintSeverity};
+use crate::parser::TemplateStringInfo;
+use crate::project_config::{GrammarConfig, ProjectConfig};
+
+pub(crate) const RULE_GRAMMAR_ERROR: &str = "grammar-error";
+const DEFAULT_PLACEHOLDER: &str = "t_linter_expr";
+
+/// The runtime-loaded grammars configured for a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomGrammars {
+    root: PathBuf,
+    grammars: HashMap<String, GrammarConfig>,
+}
+
+/// A loaded grammar. Its query files are read when highlighting, so edits to
+/// them apply without a restart.
+#[derive(Debug, Clone)]
+pub(crate) struct CustomGrammar {
+    pub language: Language,
+    pub placeholder: String,
+    highlights: Option<PathBuf>,
+    injections: Option<PathBuf>,
+}
+
+impl CustomGrammar {
+    pub(crate) fn highlights_query(&self) -> Result<String> {
+        read_query(self.highlights.as_deref())
+    }
+
+    pub(crate) fn has_injections(&self) -> bool {
+        self.injections.is_some()
+    }
+
+    pub(crate) fn injections_query(&self) -> Result<String> {
+        read_query(self.injections.as_deref())
+    }
+}
+
+impl CustomGrammars {
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        Self {
+            root: config.root.clone(),
+            grammars: config.grammars.clone(),
+        }
+    }
+
+    pub fn supports_language(&self, language: &str) -> bool {
+        self.grammar(language).is_some()
+    }
+
+    /// The configured language names, lowercased.
+    pub(crate) fn languages(&self) -> impl Iterator<Item = &str> {
+        self.grammars
+            .iter()
+            .filter(|(_, grammar)| grammar.library.is_some())
+            .map(|(language, _)| language.as_str())
+    }
+
+    fn grammar(&self, language: &str) -> Option<&GrammarConfig> {
+        self.grammars
+            .get(&language.trim().to_ascii_lowercase())
+            .filter(|grammar| grammar.library.is_some())
+    }
+
+    /// Loads the grammar for a language, or returns `None` when no grammar
+    /// is configured for it.
+    pub(crate) fn load(&self, language: &str) -> Option<Result<CustomGrammar>> {
+        let language = language.trim().to_ascii_lowercase();
+        let grammar = self.grammar(&language)?;
+        Some(self.load_grammar(&language, grammar))
+    }
+
+    fn load_grammar(&self, language: &str, grammar: &GrammarConfig) -> Result<CustomGrammar> {
+        let library = self
+            .root
+            .join(grammar.library.as_deref().unwrap_or_default());
+        let symbol = grammar
+            .symbol
+            .clone()
+            .unwrap_or_else(|| format!("tree_sitter_{}", language.replace('-', "_")));
+        Ok(CustomGrammar {
+            language: load_language(&library, &symbol)?,
+            placeholder: grammar
+                .placeholder
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PLACEHOLDER.to_string()),
+            highlights: grammar.highlights.as_ref().map(|path| self.root.join(path)),
+            injections: grammar.injections.as_ref().map(|path| self.root.join(path)),
+        })
+    }
+}
+
+fn read_query(path: Option<&Path>) -> Result<String> {
+    let Some(path) = path else {
+        return Ok(String::new());
+    };
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Loads a language from a grammar library. Libraries stay loaded for the
+/// rest of the process, since every tree and query refers into them.
+fn load_language(library: &Path, symbol: &str) -> Result<Language> {
+    static LANGUAGES: OnceLock<Mutex<HashMap<(PathBuf, String), Language>>> = OnceLock::new();
+    let mut languages = LANGUAGES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let key = (library.to_path_buf(), symbol.to_string());
+    if let Some(language) = languages.get(&key) {
+        return Ok(language.clone());
+    }
+
+    // SAFETY: loading a library runs its initializers, so only projects the
+    // user chose to run t-linter on reach this: the command line loads the
+    // libraries of the project it was run in, and the language server only
+    // those of the languages the user allowed in its settings.
+    let loaded = unsafe { Library::new(library) }
+        .with_context(|| format!("Failed to load grammar library {}", library.display()))?;
+    let language = {
+        // SAFETY: Tree-sitter grammars export `const TSLanguage *name(void)`.
+        let function: Symbol<unsafe extern "C" fn() -> *const ()> =
+            unsafe { loaded.get(symbol.as_bytes()) }.with_context(|| {
+                format!(
+                    "Grammar library {} does not export {symbol}",
+                    library.display()
+                )
+            })?;
+        // SAFETY: as above, the symbol is a generated language function.
+        Language::new(unsafe { LanguageFn::from_raw(*function) })
+    };
+    let version = language.abi_version();
+    if !(MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version) {
+        bail!(
+            "Grammar library {} uses Tree-sitter ABI version {version}, but only versions {MIN_COMPATIBLE_LANGUAGE_VERSION} to {LANGUAGE_VERSION} are supported",
+            library.display()
+        );
+    }
+    std::mem::forget(loaded);
+    languages.insert(key, language.clone());
+    Ok(language)
+}
+
+/// Reports a grammar that failed to load on the template that needed it.
+pub(crate) fn grammar_error(
+    path: &Path,
+    template: &TemplateStringInfo,
+    language: &str,
+    error: &anyhow::Error,
+) -> LintDiagnostic {
+    LintDiagnostic {
+        rule: RULE_GRAMMAR_ERROR.to_string(),
+        severity: LintSeverity::Error,
+        language: Some(language.to_string()),
+        message: format!("Tree-sitter grammar for '{language}' failed to load: {error:#}"),
+        file: path.to_path_buf(),
+        start_line: template.location.start_line,
+        start_column: template.location.start_column,
+        end_line: template.location.end_line,
+        end_column: template.location.end_column,
+        expected_type: None,
+        found_type: None,
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use crate::highlighter::TemplateHighlighter;
+    use crate::lint::{lint_custom_grammar_template, lint_source_with_config};
+    use crate::parser::TemplateStringParser;
+
+    const SOURCE: &str = "from typing import Annotated\nfrom string.templatelib import Template\n\nname = \"ada\"\nscript: Annotated[Template, \"lua\"] = t\"print({name})\"\n";
+
+    fn project(root: &Path, library: &str) -> ProjectConfig {
+        let mut config = ProjectConfig {
+            root: root.to_path_buf(),
+            ..ProjectConfig::default()
+        };
+        config.grammars.insert(
+            "lua".to_string(),
+            GrammarConfig {
+                library: Some(library.to_string()),
+                ..GrammarConfig::default()
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn grammars_without_a_library_are_ignored() {
+        let mut config = project(Path::new("."), "lua.so");
+        config
+            .grammars
+            .insert("hcl".to_string(), GrammarConfig::default());
+        let grammars = CustomGrammars::from_config(&config);
+
+        assert!(grammars.supports_language("Lua"));
+        assert!(!grammars.supports_language("hcl"));
+        assert!(grammars.load("hcl").is_none());
+        assert_eq!(grammars.languages().collect::<Vec<_>>(), vec!["lua"]);
+    }
+
+    #[test]
+    fn grammar_load_failures_are_reported_on_the_template() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("lua.so"), "not a shared library").unwrap();
+        for library in ["missing.so", "lua.so"] {
+            let config = project(temp.path(), library);
+
+            let diagnostics = lint_source_with_config(Path::new("script.py"), SOURCE, &config)
+                .unwrap()
+                .diagnostics;
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, RULE_GRAMMAR_ERROR);
+            assert!(diagnostics[0].message.starts_with(&format!(
+                "Tree-sitter grammar for 'lua' failed to load: Failed to load grammar library {}",
+                temp.path().join(library).display()
+            )));
+            assert_eq!(diagnostics[0].start_line, 5);
+
+            let template = TemplateStringParser::new()
+                .unwrap()
+                .find_template_strings(SOURCE)
+                .unwrap()
+                .remove(0);
+            let mut highlighter = TemplateHighlighter::new().unwrap();
+            assert!(!highlighter.supports_language("lua"));
+            highlighter.set_grammars(CustomGrammars::from_config(&config));
+            assert!(highlighter.supports_language("lua"));
+            assert!(highlighter.highlight_template(&template).is_err());
+        }
+    }
+
+    #[test]
+    fn loaded_grammars_check_and_highlight_templates() {
+        let temp = tempfile::tempdir().unwrap();
+        let highlights = temp.path().join("highlights.scm");
+        fs::write(&highlights, tree_sitter_bash::HIGHLIGHT_QUERY).unwrap();
+        let grammar = CustomGrammar {
+            language: tree_sitter_bash::LANGUAGE.into(),
+            placeholder: DEFAULT_PLACEHOLDER.to_string(),
+            highlights: Some(highlights.clone()),
+            injections: None,
+        };
+        let source = "from typing import Annotated\nfrom string.templatelib import Template\n\nname = \"ada\"\nok: Annotated[Template, \"mysh\"] = t\"echo {name}\"\nbroken: Annotated[Template, \"mysh\"] = t\"if {name}; then\"\n";
+        let templates = TemplateStringParser::new()
+            .unwrap()
+            .find_template_strings(source)
+            .unwrap();
+
+        let path = Path::new("script.py");
+        assert!(
+            lint_custom_grammar_template(path, &templates[0], "mysh", &grammar)
+                .unwrap()
+                .is_empty()
+        );
+        let diagnostics =
+            lint_custom_grammar_template(path, &templates[1], "mysh", &grammar).unwrap();
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.rule.as_str(), diagnostic.start_line))
+                .collect::<Vec<_>>(),
+            vec![("embedded-parse-error", 6)]
+        );
+
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        let highlight = |highlighter: &mut TemplateHighlighter| {
+            highlighter
+                .highlight_custom_grammar_template(&templates[0], "mysh", &grammar)
+                .unwrap()
+                .into_iter()
+                .map(|range| (range.start_byte..range.end_byte, range.highlight_name))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            highlight(&mut highlighter),
+            vec![
+                (0..4, "function".to_string()),
+                (5..7, "variable.parameter".to_string())
+            ]
+        );
+        // The compiled configuration is reused until the query file changes.
+        assert_eq!(highlight(&mut highlighter).len(), 2);
+        fs::write(&highlights, "").unwrap();
+        assert_eq!(
+            highlight(&mut highlighter),
+            vec![(5..7, "variable.parameter".to_string())]
+        );
+    }
+}
@@ -0,0 +1,497 @@
+use std::ops::Range;
+
+use tstring_format_doc::{Doc, RenderOptions, render};
+use tstring_syntax::{BackendResult, TemplateInput};
+
+use super::syntax::{
+    self, Argument, Definition, Directive, Field, GraphqlSource, Operation, Selection,
+    SelectionSet, TypeRef, Value, ValueKind, VariableDefinition,
+};
+use crate::template_text::{check_raw_sources, keep_block_breaks};
+
+const INDENT_WIDTH: usize = 2;
+
+/// Formats a `graphql` template: one selection per line, indented two spaces,
+/// a blank line between definitions, and argument and variable lists that
+/// break one item per line when they do not fit. Type system definitions are
+/// kept as written.
+///
+/// Comments between definitions and between selections are kept on their
+/// own lines, or after the selection they follow; a template with comments
+/// anywhere else is left unchanged. Interpolations are kept byte-for-byte,
+/// and templates that are not valid GraphQL are refused with an error
+/// pointing at the problem.
+pub fn format_template(input: &TemplateInput, line_length: usize) -> BackendResult<String> {
+    check_raw_sources(input, "graphql")?;
+    let source = GraphqlSource::new(input);
+    let document = syntax::parse(&source);
+    syntax::validate(&source, &document)?;
+
+    let mut formatter = GraphqlFormatter {
+        source: &source,
+        comments: &document.comments,
+        placed: 0,
+    };
+    let items = document
+        .definitions
+        .iter()
+        .map(|definition| (definition.range(), formatter.definition(definition)))
+        .collect();
+    let doc = formatter.sequence(items, 0..source.text.len(), true);
+    if formatter.placed < document.comments.len() {
+        return Ok(source.template_text(0..source.text.len()));
+    }
+    let formatted = render(
+        &doc,
+        RenderOptions {
+            line_length,
+            indent_width: INDENT_WIDTH,
+        },
+    );
+    Ok(keep_block_breaks(&source.text, formatted))
+}
+
+struct GraphqlFormatter<'a> {
+    source: &'a GraphqlSource,
+    comments: &'a [Range<usize>],
+    /// How many comments the layout has kept so far.
+    placed: usize,
+}
+
+impl GraphqlFormatter<'_> {
+    /// Lays out items one per line with the comments between them within
+    /// `region`. A comment on the same line as the item before it stays
+    /// there; elsewhere a single blank line is kept where the source had one,
+    /// and `spaced` items are always set apart by one.
+    fn sequence(
+        &mut self,
+        items: Vec<(Range<usize>, Doc)>,
+        region: Range<usize>,
+        spaced: bool,
+    ) -> Doc {
+        let mut entries = items
+            .into_iter()
+            .map(|(range, doc)| (range, Some(doc)))
+            .collect::<Vec<_>>();
+        for comment in self.comments {
+            let inside_item = entries
+                .iter()
+                .any(|(range, _)| range.start <= comment.start && comment.start < range.end);
+            if region.start <= comment.start && comment.start < region.end && !inside_item {
+                entries.push((comment.clone(), None));
+            }
+        }
+        entries.sort_by_key(|(range, _)| range.start);
+
+        let mut docs = Vec::new();
+        let mut previous: Option<(usize, bool)> = None;
+        for (range, doc) in entries {
+            let is_item = doc.is_some();
+            let doc = doc.unwrap_or_else(|| {
+                self.placed += 1;
+                Doc::text(self.source.template_text(range.clone()))
+            });
+            if let Some((previous_end, previous_is_item)) = previous {
+                let gap = &self.source.text[previous_end..range.start];
+                if !is_item && !gap.contains('\n') {
+                    docs.push(Doc::text(" "));
+                    docs.push(doc);
+                    previous = Some((range.end, previous_is_item));
+                    continue;
+                }
+                if gap.matches('\n').count() > 1 || (spaced && previous_is_item) {
+                    docs.push(Doc::text("\n"));
+                }
+                docs.push(Doc::hard_line());
+            }
+            docs.push(doc);
+            previous = Some((range.end, is_item));
+        }
+        Doc::concat(docs)
+    }
+
+    fn definition(&mut self, definition: &Definition) -> Doc {
+        match definition {
+            Definition::Operation(operation) => self.operation(operation),
+            Definition::Fragment(fragment) => {
+                let mut docs = vec![Doc::text(format!(
+                    "fragment {} on {}",
+                    self.source.template_text(fragment.name.clone()),
+                    self.source.template_text(fragment.type_condition.clone())
+                ))];
+                docs.push(self.directives(&fragment.directives));
+                docs.push(Doc::text(" "));
+                docs.push(self.selection_set(&fragment.selection_set));
+                Doc::concat(docs)
+            }
+            Definition::TypeSystem(definition) => {
+                self.placed += self
+                    .comments
+                    .iter()
+                    .filter(|comment| definition.range.contains(&comment.start))
+                    .count();
+                Doc::text(self.source.template_text(definition.range.clone()))
+            }
+            Definition::Interpolation(range) => Doc::text(self.source.template_text(range.clone())),
+        }
+    }
+
+    fn operation(&mut self, operation: &Operation) -> Doc {
+        let Some(keyword) = &operation.keyword else {
+            return self.selection_set(&operation.selection_set);
+        };
+        let mut docs = vec![Doc::text(self.source.text(keyword).to_string())];
+        if let Some(name) = &operation.name {
+            docs.push(Doc::text(format!(
+                " {}",
+                self.source.template_text(name.clone())
+            )));
+        } else if !operation.variables.is_empty() {
+            docs.push(Doc::text(" "));
+        }
+        if !operation.variables.is_empty() {
+            let variables = operation
+                .variables
+                .iter()
+                .map(|variable| self.variable_definition(variable))
+                .collect();
+            docs.push(list("(", variables, ")", false));
+        }
+        docs.push(self.directives(&operation.directives));
+        docs.push(Doc::text(" "));
+        docs.push(self.selection_set(&operation.selection_set));
+        Doc::concat(docs)
+    }
+
+    fn variable_definition(&self, variable: &VariableDefinition) -> Doc {
+        let mut docs = vec![Doc::text(format!(
+            "{}: {}",
+            self.source.template_text(variable.variable.clone()),
+            self.type_ref(&variable.ty)
+        ))];
+        if let Some(default) = &variable.default {
+            docs.push(Doc::text(" = "));
+            docs.push(self.value(default));
+        }
+        docs.push(self.directives(&variable.directives));
+        Doc::concat(docs)
+    }
+
+    fn selection_set(&mut self, selection_set: &SelectionSet) -> Doc {
+        let items = selection_set
+            .selections
+            .iter()
+            .map(|selection| (selection.range(), self.selection(selection)))
+            .collect();
+        let range = &selection_set.range;
+        let body = self.sequence(items, range.start + 1..range.end - 1, false);
+        Doc::concat(vec![
+            Doc::text("{{"),
+            Doc::concat(vec![Doc::hard_line(), body]).indent(),
+            Doc::hard_line(),
+            Doc::text("}}"),
+        ])
+    }
+
+    fn selection(&mut self, selection: &Selection) -> Doc {
+        match selection {
+            Selection::Field(field) => self.field(field),
+            Selection::FragmentSpread {
+                name, directives, ..
+            } => Doc::concat(vec![
+                Doc::text(format!("...{}", self.source.template_text(name.clone()))),
+                self.directives(directives),
+            ]),
+            Selection::InlineFragment {
+                type_condition,
+                directives,
+                selection_set,
+                ..
+            } => {
+                let mut docs = vec![Doc::text("...")];
+                if let Some(type_condition) = type_condition {
+                    docs.push(Doc::text(format!(
+                        " on {}",
+                        self.source.template_text(type_condition.clone())
+                    )));
+                }
+                docs.push(self.directives(directives));
+                docs.push(Doc::text(" "));
+                docs.push(self.selection_set(selection_set));
+                Doc::concat(docs)
+            }
+            Selection::Interpolation(range) => Doc::text(self.source.template_text(range.clone())),
+        }
+    }
+
+    fn field(&mut self, field: &Field) -> Doc {
+        let mut docs = Vec::new();
+        if let Some(alias) = &field.alias {
+            docs.push(Doc::text(format!(
+                "{}: ",
+                self.source.template_text(alias.clone())
+            )));
+        }
+        docs.push(Doc::text(self.source.template_text(field.name.clone())));
+        docs.push(self.arguments(&field.arguments));
+        docs.push(self.directives(&field.directives));
+        if let Some(selection_set) = &field.selection_set {
+            docs.push(Doc::text(" "));
+            docs.push(self.selection_set(selection_set));
+        }
+        Doc::concat(docs)
+    }
+
+    fn arguments(&self, arguments: &[Argument]) -> Doc {
+        if arguments.is_empty() {
+            return Doc::concat(Vec::new());
+        }
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.argument(argument))
+            .collect();
+        list("(", arguments, ")", false)
+    }
+
+    fn argument(&self, argument: &Argument) -> Doc {
+        Doc::concat(vec![
+            Doc::text(format!(
+                "{}: ",
+                self.source.template_text(argument.name.clone())
+            )),
+            self.value(&argument.value),
+        ])
+    }
+
+    fn directives(&self, directives: &[Directive]) -> Doc {
+        let docs = directives
+            .iter()
+            .flat_map(|directive| {
+                [
+                    Doc::text(format!(
+                        " @{}",
+                        self.source.template_text(directive.name.clone())
+                    )),
+                    self.arguments(&directive.arguments),
+                ]
+            })
+            .collect();
+        Doc::concat(docs)
+    }
+
+    fn value(&self, value: &Value) -> Doc {
+        match &value.kind {
+            ValueKind::List(values) if values.is_empty() => Doc::text("[]"),
+            ValueKind::List(values) => list(
+                "[",
+                values.iter().map(|value| self.value(value)).collect(),
+                "]",
+                false,
+            ),
+            ValueKind::Object(fields) if fields.is_empty() => Doc::text("{{}}"),
+            ValueKind::Object(fields) => list(
+                "{{",
+                fields.iter().map(|field| self.argument(field)).collect(),
+                "}}",
+                true,
+            ),
+            ValueKind::Variable
+            | ValueKind::String
+            | ValueKind::Scalar
+            | ValueKind::Interpolation => Doc::text(self.source.template_text(value.range.clone())),
+        }
+    }
+
+    /// A type reference without the whitespace GraphQL allows inside it.
+    fn type_ref(&self, ty: &TypeRef) -> String {
+        let mut text = String::new();
+        let mut offset = ty.range.start;
+        for (&start, &end) in self.source.atoms.range(ty.range.clone()) {
+            text.extend(
+                self.source.text[offset..start]
+                    .chars()
+                    .filter(|character| !character.is_whitespace() && *character != ','),
+            );
+            text.push_str(&self.source.text[start..end]);
+            offset = end;
+        }
+        text.extend(
+            self.source.text[offset..ty.range.end]
+                .chars()
+                .filter(|character| !character.is_whitespace() && *character != ','),
+        );
+        text
+    }
+}
+
+/// Items separated by commas that stay on one line when they fit and
+/// otherwise go one per line. `padded` lists keep a space inside their
+/// delimiters, as object values do.
+fn list(open: &str, items: Vec<Doc>, close: &str, padded: bool) -> Doc {
+    let separator = || {
+        if padded {
+            Doc::line()
+        } else {
+            Doc::soft_line()
+        }
+    };
+    let mut body = vec![separator()];
+    for (index, item) in items.into_iter().enumerate() {
+        if index > 0 {
+            body.push(Doc::text(","));
+            body.push(Doc::line());
+        }
+        body.push(item);
+    }
+    Doc::concat(vec![
+        Doc::text(open),
+        Doc::concat(body).indent(),
+        separator(),
+        Doc::text(close),
+    ])
+    .group()
+}
+
+#[cfg(test)]
+mod tests {
+    use tstring_syntax::{TemplateInterpolation, TemplateSegment};
+
+    use super::*;
+
+    fn template(segments: &[&str]) -> TemplateInput {
+        let segments = segments
+            .iter()
+            .enumerate()
+            .map(
+                |(index, text)| match text.strip_prefix('{').filter(|rest| !rest.contains(' ')) {
+                    Some(expression) => TemplateSegment::Interpolation(TemplateInterpolation {
+                        expression: expression.trim_end_matches('}').to_string(),
+                        conversion: None,
+                        format_spec: String::new(),
+                        interpolation_index: index,
+                        raw_source: Some(text.to_string()),
+                    }),
+                    None => TemplateSegment::StaticText(text.to_string()),
+                },
+            )
+            .collect();
+        TemplateInput::from_segments(segments)
+    }
+
+    fn format_graphql(segments: &[&str], line_length: usize) -> String {
+        format_template(&template(segments), line_length).expect("graphql template formats")
+    }
+
+    #[test]
+    fn formats_operations_fragments_and_comments() {
+        assert_eq!(
+            format_graphql(
+                &[
+                    "\nquery User($id:ID!,$first:Int=10)@live{user(id:$id){id,name # display\n\n\n...UserFields ... on Admin{level}\n",
+                    "{extra}",
+                    "\nposts(first:$first,where:{published:true,tags:[\"a\",\"b\"]}){title}}}\n# fragments\nfragment UserFields on User{email}",
+                    "{more}",
+                    "\n",
+                ],
+                80,
+            ),
+            "
+query User($id: ID!, $first: Int = 10) @live {{
+  user(id: $id) {{
+    id
+    name # display
+
+    ...UserFields
+    ... on Admin {{
+      level
+    }}
+    {extra}
+    posts(first: $first, where: {{ published: true, tags: [\"a\", \"b\"] }}) {{
+      title
+    }}
+  }}
+}}
+
+# fragments
+fragment UserFields on User {{
+  email
+}}
+
+{more}
+"
+        );
+    }
+
+    #[test]
+    fn breaks_long_argument_lists_and_keeps_other_comments_as_written() {
+        assert_eq!(
+            format_graphql(
+                &["{ search(text: \"graphql\", first: 10, after: \"abc\") { id } }"],
+                30
+            ),
+            "{{
+  search(
+    text: \"graphql\",
+    first: 10,
+    after: \"abc\"
+  ) {{
+    id
+  }}
+}}"
+        );
+        let commented = "{ user(\n  # the id\n  id: 1) { id } }";
+        assert_eq!(
+            format_graphql(&[commented], 80),
+            "{{ user(\n  # the id\n  id: 1) {{ id }} }}"
+        );
+    }
+
+    #[test]
+    fn reports_syntax_and_variable_errors_at_their_span() {
+        let cases: &[(&[&str], &str, usize)] = &[
+            (
+                &["{ user { } }"],
+                "Expected a field, fragment spread, or inline fragment, found `}`.",
+                9,
+            ),
+            (
+                &["query { user(id: ) }"],
+                "Expected a value, found `)`.",
+                17,
+            ),
+            (
+                &["{ user(name: \"a\\q\") }"],
+                "Invalid escape sequence `\\q` in string.",
+                15,
+            ),
+            (&["{ user(first: 01) }"], "Invalid number `01`.", 14),
+            (
+                &["query { user"],
+                "Expected `}`, found the end of the template.",
+                8,
+            ),
+            (
+                &["query User { user(id: $id) { id } }"],
+                "Variable `$id` is not declared by operation `User`.",
+                22,
+            ),
+            (
+                &["query A { a } { b }"],
+                "Anonymous operations must be the only operation in the document.",
+                14,
+            ),
+        ];
+        for (segments, message, offset) in cases {
+            let error =
+                syntax::check_template(&template(segments)).expect_err("template is refused");
+            assert_eq!(error.message, *message, "{segments:?}");
+            let span = error.diagnostics[0].span.as_ref().expect("error span");
+            assert_eq!(span.start.offset, *offset, "{segments:?}");
+        }
+
+        let error = syntax::check_template(&template(&["{ user(id: ", "{id}", " { id } }"]))
+            .expect_err("missing close paren is refused");
+        let span = error.diagnostics[0].span.as_ref().expect("error span");
+        assert_eq!(span.start.token_index, 2);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod format;
+pub mod rules;
+pub mod schema;
+pub mod syntax;
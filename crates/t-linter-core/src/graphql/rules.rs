@@ -0,0 +1,437 @@
+use std::ops::Range;
+use std::path::Path;
+
+use crate::lint::{LintDiagnostic, LintSeverity, closest_key};
+use crate::parser::{InterpolationInfo, Location, TemplatePart, TemplateStringInfo};
+use crate::project_config::ProjectConfig;
+
+use super::schema::{Schema, SchemaType};
+use super::syntax::{
+    self, Argument, Definition, Directive, GraphqlSource, Selection, SelectionSet, TypeKind, Value,
+    ValueKind,
+};
+
+const RULE_INTERPOLATED_VALUE: &str = "graphql-interpolated-value";
+const RULE_UNKNOWN_FIELD: &str = "graphql-unknown-field";
+const RULE_UNKNOWN_ARGUMENT: &str = "graphql-unknown-argument";
+const RULE_UNKNOWN_TYPE: &str = "graphql-unknown-type";
+const RULE_SCHEMA_UNRESOLVED: &str = "graphql-schema-unresolved";
+
+/// Reports interpolations used as inline values, which should be passed as
+/// GraphQL variables instead, and, when `[tool.t-linter.graphql]` names a
+/// schema, fields, arguments, and types the schema does not define.
+pub fn lint_rules(
+    path: &Path,
+    template: &TemplateStringInfo,
+    config: &ProjectConfig,
+) -> Vec<LintDiagnostic> {
+    let source = GraphqlSource::new(&template.to_template_input());
+    let document = syntax::parse(&source);
+    if syntax::validate(&source, &document).is_err() {
+        return Vec::new();
+    }
+    let mut linter = Linter {
+        path,
+        template,
+        source: &source,
+        interpolations: template
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                TemplatePart::Interpolation(interpolation) => Some(interpolation),
+                TemplatePart::Static(_) => None,
+            })
+            .collect(),
+        schema: None,
+        diagnostics: Vec::new(),
+    };
+
+    if let Some(file) = &config.graphql.schema {
+        match Schema::load(&config.root, path, file) {
+            Ok(schema) => linter.schema = Some(schema),
+            Err(message) => linter.diagnostics.push(linter.diagnostic(
+                RULE_SCHEMA_UNRESOLVED,
+                LintSeverity::Warning,
+                message,
+                template.location.clone(),
+            )),
+        }
+    }
+
+    for definition in &document.definitions {
+        match definition {
+            Definition::Operation(operation) => {
+                for variable in &operation.variables {
+                    if let Some(value) = &variable.default {
+                        linter.value(value);
+                    }
+                    linter.directives(&variable.directives);
+                }
+                linter.directives(&operation.directives);
+                let kind = operation
+                    .keyword
+                    .as_ref()
+                    .map_or("query", |keyword| source.text(keyword));
+                let root = match &linter.schema {
+                    Some(schema) => match schema.root(kind) {
+                        Some(root) => Some(root.to_string()),
+                        None => {
+                            let range = operation.keyword.clone().unwrap_or(
+                                operation.selection_set.range.start
+                                    ..operation.selection_set.range.start + 1,
+                            );
+                            linter.report(
+                                RULE_UNKNOWN_TYPE,
+                                format!("The GraphQL schema does not define a {kind} root type."),
+                                range,
+                            );
+                            None
+                        }
+                    },
+                    None => None,
+                };
+                linter.selection_set(&operation.selection_set, root.as_deref());
+            }
+            Definition::Fragment(fragment) => {
+                linter.directives(&fragment.directives);
+                let parent = linter.type_condition(&fragment.type_condition);
+                linter.selection_set(&fragment.selection_set, parent.as_deref());
+            }
+            Definition::TypeSystem(_) | Definition::Interpolation(_) => {}
+        }
+    }
+    linter.diagnostics
+}
+
+struct Linter<'a> {
+    path: &'a Path,
+    template: &'a TemplateStringInfo,
+    source: &'a GraphqlSource,
+    interpolations: Vec<&'a InterpolationInfo>,
+    schema: Option<Schema>,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl Linter<'_> {
+    /// Checks each selection against `parent`, the type it selects from, when
+    /// the schema knows it.
+    fn selection_set(&mut self, selection_set: &SelectionSet, parent: Option<&str>) {
+        for selection in &selection_set.selections {
+            match selection {
+                Selection::Field(field) => {
+                    self.arguments(&field.arguments);
+                    self.directives(&field.directives);
+                    let child =
+                        parent.and_then(|parent| self.field(parent, &field.name, &field.arguments));
+                    if let Some(selection_set) = &field.selection_set {
+                        self.selection_set(selection_set, child.as_deref());
+                    }
+                }
+                Selection::FragmentSpread { directives, .. } => self.directives(directives),
+                Selection::InlineFragment {
+                    type_condition,
+                    directives,
+                    selection_set,
+                    ..
+                } => {
+                    self.directives(directives);
+                    let child = match type_condition {
+                        Some(type_condition) => self.type_condition(type_condition),
+                        None => parent.map(str::to_string),
+                    };
+                    self.selection_set(selection_set, child.as_deref());
+                }
+                Selection::Interpolation(_) => {}
+            }
+        }
+    }
+
+    /// Checks a field and its arguments, returning the type it selects into.
+    fn field(
+        &mut self,
+        parent: &str,
+        name: &Range<usize>,
+        arguments: &[Argument],
+    ) -> Option<String> {
+        if self.source.has_atom(name) {
+            return None;
+        }
+        let schema = self.schema.as_ref()?;
+        let parent_type = schema.types.get(parent)?;
+        let field_name = self.source.text(name);
+        let is_root = schema.root("query") == Some(parent);
+        if field_name == "__typename" || (is_root && matches!(field_name, "__schema" | "__type")) {
+            return None;
+        }
+        let Some(field) = parent_type.fields.get(field_name) else {
+            let message = unknown_field_message(parent, parent_type, field_name);
+            self.report(RULE_UNKNOWN_FIELD, message, name.clone());
+            return None;
+        };
+
+        let mut unknown = Vec::new();
+        for argument in arguments {
+            let argument_name = self.source.text(&argument.name);
+            if self.source.has_atom(&argument.name)
+                || field.arguments.iter().any(|known| known == argument_name)
+            {
+                continue;
+            }
+            let mut message =
+                format!("Field `{parent}.{field_name}` has no argument `{argument_name}`");
+            if let Some(suggestion) =
+                closest_key(argument_name, field.arguments.iter().map(String::as_str))
+            {
+                message.push_str(&format!("; did you mean `{suggestion}`?"));
+            }
+            unknown.push((message, argument.name.clone()));
+        }
+        let child = field.ty.clone();
+        for (message, range) in unknown {
+            self.report(RULE_UNKNOWN_ARGUMENT, message, range);
+        }
+        Some(child)
+    }
+
+    /// Checks that a fragment's type condition names a type the schema
+    /// defines, returning it.
+    fn type_condition(&mut self, name: &Range<usize>) -> Option<String> {
+        if self.source.has_atom(name) {
+            return None;
+        }
+        let schema = self.schema.as_ref()?;
+        let type_name = self.source.text(name);
+        if schema.types.contains_key(type_name) {
+            return Some(type_name.to_string());
+        }
+        let mut message = format!("The GraphQL schema has no type `{type_name}`");
+        if let Some(suggestion) = closest_key(type_name, schema.types.keys().map(String::as_str)) {
+            message.push_str(&format!("; did you mean `{suggestion}`?"));
+        }
+        self.report(RULE_UNKNOWN_TYPE, message, name.clone());
+        None
+    }
+
+    fn directives(&mut self, directives: &[Directive]) {
+        for directive in directives {
+            self.arguments(&directive.arguments);
+        }
+    }
+
+    fn arguments(&mut self, arguments: &[Argument]) {
+        for argument in arguments {
+            self.value(&argument.value);
+        }
+    }
+
+    fn value(&mut self, value: &Value) {
+        match &value.kind {
+            ValueKind::Interpolation => {
+                self.interpolated_value(
+                    value.range.start,
+                    "into the query as an inline value",
+                    "the value",
+                );
+            }
+            ValueKind::String => {
+                let atoms = self
+                    .source
+                    .atoms
+                    .range(value.range.clone())
+                    .map(|(&start, _)| start)
+                    .collect::<Vec<_>>();
+                for start in atoms {
+                    self.interpolated_value(start, "into a GraphQL string", "the whole string");
+                }
+            }
+            ValueKind::List(values) => {
+                for value in values {
+                    self.value(value);
+                }
+            }
+            ValueKind::Object(fields) => self.arguments(fields),
+            ValueKind::Variable | ValueKind::Scalar => {}
+        }
+    }
+
+    fn interpolated_value(&mut self, start: usize, place: &str, what: &str) {
+        let index = self.source.atoms.range(..start).count();
+        let Some(interpolation) = self.interpolations.get(index) else {
+            return;
+        };
+        let message = format!(
+            "Interpolation '{}' is spliced {place}; declare a variable such as `${}` and pass {what} in the request variables",
+            interpolation.expression,
+            variable_name(&interpolation.expression)
+        );
+        let location = interpolation.location.clone();
+        self.diagnostics.push(self.diagnostic(
+            RULE_INTERPOLATED_VALUE,
+            LintSeverity::Error,
+            message,
+            location,
+        ));
+    }
+
+    fn report(&mut self, rule: &str, message: String, range: Range<usize>) {
+        let location = self
+            .template
+            .backend_span_to_location(&self.source.span(range));
+        self.diagnostics
+            .push(self.diagnostic(rule, LintSeverity::Error, message, location));
+    }
+
+    fn diagnostic(
+        &self,
+        rule: &str,
+        severity: LintSeverity,
+        message: String,
+        location: Location,
+    ) -> LintDiagnostic {
+        LintDiagnostic {
+            rule: rule.to_string(),
+            severity,
+            language: Some("graphql".to_string()),
+            message,
+            file: self.path.to_path_buf(),
+            start_line: location.start_line,
+            start_column: location.start_column,
+            end_line: location.end_line,
+            end_column: location.end_column,
+            expected_type: None,
+            found_type: None,
+            schema_pointer: None,
+            source_of_truth: None,
+            suggested_edits: Vec::new(),
+        }
+    }
+}
+
+fn unknown_field_message(parent: &str, parent_type: &SchemaType, field: &str) -> String {
+    if parent_type.kind == TypeKind::Union {
+        return format!(
+            "Union `{parent}` has no field `{field}`; select it inside an inline fragment such as `... on Type`"
+        );
+    }
+    let mut message = format!("Type `{parent}` has no field `{field}`");
+    if let Some(suggestion) = closest_key(field, parent_type.fields.keys().map(String::as_str)) {
+        message.push_str(&format!("; did you mean `{suggestion}`?"));
+    }
+    message
+}
+
+/// A variable name for an interpolated expression: its last identifier, so
+/// `user["id"]` suggests `$id`.
+fn variable_name(expression: &str) -> &str {
+    expression
+        .rsplit(|character: char| !(character.is_alphanumeric() || character == '_'))
+        .find(|part| {
+            !part.is_empty() && !part.starts_with(|character: char| character.is_ascii_digit())
+        })
+        .unwrap_or("value")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::lint::lint_source_with_config;
+    use crate::project_config::ProjectConfig;
+
+    fn graphql_diagnostics(query: &str, config: &ProjectConfig) -> Vec<(String, String)> {
+        let source = format!(
+            "from typing import Annotated\nfrom string.templatelib import Template\n\nuser_id = 1\nquery: Annotated[Template, \"graphql\"] = t\"\"\"{query}\"\"\"\n"
+        );
+        lint_source_with_config(&config.root.join("queries.py"), &source, config)
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn reports_interpolations_used_as_inline_values() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let config = ProjectConfig {
+            root: temp.path().to_path_buf(),
+            ..ProjectConfig::default()
+        };
+        assert_eq!(
+            graphql_diagnostics(
+                "query {{ user(id: {user_id}, filter: {{ name: \"a {user_id}\" }}) {{ id }} }}",
+                &config
+            ),
+            vec![
+                (
+                    "graphql-interpolated-value".to_string(),
+                    "Interpolation 'user_id' is spliced into the query as an inline value; declare a variable such as `$user_id` and pass the value in the request variables".to_string()
+                ),
+                (
+                    "graphql-interpolated-value".to_string(),
+                    "Interpolation 'user_id' is spliced into a GraphQL string; declare a variable such as `$user_id` and pass the whole string in the request variables".to_string()
+                ),
+            ]
+        );
+        assert!(
+            graphql_diagnostics(
+                "query User($id: ID!) {{ user(id: $id) {{ ...{user_id} }} }} {user_id}",
+                &config
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn checks_fields_arguments_and_types_against_the_configured_schema() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("schema.graphql"),
+            "type Query { user(id: ID!): User search: [Result] }\ntype User { id: ID! email: String }\ntype Post { title: String }\nunion Result = User | Post\n",
+        )
+        .expect("write schema");
+        let mut config = ProjectConfig {
+            root: temp.path().to_path_buf(),
+            ..ProjectConfig::default()
+        };
+        config.graphql.schema = Some("schema.graphql".to_string());
+
+        assert_eq!(
+            graphql_diagnostics(
+                "query($id: ID!) {{ __typename user(id: $id, locale: $id) {{ emial }} search {{ title ... on Pots {{ title }} ... on Post {{ body }} }} }}",
+                &config
+            ),
+            vec![
+                (
+                    "graphql-unknown-argument".to_string(),
+                    "Field `Query.user` has no argument `locale`".to_string()
+                ),
+                (
+                    "graphql-unknown-field".to_string(),
+                    "Type `User` has no field `emial`; did you mean `email`?".to_string()
+                ),
+                (
+                    "graphql-unknown-field".to_string(),
+                    "Union `Result` has no field `title`; select it inside an inline fragment such as `... on Type`".to_string()
+                ),
+                (
+                    "graphql-unknown-type".to_string(),
+                    "The GraphQL schema has no type `Pots`; did you mean `Post`?".to_string()
+                ),
+                (
+                    "graphql-unknown-field".to_string(),
+                    "Type `Post` has no field `body`".to_string()
+                ),
+            ]
+        );
+
+        config.graphql.schema = Some("missing.graphql".to_string());
+        assert_eq!(
+            graphql_diagnostics("{{ user {{ id }} }}", &config),
+            vec![(
+                "graphql-schema-unresolved".to_string(),
+                "Could not find GraphQL schema file 'missing.graphql'.".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use tstring_syntax::{TemplateInput, TemplateSegment};
+
+use super::syntax::{self, Definition, GraphqlSource, TypeKind};
+
+/// Names every schema defines without declaring them.
+const BUILTIN_SCALARS: &[&str] = &["Boolean", "Float", "ID", "Int", "String"];
+
+/// The types of a GraphQL schema, read from SDL, with extensions merged
+/// into the types they extend.
+#[derive(Debug, Default)]
+pub(crate) struct Schema {
+    pub(crate) types: HashMap<String, SchemaType>,
+    roots: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct SchemaType {
+    pub(crate) kind: TypeKind,
+    pub(crate) fields: HashMap<String, SchemaField>,
+}
+
+#[derive(Debug)]
+pub(crate) struct SchemaField {
+    pub(crate) arguments: Vec<String>,
+    /// The named type the field returns, without list or non-null wrappers.
+    pub(crate) ty: String,
+}
+
+impl Schema {
+    /// Reads the schema file, resolved against the project root and then the
+    /// linted file's directory.
+    pub(crate) fn load(root: &Path, path: &Path, file: &str) -> Result<Self, String> {
+        let candidates = [
+            Some(root.join(file)),
+            path.parent().map(|parent| parent.join(file)),
+        ];
+        let Some(schema_path) = candidates.into_iter().flatten().find(|path| path.is_file()) else {
+            return Err(format!("Could not find GraphQL schema file '{file}'."));
+        };
+        let text = std::fs::read_to_string(&schema_path)
+            .map_err(|error| format!("Could not load GraphQL schema file '{file}': {error}."))?;
+        Self::parse(&text)
+            .map_err(|error| format!("Could not load GraphQL schema file '{file}': {error}"))
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let input =
+            TemplateInput::from_segments(vec![TemplateSegment::StaticText(text.to_string())]);
+        let source = GraphqlSource::new(&input);
+        let document = syntax::parse(&source);
+        if let Some(error) = document.errors.first() {
+            let line = text[..error.range.start].matches('\n').count() + 1;
+            return Err(format!("{} (line {line})", error.message));
+        }
+
+        let mut schema = Self::default();
+        for definition in &document.definitions {
+            let Definition::TypeSystem(definition) = definition else {
+                continue;
+            };
+            if definition.kind == TypeKind::Schema {
+                for field in &definition.fields {
+                    if let Some(ty) = &field.ty {
+                        schema.roots.insert(
+                            source.text(&field.name).to_string(),
+                            source.text(&ty.name).to_string(),
+                        );
+                    }
+                }
+                continue;
+            }
+            let Some(name) = &definition.name else {
+                continue;
+            };
+            if definition.kind == TypeKind::Directive {
+                continue;
+            }
+            let ty = schema
+                .types
+                .entry(source.text(name).to_string())
+                .or_insert_with(|| SchemaType {
+                    kind: definition.kind,
+                    fields: HashMap::new(),
+                });
+            for field in &definition.fields {
+                ty.fields.insert(
+                    source.text(&field.name).to_string(),
+                    SchemaField {
+                        arguments: field
+                            .arguments
+                            .iter()
+                            .map(|argument| source.text(&argument.name).to_string())
+                            .collect(),
+                        ty: field
+                            .ty
+                            .as_ref()
+                            .map(|ty| source.text(&ty.name).to_string())
+                            .unwrap_or_default(),
+                    },
+                );
+            }
+        }
+        for scalar in BUILTIN_SCALARS {
+            schema
+                .types
+                .entry((*scalar).to_string())
+                .or_insert_with(|| SchemaType {
+                    kind: TypeKind::Scalar,
+                    fields: HashMap::new(),
+                });
+        }
+        Ok(schema)
+    }
+
+    /// The root type of `query`, `mutation`, or `subscription` operations,
+    /// from the schema definition or else the conventional type name.
+    pub(crate) fn root(&self, operation: &str) -> Option<&str> {
+        if let Some(root) = self.roots.get(operation) {
+            return Some(root);
+        }
+        if !self.roots.is_empty() {
+            return None;
+        }
+        let name = match operation {
+            "query" => "Query",
+            "mutation" => "Mutation",
+            "subscription" => "Subscription",
+            _ => return None,
+        };
+        self.types.contains_key(name).then_some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_types_fields_and_roots_and_merges_extensions() {
+        let schema = Schema::parse(
+            r#"
+"""The API."""
+schema { query: RootQuery }
+type RootQuery { user(id: ID!, "Unused" locale: String = "en"): User }
+type User implements Node & Named @key(fields: "id") { id: ID! name: String }
+extend type User { posts(first: Int): [Post!]! }
+union Result = | User | Post
+enum Role { ADMIN USER }
+directive @key(fields: String!) repeatable on OBJECT | INTERFACE
+"#,
+        )
+        .expect("schema parses");
+
+        assert_eq!(schema.root("query"), Some("RootQuery"));
+        assert_eq!(schema.root("mutation"), None);
+        let user = &schema.types["User"];
+        assert_eq!(user.kind, TypeKind::Object);
+        assert_eq!(user.fields["posts"].ty, "Post");
+        assert_eq!(user.fields["posts"].arguments, vec!["first"]);
+        assert_eq!(
+            schema.types["RootQuery"].fields["user"].arguments,
+            vec!["id", "locale"]
+        );
+        assert_eq!(schema.types["Result"].kind, TypeKind::Union);
+        assert!(schema.types.contains_key("ID"));
+
+        let error = Schema::parse("type User {\n  id: ID!\n  name\n}").expect_err("invalid");
+        assert_eq!(error, "Expected `:`, found `}`. (line 4)");
+    }
+}
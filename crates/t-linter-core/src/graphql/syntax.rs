@@ -0,0 +1,1324 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use tstring_syntax::{
+    BackendError, BackendResult, SourcePosition, SourceSpan, TemplateInput, TemplateSegment,
+};
+
+use crate::parser::{TemplatePart, TemplateStringInfo};
+use crate::template_text;
+
+/// Words that start a type system definition rather than an operation.
+const TYPE_SYSTEM_KEYWORDS: &[&str] = &[
+    "directive",
+    "enum",
+    "extend",
+    "input",
+    "interface",
+    "scalar",
+    "schema",
+    "type",
+    "union",
+];
+
+/// Checks that the template is a GraphQL document whose operations declare
+/// every variable they use, reporting the first problem at its span.
+pub fn check_template(input: &TemplateInput) -> BackendResult<()> {
+    let source = GraphqlSource::new(input);
+    let document = parse(&source);
+    validate(&source, &document)
+}
+
+/// Reports the first syntax error in the document, then the first operation
+/// that is not valid on its own.
+pub(crate) fn validate(source: &GraphqlSource, document: &Document) -> BackendResult<()> {
+    if let Some(error) = document.errors.first() {
+        return Err(BackendError::parse_at(
+            "graphql.parse",
+            error.message.clone(),
+            source.span(error.range.clone()),
+        ));
+    }
+    if let Some(error) = operation_errors(source, document).first() {
+        return Err(BackendError::semantic_at(
+            "graphql.validation",
+            error.message.clone(),
+            source.span(error.range.clone()),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) struct GraphqlSource {
+    /// The template with each interpolation's source text in place.
+    pub(crate) text: String,
+    /// The start of each interpolation mapped to its end.
+    pub(crate) atoms: BTreeMap<usize, usize>,
+    /// The byte range of each template segment and whether it is an
+    /// interpolation, for mapping offsets back to backend spans.
+    segments: Vec<(Range<usize>, bool)>,
+}
+
+impl GraphqlSource {
+    pub(crate) fn new(input: &TemplateInput) -> Self {
+        let mut text = String::new();
+        let mut atoms = BTreeMap::new();
+        let mut segments = Vec::new();
+        for segment in &input.segments {
+            let start = text.len();
+            match segment {
+                TemplateSegment::StaticText(static_text) => {
+                    text.push_str(static_text);
+                    segments.push((start..text.len(), false));
+                }
+                TemplateSegment::Interpolation(interpolation) => {
+                    text.push_str(interpolation.raw_source.as_deref().unwrap_or("{}"));
+                    atoms.insert(start, text.len());
+                    segments.push((start..text.len(), true));
+                }
+            }
+        }
+        Self {
+            text,
+            atoms,
+            segments,
+        }
+    }
+
+    /// The template content as the highlighter sees it, where every
+    /// interpolation takes two bytes.
+    pub(crate) fn from_template(template: &TemplateStringInfo) -> Self {
+        let mut atoms = BTreeMap::new();
+        let mut offset = 0;
+        for part in &template.parts {
+            match part {
+                TemplatePart::Static(part) => offset += part.text.len(),
+                TemplatePart::Interpolation(_) => {
+                    atoms.insert(offset, offset + 2);
+                    offset += 2;
+                }
+            }
+        }
+        Self {
+            text: template.content.clone(),
+            atoms,
+            segments: Vec::new(),
+        }
+    }
+
+    pub(crate) fn span(&self, range: Range<usize>) -> SourceSpan {
+        SourceSpan::between(
+            self.position(range.start, false),
+            self.position(range.end, true),
+        )
+    }
+
+    /// The template position of a byte offset. An interpolation spans
+    /// offsets `0..2` of its own token.
+    fn position(&self, offset: usize, is_end: bool) -> SourcePosition {
+        for (token_index, (range, is_interpolation)) in self.segments.iter().enumerate() {
+            let within = if is_end {
+                range.start < offset && offset <= range.end
+            } else {
+                range.start <= offset && offset < range.end
+            };
+            if !within {
+                continue;
+            }
+            let offset = if *is_interpolation {
+                if is_end { 2 } else { 0 }
+            } else {
+                self.text[range.start..offset].chars().count()
+            };
+            return SourcePosition {
+                token_index,
+                offset,
+            };
+        }
+        SourcePosition {
+            token_index: self.segments.len().saturating_sub(1),
+            offset: 0,
+        }
+    }
+
+    pub(crate) fn template_text(&self, range: Range<usize>) -> String {
+        template_text::template_text(&self.text, &self.atoms, range)
+    }
+
+    pub(crate) fn has_atom(&self, range: &Range<usize>) -> bool {
+        template_text::has_atom(&self.atoms, range)
+    }
+
+    pub(crate) fn text(&self, range: &Range<usize>) -> &str {
+        &self.text[range.clone()]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SyntaxError {
+    pub(crate) message: String,
+    pub(crate) range: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// A name, possibly with interpolations inside it.
+    Name,
+    /// An interpolation standing on its own.
+    Interpolation,
+    /// `$` and the variable name.
+    Variable,
+    Int,
+    Float,
+    String,
+    BlockString,
+    Punctuator,
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) range: Range<usize>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Document {
+    pub(crate) definitions: Vec<Definition>,
+    pub(crate) comments: Vec<Range<usize>>,
+    pub(crate) errors: Vec<SyntaxError>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Definition {
+    Operation(Operation),
+    Fragment(Fragment),
+    TypeSystem(TypeDefinition),
+    Interpolation(Range<usize>),
+}
+
+impl Definition {
+    pub(crate) fn range(&self) -> Range<usize> {
+        match self {
+            Self::Operation(operation) => operation.range.clone(),
+            Self::Fragment(fragment) => fragment.range.clone(),
+            Self::TypeSystem(definition) => definition.range.clone(),
+            Self::Interpolation(range) => range.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Operation {
+    pub(crate) range: Range<usize>,
+    /// `query`, `mutation`, or `subscription`; `None` for the `{ ... }`
+    /// shorthand.
+    pub(crate) keyword: Option<Range<usize>>,
+    pub(crate) name: Option<Range<usize>>,
+    pub(crate) variables: Vec<VariableDefinition>,
+    pub(crate) directives: Vec<Directive>,
+    pub(crate) selection_set: SelectionSet,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct VariableDefinition {
+    pub(crate) variable: Range<usize>,
+    pub(crate) ty: TypeRef,
+    pub(crate) default: Option<Value>,
+    pub(crate) directives: Vec<Directive>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Fragment {
+    pub(crate) range: Range<usize>,
+    pub(crate) name: Range<usize>,
+    pub(crate) type_condition: Range<usize>,
+    pub(crate) directives: Vec<Directive>,
+    pub(crate) selection_set: SelectionSet,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SelectionSet {
+    pub(crate) range: Range<usize>,
+    pub(crate) selections: Vec<Selection>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Selection {
+    Field(Field),
+    FragmentSpread {
+        range: Range<usize>,
+        name: Range<usize>,
+        directives: Vec<Directive>,
+    },
+    InlineFragment {
+        range: Range<usize>,
+        type_condition: Option<Range<usize>>,
+        directives: Vec<Directive>,
+        selection_set: SelectionSet,
+    },
+    Interpolation(Range<usize>),
+}
+
+impl Selection {
+    pub(crate) fn range(&self) -> Range<usize> {
+        match self {
+            Self::Field(field) => field.range.clone(),
+            Self::FragmentSpread { range, .. }
+            | Self::InlineFragment { range, .. }
+            | Self::Interpolation(range) => range.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Field {
+    pub(crate) range: Range<usize>,
+    pub(crate) alias: Option<Range<usize>>,
+    pub(crate) name: Range<usize>,
+    pub(crate) arguments: Vec<Argument>,
+    pub(crate) directives: Vec<Directive>,
+    pub(crate) selection_set: Option<SelectionSet>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Argument {
+    pub(crate) name: Range<usize>,
+    pub(crate) value: Value,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Directive {
+    pub(crate) name: Range<usize>,
+    pub(crate) arguments: Vec<Argument>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Value {
+    pub(crate) range: Range<usize>,
+    pub(crate) kind: ValueKind,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ValueKind {
+    Variable,
+    /// A string or block string, which may have interpolations inside it.
+    String,
+    /// A number, boolean, `null`, or enum value.
+    Scalar,
+    List(Vec<Value>),
+    Object(Vec<Argument>),
+    Interpolation,
+}
+
+/// A possibly wrapped type such as `[ID!]!`, with the name it wraps.
+#[derive(Debug, Clone)]
+pub(crate) struct TypeRef {
+    pub(crate) range: Range<usize>,
+    pub(crate) name: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TypeKind {
+    Schema,
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+    Directive,
+}
+
+/// A type system definition or extension. Schema definitions list their
+/// root operation types as fields, enums their values, and directive
+/// definitions their arguments.
+#[derive(Debug, Clone)]
+pub(crate) struct TypeDefinition {
+    pub(crate) range: Range<usize>,
+    pub(crate) kind: TypeKind,
+    pub(crate) name: Option<Range<usize>>,
+    pub(crate) fields: Vec<FieldDefinition>,
+    /// Implemented interfaces, or the members of a union.
+    pub(crate) members: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct FieldDefinition {
+    pub(crate) name: Range<usize>,
+    pub(crate) arguments: Vec<FieldDefinition>,
+    pub(crate) ty: Option<TypeRef>,
+}
+
+pub(crate) fn parse(source: &GraphqlSource) -> Document {
+    let (tokens, mut errors) = tokenize(source);
+    let (comments, tokens): (Vec<_>, Vec<_>) = tokens
+        .into_iter()
+        .partition(|token| token.kind == TokenKind::Comment);
+    let mut parser = Parser {
+        source,
+        tokens,
+        index: 0,
+        last_end: 0,
+    };
+    let definitions = match parser.document() {
+        Ok(definitions) => definitions,
+        Err(error) => {
+            errors.push(error);
+            Vec::new()
+        }
+    };
+    errors.sort_by_key(|error| error.range.start);
+    Document {
+        definitions,
+        comments: comments.into_iter().map(|token| token.range).collect(),
+        errors,
+    }
+}
+
+/// Splits the template into tokens, skipping whitespace and commas and
+/// recovering from bad characters, numbers, and strings.
+pub(crate) fn tokenize(source: &GraphqlSource) -> (Vec<Token>, Vec<SyntaxError>) {
+    let mut lexer = Lexer {
+        source,
+        offset: 0,
+        tokens: Vec::new(),
+        errors: Vec::new(),
+    };
+    lexer.run();
+    (lexer.tokens, lexer.errors)
+}
+
+struct Lexer<'a> {
+    source: &'a GraphqlSource,
+    offset: usize,
+    tokens: Vec<Token>,
+    errors: Vec<SyntaxError>,
+}
+
+impl Lexer<'_> {
+    fn run(&mut self) {
+        let text = &self.source.text;
+        while self.offset < text.len() {
+            let start = self.offset;
+            if self.source.atoms.contains_key(&start) {
+                self.name(start);
+                continue;
+            }
+            let Some(character) = text[start..].chars().next() else {
+                break;
+            };
+            match character {
+                character if character.is_whitespace() || character == ',' => {
+                    self.offset += character.len_utf8();
+                }
+                '\u{feff}' => self.offset += character.len_utf8(),
+                '#' => {
+                    self.skip_until(|character| matches!(character, '\n' | '\r'));
+                    self.push(TokenKind::Comment, start);
+                }
+                '"' if text[start..].starts_with("\"\"\"") => self.block_string(start),
+                '"' => self.string(start),
+                '$' => {
+                    self.offset += 1;
+                    self.name_run();
+                    if self.offset == start + 1 {
+                        self.error("Expected a variable name after `$`.", start..start + 1);
+                    } else {
+                        self.push(TokenKind::Variable, start);
+                    }
+                }
+                '.' if text[start..].starts_with("...") => {
+                    self.offset += 3;
+                    self.push(TokenKind::Punctuator, start);
+                }
+                '!' | '&' | '(' | ')' | ':' | '=' | '@' | '[' | ']' | '{' | '|' | '}' => {
+                    self.offset += 1;
+                    self.push(TokenKind::Punctuator, start);
+                }
+                '_' | 'a'..='z' | 'A'..='Z' => self.name(start),
+                '-' | '0'..='9' => self.number(start),
+                character => {
+                    self.offset += character.len_utf8();
+                    let message = if character == '.' {
+                        "Unexpected character `.`; spreads are written `...`.".to_string()
+                    } else {
+                        format!("Unexpected character `{character}`.")
+                    };
+                    self.error(message, start..self.offset);
+                }
+            }
+        }
+    }
+
+    /// A name, which interpolations may stand in for or be part of.
+    fn name(&mut self, start: usize) {
+        self.name_run();
+        let kind = if self.source.atoms.get(&start) == Some(&self.offset) {
+            TokenKind::Interpolation
+        } else {
+            TokenKind::Name
+        };
+        self.push(kind, start);
+    }
+
+    fn name_run(&mut self) {
+        let text = &self.source.text;
+        while self.offset < text.len() {
+            if let Some(&end) = self.source.atoms.get(&self.offset) {
+                self.offset = end;
+            } else if text.as_bytes()[self.offset].is_ascii_alphanumeric()
+                || text.as_bytes()[self.offset] == b'_'
+            {
+                self.offset += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self, start: usize) {
+        let bytes = self.source.text.as_bytes();
+        let digits = |offset: &mut usize| {
+            let begin = *offset;
+            while *offset < bytes.len() && bytes[*offset].is_ascii_digit() {
+                *offset += 1;
+            }
+            *offset > begin
+        };
+        let mut offset = start;
+        if bytes[offset] == b'-' {
+            offset += 1;
+        }
+        let integer_start = offset;
+        let mut valid = digits(&mut offset);
+        if valid && bytes[integer_start] == b'0' && offset - integer_start > 1 {
+            valid = false;
+        }
+        let mut kind = TokenKind::Int;
+        if offset < bytes.len() && bytes[offset] == b'.' {
+            offset += 1;
+            valid &= digits(&mut offset);
+            kind = TokenKind::Float;
+        }
+        if offset < bytes.len() && matches!(bytes[offset], b'e' | b'E') {
+            offset += 1;
+            if offset < bytes.len() && matches!(bytes[offset], b'+' | b'-') {
+                offset += 1;
+            }
+            valid &= digits(&mut offset);
+            kind = TokenKind::Float;
+        }
+        // A number runs straight into a following name, dot, or interpolation.
+        if offset < bytes.len()
+            && (bytes[offset].is_ascii_alphanumeric()
+                || matches!(bytes[offset], b'_' | b'.')
+                || self.source.atoms.contains_key(&offset))
+        {
+            valid = false;
+            self.offset = offset;
+            self.name_run();
+            offset = self.offset.max(offset + 1);
+        }
+        self.offset = offset;
+        if valid {
+            self.push(kind, start);
+        } else {
+            let number = self.source.text[start..offset].to_string();
+            self.error(format!("Invalid number `{number}`."), start..offset);
+        }
+    }
+
+    fn string(&mut self, start: usize) {
+        let text = &self.source.text;
+        self.offset += 1;
+        while self.offset < text.len() {
+            if let Some(&end) = self.source.atoms.get(&self.offset) {
+                self.offset = end;
+                continue;
+            }
+            let Some(character) = text[self.offset..].chars().next() else {
+                break;
+            };
+            match character {
+                '"' => {
+                    self.offset += 1;
+                    self.push(TokenKind::String, start);
+                    return;
+                }
+                '\n' | '\r' => break,
+                '\\' => {
+                    let escape_start = self.offset;
+                    self.offset += 1;
+                    let valid = match text[self.offset..].chars().next() {
+                        Some('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't') => {
+                            self.offset += 1;
+                            true
+                        }
+                        Some('u') => {
+                            let hex = text[self.offset + 1..]
+                                .bytes()
+                                .take(4)
+                                .take_while(u8::is_ascii_hexdigit)
+                                .count();
+                            self.offset += 1 + hex;
+                            hex == 4
+                        }
+                        _ => false,
+                    };
+                    if !valid {
+                        let escape = text[escape_start..]
+                            .chars()
+                            .take((self.offset - escape_start).max(2))
+                            .collect::<String>();
+                        let end = escape_start + escape.len();
+                        self.offset = self.offset.max(end);
+                        self.error(
+                            format!("Invalid escape sequence `{escape}` in string."),
+                            escape_start..end,
+                        );
+                    }
+                }
+                character => self.offset += character.len_utf8(),
+            }
+        }
+        self.error("Unterminated string.", start..self.offset);
+    }
+
+    fn block_string(&mut self, start: usize) {
+        let text = &self.source.text;
+        self.offset += 3;
+        while self.offset < text.len() {
+            if let Some(&end) = self.source.atoms.get(&self.offset) {
+                self.offset = end;
+            } else if text[self.offset..].starts_with("\\\"\"\"") {
+                self.offset += 4;
+            } else if text[self.offset..].starts_with("\"\"\"") {
+                self.offset += 3;
+                self.push(TokenKind::BlockString, start);
+                return;
+            } else {
+                self.offset += text[self.offset..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        self.error("Unterminated block string.", start..self.offset);
+    }
+
+    fn skip_until(&mut self, stop: impl Fn(char) -> bool) {
+        let text = &self.source.text;
+        while self.offset < text.len() {
+            if let Some(&end) = self.source.atoms.get(&self.offset) {
+                self.offset = end;
+                continue;
+            }
+            let Some(character) = text[self.offset..].chars().next() else {
+                break;
+            };
+            if stop(character) {
+                break;
+            }
+            self.offset += character.len_utf8();
+        }
+    }
+
+    fn push(&mut self, kind: TokenKind, start: usize) {
+        self.tokens.push(Token {
+            kind,
+            range: start..self.offset,
+        });
+    }
+
+    fn error(&mut self, message: impl Into<String>, range: Range<usize>) {
+        self.errors.push(SyntaxError {
+            message: message.into(),
+            range,
+        });
+    }
+}
+
+type ParseResult<T> = Result<T, SyntaxError>;
+
+struct Parser<'a> {
+    source: &'a GraphqlSource,
+    tokens: Vec<Token>,
+    index: usize,
+    last_end: usize,
+}
+
+impl Parser<'_> {
+    fn document(&mut self) -> ParseResult<Vec<Definition>> {
+        let mut definitions = Vec::new();
+        while let Some(token) = self.peek() {
+            let definition = match token.kind {
+                TokenKind::Interpolation => Definition::Interpolation(self.advance().range),
+                TokenKind::Punctuator if self.at("{") => Definition::Operation(self.operation()?),
+                TokenKind::String | TokenKind::BlockString => {
+                    Definition::TypeSystem(self.type_system()?)
+                }
+                TokenKind::Name => match self.source.text(&token.range) {
+                    "query" | "mutation" | "subscription" => {
+                        Definition::Operation(self.operation()?)
+                    }
+                    "fragment" => Definition::Fragment(self.fragment()?),
+                    keyword if TYPE_SYSTEM_KEYWORDS.contains(&keyword) => {
+                        Definition::TypeSystem(self.type_system()?)
+                    }
+                    _ => {
+                        return Err(self.unexpected(
+                            "Expected `query`, `mutation`, `subscription`, `fragment`, or `{`",
+                        ));
+                    }
+                },
+                _ => {
+                    return Err(self.unexpected(
+                        "Expected `query`, `mutation`, `subscription`, `fragment`, or `{`",
+                    ));
+                }
+            };
+            definitions.push(definition);
+        }
+        if definitions.is_empty() {
+            return Err(SyntaxError {
+                message: "GraphQL documents must contain at least one definition.".to_string(),
+                range: 0..self.source.text.len(),
+            });
+        }
+        Ok(definitions)
+    }
+
+    fn operation(&mut self) -> ParseResult<Operation> {
+        let start = self.start();
+        let mut operation = Operation {
+            range: start..start,
+            keyword: None,
+            name: None,
+            variables: Vec::new(),
+            directives: Vec::new(),
+            selection_set: SelectionSet {
+                range: start..start,
+                selections: Vec::new(),
+            },
+        };
+        if !self.at("{") {
+            operation.keyword = Some(self.advance().range);
+            if self.at_name() {
+                operation.name = Some(self.advance().range);
+            }
+            if self.eat("(") {
+                loop {
+                    operation.variables.push(self.variable_definition()?);
+                    if self.eat(")") {
+                        break;
+                    }
+                }
+            }
+            operation.directives = self.directives(false)?;
+        }
+        operation.selection_set = self.selection_set()?;
+        operation.range = start..self.last_end;
+        Ok(operation)
+    }
+
+    fn variable_definition(&mut self) -> ParseResult<VariableDefinition> {
+        let Some(variable) = self
+            .peek()
+            .filter(|token| token.kind == TokenKind::Variable)
+        else {
+            return Err(self.unexpected("Expected a variable such as `$id`"));
+        };
+        let variable = variable.range.clone();
+        self.advance();
+        self.expect(":")?;
+        let ty = self.type_ref()?;
+        let default = if self.eat("=") {
+            Some(self.value(true)?)
+        } else {
+            None
+        };
+        Ok(VariableDefinition {
+            variable,
+            ty,
+            default,
+            directives: self.directives(true)?,
+        })
+    }
+
+    fn fragment(&mut self) -> ParseResult<Fragment> {
+        let start = self.start();
+        self.advance();
+        if self.at_keyword("on") {
+            return Err(self.unexpected("Expected a fragment name"));
+        }
+        let name = self.name("Expected a fragment name")?;
+        self.expect_keyword("on")?;
+        let type_condition = self.name("Expected a type name")?;
+        let directives = self.directives(false)?;
+        let selection_set = self.selection_set()?;
+        Ok(Fragment {
+            range: start..self.last_end,
+            name,
+            type_condition,
+            directives,
+            selection_set,
+        })
+    }
+
+    fn selection_set(&mut self) -> ParseResult<SelectionSet> {
+        let start = self.start();
+        self.expect("{")?;
+        let mut selections = Vec::new();
+        loop {
+            selections.push(self.selection()?);
+            if self.eat("}") {
+                break;
+            }
+            if self.peek().is_none() {
+                return Err(self.unexpected("Expected `}`"));
+            }
+        }
+        Ok(SelectionSet {
+            range: start..self.last_end,
+            selections,
+        })
+    }
+
+    fn selection(&mut self) -> ParseResult<Selection> {
+        let start = self.start();
+        if self.eat("...") {
+            if self.at_keyword("on") || self.at("@") || self.at("{") {
+                let type_condition = if self.at_keyword("on") {
+                    self.advance();
+                    Some(self.name("Expected a type name")?)
+                } else {
+                    None
+                };
+                let directives = self.directives(false)?;
+                let selection_set = self.selection_set()?;
+                return Ok(Selection::InlineFragment {
+                    range: start..self.last_end,
+                    type_condition,
+                    directives,
+                    selection_set,
+                });
+            }
+            let name = self.name("Expected a fragment name or `on`")?;
+            let directives = self.directives(false)?;
+            return Ok(Selection::FragmentSpread {
+                range: start..self.last_end,
+                name,
+                directives,
+            });
+        }
+        if self
+            .peek()
+            .is_some_and(|token| token.kind == TokenKind::Interpolation)
+            && !self
+                .tokens
+                .get(self.index + 1)
+                .is_some_and(|token| ["(", ":", "@", "{"].contains(&self.source.text(&token.range)))
+        {
+            return Ok(Selection::Interpolation(self.advance().range));
+        }
+        if !self.at_name() {
+            return Err(self.unexpected("Expected a field, fragment spread, or inline fragment"));
+        }
+        let mut name = self.advance().range;
+        let mut alias = None;
+        if self.eat(":") {
+            alias = Some(name);
+            name = self.name("Expected a field name")?;
+        }
+        let arguments = self.arguments(false)?;
+        let directives = self.directives(false)?;
+        let selection_set = if self.at("{") {
+            Some(self.selection_set()?)
+        } else {
+            None
+        };
+        Ok(Selection::Field(Field {
+            range: start..self.last_end,
+            alias,
+            name,
+            arguments,
+            directives,
+            selection_set,
+        }))
+    }
+
+    fn arguments(&mut self, constant: bool) -> ParseResult<Vec<Argument>> {
+        let mut arguments = Vec::new();
+        if !self.eat("(") {
+            return Ok(arguments);
+        }
+        loop {
+            let name = self.name("Expected an argument name")?;
+            self.expect(":")?;
+            arguments.push(Argument {
+                name,
+                value: self.value(constant)?,
+            });
+            if self.eat(")") {
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn directives(&mut self, constant: bool) -> ParseResult<Vec<Directive>> {
+        let mut directives = Vec::new();
+        while self.eat("@") {
+            let name = self.name("Expected a directive name")?;
+            directives.push(Directive {
+                name,
+                arguments: self.arguments(constant)?,
+            });
+        }
+        Ok(directives)
+    }
+
+    /// A value; `constant` values, such as variable defaults, may not use
+    /// variables.
+    fn value(&mut self, constant: bool) -> ParseResult<Value> {
+        let start = self.start();
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.unexpected("Expected a value"));
+        };
+        let kind = match token.kind {
+            TokenKind::Variable if constant => {
+                return Err(SyntaxError {
+                    message: format!(
+                        "Variable `{}` cannot be used in a constant value.",
+                        self.source.text(&token.range)
+                    ),
+                    range: token.range,
+                });
+            }
+            TokenKind::Variable => ValueKind::Variable,
+            TokenKind::String | TokenKind::BlockString => ValueKind::String,
+            TokenKind::Int | TokenKind::Float | TokenKind::Name => ValueKind::Scalar,
+            TokenKind::Interpolation => ValueKind::Interpolation,
+            TokenKind::Punctuator if self.at("[") => {
+                self.advance();
+                let mut values = Vec::new();
+                while !self.eat("]") {
+                    values.push(self.value(constant)?);
+                }
+                return Ok(Value {
+                    range: start..self.last_end,
+                    kind: ValueKind::List(values),
+                });
+            }
+            TokenKind::Punctuator if self.at("{") => {
+                self.advance();
+                let mut fields = Vec::new();
+                while !self.eat("}") {
+                    let name = self.name("Expected an input field name")?;
+                    self.expect(":")?;
+                    fields.push(Argument {
+                        name,
+                        value: self.value(constant)?,
+                    });
+                }
+                return Ok(Value {
+                    range: start..self.last_end,
+                    kind: ValueKind::Object(fields),
+                });
+            }
+            _ => return Err(self.unexpected("Expected a value")),
+        };
+        self.advance();
+        Ok(Value {
+            range: token.range,
+            kind,
+        })
+    }
+
+    fn type_ref(&mut self) -> ParseResult<TypeRef> {
+        let start = self.start();
+        let name = if self.eat("[") {
+            let inner = self.type_ref()?;
+            self.expect("]")?;
+            inner.name
+        } else {
+            self.name("Expected a type")?
+        };
+        self.eat("!");
+        Ok(TypeRef {
+            range: start..self.last_end,
+            name,
+        })
+    }
+
+    fn type_system(&mut self) -> ParseResult<TypeDefinition> {
+        let start = self.start();
+        self.description();
+        if self.at_keyword("extend") {
+            self.advance();
+        }
+        let keyword = match self.peek() {
+            Some(token) if token.kind == TokenKind::Name => self.source.text(&token.range),
+            _ => "",
+        };
+        let kind = match keyword {
+            "schema" => TypeKind::Schema,
+            "scalar" => TypeKind::Scalar,
+            "type" => TypeKind::Object,
+            "interface" => TypeKind::Interface,
+            "union" => TypeKind::Union,
+            "enum" => TypeKind::Enum,
+            "input" => TypeKind::InputObject,
+            "directive" => TypeKind::Directive,
+            _ => return Err(self.unexpected("Expected a type system definition")),
+        };
+        self.advance();
+        let mut definition = TypeDefinition {
+            range: start..start,
+            kind,
+            name: None,
+            fields: Vec::new(),
+            members: Vec::new(),
+        };
+        match kind {
+            TypeKind::Schema => {
+                self.directives(true)?;
+                if self.eat("{") {
+                    while !self.eat("}") {
+                        let name = self.name("Expected `query`, `mutation`, or `subscription`")?;
+                        self.expect(":")?;
+                        let ty = self.type_ref()?;
+                        definition.fields.push(FieldDefinition {
+                            name,
+                            arguments: Vec::new(),
+                            ty: Some(ty),
+                        });
+                    }
+                }
+            }
+            TypeKind::Directive => {
+                self.expect("@")?;
+                definition.name = Some(self.name("Expected a directive name")?);
+                if self.eat("(") {
+                    definition.fields = self.input_values(")")?;
+                }
+                if self.at_keyword("repeatable") {
+                    self.advance();
+                }
+                self.expect_keyword("on")?;
+                self.eat("|");
+                loop {
+                    self.name("Expected a directive location")?;
+                    if !self.eat("|") {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                definition.name = Some(self.name("Expected a type name")?);
+                if self.at_keyword("implements") {
+                    self.advance();
+                    self.eat("&");
+                    loop {
+                        definition
+                            .members
+                            .push(self.name("Expected an interface name")?);
+                        if !self.eat("&") {
+                            break;
+                        }
+                    }
+                }
+                self.directives(true)?;
+                if kind == TypeKind::Union && self.eat("=") {
+                    self.eat("|");
+                    loop {
+                        definition.members.push(self.name("Expected a type name")?);
+                        if !self.eat("|") {
+                            break;
+                        }
+                    }
+                }
+                if self.eat("{") {
+                    definition.fields = match kind {
+                        TypeKind::Object | TypeKind::Interface => self.field_definitions()?,
+                        TypeKind::InputObject => self.input_values("}")?,
+                        TypeKind::Enum => self.enum_values()?,
+                        _ => return Err(self.unexpected("Expected the end of the definition")),
+                    };
+                }
+            }
+        }
+        definition.range = start..self.last_end;
+        Ok(definition)
+    }
+
+    fn field_definitions(&mut self) -> ParseResult<Vec<FieldDefinition>> {
+        let mut fields = Vec::new();
+        while !self.eat("}") {
+            self.description();
+            let name = self.name("Expected a field name")?;
+            let arguments = if self.eat("(") {
+                self.input_values(")")?
+            } else {
+                Vec::new()
+            };
+            self.expect(":")?;
+            let ty = self.type_ref()?;
+            self.directives(true)?;
+            fields.push(FieldDefinition {
+                name,
+                arguments,
+                ty: Some(ty),
+            });
+        }
+        Ok(fields)
+    }
+
+    fn input_values(&mut self, close: &str) -> ParseResult<Vec<FieldDefinition>> {
+        let mut values = Vec::new();
+        while !self.eat(close) {
+            self.description();
+            let name = self.name("Expected an argument name")?;
+            self.expect(":")?;
+            let ty = self.type_ref()?;
+            if self.eat("=") {
+                self.value(true)?;
+            }
+            self.directives(true)?;
+            values.push(FieldDefinition {
+                name,
+                arguments: Vec::new(),
+                ty: Some(ty),
+            });
+        }
+        Ok(values)
+    }
+
+    fn enum_values(&mut self) -> ParseResult<Vec<FieldDefinition>> {
+        let mut values = Vec::new();
+        while !self.eat("}") {
+            self.description();
+            let name = self.name("Expected an enum value")?;
+            self.directives(true)?;
+            values.push(FieldDefinition {
+                name,
+                arguments: Vec::new(),
+                ty: None,
+            });
+        }
+        Ok(values)
+    }
+
+    fn description(&mut self) {
+        if self
+            .peek()
+            .is_some_and(|token| matches!(token.kind, TokenKind::String | TokenKind::BlockString))
+        {
+            self.advance();
+        }
+    }
+
+    fn name(&mut self, expected: &str) -> ParseResult<Range<usize>> {
+        if self.at_name() {
+            Ok(self.advance().range)
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    fn expect(&mut self, punctuator: &str) -> ParseResult<()> {
+        if self.eat(punctuator) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("Expected `{punctuator}`")))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> ParseResult<()> {
+        if self.at_keyword(keyword) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("Expected `{keyword}`")))
+        }
+    }
+
+    fn eat(&mut self, punctuator: &str) -> bool {
+        let matched = self.at(punctuator);
+        if matched {
+            self.advance();
+        }
+        matched
+    }
+
+    fn at(&self, punctuator: &str) -> bool {
+        self.peek().is_some_and(|token| {
+            token.kind == TokenKind::Punctuator && self.source.text(&token.range) == punctuator
+        })
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|token| {
+            token.kind == TokenKind::Name && self.source.text(&token.range) == keyword
+        })
+    }
+
+    fn at_name(&self) -> bool {
+        self.peek()
+            .is_some_and(|token| matches!(token.kind, TokenKind::Name | TokenKind::Interpolation))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.index].clone();
+        self.index += 1;
+        self.last_end = token.range.end;
+        token
+    }
+
+    fn start(&self) -> usize {
+        self.peek().map_or(self.last_end, |token| token.range.start)
+    }
+
+    /// An error at the next token, or at the last one when the template
+    /// ends early.
+    fn unexpected(&self, expected: &str) -> SyntaxError {
+        let Some(token) = self.peek() else {
+            let range = self
+                .tokens
+                .last()
+                .map_or(0..self.source.text.len(), |token| token.range.clone());
+            return SyntaxError {
+                message: format!("{expected}, found the end of the template."),
+                range,
+            };
+        };
+        let found = match token.kind {
+            TokenKind::Interpolation => "an interpolation".to_string(),
+            TokenKind::String | TokenKind::BlockString => "a string".to_string(),
+            _ => format!("`{}`", self.source.text(&token.range)),
+        };
+        SyntaxError {
+            message: format!("{expected}, found {found}."),
+            range: token.range.clone(),
+        }
+    }
+}
+
+/// Operations must be named uniquely unless there is only one, and may only
+/// use variables they declare. Variables used in fragments are not checked,
+/// since fragments may be spread into several operations.
+fn operation_errors(source: &GraphqlSource, document: &Document) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    let operations = document
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Operation(operation) => Some(operation),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut names = HashSet::new();
+    for operation in &operations {
+        match &operation.name {
+            None if operations.len() > 1 => errors.push(SyntaxError {
+                message: "Anonymous operations must be the only operation in the document."
+                    .to_string(),
+                range: operation.keyword.clone().unwrap_or_else(|| {
+                    operation.selection_set.range.start..operation.selection_set.range.start + 1
+                }),
+            }),
+            Some(name) if !source.has_atom(name) && !names.insert(source.text(name)) => {
+                errors.push(SyntaxError {
+                    message: format!(
+                        "Operation `{}` is defined more than once.",
+                        source.text(name)
+                    ),
+                    range: name.clone(),
+                });
+            }
+            _ => {}
+        }
+
+        let mut declared = HashSet::new();
+        for definition in &operation.variables {
+            let variable = source.text(&definition.variable);
+            if !source.has_atom(&definition.variable) && !declared.insert(variable) {
+                errors.push(SyntaxError {
+                    message: format!("Variable `{variable}` is declared more than once."),
+                    range: definition.variable.clone(),
+                });
+            }
+        }
+        let mut used = Vec::new();
+        for directive in &operation.directives {
+            arguments_variables(&directive.arguments, &mut used);
+        }
+        selection_set_variables(&operation.selection_set, &mut used);
+        for range in used {
+            let variable = source.text(&range);
+            if source.has_atom(&range) || declared.contains(variable) {
+                continue;
+            }
+            let message = match &operation.name {
+                Some(name) => format!(
+                    "Variable `{variable}` is not declared by operation `{}`.",
+                    source.text(name)
+                ),
+                None => format!("Variable `{variable}` is not declared by the operation."),
+            };
+            errors.push(SyntaxError { message, range });
+        }
+    }
+    errors.sort_by_key(|error| error.range.start);
+    errors
+}
+
+fn selection_set_variables(selection_set: &SelectionSet, used: &mut Vec<Range<usize>>) {
+    for selection in &selection_set.selections {
+        match selection {
+            Selection::Field(field) => {
+                arguments_variables(&field.arguments, used);
+                directives_variables(&field.directives, used);
+                if let Some(selection_set) = &field.selection_set {
+                    selection_set_variables(selection_set, used);
+                }
+            }
+            Selection::FragmentSpread { directives, .. } => directives_variables(directives, used),
+            Selection::InlineFragment {
+                directives,
+                selection_set,
+                ..
+            } => {
+                directives_variables(directives, used);
+                selection_set_variables(selection_set, used);
+            }
+            Selection::Interpolation(_) => {}
+        }
+    }
+}
+
+fn directives_variables(directives: &[Directive], used: &mut Vec<Range<usize>>) {
+    for directive in directives {
+        arguments_variables(&directive.arguments, used);
+    }
+}
+
+fn arguments_variables(arguments: &[Argument], used: &mut Vec<Range<usize>>) {
+    for argument in arguments {
+        value_variables(&argument.value, used);
+    }
+}
+
+fn value_variables(value: &Value, used: &mut Vec<Range<usize>>) {
+    match &value.kind {
+        ValueKind::Variable => used.push(value.range.clone()),
+        ValueKind::List(values) => {
+            for value in values {
+                value_variables(value, used);
+            }
+        }
+        ValueKind::Object(fields) => arguments_variables(fields, used),
+        ValueKind::String | ValueKind::Scalar | ValueKind::Interpolation => {}
+    }
+}
@@ -1,9 +1,17 @@
 #[cfg(feature = "sql")]
 use tree_sitter_sequel;
 
-use crate::parser::{TemplatePart, TemplateStringInfo, raw_static_prefix_len};
+use crate::grammar::{CustomGrammar, CustomGrammars};
+use crate::graphql::syntax::{GraphqlSource, TokenKind};
+use crate::markdown::syntax::{MarkdownSource, SpanKind};
+use crate::parser::{StaticTextSegment, TemplatePart, TemplateStringInfo, raw_static_prefix_len};
+use crate::plugin::{LanguagePlugins, PluginSpan};
+use crate::project_config::SqlConfig;
+use crate::regex::syntax::{RegexSource, TokenKind as RegexTokenKind};
+use crate::xml::syntax::{Token, XmlSource};
 use anyhow::Result;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::info;
 use tree_sitter::{Language, Parser};
 use tree_sitter_highlight::{HighlightConfiguration, HighlightEvent, Highlighter};
@@ -20,6 +28,11 @@ pub struct TemplateHighlighter {
     highlighter: Highlighter,
     language_configs: HashMap<String, LanguageConfig>,
     highlight_names: Vec<String>,
+    #[cfg(feature = "sql")]
+    sql_config: SqlConfig,
+    plugins: LanguagePlugins,
+    grammars: CustomGrammars,
+    grammar_configs: HashMap<String, GrammarHighlightConfig>,
 }
 
 #[derive(Clone)]
@@ -27,6 +40,15 @@ struct LanguageConfig {
     language: Language,
 }
 
+/// A compiled highlight configuration for a runtime-loaded grammar, reused
+/// until the grammar or its query files change.
+struct GrammarHighlightConfig {
+    language: Language,
+    highlights: String,
+    injections: String,
+    config: Arc<HighlightConfiguration>,
+}
+
 #[derive(Debug, Clone)]
 struct ProcessedHighlightContent {
     content: String,
@@ -129,6 +151,13 @@ impl TemplateHighlighter {
             },
         );
 
+        let shell_config = LanguageConfig {
+            language: tree_sitter_bash::LANGUAGE.into(),
+        };
+        language_configs.insert("shell".to_string(), shell_config.clone());
+        language_configs.insert("sh".to_string(), shell_config.clone());
+        language_configs.insert("bash".to_string(), shell_config);
+
         #[cfg(feature = "sql")]
         language_configs.insert(
             "sql".to_string(),
@@ -141,12 +170,46 @@ impl TemplateHighlighter {
             highlighter: Highlighter::new(),
             language_configs,
             highlight_names,
+            #[cfg(feature = "sql")]
+            sql_config: SqlConfig::default(),
+            plugins: LanguagePlugins::default(),
+            grammars: CustomGrammars::default(),
+            grammar_configs: HashMap::new(),
         })
     }
 
+    /// Sets the `[tool.t-linter.sql]` settings used to pick the SQL dialect
+    /// of templates without a dialect profile.
+    pub fn set_sql_config(&mut self, config: SqlConfig) {
+        #[cfg(feature = "sql")]
+        {
+            self.sql_config = config;
+        }
+        #[cfg(not(feature = "sql"))]
+        let _ = config;
+    }
+
+    /// Sets the language plugins that highlight templates in languages
+    /// t-linter does not ship, read from `[tool.t-linter.plugins]`.
+    pub fn set_plugins(&mut self, plugins: LanguagePlugins) {
+        self.plugins = plugins;
+    }
+
+    /// Sets the Tree-sitter grammars loaded at runtime for languages the
+    /// binary does not bundle, read from `[tool.t-linter.grammars]`.
+    pub fn set_grammars(&mut self, grammars: CustomGrammars) {
+        self.grammars = grammars;
+    }
+
     pub fn supports_language(&self, language: &str) -> bool {
-        self.language_configs
-            .contains_key(language.to_ascii_lowercase().as_str())
+        let language = language.to_ascii_lowercase();
+        self.plugins.supports_language(&language)
+            || self.grammars.supports_language(&language)
+            || self.language_configs.contains_key(language.as_str())
+            || is_xml_language(&language)
+            || is_graphql_language(&language)
+            || is_markdown_language(&language)
+            || is_regex_language(&language)
     }
 
     pub fn highlight_template(
@@ -163,12 +226,41 @@ impl TemplateHighlighter {
             language, template.content
         );
 
+        if let Some(spans) = self.plugins.highlight_template(template) {
+            return Ok(self.highlight_plugin_template(template, spans?));
+        }
+        if let Some(grammar) = self.grammars.load(language) {
+            return self.highlight_custom_grammar_template(template, language, &grammar?);
+        }
+        if is_xml_language(&language.to_ascii_lowercase()) {
+            return Ok(self.highlight_xml_template(template));
+        }
+        if is_graphql_language(&language.to_ascii_lowercase()) {
+            return Ok(self.highlight_graphql_template(template));
+        }
+        if is_markdown_language(&language.to_ascii_lowercase()) {
+            return Ok(self.highlight_markdown_template(template));
+        }
+        if is_regex_language(&language.to_ascii_lowercase()) {
+            return Ok(self.highlight_regex_template(template));
+        }
+
         let config = self
             .language_configs
             .get(language.to_lowercase().as_str())
             .ok_or_else(|| anyhow::anyhow!("Unsupported language: {}", language))?;
 
-        let processed = self.prepare_content_for_highlighting(template, language);
+        #[cfg_attr(not(feature = "sql"), allow(unused_mut))]
+        let mut processed = self.prepare_content_for_highlighting(template, language);
+        #[cfg_attr(not(feature = "sql"), allow(unused_mut))]
+        let mut dialect_keywords: Vec<std::ops::Range<usize>> = Vec::new();
+        #[cfg(feature = "sql")]
+        if language.eq_ignore_ascii_case("sql") {
+            let dialect = crate::sql::dialect::resolve(template, &self.sql_config);
+            let normalized = crate::sql::dialect::normalize(&processed.content, dialect);
+            processed.content = normalized.content;
+            dialect_keywords = normalized.keywords;
+        }
         let processed_content = processed.content.as_str();
 
         let mut parser = Parser::new();
@@ -189,6 +281,7 @@ impl TemplateHighlighter {
                 "json" => tree_sitter_json::HIGHLIGHTS_QUERY,
                 "yaml" | "yml" => tree_sitter_yaml::HIGHLIGHTS_QUERY,
                 "toml" => tree_sitter_toml_ng::HIGHLIGHTS_QUERY,
+                "shell" | "sh" | "bash" => tree_sitter_bash::HIGHLIGHT_QUERY,
                 #[cfg(feature = "sql")]
                 "sql" => tree_sitter_sequel::HIGHLIGHTS_QUERY,
                 _ => {
@@ -203,9 +296,41 @@ impl TemplateHighlighter {
         )?;
         temp_config.configure(&self.highlight_names);
 
+        let mut highlighted_ranges =
+            self.highlight_processed_content(&temp_config, &processed, &HashMap::new())?;
+
+        for keyword in dialect_keywords {
+            highlighted_ranges.push(HighlightedRange {
+                start_byte: Self::map_processed_offset(
+                    &processed.processed_to_original,
+                    keyword.start,
+                ),
+                end_byte: Self::map_processed_offset(&processed.processed_to_original, keyword.end),
+                highlight_name: "keyword".to_string(),
+                highlight_index: self.get_highlight_index("keyword"),
+            });
+        }
+
+        Ok(self.finish_highlighted_ranges(highlighted_ranges, &processed))
+    }
+
+    /// Runs Tree-sitter highlighting over prepared content and maps the
+    /// highlighted spans back to template content offsets, leaving out the
+    /// interpolation placeholders. Injected languages resolve to the given
+    /// configurations.
+    fn highlight_processed_content(
+        &mut self,
+        config: &HighlightConfiguration,
+        processed: &ProcessedHighlightContent,
+        injections: &HashMap<String, Arc<HighlightConfiguration>>,
+    ) -> Result<Vec<HighlightedRange>> {
         let highlights =
             self.highlighter
-                .highlight(&temp_config, processed_content.as_bytes(), None, |_| None)?;
+                .highlight(config, processed.content.as_bytes(), None, |name| {
+                    injections
+                        .get(name.to_ascii_lowercase().as_str())
+                        .map(Arc::as_ref)
+                })?;
 
         let mut highlighted_ranges = Vec::new();
         let mut active_highlights: Vec<usize> = Vec::new();
@@ -244,6 +369,15 @@ impl TemplateHighlighter {
             }
         }
 
+        Ok(highlighted_ranges)
+    }
+
+    /// Adds the interpolation ranges and sorts the result.
+    fn finish_highlighted_ranges(
+        &self,
+        mut highlighted_ranges: Vec<HighlightedRange>,
+        processed: &ProcessedHighlightContent,
+    ) -> Vec<HighlightedRange> {
         for placeholder in &processed.placeholders {
             highlighted_ranges.push(HighlightedRange {
                 start_byte: placeholder.start,
@@ -263,7 +397,382 @@ impl TemplateHighlighter {
             );
         }
 
-        Ok(highlighted_ranges)
+        highlighted_ranges
+    }
+
+    /// Highlights a template with a runtime-loaded grammar. Injected
+    /// languages are highlighted when they are configured grammars too.
+    pub(crate) fn highlight_custom_grammar_template(
+        &mut self,
+        template: &TemplateStringInfo,
+        language: &str,
+        grammar: &CustomGrammar,
+    ) -> Result<Vec<HighlightedRange>> {
+        let processed = Self::prepare_content_with_placeholder(template, &grammar.placeholder);
+        let config = self.custom_highlight_config(language, grammar)?;
+        let mut injections = HashMap::new();
+        if grammar.has_injections() {
+            let languages = self
+                .grammars
+                .languages()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            for injected in languages {
+                let Some(Ok(injected_grammar)) = self.grammars.load(&injected) else {
+                    continue;
+                };
+                let injected_config = self.custom_highlight_config(&injected, &injected_grammar)?;
+                injections.insert(injected, injected_config);
+            }
+        }
+        let highlighted_ranges =
+            self.highlight_processed_content(&config, &processed, &injections)?;
+        Ok(self.finish_highlighted_ranges(highlighted_ranges, &processed))
+    }
+
+    /// The highlight configuration for a runtime-loaded grammar. Compiling
+    /// the queries is costly, so it happens again only when the grammar or
+    /// its query files change.
+    fn custom_highlight_config(
+        &mut self,
+        language: &str,
+        grammar: &CustomGrammar,
+    ) -> Result<Arc<HighlightConfiguration>> {
+        let highlights = grammar.highlights_query()?;
+        let injections = grammar.injections_query()?;
+        if let Some(cached) = self.grammar_configs.get(language)
+            && cached.language == grammar.language
+            && cached.highlights == highlights
+            && cached.injections == injections
+        {
+            return Ok(cached.config.clone());
+        }
+        let mut config = HighlightConfiguration::new(
+            grammar.language.clone(),
+            language,
+            &highlights,
+            &injections,
+            "",
+        )?;
+        config.configure(&self.highlight_names);
+        let config = Arc::new(config);
+        self.grammar_configs.insert(
+            language.to_string(),
+            GrammarHighlightConfig {
+                language: grammar.language.clone(),
+                highlights,
+                injections,
+                config: config.clone(),
+            },
+        );
+        Ok(config)
+    }
+
+    /// Highlights xml and svg templates from the xml lexer, which recovers
+    /// from malformed markup and knows processing instructions and CDATA.
+    fn highlight_xml_template(&self, template: &TemplateStringInfo) -> Vec<HighlightedRange> {
+        let source = XmlSource::from_template(template);
+        let (tokens, _) = crate::xml::syntax::tokenize(&source);
+        let placeholders = source
+            .atoms
+            .iter()
+            .map(|(&start, &end)| Placeholder { start, end })
+            .collect::<Vec<_>>();
+
+        let mut spans = Vec::new();
+        for token in tokens {
+            match token {
+                Token::StartTag(tag) => {
+                    spans.push((tag.range.start..tag.name.start, "punctuation.bracket"));
+                    spans.push((tag.name, "tag"));
+                    for attribute in tag.attributes {
+                        spans.push((attribute.name, "attribute"));
+                        spans.push((attribute.value, "string"));
+                    }
+                    let closing = if tag.self_closing { 2 } else { 1 };
+                    spans.push((
+                        tag.range.end - closing..tag.range.end,
+                        "punctuation.bracket",
+                    ));
+                }
+                Token::EndTag { range, name } => {
+                    spans.push((range.start..name.start, "punctuation.bracket"));
+                    spans.push((name, "tag"));
+                    spans.push((range.end - 1..range.end, "punctuation.bracket"));
+                }
+                Token::Comment(range) => spans.push((range, "comment")),
+                Token::CData(range) => spans.push((range, "string.special")),
+                Token::Instruction { range, .. } | Token::Doctype(range) => {
+                    spans.push((range, "keyword"));
+                }
+                Token::Text(_) | Token::Interpolation(_) => {}
+            }
+        }
+        self.spans_to_highlighted_ranges(spans, &placeholders)
+    }
+
+    /// Highlights graphql templates from the graphql lexer, using the tokens
+    /// around each name to tell keywords, types, fields, and values apart.
+    fn highlight_graphql_template(&self, template: &TemplateStringInfo) -> Vec<HighlightedRange> {
+        let source = GraphqlSource::from_template(template);
+        let (tokens, _) = crate::graphql::syntax::tokenize(&source);
+        let placeholders = source
+            .atoms
+            .iter()
+            .map(|(&start, &end)| Placeholder { start, end })
+            .collect::<Vec<_>>();
+        let code = tokens
+            .iter()
+            .filter(|token| token.kind != TokenKind::Comment)
+            .collect::<Vec<_>>();
+
+        let mut spans = Vec::new();
+        for token in tokens
+            .iter()
+            .filter(|token| token.kind == TokenKind::Comment)
+        {
+            spans.push((token.range.clone(), "comment"));
+        }
+        let mut braces = 0usize;
+        let mut parens = 0usize;
+        let mut type_system = false;
+        for (index, token) in code.iter().enumerate() {
+            let text = source.text(&token.range);
+            let previous = index
+                .checked_sub(1)
+                .map(|index| source.text(&code[index].range));
+            let next = code.get(index + 1).map(|token| source.text(&token.range));
+            let name = match token.kind {
+                TokenKind::Comment | TokenKind::Interpolation => continue,
+                TokenKind::String | TokenKind::BlockString => "string",
+                TokenKind::Int | TokenKind::Float => "number",
+                TokenKind::Variable => "variable",
+                TokenKind::Punctuator => {
+                    match text {
+                        "{" => braces += 1,
+                        "}" => braces = braces.saturating_sub(1),
+                        "(" => parens += 1,
+                        ")" => parens = parens.saturating_sub(1),
+                        _ => {}
+                    }
+                    match text {
+                        "{" | "}" | "(" | ")" | "[" | "]" => "punctuation.bracket",
+                        "@" => "attribute",
+                        _ => "punctuation.delimiter",
+                    }
+                }
+                TokenKind::Name => {
+                    if braces == 0 && parens == 0 && is_graphql_keyword(text) {
+                        if matches!(text, "query" | "mutation" | "subscription" | "fragment") {
+                            type_system = false;
+                        } else if !matches!(text, "on" | "implements" | "repeatable") {
+                            type_system = true;
+                        }
+                        "keyword"
+                    } else if text == "on" && previous == Some("...") {
+                        "keyword"
+                    } else if previous == Some("@") {
+                        "attribute"
+                    } else if matches!(previous, Some("on" | "implements" | "&" | "|"))
+                        || (braces == 0
+                            && matches!(
+                                previous,
+                                Some("type" | "interface" | "union" | "enum" | "input" | "scalar")
+                            ))
+                        || ((type_system || (braces == 0 && parens > 0))
+                            && matches!(previous, Some(":" | "[")))
+                        || (braces == 0 && type_system && previous == Some("="))
+                    {
+                        "type"
+                    } else if matches!(previous, Some("..." | "fragment")) {
+                        "label"
+                    } else if braces == 0
+                        && matches!(previous, Some("query" | "mutation" | "subscription"))
+                    {
+                        "function"
+                    } else if matches!(next, Some(":" | "(" | "{")) {
+                        "property"
+                    } else if matches!(text, "true" | "false") {
+                        "boolean"
+                    } else if text == "null" {
+                        "constant.builtin"
+                    } else if parens > 0 && matches!(previous, Some(":" | "=" | "[")) {
+                        "constant"
+                    } else {
+                        "property"
+                    }
+                }
+            };
+            spans.push((token.range.clone(), name));
+        }
+        self.spans_to_highlighted_ranges(spans, &placeholders)
+    }
+
+    /// Highlights markdown templates from the markdown scanner, highlighting
+    /// the content of fenced code blocks in the language their info string
+    /// names.
+    fn highlight_markdown_template(
+        &mut self,
+        template: &TemplateStringInfo,
+    ) -> Vec<HighlightedRange> {
+        let source = MarkdownSource::from_template(template);
+        let document = crate::markdown::syntax::parse(&source);
+        let placeholders = source
+            .atoms
+            .iter()
+            .map(|(&start, &end)| Placeholder { start, end })
+            .collect::<Vec<_>>();
+
+        let mut spans = document
+            .spans
+            .iter()
+            .map(|(range, kind)| {
+                let name = match kind {
+                    SpanKind::Marker | SpanKind::EmphasisDelimiter => "punctuation.special",
+                    SpanKind::CodeSpan | SpanKind::Code | SpanKind::Escape => "string.special",
+                    SpanKind::FenceInfo => "type",
+                    SpanKind::LinkBracket => "punctuation.bracket",
+                    SpanKind::Destination | SpanKind::Title => "string",
+                    SpanKind::Label => "label",
+                    SpanKind::Html => "tag",
+                    SpanKind::TablePipe => "punctuation.delimiter",
+                };
+                (range.clone(), name)
+            })
+            .collect::<Vec<_>>();
+
+        let mut injected = Vec::new();
+        for block in &document.code_blocks {
+            let embedded = block
+                .info
+                .as_ref()
+                .map(|info| source.text(info))
+                .filter(|info| {
+                    block.top_level
+                        && !is_markdown_language(&info.to_ascii_lowercase())
+                        && self.supports_language(info)
+                });
+            let ranges = embedded.and_then(|language| {
+                let embedded = embedded_template(template, language, block.content.clone());
+                self.highlight_template(&embedded).ok()
+            });
+            match ranges {
+                Some(ranges) => injected.extend(ranges.into_iter().map(|mut range| {
+                    range.start_byte += block.content.start;
+                    range.end_byte += block.content.start;
+                    range
+                })),
+                None => spans.push((block.content.clone(), "string.special")),
+            }
+        }
+
+        let placeholder_starts = placeholders
+            .iter()
+            .map(|placeholder| placeholder.start)
+            .collect::<Vec<_>>();
+        let mut highlighted_ranges = self.spans_to_highlighted_ranges(spans, &placeholders);
+        highlighted_ranges.extend(injected.into_iter().filter(|range| {
+            !(range.highlight_name == "variable.parameter"
+                && placeholder_starts.contains(&range.start_byte))
+        }));
+        highlighted_ranges.sort_by_key(|r| r.start_byte);
+        highlighted_ranges
+    }
+
+    /// Turns the spans a language plugin gave the template content into
+    /// ranges that skip interpolations, which are highlighted as parameters.
+    /// Callers that run the plugin themselves, so a slow plugin does not hold
+    /// the highlighter, pass its spans here.
+    pub fn highlight_plugin_template(
+        &self,
+        template: &TemplateStringInfo,
+        spans: Vec<PluginSpan>,
+    ) -> Vec<HighlightedRange> {
+        let mut placeholders = Vec::new();
+        let mut offset = 0;
+        for part in &template.parts {
+            match part {
+                TemplatePart::Static(part) => offset += part.text.len(),
+                TemplatePart::Interpolation(_) => {
+                    placeholders.push(Placeholder {
+                        start: offset,
+                        end: offset + 2,
+                    });
+                    offset += 2;
+                }
+            }
+        }
+        // Token types the editor does not know are left unhighlighted.
+        let spans = spans
+            .iter()
+            .filter(|(_, name)| self.highlight_names.contains(name))
+            .map(|(range, name)| (range.clone(), name.as_str()))
+            .collect();
+        self.spans_to_highlighted_ranges(spans, &placeholders)
+    }
+
+    fn highlight_regex_template(&self, template: &TemplateStringInfo) -> Vec<HighlightedRange> {
+        let source = RegexSource::from_template(template);
+        let pattern = crate::regex::syntax::parse(&source);
+        let placeholders = source
+            .atoms
+            .iter()
+            .map(|(&start, &end)| Placeholder { start, end })
+            .collect::<Vec<_>>();
+        let spans = pattern
+            .tokens
+            .into_iter()
+            .map(|(range, kind)| {
+                let name = match kind {
+                    RegexTokenKind::Escape => "string.special",
+                    RegexTokenKind::Anchor => "keyword",
+                    RegexTokenKind::Quantifier
+                    | RegexTokenKind::Alternation
+                    | RegexTokenKind::ClassOperator => "operator",
+                    RegexTokenKind::GroupBracket | RegexTokenKind::ClassBracket => {
+                        "punctuation.bracket"
+                    }
+                    RegexTokenKind::GroupName | RegexTokenKind::Backreference => "label",
+                    RegexTokenKind::Flags => "attribute",
+                    RegexTokenKind::Comment => "comment",
+                };
+                (range, name)
+            })
+            .collect();
+        self.spans_to_highlighted_ranges(spans, &placeholders)
+    }
+
+    fn spans_to_highlighted_ranges(
+        &self,
+        spans: Vec<(std::ops::Range<usize>, &str)>,
+        placeholders: &[Placeholder],
+    ) -> Vec<HighlightedRange> {
+        let mut highlighted_ranges = Vec::new();
+        for (range, name) in spans {
+            let highlight_index = self.get_highlight_index(name);
+            for (start_byte, end_byte) in
+                Self::subtract_placeholder_ranges(range.start, range.end, placeholders)
+            {
+                if start_byte < end_byte {
+                    highlighted_ranges.push(HighlightedRange {
+                        start_byte,
+                        end_byte,
+                        highlight_name: name.to_string(),
+                        highlight_index,
+                    });
+                }
+            }
+        }
+        for placeholder in placeholders {
+            highlighted_ranges.push(HighlightedRange {
+                start_byte: placeholder.start,
+                end_byte: placeholder.end,
+                highlight_name: "variable.parameter".to_string(),
+                highlight_index: self.get_highlight_index("variable.parameter"),
+            });
+        }
+        highlighted_ranges.sort_by_key(|r| r.start_byte);
+        highlighted_ranges
     }
 
     fn prepare_content_for_highlighting(
@@ -274,12 +783,20 @@ impl TemplateHighlighter {
         if language.eq_ignore_ascii_case("tdom") {
             return Self::prepare_tdom_content_for_highlighting(template);
         }
+        Self::prepare_content_with_placeholder(
+            template,
+            Self::placeholder_text_for_language(language),
+        )
+    }
 
+    fn prepare_content_with_placeholder(
+        template: &TemplateStringInfo,
+        placeholder_text: &str,
+    ) -> ProcessedHighlightContent {
         let mut processed = String::new();
         let mut processed_to_original = vec![0];
         let mut placeholders = Vec::new();
         let mut original_offset = 0;
-        let placeholder_text = Self::placeholder_text_for_language(language);
 
         for part in &template.parts {
             match part {
@@ -613,12 +1130,93 @@ impl TemplateHighlighter {
             "tag" => 2,
             "attribute" => 9,
             "operator" => 21,
-            "punctuation" | "punctuation.bracket" | "punctuation.delimiter" => 21,
+            "punctuation"
+            | "punctuation.bracket"
+            | "punctuation.delimiter"
+            | "punctuation.special" => 21,
             _ => 8,
         }
     }
 }
 
+fn is_xml_language(language: &str) -> bool {
+    matches!(language, "xml" | "svg")
+}
+
+fn is_markdown_language(language: &str) -> bool {
+    matches!(language, "markdown" | "md")
+}
+
+fn is_regex_language(language: &str) -> bool {
+    matches!(language, "regex" | "re")
+}
+
+/// The part of a template between two content offsets, as a template in
+/// `language`, for highlighting code embedded in another language.
+fn embedded_template(
+    template: &TemplateStringInfo,
+    language: &str,
+    content: std::ops::Range<usize>,
+) -> TemplateStringInfo {
+    let mut parts = Vec::new();
+    let mut offset = 0;
+    for part in &template.parts {
+        match part {
+            TemplatePart::Static(part) => {
+                let start = content.start.clamp(offset, offset + part.text.len()) - offset;
+                let end = content.end.clamp(offset, offset + part.text.len()) - offset;
+                if start < end {
+                    let text = part.text[start..end].to_string();
+                    parts.push(TemplatePart::Static(StaticTextSegment {
+                        raw_text: text.clone(),
+                        text,
+                    }));
+                }
+                offset += part.text.len();
+            }
+            TemplatePart::Interpolation(_) => {
+                if content.contains(&offset) {
+                    parts.push(part.clone());
+                }
+                offset += 2;
+            }
+        }
+    }
+    TemplateStringInfo {
+        content: template.content[content].to_string(),
+        language: Some(language.to_string()),
+        profile: None,
+        parts,
+        ..template.clone()
+    }
+}
+
+fn is_graphql_language(language: &str) -> bool {
+    matches!(language, "graphql" | "gql")
+}
+
+fn is_graphql_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "query"
+            | "mutation"
+            | "subscription"
+            | "fragment"
+            | "on"
+            | "schema"
+            | "scalar"
+            | "type"
+            | "interface"
+            | "union"
+            | "enum"
+            | "input"
+            | "directive"
+            | "extend"
+            | "implements"
+            | "repeatable"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1403,6 +2001,42 @@ B<span>{value}</span>
         );
     }
 
+    #[cfg(feature = "sql")]
+    #[test]
+    fn test_sql_highlighting_uses_configured_dialect() {
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        highlighter.set_sql_config(SqlConfig {
+            dialect: Some(crate::project_config::SqlDialect::Sqlite),
+            ..SqlConfig::default()
+        });
+
+        let content = "INSERT OR REPLACE INTO users (id) VALUES (:id)";
+        let template = make_template(
+            content,
+            &format!("t\"{content}\""),
+            "sql",
+            Location {
+                start_line: 1,
+                start_column: 1,
+                end_line: 1,
+                end_column: 50,
+            },
+            Vec::new(),
+            TemplateStringFlags::default(),
+        );
+
+        let ranges = highlighter.highlight_template(&template).unwrap();
+        let keywords = ranges
+            .iter()
+            .filter(|range| range.highlight_name == "keyword")
+            .map(|range| &content[range.start_byte..range.end_byte])
+            .collect::<Vec<_>>();
+
+        assert!(keywords.contains(&"OR"));
+        assert!(keywords.contains(&"REPLACE"));
+        assert!(keywords.contains(&"INTO"));
+    }
+
     #[test]
     fn test_yaml_highlighting_with_yml_alias() {
         let mut highlighter = TemplateHighlighter::new().unwrap();
@@ -1711,4 +2345,228 @@ config: Annotated[Template, "json"] = t"""
         let tokens = highlighter.to_lsp_tokens(ranges, &template);
         assert_expression_tokens_match_template(&tokens, &template);
     }
+
+    #[test]
+    fn test_xml_semantic_tokens_cover_tags_attributes_and_declarations() {
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        assert!(highlighter.supports_language("svg"));
+        let template = parse_single_template(
+            r#"from typing import Annotated
+from string.templatelib import Template
+
+link = "https://example.com"
+
+feed: Annotated[Template, "xml"] = t"""
+<?xml version="1.0"?>
+<atom:link href="{link}" xmlns:atom="http://www.w3.org/2005/Atom"/>
+"""
+"#,
+        );
+
+        let ranges = highlighter.highlight_template(&template).unwrap();
+        assert_non_variable_ranges_avoid_placeholders(&ranges, &template);
+
+        let tokens = highlighter.to_lsp_tokens(ranges, &template);
+        assert_expression_tokens_match_template(&tokens, &template);
+        assert_has_token_start(
+            &tokens,
+            7,
+            1,
+            highlighter.token_type_to_index("keyword"),
+            21,
+        );
+        assert_has_token_start(&tokens, 8, 2, highlighter.token_type_to_index("tag"), 9);
+        assert_has_token_start(
+            &tokens,
+            8,
+            12,
+            highlighter.token_type_to_index("attribute"),
+            4,
+        );
+        assert_has_token_start(&tokens, 8, 17, highlighter.token_type_to_index("string"), 1);
+    }
+
+    #[test]
+    fn test_graphql_semantic_tokens_cover_keywords_types_fields_and_comments() {
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        assert!(highlighter.supports_language("gql"));
+        let template = parse_single_template(
+            r#"from typing import Annotated
+from string.templatelib import Template
+
+extra = "id"
+
+query: Annotated[Template, "graphql"] = t"""
+query GetUser($id: ID!) {{
+  user(id: $id) {{ ...UserFields # fields
+  {extra}
+  }}
+}}
+"""
+"#,
+        );
+
+        let ranges = highlighter.highlight_template(&template).unwrap();
+        assert_non_variable_ranges_avoid_placeholders(&ranges, &template);
+
+        let tokens = highlighter.to_lsp_tokens(ranges, &template);
+        assert_expression_tokens_match_template(&tokens, &template);
+        assert_has_token_start(&tokens, 7, 1, highlighter.token_type_to_index("keyword"), 5);
+        assert_has_token_start(
+            &tokens,
+            7,
+            7,
+            highlighter.token_type_to_index("function"),
+            7,
+        );
+        assert_has_token_start(
+            &tokens,
+            7,
+            15,
+            highlighter.token_type_to_index("variable"),
+            3,
+        );
+        assert_has_token_start(&tokens, 7, 20, highlighter.token_type_to_index("type"), 2);
+        assert_has_token_start(
+            &tokens,
+            8,
+            3,
+            highlighter.token_type_to_index("property"),
+            4,
+        );
+        assert_has_token_start(&tokens, 8, 23, highlighter.token_type_to_index("label"), 10);
+        assert_has_token_start(
+            &tokens,
+            8,
+            34,
+            highlighter.token_type_to_index("comment"),
+            8,
+        );
+    }
+
+    #[test]
+    fn test_markdown_semantic_tokens_cover_markup_and_fenced_code() {
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        assert!(highlighter.supports_language("md"));
+        let template = parse_single_template(
+            r#"from typing import Annotated
+from string.templatelib import Template
+
+name = "x"
+
+note: Annotated[Template, "markdown"] = t"""
+# Hello {name}
+See [docs](https://example.com/{name}) and `code`.
+```json
+{{"id": {name}}}
+```
+"""
+"#,
+        );
+
+        let ranges = highlighter.highlight_template(&template).unwrap();
+        assert_non_variable_ranges_avoid_placeholders(&ranges, &template);
+
+        let tokens = highlighter.to_lsp_tokens(ranges, &template);
+        assert_expression_tokens_match_template(&tokens, &template);
+        let markup = highlighter.token_type_to_index("punctuation.special");
+        let string = highlighter.token_type_to_index("string");
+        assert_has_token_start(&tokens, 7, 1, markup, 1);
+        assert_has_token_start(
+            &tokens,
+            8,
+            5,
+            highlighter.token_type_to_index("punctuation.bracket"),
+            1,
+        );
+        assert_has_token_start(&tokens, 8, 12, string, 20);
+        assert_has_token_start(&tokens, 8, 44, string, 6);
+        assert_has_token_start(&tokens, 9, 1, markup, 3);
+        assert_has_token_start(&tokens, 9, 4, highlighter.token_type_to_index("type"), 4);
+        assert_has_token_start(&tokens, 10, 3, string, 4);
+    }
+
+    #[test]
+    fn test_shell_semantic_tokens_cover_commands_strings_and_comments() {
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        assert!(highlighter.supports_language("sh"));
+        let template = parse_single_template(
+            r#"from typing import Annotated
+from string.templatelib import Template
+
+target = "x"
+
+command: Annotated[Template, "bash"] = t"""
+rsync -a "build/" {target} # sync
+"""
+"#,
+        );
+
+        let ranges = highlighter.highlight_template(&template).unwrap();
+        assert_non_variable_ranges_avoid_placeholders(&ranges, &template);
+
+        let tokens = highlighter.to_lsp_tokens(ranges, &template);
+        assert_expression_tokens_match_template(&tokens, &template);
+        assert_has_token_start(
+            &tokens,
+            7,
+            1,
+            highlighter.token_type_to_index("function"),
+            5,
+        );
+        assert_has_token_start(&tokens, 7, 10, highlighter.token_type_to_index("string"), 8);
+        assert_has_token_start(
+            &tokens,
+            7,
+            28,
+            highlighter.token_type_to_index("comment"),
+            6,
+        );
+    }
+
+    #[test]
+    fn test_regex_semantic_tokens_cover_groups_escapes_and_quantifiers() {
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        assert!(highlighter.supports_language("re"));
+        let template = parse_single_template(
+            r#"from typing import Annotated
+from string.templatelib import Template
+
+word = "x"
+
+pattern: Annotated[Template, "regex"] = t"""
+^(?P<word>{word})\d+(?i:x|y)$
+"""
+"#,
+        );
+
+        let ranges = highlighter.highlight_template(&template).unwrap();
+        assert_non_variable_ranges_avoid_placeholders(&ranges, &template);
+
+        let tokens = highlighter.to_lsp_tokens(ranges, &template);
+        assert_expression_tokens_match_template(&tokens, &template);
+        assert_has_token_start(&tokens, 7, 1, highlighter.token_type_to_index("keyword"), 1);
+        assert_has_token_start(&tokens, 7, 6, highlighter.token_type_to_index("label"), 4);
+        assert_has_token_start(
+            &tokens,
+            7,
+            18,
+            highlighter.token_type_to_index("string.special"),
+            2,
+        );
+        assert_has_token_start(
+            &tokens,
+            7,
+            20,
+            highlighter.token_type_to_index("operator"),
+            1,
+        );
+        assert_has_token_start(
+            &tokens,
+            7,
+            21,
+            highlighter.token_type_to_index("attribute"),
+            4,
+        );
+    }
 }
@@ -0,0 +1,823 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use tree_sitter::{Node, Parser, Tree};
+use tstring_format_doc::{Doc, RenderOptions, render};
+use tstring_syntax::{
+    BackendError, BackendResult, SourcePosition, SourceSpan, TemplateInput, TemplateSegment,
+};
+
+use crate::project_config::{JavascriptFormatConfig, QuoteStyle, Semicolons};
+use crate::template_text::{self, keep_block_breaks};
+
+/// Statements the grammar terminates with `;`.
+const SEMICOLON_STATEMENTS: &[&str] = &[
+    "break_statement",
+    "continue_statement",
+    "debugger_statement",
+    "do_statement",
+    "export_statement",
+    "expression_statement",
+    "field_definition",
+    "import_statement",
+    "lexical_declaration",
+    "return_statement",
+    "throw_statement",
+    "variable_declaration",
+];
+
+/// Declarations an `export` wraps without a trailing `;`.
+const BLOCK_DECLARATIONS: &[&str] = &[
+    "class",
+    "class_declaration",
+    "function_declaration",
+    "function_expression",
+    "generator_function",
+    "generator_function_declaration",
+];
+
+/// Nodes whose named children are laid out one statement per line.
+const STATEMENT_LISTS: &[&str] = &["program", "statement_block", "class_body", "switch_body"];
+
+/// Nodes whose statement-position children are statements in their own right.
+const STATEMENT_PARENTS: &[&str] = &[
+    "else_clause",
+    "labeled_statement",
+    "switch_case",
+    "switch_default",
+];
+
+/// Nodes kept exactly as written, since whitespace inside them is significant.
+const VERBATIM: &[&str] = &["comment", "hash_bang_line", "regex", "template_string"];
+
+/// Characters that continue the previous line when a statement starts with
+/// them, so the statement before must keep its `;`.
+const ASI_HAZARDS: &[char] = &['(', '[', '`', '+', '-', '/', '*'];
+
+/// Formats a `javascript` template: one statement per line, blocks indented,
+/// normalized spacing, and semicolons and string quotes per the configured
+/// style. Bracketed lists that do not fit put one item per line. Templates,
+/// regular expressions, comments and JSX are kept as written.
+///
+/// Interpolations are treated as opaque expressions and kept byte-for-byte.
+/// A template whose interpolation stands alone as a statement, or that does
+/// not parse, is refused with an error pointing at the offending span.
+pub fn format_template(
+    input: &TemplateInput,
+    config: &JavascriptFormatConfig,
+    line_length: usize,
+) -> BackendResult<String> {
+    let source = TemplateSource::new(input)?;
+    let tree = source.parse()?;
+    source.validate(&tree)?;
+    let root = tree.root_node();
+    if root.named_child_count() == 0 {
+        return Ok(source.text.clone());
+    }
+
+    let mut formatter = JavascriptFormatter {
+        source: &source,
+        config,
+        dropped: HashSet::new(),
+    };
+    formatter.collect_dropped_semicolons(root);
+    let doc = formatter.node(root);
+    let formatted = render(
+        &doc,
+        RenderOptions {
+            line_length,
+            indent_width: config.indent_width,
+        },
+    );
+    Ok(keep_block_breaks(&source.text, formatted))
+}
+
+/// Checks that the template parses and that no interpolation stands alone as
+/// a statement.
+pub fn check_template(input: &TemplateInput) -> BackendResult<()> {
+    let source = TemplateSource::new(input)?;
+    let tree = source.parse()?;
+    source.validate(&tree)
+}
+
+struct TemplateSource {
+    /// The template with each interpolation's raw source in place.
+    text: String,
+    /// `text` with every interpolation blanked to underscores of the same
+    /// length, so it parses as an identifier and byte offsets line up.
+    parsed: String,
+    /// The start of each interpolation mapped to its end.
+    atoms: BTreeMap<usize, usize>,
+    /// The byte range of each template segment and whether it is an
+    /// interpolation, for mapping offsets back to backend spans.
+    segments: Vec<(Range<usize>, bool)>,
+}
+
+impl TemplateSource {
+    fn new(input: &TemplateInput) -> BackendResult<Self> {
+        let mut text = String::new();
+        let mut parsed = String::new();
+        let mut atoms = BTreeMap::new();
+        let mut segments = Vec::new();
+        for segment in &input.segments {
+            let start = text.len();
+            match segment {
+                TemplateSegment::StaticText(static_text) => {
+                    text.push_str(static_text);
+                    parsed.push_str(static_text);
+                    segments.push((start..text.len(), false));
+                }
+                TemplateSegment::Interpolation(interpolation) => {
+                    let raw_source = template_text::raw_source(interpolation, "javascript")?;
+                    text.push_str(raw_source);
+                    parsed.push_str(&"_".repeat(raw_source.len()));
+                    atoms.insert(start, text.len());
+                    segments.push((start..text.len(), true));
+                }
+            }
+        }
+        Ok(Self {
+            text,
+            parsed,
+            atoms,
+            segments,
+        })
+    }
+
+    fn parse(&self) -> BackendResult<Tree> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_javascript::LANGUAGE.into())
+            .map_err(|error| {
+                BackendError::parse(format!("Failed to initialize JavaScript parser: {error}"))
+            })?;
+        parser
+            .parse(&self.parsed, None)
+            .ok_or_else(|| BackendError::parse("Failed to parse javascript template."))
+    }
+
+    fn validate(&self, tree: &Tree) -> BackendResult<()> {
+        let root = tree.root_node();
+        if root.has_error() {
+            let node = first_error(root).unwrap_or(root);
+            return Err(BackendError::parse_at(
+                "javascript.parse",
+                "Invalid javascript syntax in template string.",
+                self.span(node.byte_range()),
+            ));
+        }
+
+        for (&start, &end) in &self.atoms {
+            let Some(node) = root.descendant_for_byte_range(start, end) else {
+                continue;
+            };
+            let is_statement = node.kind() == "identifier"
+                && node
+                    .parent()
+                    .filter(|parent| parent.kind() == "expression_statement")
+                    .and_then(|parent| parent.named_child(0))
+                    .is_some_and(|expression| expression.id() == node.id());
+            if is_statement {
+                return Err(BackendError::semantic_at(
+                    "javascript.interpolation",
+                    format!(
+                        "Interpolation '{}' stands alone as a javascript statement; it may hold code whose meaning depends on line breaks, so the template is left unformatted.",
+                        &self.text[start..end]
+                    ),
+                    self.span(node.byte_range()),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn span(&self, range: Range<usize>) -> SourceSpan {
+        SourceSpan::between(
+            self.position(range.start, false),
+            self.position(range.end, true),
+        )
+    }
+
+    /// The template position of a byte offset. An interpolation spans
+    /// offsets `0..2` of its own token.
+    fn position(&self, offset: usize, is_end: bool) -> SourcePosition {
+        for (token_index, (range, is_interpolation)) in self.segments.iter().enumerate() {
+            let within = if is_end {
+                range.start < offset && offset <= range.end
+            } else {
+                range.start <= offset && offset < range.end
+            };
+            if !within {
+                continue;
+            }
+            let offset = if *is_interpolation {
+                if is_end { 2 } else { 0 }
+            } else {
+                self.text[range.start..offset].chars().count()
+            };
+            return SourcePosition {
+                token_index,
+                offset,
+            };
+        }
+        SourcePosition {
+            token_index: 0,
+            offset: 0,
+        }
+    }
+
+    fn template_text(&self, range: Range<usize>) -> String {
+        template_text::template_text(&self.text, &self.atoms, range)
+    }
+
+    fn has_atom(&self, range: &Range<usize>) -> bool {
+        template_text::has_atom(&self.atoms, range)
+    }
+}
+
+fn first_error(node: Node<'_>) -> Option<Node<'_>> {
+    if node.is_error() || node.is_missing() {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor).find_map(first_error)
+}
+
+struct JavascriptFormatter<'a> {
+    source: &'a TemplateSource,
+    config: &'a JavascriptFormatConfig,
+    /// Start bytes of `;` tokens the semicolon style re-emits or omits.
+    dropped: HashSet<usize>,
+}
+
+impl JavascriptFormatter<'_> {
+    fn collect_dropped_semicolons(&mut self, node: Node<'_>) {
+        if self.needs_semicolon(node) {
+            let last = last_leaf(node);
+            if last.kind() == ";" {
+                self.dropped.insert(last.start_byte());
+            }
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.collect_dropped_semicolons(child);
+        }
+    }
+
+    fn node(&self, node: Node<'_>) -> Doc {
+        let kind = node.kind();
+        let doc = if VERBATIM.contains(&kind) || kind.starts_with("jsx_") || node.child_count() == 0
+        {
+            Doc::text(self.text(node))
+        } else {
+            match kind {
+                "program" => self.statements(&statement_children(node)),
+                "statement_block" | "class_body" | "switch_body" => self.block(node),
+                "switch_case" | "switch_default" => self.switch_case(node),
+                "object" | "object_pattern" | "named_imports" | "export_clause" => {
+                    self.list(node, true)
+                }
+                "array" | "array_pattern" | "arguments" | "formal_parameters" => {
+                    self.list(node, false)
+                }
+                "string" => Doc::text(self.string(node)),
+                _ => self.inline(node, &children(node)),
+            }
+        };
+        if self.needs_semicolon(node) && self.keeps_semicolon(node) {
+            Doc::concat(vec![doc, Doc::text(";")])
+        } else {
+            doc
+        }
+    }
+
+    /// Statements one per line, keeping a single blank line where the source
+    /// had one and comments that end a statement's line on that line.
+    fn statements(&self, statements: &[Node<'_>]) -> Doc {
+        let mut docs = Vec::new();
+        let mut previous: Option<Node<'_>> = None;
+        for &statement in statements {
+            if let Some(previous) = previous {
+                let same_line = statement.start_position().row == previous.end_position().row;
+                if statement.kind() == "comment" && same_line {
+                    docs.push(Doc::text(" "));
+                    docs.push(self.node(statement));
+                    continue;
+                }
+                if statement.start_position().row > previous.end_position().row + 1 {
+                    docs.push(Doc::text("\n"));
+                }
+                docs.push(Doc::hard_line());
+            }
+            docs.push(self.node(statement));
+            previous = Some(statement);
+        }
+        Doc::concat(docs)
+    }
+
+    fn block(&self, node: Node<'_>) -> Doc {
+        // Comments after the closing brace are parsed into the block.
+        let children = children(node);
+        let close = children
+            .iter()
+            .rposition(|child| child.kind() == "}")
+            .unwrap_or(children.len());
+        let statements = children[..close]
+            .iter()
+            .copied()
+            .filter(|child| child.is_named() && child.kind() != "empty_statement")
+            .collect::<Vec<_>>();
+        let mut docs = if statements.is_empty() {
+            vec![Doc::text("{{}}")]
+        } else {
+            vec![
+                Doc::text("{{"),
+                Doc::concat(vec![Doc::hard_line(), self.statements(&statements)]).indent(),
+                Doc::hard_line(),
+                Doc::text("}}"),
+            ]
+        };
+        for &comment in children.iter().skip(close + 1) {
+            if let Some(brace) = children.get(close)
+                && comment.start_position().row == brace.end_position().row
+            {
+                docs.push(Doc::text(" "));
+            } else {
+                docs.push(Doc::hard_line());
+            }
+            docs.push(self.node(comment));
+        }
+        Doc::concat(docs)
+    }
+
+    fn switch_case(&self, node: Node<'_>) -> Doc {
+        let children = children(node);
+        let colon = children
+            .iter()
+            .position(|child| child.kind() == ":")
+            .unwrap_or(children.len().saturating_sub(1));
+        let head = self.inline(node, &children[..=colon]);
+        let statements = children[colon + 1..]
+            .iter()
+            .copied()
+            .filter(|child| child.is_named() && child.kind() != "empty_statement")
+            .collect::<Vec<_>>();
+        if statements.is_empty() {
+            return head;
+        }
+        Doc::concat(vec![
+            head,
+            Doc::concat(vec![Doc::hard_line(), self.statements(&statements)]).indent(),
+        ])
+    }
+
+    /// A bracketed, comma-separated list: on one line when it fits, otherwise
+    /// one item per line. Call arguments ending in a function, object or array
+    /// hug it instead, so only its body breaks.
+    fn list(&self, node: Node<'_>, padded: bool) -> Doc {
+        let children = children(node);
+        let (Some(open), Some(close)) = (children.first(), children.last()) else {
+            return self.inline(node, &children);
+        };
+        let inner = &children[1..children.len() - 1];
+        let mut previous_comma = true;
+        for child in inner.iter().filter(|child| child.kind() != "comment") {
+            let is_comma = child.kind() == ",";
+            // Array holes are kept as written.
+            if is_comma && previous_comma {
+                return self.inline(node, &children);
+            }
+            previous_comma = is_comma;
+        }
+
+        let mut items: Vec<ListItem<'_>> = Vec::new();
+        let mut pending = Vec::new();
+        let mut last_row = open.end_position().row;
+        for &child in inner {
+            match child.kind() {
+                "," => last_row = child.end_position().row,
+                "comment" => {
+                    let trailing = child.start_position().row == last_row;
+                    match items.last_mut() {
+                        Some(item) if trailing && item.trailing.is_none() && pending.is_empty() => {
+                            item.trailing = Some(child);
+                        }
+                        _ => pending.push(child),
+                    }
+                }
+                _ => {
+                    items.push(ListItem {
+                        leading: std::mem::take(&mut pending),
+                        node: child,
+                        trailing: None,
+                    });
+                    last_row = child.end_position().row;
+                }
+            }
+        }
+        if items.is_empty() && pending.is_empty() {
+            return Doc::text(format!("{}{}", self.text(*open), self.text(*close)));
+        }
+
+        let has_comments = !pending.is_empty() || items.iter().any(|item| item.has_comments());
+        let hugs = node.kind() == "arguments"
+            && !has_comments
+            && items.last().is_some_and(|item| {
+                matches!(
+                    item.node.kind(),
+                    "arrow_function" | "function_expression" | "object" | "array"
+                )
+            });
+        if hugs {
+            let mut docs = vec![Doc::text(self.text(*open))];
+            for (position, item) in items.iter().enumerate() {
+                if position > 0 {
+                    docs.push(Doc::text(", "));
+                }
+                docs.push(self.node(item.node));
+            }
+            docs.push(Doc::text(self.text(*close)));
+            return Doc::concat(docs);
+        }
+
+        let mut docs = Vec::new();
+        let count = items.len();
+        let mut break_next = false;
+        for (position, item) in items.into_iter().enumerate() {
+            if position > 0 {
+                docs.push(if break_next {
+                    Doc::hard_line()
+                } else {
+                    Doc::line()
+                });
+            }
+            for comment in item.leading {
+                docs.push(Doc::text(self.text(comment)));
+                docs.push(Doc::hard_line());
+            }
+            docs.push(self.node(item.node));
+            if position + 1 < count {
+                docs.push(Doc::text(","));
+            }
+            break_next = false;
+            if let Some(comment) = item.trailing {
+                docs.push(Doc::text(" "));
+                docs.push(Doc::text(self.text(comment)));
+                break_next = true;
+            }
+        }
+        for comment in pending {
+            docs.push(Doc::hard_line());
+            docs.push(Doc::text(self.text(comment)));
+            break_next = true;
+        }
+
+        // An object written across lines stays expanded.
+        let expanded = node.kind() == "object"
+            && inner
+                .first()
+                .is_some_and(|first| first.start_position().row > open.end_position().row);
+        let separator = || {
+            if padded {
+                Doc::line()
+            } else {
+                Doc::soft_line()
+            }
+        };
+        let opening = if expanded {
+            Doc::hard_line()
+        } else {
+            separator()
+        };
+        let closing = if break_next {
+            Doc::hard_line()
+        } else {
+            separator()
+        };
+        Doc::concat(vec![
+            Doc::text(self.text(*open)),
+            Doc::concat(vec![opening, Doc::concat(docs)]).indent(),
+            closing,
+            Doc::text(self.text(*close)),
+        ])
+        .group()
+    }
+
+    fn inline(&self, parent: Node<'_>, children: &[Node<'_>]) -> Doc {
+        let mut docs = Vec::new();
+        let mut previous: Option<Node<'_>> = None;
+        for &child in children {
+            if child.kind() == ";" && self.dropped.contains(&child.start_byte()) {
+                continue;
+            }
+            if let Some(previous) = previous {
+                if is_line_comment(previous, self.source) {
+                    docs.push(Doc::hard_line());
+                } else if self.space_between(parent, previous, child) {
+                    docs.push(Doc::text(" "));
+                }
+            }
+            docs.push(self.node(child));
+            previous = Some(child);
+        }
+        Doc::concat(docs)
+    }
+
+    fn space_between(&self, parent: Node<'_>, previous: Node<'_>, next: Node<'_>) -> bool {
+        if previous.kind() == "comment" || next.kind() == "comment" {
+            return true;
+        }
+        let left = last_leaf(previous);
+        let right = first_leaf(next);
+        let parent_kind = parent.kind();
+        if matches!(right.kind(), "," | ";" | ")" | "]" | "." | "optional_chain")
+            || matches!(
+                left.kind(),
+                "(" | "[" | "." | "..." | "optional_chain" | "@"
+            )
+        {
+            return false;
+        }
+        if right.kind() == ":" {
+            return parent_kind == "ternary_expression";
+        }
+        if next.kind() == "arguments"
+            || (next.kind() == "template_string" && parent_kind == "call_expression")
+            || (right.kind() == "[" && parent_kind == "subscript_expression")
+        {
+            return false;
+        }
+        if next.kind() == "formal_parameters" {
+            return !left.is_named() && left.kind() != "*";
+        }
+        match parent_kind {
+            "unary_expression" => {
+                let operator = self.source.text[left.byte_range()].to_string();
+                operator
+                    .chars()
+                    .all(|character| character.is_ascii_alphabetic())
+                    || matches!(
+                        (operator.as_str(), self.text(right).chars().next()),
+                        ("-", Some('-')) | ("+", Some('+'))
+                    )
+            }
+            "update_expression" => false,
+            "method_definition" => left.kind() != "*",
+            "generator_function" | "generator_function_declaration" | "yield_expression" => {
+                right.kind() != "*"
+            }
+            _ => true,
+        }
+    }
+
+    /// The string with its quotes switched to the configured style, unless it
+    /// holds quotes, escapes or interpolations of its own.
+    fn string(&self, node: Node<'_>) -> String {
+        let text = self.text(node);
+        let quote = match self.config.quote_style {
+            QuoteStyle::Double => '"',
+            QuoteStyle::Single => '\'',
+            QuoteStyle::Preserve => return text,
+        };
+        let range = node.byte_range();
+        let body = &self.source.text[range.start + 1..range.end - 1];
+        if self.source.has_atom(&range) || body.contains(['"', '\'', '\\']) {
+            return text;
+        }
+        format!("{quote}{}{quote}", &text[1..text.len() - 1])
+    }
+
+    fn needs_semicolon(&self, node: Node<'_>) -> bool {
+        if !SEMICOLON_STATEMENTS.contains(&node.kind()) || !is_statement_position(node) {
+            return false;
+        }
+        if node.kind() == "export_statement" {
+            let mut cursor = node.walk();
+            let declaration = node.named_children(&mut cursor).last();
+            return declaration.is_none_or(|child| !BLOCK_DECLARATIONS.contains(&child.kind()));
+        }
+        true
+    }
+
+    fn keeps_semicolon(&self, node: Node<'_>) -> bool {
+        match self.config.semicolons {
+            Semicolons::Always => true,
+            Semicolons::AsNeeded => next_token(node).is_some_and(|token| {
+                token.kind() != ";"
+                    && self
+                        .text(token)
+                        .chars()
+                        .next()
+                        .is_some_and(|character| ASI_HAZARDS.contains(&character))
+            }),
+        }
+    }
+
+    fn text(&self, node: Node<'_>) -> String {
+        self.source.template_text(node.byte_range())
+    }
+}
+
+struct ListItem<'tree> {
+    leading: Vec<Node<'tree>>,
+    node: Node<'tree>,
+    trailing: Option<Node<'tree>>,
+}
+
+impl ListItem<'_> {
+    fn has_comments(&self) -> bool {
+        !self.leading.is_empty() || self.trailing.is_some()
+    }
+}
+
+fn children<'tree>(node: Node<'tree>) -> Vec<Node<'tree>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).collect()
+}
+
+fn statement_children<'tree>(node: Node<'tree>) -> Vec<Node<'tree>> {
+    children(node)
+        .into_iter()
+        .filter(|child| child.is_named() && child.kind() != "empty_statement")
+        .collect()
+}
+
+/// Whether the node is a statement of its own, rather than part of a `for`
+/// header or an `export`.
+fn is_statement_position(node: Node<'_>) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    if STATEMENT_LISTS.contains(&parent.kind()) || STATEMENT_PARENTS.contains(&parent.kind()) {
+        return true;
+    }
+    ["body", "consequence"].iter().any(|field| {
+        parent
+            .child_by_field_name(field)
+            .is_some_and(|child| child.id() == node.id())
+    })
+}
+
+fn is_line_comment(node: Node<'_>, source: &TemplateSource) -> bool {
+    node.kind() == "comment" && source.text[node.byte_range()].starts_with("//")
+}
+
+fn first_leaf(node: Node<'_>) -> Node<'_> {
+    let mut node = node;
+    while let Some(child) = node.child(0) {
+        node = child;
+    }
+    node
+}
+
+fn last_leaf(node: Node<'_>) -> Node<'_> {
+    let mut node = node;
+    while let Some(child) = node.child(node.child_count().saturating_sub(1) as u32) {
+        node = child;
+    }
+    node
+}
+
+/// The first token after the node, skipping comments.
+fn next_token(node: Node<'_>) -> Option<Node<'_>> {
+    let mut current = node;
+    loop {
+        let mut sibling = current.next_sibling();
+        while let Some(candidate) = sibling {
+            if candidate.kind() != "comment" {
+                return Some(first_leaf(candidate));
+            }
+            sibling = candidate.next_sibling();
+        }
+        current = current.parent()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tstring_syntax::TemplateInterpolation;
+
+    use super::*;
+
+    fn template(segments: &[&str]) -> TemplateInput {
+        let segments = segments
+            .iter()
+            .enumerate()
+            .map(|(index, text)| match text.strip_prefix('{') {
+                Some(expression) => TemplateSegment::Interpolation(TemplateInterpolation {
+                    expression: expression.trim_end_matches('}').to_string(),
+                    conversion: None,
+                    format_spec: String::new(),
+                    interpolation_index: index,
+                    raw_source: Some(text.to_string()),
+                }),
+                None => TemplateSegment::StaticText(text.to_string()),
+            })
+            .collect();
+        TemplateInput::from_segments(segments)
+    }
+
+    fn format_js(segments: &[&str], config: &JavascriptFormatConfig, line_length: usize) -> String {
+        format_template(&template(segments), config, line_length)
+            .expect("javascript template formats")
+    }
+
+    #[test]
+    fn formats_statements_blocks_and_keeps_interpolations() {
+        assert_eq!(
+            format_js(
+                &[
+                    "\nconst x=",
+                    "{value}",
+                    ";let y = 'a'+`b ${x}`\nif(x){console.log( x ,y)}else{ y++ }\n\n\nfunction  foo(a,b){return a+b} // done\nitems.forEach((item)=>{ render(item, {name:item.name}) })\n",
+                ],
+                &JavascriptFormatConfig::default(),
+                80,
+            ),
+            "
+const x = {value};
+let y = \"a\" + `b ${{x}}`;
+if (x) {{
+  console.log(x, y);
+}} else {{
+  y++;
+}}
+
+function foo(a, b) {{
+  return a + b;
+}} // done
+items.forEach((item) => {{
+  render(item, {{ name: item.name }});
+}});
+"
+        );
+    }
+
+    #[test]
+    fn applies_semicolon_and_quote_settings() {
+        let config = JavascriptFormatConfig {
+            indent_width: 4,
+            semicolons: Semicolons::AsNeeded,
+            quote_style: QuoteStyle::Single,
+        };
+        assert_eq!(
+            format_js(
+                &[
+                    "let x = \"a\";\n[1, 2].forEach(f);\nclass A { b = \"it's\"; c() { return 1; } }"
+                ],
+                &config,
+                80,
+            ),
+            "let x = 'a';
+[1, 2].forEach(f)
+class A {{
+    b = \"it's\"
+    c() {{
+        return 1
+    }}
+}}"
+        );
+    }
+
+    #[test]
+    fn breaks_lists_that_do_not_fit() {
+        assert_eq!(
+            format_js(
+                &[
+                    "call(firstArgument, secondArgument, ",
+                    "{third}",
+                    ")\nconst list = [\n1, // one\n2,\n]",
+                ],
+                &JavascriptFormatConfig::default(),
+                30,
+            ),
+            "call(
+  firstArgument,
+  secondArgument,
+  {third}
+);
+const list = [
+  1, // one
+  2
+];"
+        );
+    }
+
+    #[test]
+    fn refuses_templates_it_cannot_format_safely() {
+        let error = format_template(
+            &template(&["init();\n", "{body}", "\ndone();"]),
+            &JavascriptFormatConfig::default(),
+            80,
+        )
+        .expect_err("standalone interpolation is refused");
+        assert!(error.message.contains("'{body}' stands alone"));
+        let span = error.diagnostics[0].span.as_ref().expect("error span");
+        assert_eq!(span.start.token_index, 1);
+        assert_eq!(span.end.token_index, 1);
+
+        let error =
+            check_template(&template(&["if (x) {"])).expect_err("invalid syntax is refused");
+        assert!(error.message.contains("Invalid javascript syntax"));
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+
+use tree_sitter::Node;
+
+use crate::lint::{EmbeddedDocument, LintDiagnostic, LintSeverity};
+
+const RULE_EVAL: &str = "javascript-eval";
+const RULE_INTERPOLATION_IN_STRING: &str = "javascript-interpolation-in-string";
+const RULE_LOOSE_EQUALITY: &str = "javascript-loose-equality";
+const RULE_UNDECLARED_ASSIGNMENT: &str = "javascript-undeclared-assignment";
+const RULE_DEBUGGER: &str = "javascript-debugger";
+
+/// Reports `eval` and `new Function`, interpolations spliced into string
+/// literals, `==`/`!=` comparisons, assignments to names the template never
+/// declares, and `debugger` statements.
+pub fn lint_rules(document: &EmbeddedDocument<'_>) -> Vec<LintDiagnostic> {
+    let root = document.tree.root_node();
+    let mut declared = HashSet::new();
+    visit(root, &mut |node| {
+        if let Some(binding) = binding_target(node) {
+            visit(binding, &mut |node| {
+                if matches!(
+                    node.kind(),
+                    "identifier" | "shorthand_property_identifier_pattern"
+                ) {
+                    declared.insert(document.text(node));
+                }
+            });
+        }
+    });
+
+    let mut diagnostics = Vec::new();
+    visit(root, &mut |node| match node.kind() {
+        "call_expression" => {
+            if let Some(callee) = node.child_by_field_name("function")
+                && matches!(document.text(callee), "eval" | "Function")
+            {
+                diagnostics.push(eval_diagnostic(document, callee, document.text(callee)));
+            }
+        }
+        "new_expression" => {
+            if let Some(constructor) = node.child_by_field_name("constructor")
+                && document.text(constructor) == "Function"
+            {
+                diagnostics.push(eval_diagnostic(document, constructor, "new Function"));
+            }
+        }
+        "string_fragment" => {
+            for interpolation in document.interpolations_in(node) {
+                let mut diagnostic = document.diagnostic(
+                    node,
+                    RULE_INTERPOLATION_IN_STRING,
+                    LintSeverity::Error,
+                    format!(
+                        "Interpolation '{}' is spliced into a JavaScript string literal; interpolate a JSON-encoded value in place of the whole string",
+                        interpolation.expression
+                    ),
+                );
+                diagnostic.start_line = interpolation.location.start_line;
+                diagnostic.start_column = interpolation.location.start_column;
+                diagnostic.end_line = interpolation.location.end_line;
+                diagnostic.end_column = interpolation.location.end_column;
+                diagnostics.push(diagnostic);
+            }
+        }
+        "binary_expression" => {
+            let Some(operator) = node.child_by_field_name("operator") else {
+                return;
+            };
+            let strict = match operator.kind() {
+                "==" => "===",
+                "!=" => "!==",
+                _ => return,
+            };
+            // `x == null` is the idiomatic check for both null and undefined.
+            let compares_null = [
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|side| side.kind() == "null");
+            if !compares_null {
+                diagnostics.push(document.diagnostic(
+                    operator,
+                    RULE_LOOSE_EQUALITY,
+                    LintSeverity::Warning,
+                    format!("Use '{strict}' instead of '{}'", operator.kind()),
+                ));
+            }
+        }
+        "assignment_expression" | "augmented_assignment_expression" | "update_expression" => {
+            let field = if node.kind() == "update_expression" {
+                "argument"
+            } else {
+                "left"
+            };
+            if let Some(target) = node.child_by_field_name(field)
+                && target.kind() == "identifier"
+                && !declared.contains(document.text(target))
+                && !document.touches_interpolation(target)
+            {
+                diagnostics.push(document.diagnostic(
+                    target,
+                    RULE_UNDECLARED_ASSIGNMENT,
+                    LintSeverity::Warning,
+                    format!(
+                        "Assignment to undeclared variable '{}'; declare it with let, const, or var",
+                        document.text(target)
+                    ),
+                ));
+            }
+        }
+        "debugger_statement" => diagnostics.push(document.diagnostic(
+            node,
+            RULE_DEBUGGER,
+            LintSeverity::Warning,
+            "Remove the 'debugger' statement".to_string(),
+        )),
+        _ => {}
+    });
+    diagnostics
+}
+
+fn eval_diagnostic(document: &EmbeddedDocument<'_>, node: Node<'_>, name: &str) -> LintDiagnostic {
+    document.diagnostic(
+        node,
+        RULE_EVAL,
+        LintSeverity::Error,
+        format!("'{name}' runs a string as code; avoid it in JavaScript templates"),
+    )
+}
+
+/// The pattern a declaration binds names in, scope-insensitively.
+fn binding_target(node: Node<'_>) -> Option<Node<'_>> {
+    match node.kind() {
+        "variable_declarator"
+        | "function_declaration"
+        | "generator_function_declaration"
+        | "function_expression"
+        | "class_declaration" => node.child_by_field_name("name"),
+        "arrow_function" => node.child_by_field_name("parameter"),
+        "catch_clause" => node.child_by_field_name("parameter"),
+        "for_in_statement" => node
+            .child_by_field_name("kind")
+            .and_then(|_| node.child_by_field_name("left")),
+        "formal_parameters" | "import_clause" => Some(node),
+        _ => None,
+    }
+}
+
+fn visit<'tree>(node: Node<'tree>, callback: &mut impl FnMut(Node<'tree>)) {
+    if node.is_error() {
+        return;
+    }
+    callback(node);
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        visit(child, callback);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::lint_source;
+
+    fn js_diagnostics(script: &str) -> Vec<(String, String)> {
+        let source = format!(
+            "from typing import Annotated\nfrom string.templatelib import Template\n\nuser = 1\nscript: Annotated[Template, \"javascript\"] = t\"\"\"{script}\"\"\"\n"
+        );
+        lint_source(std::path::Path::new("script.py"), &source)
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.message))
+            .collect()
+    }
+
+    fn rules(script: &str) -> Vec<String> {
+        js_diagnostics(script)
+            .into_iter()
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+
+    #[test]
+    fn reports_eval_loose_equality_and_debugger() {
+        assert_eq!(
+            rules(
+                "eval(code); const f = new Function('a', 'return a'); if (a == 1 || b != c) {{ debugger; }}"
+            ),
+            vec![
+                "javascript-eval",
+                "javascript-eval",
+                "javascript-loose-equality",
+                "javascript-loose-equality",
+                "javascript-debugger",
+            ]
+        );
+        assert!(rules("if (value == null) {{ report(value === 1); }}").is_empty());
+    }
+
+    #[test]
+    fn reports_interpolations_inside_string_literals_only() {
+        assert_eq!(
+            js_diagnostics("const a = \"hello {user}\"; const b = `${{x}} {user}`; const c = {user};"),
+            vec![
+                (
+                    "javascript-interpolation-in-string".to_string(),
+                    "Interpolation 'user' is spliced into a JavaScript string literal; interpolate a JSON-encoded value in place of the whole string".to_string()
+                ),
+                (
+                    "javascript-interpolation-in-string".to_string(),
+                    "Interpolation 'user' is spliced into a JavaScript string literal; interpolate a JSON-encoded value in place of the whole string".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_assignments_to_undeclared_names() {
+        assert_eq!(
+            rules(
+                "let count = 0; count += 1; total = count; function add(step, {{ scale }}) {{ step++; scale = 2; }} for (const item of items) {{ item = 1; }} missing++; {user} = 1;"
+            ),
+            vec![
+                "javascript-undeclared-assignment",
+                "javascript-undeclared-assignment"
+            ]
+        );
+    }
+}
@@ -1,22 +1,33 @@
 use anyhow::Result;
 
 pub(crate) mod backend;
+pub(crate) mod css;
 pub mod formatting;
+pub mod grammar;
+pub(crate) mod graphql;
 pub mod highlighter;
+pub(crate) mod javascript;
 pub mod lint;
+pub(crate) mod markdown;
 pub mod parser;
+pub mod plugin;
 pub mod project_config;
 pub(crate) mod python;
+pub(crate) mod regex;
 pub mod shadow;
+pub(crate) mod shell;
 #[cfg(feature = "sql")]
 pub(crate) mod sql;
 pub(crate) mod tdom;
+pub(crate) mod template_text;
+pub(crate) mod xml;
 
 pub use formatting::{
     FormatError, FormatOptions, TemplateEdit, apply_diagnostic_edits, apply_template_edits,
     format_document, format_document_in_file, format_document_in_file_with_options,
     format_document_range, format_document_range_with_options, format_document_with_options,
 };
+pub use grammar::CustomGrammars;
 pub use highlighter::{HighlightedRange, TemplateHighlighter};
 pub use lint::{
     DiagnosticData, DiagnosticEdit, DiagnosticEditRange, LintDiagnostic, LintFileResult,
@@ -26,9 +37,12 @@ pub use parser::{
     Expression, InterpolationInfo, LanguageDetection, Location, StaticTextSegment, TemplatePart,
     TemplateStringInfo, TemplateStringParser,
 };
+pub use plugin::LanguagePlugins;
 pub use project_config::{
-    ProjectConfig, RuleSeverity, SqlConfig, find_config_root, load_project_config,
-    load_project_config_for_path,
+    CommaStyle, CssFormatConfig, DeclarationOrder, FormatConfig, GrammarConfig,
+    InterpolationConfig, JavascriptFormatConfig, KeywordCase, PluginConfig, ProjectConfig,
+    QuoteStyle, RuleSeverity, Semicolons, SqlConfig, SqlDialect, SqlFormatConfig, find_config_root,
+    load_project_config, load_project_config_for_path,
 };
 pub use shadow::{
     ShadowCheckSite, ShadowDocument, synthesize_for_type_check,
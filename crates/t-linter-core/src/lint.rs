@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
@@ -11,26 +11,48 @@ use tstring_tdom as backend_tdom;
 use tstring_thtml as backend_thtml;
 
 use crate::backend::TemplateBackend;
+use crate::grammar::{CustomGrammar, CustomGrammars, grammar_error};
 use crate::parser::{CallableParameter, CallableValueType, ModuleContext};
-use crate::project_config::{ProjectConfig, RuleSeverity, SqlConfig, load_project_config_for_path};
+use crate::plugin::LanguagePlugins;
+use crate::project_config::{
+    InterpolationConfig, ProjectConfig, RuleSeverity, load_project_config_for_path,
+};
 use crate::tdom::resolve_component_signature;
 use crate::{TemplatePart, TemplateStringInfo, TemplateStringParser};
 
-const RULE_EMBEDDED_PARSE_ERROR: &str = "embedded-parse-error";
+pub(crate) const RULE_EMBEDDED_PARSE_ERROR: &str = "embedded-parse-error";
 const RULE_FILE_READ_ERROR: &str = "file-read-error";
 const RULE_PYTHON_PARSE_ERROR: &str = "python-parse-error";
 const RULE_COMPONENT_MISSING_PROP: &str = "component-missing-prop";
 const RULE_COMPONENT_UNEXPECTED_PROP: &str = "component-unexpected-prop";
 const RULE_COMPONENT_PROP_TYPE_ERROR: &str = "component-prop-type-error";
 const RULE_COMPONENT_UNRESOLVED: &str = "component-unresolved";
+const RULE_COMPONENT_UNEXPECTED_CHILDREN: &str = "component-unexpected-children";
+const RULE_COMPONENT_MISSING_CHILDREN: &str = "component-missing-children";
+const RULE_COMPONENT_SELF_CLOSING: &str = "component-self-closing";
+const RULE_COMPONENT_RETURN_TYPE: &str = "component-return-type";
 const RULE_TEMPLATE_SCHEMA_MISSING_KEY: &str = "template-schema-missing-key";
 const RULE_TEMPLATE_SCHEMA_UNKNOWN_KEY: &str = "template-schema-unknown-key";
 const RULE_TEMPLATE_SCHEMA_TYPE_SHAPE: &str = "template-schema-type-shape";
+const RULE_TEMPLATE_SCHEMA_INVALID_VALUE: &str = "template-schema-invalid-value";
+const RULE_TEMPLATE_STRING_EXPECTED: &str = "template-string-expected";
 const RULE_TEMPLATE_METADATA_CONFLICT: &str = "template-metadata-conflict";
 const RULE_TEMPLATE_METADATA_REDUNDANT_LANGUAGE: &str = "template-metadata-redundant-language";
 const RULE_BINDING_UNRESOLVED: &str = "binding-unresolved";
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+const RULE_INTERPOLATION_REPR_CONVERSION: &str = "interpolation-repr-conversion";
+const RULE_INTERPOLATION_DEBUG_SPECIFIER: &str = "interpolation-debug-specifier";
+const RULE_INTERPOLATION_FORMAT_SPEC_UNKNOWN: &str = "interpolation-format-spec-unknown";
+const RULE_INTERPOLATION_CONSTANT_LITERAL: &str = "interpolation-constant-literal";
+const RULE_INTERPOLATION_DUPLICATE_EXPRESSION: &str = "interpolation-duplicate-expression";
+const RULE_INTERPOLATION_LANGUAGE_MISMATCH: &str = "interpolation-language-mismatch";
+
+const SCHEMA_MARKERS: &[(&str, &str, &str)] = &[
+    ("Json", "json_tstring.Json", "json"),
+    ("Yaml", "yaml_tstring.Yaml", "yaml"),
+    ("Toml", "toml_tstring.Toml", "toml"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LintSeverity {
     Error,
@@ -111,6 +133,108 @@ struct ProcessedTemplate {
     processed_to_original: Vec<usize>,
 }
 
+/// An embedded template parsed with its interpolations replaced by
+/// placeholders, for rules that walk the embedded language's syntax tree.
+pub(crate) struct EmbeddedDocument<'a> {
+    pub path: &'a Path,
+    pub template: &'a TemplateStringInfo,
+    pub language: &'a str,
+    pub tree: &'a Tree,
+    content: &'a str,
+    processed_to_original: &'a [usize],
+    /// Each interpolation with its range in the template content.
+    pub interpolations: Vec<(&'a crate::parser::InterpolationInfo, std::ops::Range<usize>)>,
+}
+
+impl<'a> EmbeddedDocument<'a> {
+    fn new(
+        path: &'a Path,
+        template: &'a TemplateStringInfo,
+        language: &'a str,
+        processed: &'a ProcessedTemplate,
+        tree: &'a Tree,
+    ) -> Self {
+        let interpolations = template
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                TemplatePart::Interpolation(interpolation) => Some(interpolation),
+                TemplatePart::Static(_) => None,
+            })
+            .zip(
+                interpolation_original_ranges(template)
+                    .into_iter()
+                    .map(|(_, range)| range),
+            )
+            .collect();
+        Self {
+            path,
+            template,
+            language,
+            tree,
+            content: &processed.content,
+            processed_to_original: &processed.processed_to_original,
+            interpolations,
+        }
+    }
+
+    pub fn text(&self, node: Node<'_>) -> &'a str {
+        &self.content[node.byte_range()]
+    }
+
+    pub fn original_range(&self, node: Node<'_>) -> std::ops::Range<usize> {
+        map_processed_offset(self.processed_to_original, node.start_byte())
+            ..map_processed_offset(self.processed_to_original, node.end_byte())
+    }
+
+    /// Interpolations overlapping `node`, whose placeholder text stands in
+    /// for a value the linter cannot see.
+    pub fn interpolations_in(
+        &self,
+        node: Node<'_>,
+    ) -> impl Iterator<Item = &'a crate::parser::InterpolationInfo> + '_ {
+        let range = self.original_range(node);
+        self.interpolations
+            .iter()
+            .filter(move |(_, interpolation)| {
+                interpolation.start < range.end && range.start < interpolation.end
+            })
+            .map(|(interpolation, _)| *interpolation)
+    }
+
+    pub fn touches_interpolation(&self, node: Node<'_>) -> bool {
+        self.interpolations_in(node).next().is_some()
+    }
+
+    pub fn diagnostic(
+        &self,
+        node: Node<'_>,
+        rule: &str,
+        severity: LintSeverity,
+        message: String,
+    ) -> LintDiagnostic {
+        let range = self.original_range(node);
+        let ((start_line, start_column), (end_line, end_column)) =
+            map_content_range_to_document(self.template, range.start, range.end);
+        LintDiagnostic {
+            rule: rule.to_string(),
+            severity,
+            language: Some(self.language.to_string()),
+            message,
+            file: self.path.to_path_buf(),
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+            expected_type: None,
+            found_type: None,
+            schema_pointer: None,
+            source_of_truth: None,
+            suggested_edits: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 struct StaticSpreadAnalysis {
     bindings: std::collections::HashMap<String, Vec<StaticSpreadBinding>>,
@@ -149,14 +273,72 @@ struct ResolvedSpreadEntry {
 }
 
 #[derive(Debug, Clone, Default)]
+struct SchemaRegistry {
+    models: BTreeMap<String, SchemaModel>,
+    enums: BTreeMap<String, SchemaEnum>,
+}
+
+#[derive(Debug, Clone)]
 struct SchemaModel {
-    fields: std::collections::BTreeMap<String, SchemaField>,
+    kind: SchemaModelKind,
+    fields: BTreeMap<String, SchemaField>,
+    allows_extra: bool,
+    populate_by_name: bool,
+    origin: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SchemaModelKind {
+    TypedDict,
+    Dataclass,
+    Pydantic,
+    Attrs,
+    JsonSchema,
 }
 
 #[derive(Debug, Clone)]
 struct SchemaField {
     required: bool,
-    scalar: Option<JsonScalarKind>,
+    schema: SchemaType,
+    aliases: Vec<String>,
+    origin: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct SchemaEnum {
+    values: Vec<SchemaLiteral>,
+    complete: bool,
+}
+
+#[derive(Debug, Clone)]
+enum SchemaType {
+    Any,
+    Scalar(JsonScalarKind),
+    Named(String),
+    Array(Box<SchemaType>),
+    Map(Box<SchemaType>),
+    Literal(Vec<SchemaLiteral>),
+    Union(Vec<SchemaType>),
+    Constrained(Box<SchemaType>, SchemaConstraints),
+}
+
+#[derive(Debug, Clone, Default)]
+struct SchemaConstraints {
+    gt: Option<f64>,
+    ge: Option<f64>,
+    lt: Option<f64>,
+    le: Option<f64>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SchemaLiteral {
+    String(String),
+    Integer(i64),
+    Number(f64),
+    Boolean(bool),
+    Null,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -172,16 +354,40 @@ enum JsonScalarKind {
 
 #[derive(Debug, Clone, Default)]
 struct JsonObjectOutline {
-    pointer: String,
-    static_keys: std::collections::BTreeMap<String, JsonKeyOutline>,
+    location: Option<crate::Location>,
+    static_keys: BTreeMap<String, JsonKeyOutline>,
     has_interpolation_key: bool,
 }
 
 #[derive(Debug, Clone)]
 struct JsonKeyOutline {
     key_location: crate::Location,
-    value_location: crate::Location,
-    value_kind: Option<JsonScalarKind>,
+    quoted_key: bool,
+    value: JsonValueOutline,
+}
+
+#[derive(Debug, Clone)]
+struct JsonValueOutline {
+    location: crate::Location,
+    kind: Option<JsonScalarKind>,
+    literal: Option<SchemaLiteral>,
+    quoted: bool,
+    object: Option<JsonObjectOutline>,
+    items: Option<Vec<JsonValueOutline>>,
+}
+
+struct OutlineContext<'a> {
+    template: &'a TemplateStringInfo,
+    processed: &'a ProcessedTemplate,
+    interpolation_ranges: Vec<std::ops::Range<usize>>,
+}
+
+#[derive(Clone, Copy)]
+struct SchemaLintContext<'a> {
+    path: &'a Path,
+    registry: &'a SchemaRegistry,
+    source_of_truth: &'a str,
+    language: &'a str,
 }
 
 #[derive(Debug, Clone)]
@@ -230,14 +436,26 @@ pub fn lint_source_with_config(
             template,
             &module_context,
             &static_spread_analysis,
-            &config.sql,
+            config,
         )?);
+        diagnostics.extend(lint_interpolation_formatting(
+            path,
+            template,
+            &config.interpolation,
+        ));
     }
-    diagnostics.extend(lint_json_schema_bindings(
+    diagnostics.extend(lint_interpolation_usage(path, &templates)?);
+    diagnostics.extend(lint_template_schema_bindings(
         path,
         source,
         &templates,
         &module_context,
+        config,
+    )?);
+    diagnostics.extend(lint_template_string_expected(
+        path,
+        source,
+        &module_context,
     )?);
 
     sort_and_dedup_diagnostics(&mut diagnostics);
@@ -518,8 +736,22 @@ fn lint_template(
     template: &TemplateStringInfo,
     module_context: &ModuleContext,
     static_spread_analysis: &StaticSpreadAnalysis,
-    sql_config: &SqlConfig,
+    config: &ProjectConfig,
 ) -> Result<Vec<LintDiagnostic>> {
+    if let Some(diagnostics) = LanguagePlugins::from_config(config).lint_template(path, template) {
+        return Ok(diagnostics);
+    }
+    if let Some(language) = template.language.as_deref()
+        && let Some(grammar) = CustomGrammars::from_config(config).load(language)
+    {
+        let language = language.trim().to_ascii_lowercase();
+        return match grammar {
+            Ok(grammar) => lint_custom_grammar_template(path, template, &language, &grammar),
+            Err(error) => Ok(vec![grammar_error(path, template, &language, &error)]),
+        };
+    }
+
+    let sql_config = &config.sql;
     let Some(language) = template
         .language
         .as_deref()
@@ -537,10 +769,20 @@ fn lint_template(
         }
     }
 
+    if language == "markdown" {
+        return Ok(crate::markdown::rules::lint_rules(path, template));
+    }
+    if language == "regex" {
+        return Ok(crate::regex::rules::lint_rules(path, template));
+    }
+
     if let Some(backend) = TemplateBackend::for_language(&language)
-        && backend != TemplateBackend::Sql
+        && !matches!(
+            backend,
+            TemplateBackend::Css | TemplateBackend::Javascript | TemplateBackend::Sql
+        )
     {
-        return lint_backend_template(
+        let mut diagnostics = lint_backend_template(
             path,
             source,
             template,
@@ -548,12 +790,145 @@ fn lint_template(
             backend,
             module_context,
             static_spread_analysis,
-        );
+        )?;
+        if language == "graphql" {
+            diagnostics.extend(crate::graphql::rules::lint_rules(path, template, config));
+        }
+        return Ok(diagnostics);
     }
 
+    #[cfg(feature = "sql")]
+    let sql_dialect = crate::sql::dialect::resolve(template, sql_config);
+    #[cfg(feature = "sql")]
+    let sql_positions = if language == "sql" {
+        crate::sql::positions::classify_interpolations(template, sql_dialect)
+    } else {
+        Vec::new()
+    };
+    #[cfg(feature = "sql")]
+    let processed = prepare_template_with_placeholders(template, |interpolation| {
+        let is_identifier = sql_positions.iter().any(|entry| {
+            entry.interpolation.interpolation_index == interpolation.interpolation_index
+                && entry.position.is_identifier()
+        });
+        if is_identifier {
+            "t_linter_expr"
+        } else {
+            placeholder_for_language(&language)
+        }
+    });
+    #[cfg(feature = "sql")]
+    let processed = if language == "sql" {
+        ProcessedTemplate {
+            content: crate::sql::dialect::normalize(&processed.content, sql_dialect).content,
+            ..processed
+        }
+    } else {
+        processed
+    };
+    #[cfg(feature = "sql")]
+    let keyword_ranges = interpolation_original_ranges(template)
+        .into_iter()
+        .filter(|(index, _)| {
+            sql_positions.iter().any(|entry| {
+                entry.interpolation.interpolation_index == *index
+                    && matches!(
+                        entry.position,
+                        crate::sql::positions::SqlPosition::Keyword(_)
+                    )
+            })
+        })
+        .map(|(_, range)| range)
+        .collect::<Vec<_>>();
+    #[cfg(not(feature = "sql"))]
     let processed = prepare_template_for_lint(template, &language);
+    #[cfg(not(feature = "sql"))]
+    let keyword_ranges: Vec<std::ops::Range<usize>> = Vec::new();
     let tree = parse_embedded(&language, &processed.content)?;
 
+    let mut diagnostics = syntax_error_diagnostics(
+        path,
+        template,
+        &language,
+        &processed,
+        &tree,
+        &keyword_ranges,
+    );
+
+    #[cfg(feature = "sql")]
+    if language == "sql" {
+        if let Some(profile) = template.profile.as_deref()
+            && crate::sql::dialect::profile_dialect(Some(profile)).is_none()
+        {
+            diagnostics.push(unsupported_sql_profile_diagnostic(path, template, profile));
+        }
+        let psycopg = crate::sql::psycopg::is_enabled(sql_config, template);
+        diagnostics.extend(crate::sql::positions::lint_rules(
+            path,
+            &sql_positions,
+            psycopg,
+        ));
+        if psycopg {
+            diagnostics.extend(crate::sql::psycopg::lint_rules(
+                path,
+                template,
+                &tree,
+                sql_config,
+                module_context,
+            ));
+        }
+    }
+
+    let document = EmbeddedDocument::new(path, template, &language, &processed, &tree);
+    match language.as_str() {
+        "css" => diagnostics.extend(crate::css::rules::lint_rules(&document)),
+        "javascript" => diagnostics.extend(crate::javascript::rules::lint_rules(&document)),
+        "shell" => diagnostics.extend(crate::shell::rules::lint_rules(&document)),
+        _ => {}
+    }
+
+    sort_and_dedup_diagnostics(&mut diagnostics);
+    Ok(diagnostics)
+}
+
+/// An annotation profile that names no SQL dialect, which would otherwise
+/// fall back to the default dialect unnoticed.
+#[cfg(feature = "sql")]
+fn unsupported_sql_profile_diagnostic(
+    path: &Path,
+    template: &TemplateStringInfo,
+    profile: &str,
+) -> LintDiagnostic {
+    LintDiagnostic {
+        rule: RULE_EMBEDDED_PARSE_ERROR.to_string(),
+        severity: LintSeverity::Error,
+        language: Some("sql".to_string()),
+        message: format!(
+            "Unsupported SQL profile {profile:?}. Supported profiles: \"postgres\", \"sqlite\", \"mysql\", \"generic\"."
+        ),
+        file: path.to_path_buf(),
+        start_line: template.location.start_line,
+        start_column: template.location.start_column,
+        end_line: template.location.end_line,
+        end_column: template.location.end_column,
+        expected_type: None,
+        found_type: None,
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits: Vec::new(),
+    }
+}
+
+/// Reports the error and missing nodes of an embedded parse tree, skipping
+/// those inside `ignored_ranges` of the template content.
+fn syntax_error_diagnostics(
+    path: &Path,
+    template: &TemplateStringInfo,
+    language: &str,
+    processed: &ProcessedTemplate,
+    tree: &Tree,
+    ignored_ranges: &[std::ops::Range<usize>],
+) -> Vec<LintDiagnostic> {
     let mut diagnostics = Vec::new();
     if tree.root_node().has_error() {
         let error_nodes = collect_error_nodes(tree.root_node());
@@ -568,6 +943,12 @@ fn lint_template(
                 map_processed_offset(&processed.processed_to_original, node.start_byte());
             let mut end_offset =
                 map_processed_offset(&processed.processed_to_original, node.end_byte());
+            if ignored_ranges
+                .iter()
+                .any(|range| range.start <= start_offset && end_offset <= range.end)
+            {
+                continue;
+            }
 
             if end_offset <= start_offset {
                 end_offset = next_char_boundary(&template.content, start_offset);
@@ -579,7 +960,7 @@ fn lint_template(
             diagnostics.push(LintDiagnostic {
                 rule: RULE_EMBEDDED_PARSE_ERROR.to_string(),
                 severity: LintSeverity::Error,
-                language: Some(language.clone()),
+                language: Some(language.to_string()),
                 message: format!("Invalid {} syntax in template string", language),
                 file: path.to_path_buf(),
                 start_line,
@@ -595,17 +976,24 @@ fn lint_template(
         }
     }
 
-    #[cfg(feature = "sql")]
-    if language == "sql" && crate::sql::psycopg::is_enabled(sql_config, template) {
-        diagnostics.extend(crate::sql::psycopg::lint_rules(
-            path,
-            template,
-            &tree,
-            sql_config,
-            module_context,
-        ));
-    }
+    diagnostics
+}
 
+/// Checks a template in a runtime-loaded grammar for syntax errors.
+pub(crate) fn lint_custom_grammar_template(
+    path: &Path,
+    template: &TemplateStringInfo,
+    language: &str,
+    grammar: &CustomGrammar,
+) -> Result<Vec<LintDiagnostic>> {
+    let processed = prepare_template_with_placeholders(template, |_| &grammar.placeholder);
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language)?;
+    let tree = parser
+        .parse(&processed.content, None)
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse embedded template"))?;
+    let mut diagnostics =
+        syntax_error_diagnostics(path, template, language, &processed, &tree, &[]);
     sort_and_dedup_diagnostics(&mut diagnostics);
     Ok(diagnostics)
 }
@@ -687,367 +1075,1798 @@ fn lint_backend_template(
     Ok(diagnostics)
 }
 
-#[derive(Debug, Clone)]
-struct JsonSchemaBinding {
-    model_name: String,
-    location: crate::Location,
-}
-
-fn lint_json_schema_bindings(
+fn lint_interpolation_formatting(
     path: &Path,
-    source: &str,
-    templates: &[TemplateStringInfo],
-    module_context: &ModuleContext,
-) -> Result<Vec<LintDiagnostic>> {
-    if !source.contains("Json") || !(source.contains("schema") || source.contains("Json[")) {
-        return Ok(Vec::new());
-    }
-
-    let mut parser = Parser::new();
-    parser
-        .set_language(&tree_sitter_python::LANGUAGE.into())
-        .context("Failed to initialize Python parser")?;
-    let Some(tree) = parser.parse(source, None) else {
-        return Ok(Vec::new());
+    template: &TemplateStringInfo,
+    allowlists: &HashMap<String, InterpolationConfig>,
+) -> Vec<LintDiagnostic> {
+    let Some(language) = template.language.as_deref().and_then(normalize_language) else {
+        return Vec::new();
     };
-    let marker_names = json_marker_names(module_context);
-    let type_aliases = collect_json_schema_type_aliases(source, tree.root_node(), module_context)?;
-    let mut models = collect_schema_models(source, tree.root_node())?;
-    extend_imported_schema_models(path, module_context, &mut models)?;
+    let target = match language {
+        "html" | "thtml" | "tdom" => "markup",
+        "json" | "yaml" | "toml" => "data",
+        _ => return Vec::new(),
+    };
+    let default_allowlist = InterpolationConfig::default();
+    let allowlist = allowlists.get(language).unwrap_or(&default_allowlist);
 
     let mut diagnostics = Vec::new();
-    for template in templates {
-        let Some(binding) = json_schema_binding_for_template(
-            source,
-            tree.root_node(),
-            template,
-            &marker_names,
-            &type_aliases,
-        )?
-        else {
+    for part in &template.parts {
+        let TemplatePart::Interpolation(interpolation) = part else {
             continue;
         };
-        let Some(model) = models.get(&binding.model_name) else {
-            diagnostics.push(binding_unresolved_diagnostic(path, &binding));
-            continue;
+
+        let raw_source = interpolation.raw_source.as_str();
+        let spec_start = if interpolation.format_spec.is_empty() {
+            raw_source.len().saturating_sub(1)
+        } else {
+            raw_source
+                .len()
+                .saturating_sub(interpolation.format_spec.len() + 2)
         };
-        let Some(outline) = json_root_outline(template) else {
-            continue;
+        let debug_end = interpolation
+            .debug_prefix
+            .as_deref()
+            .map(|debug_prefix| 1 + debug_prefix.len());
+
+        // Removing `=`, `!r`, or `!a` changes the rendered text, so these
+        // diagnostics carry no edit.
+        if interpolation.debug_prefix.is_some() && !allowlist.allow_debug {
+            diagnostics.push(interpolation_formatting_diagnostic(
+                path,
+                language,
+                interpolation,
+                RULE_INTERPOLATION_DEBUG_SPECIFIER,
+                format!(
+                    "Debug specifier '=' inserts the expression text and its Python repr into {language} {target}; remove the '='"
+                ),
+                Some("="),
+            ));
+        }
+
+        // `{value=}` implies `!r`; only a conversion written out is reported.
+        let is_written = |conversion: &str| {
+            let search_start = debug_end.unwrap_or(1).min(spec_start);
+            raw_source
+                .get(search_start..spec_start)
+                .is_some_and(|text| text.contains(&format!("!{conversion}")))
         };
-        diagnostics.extend(lint_json_model_against_outline(
-            path,
-            model,
-            &outline,
-            &binding.model_name,
-        ));
+        if let Some(conversion @ ("r" | "a")) = interpolation.conversion.as_deref()
+            && is_written(conversion)
+            && !allowlist.allows_conversion(conversion)
+        {
+            let conversion_text = format!("!{conversion}");
+            diagnostics.push(interpolation_formatting_diagnostic(
+                path,
+                language,
+                interpolation,
+                RULE_INTERPOLATION_REPR_CONVERSION,
+                format!(
+                    "Conversion '{conversion_text}' inserts a Python repr into {language} {target}; remove the conversion"
+                ),
+                Some(&conversion_text),
+            ));
+        }
+
+        let spec = interpolation.format_spec.trim();
+        if !spec.is_empty()
+            && !spec.contains('{')
+            && !allowlist.allows_format_spec(spec)
+            && !is_standard_format_spec(spec)
+            && !is_strftime_format_spec(spec)
+        {
+            diagnostics.push(interpolation_formatting_diagnostic(
+                path,
+                language,
+                interpolation,
+                RULE_INTERPOLATION_FORMAT_SPEC_UNKNOWN,
+                format!(
+                    "Format spec '{spec}' is not a standard Python format spec; add it to `allowed-format-specs` for {language} if the value defines __format__"
+                ),
+                Some(spec),
+            ));
+        }
     }
+    diagnostics
+}
 
-    Ok(diagnostics)
+/// Accepts Python's format specification mini-language:
+/// `[[fill]align][sign][z][#][0][width][grouping][.precision[grouping]][type]`.
+fn is_standard_format_spec(spec: &str) -> bool {
+    let chars = spec.chars().collect::<Vec<_>>();
+    let mut index = 0;
+    let is_align = |ch: char| matches!(ch, '<' | '>' | '=' | '^');
+
+    if chars.len() >= 2 && is_align(chars[1]) {
+        index = 2;
+    } else if chars.first().copied().is_some_and(is_align) {
+        index = 1;
+    }
+    if chars
+        .get(index)
+        .is_some_and(|ch| matches!(ch, '+' | '-' | ' '))
+    {
+        index += 1;
+    }
+    for flag in ['z', '#', '0'] {
+        if chars.get(index) == Some(&flag) {
+            index += 1;
+        }
+    }
+    while chars.get(index).is_some_and(char::is_ascii_digit) {
+        index += 1;
+    }
+    if chars.get(index).is_some_and(|ch| matches!(ch, ',' | '_')) {
+        index += 1;
+    }
+    if chars.get(index) == Some(&'.') {
+        index += 1;
+        let digits_start = index;
+        while chars.get(index).is_some_and(char::is_ascii_digit) {
+            index += 1;
+        }
+        if chars.get(index).is_some_and(|ch| matches!(ch, ',' | '_')) {
+            index += 1;
+        }
+        if index == digits_start {
+            return false;
+        }
+    }
+    if chars
+        .get(index)
+        .is_some_and(|ch| "bcdeEfFgGnosxX%".contains(*ch))
+    {
+        index += 1;
+    }
+    index == chars.len()
 }
 
-fn json_marker_names(module_context: &ModuleContext) -> BTreeSet<String> {
-    let mut names = BTreeSet::from(["Json".to_string(), "json_tstring.Json".to_string()]);
-    for (local, target) in &module_context.imports {
-        if target == "json_tstring.Json" {
-            names.insert(local.clone());
+fn is_strftime_format_spec(spec: &str) -> bool {
+    let mut chars = spec.chars();
+    let mut saw_directive = false;
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            continue;
+        }
+        match chars.next() {
+            Some(directive) if directive.is_ascii_alphabetic() || directive == '%' => {
+                saw_directive = true;
+            }
+            _ => return false,
         }
     }
-    names
+    saw_directive
 }
 
-fn template_marker_languages(module_context: &ModuleContext) -> Vec<(String, String)> {
-    let mut markers = json_marker_names(module_context)
-        .into_iter()
-        .map(|name| (name, "json".to_string()))
-        .collect::<BTreeMap<_, _>>();
-    for (name, language) in &module_context.template_language_markers {
-        markers.insert(name.clone(), language.clone());
+fn interpolation_formatting_diagnostic(
+    path: &Path,
+    language: &str,
+    interpolation: &crate::parser::InterpolationInfo,
+    rule: &str,
+    message: String,
+    found_type: Option<&str>,
+) -> LintDiagnostic {
+    LintDiagnostic {
+        rule: rule.to_string(),
+        severity: LintSeverity::Warning,
+        language: Some(language.to_string()),
+        message,
+        file: path.to_path_buf(),
+        start_line: interpolation.location.start_line,
+        start_column: interpolation.location.start_column,
+        end_line: interpolation.location.end_line,
+        end_column: interpolation.location.end_column,
+        expected_type: None,
+        found_type: found_type.map(str::to_string),
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits: Vec::new(),
     }
-    let mut markers = markers.into_iter().collect::<Vec<_>>();
-    markers.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
-    markers
 }
 
-fn lint_template_metadata_markers(
+/// Reports interpolations that do not need to be interpolations: constant
+/// literals, expressions repeated within one template, and template variables
+/// whose language does not match the template they are interpolated into.
+fn lint_interpolation_usage(
     path: &Path,
-    source: &str,
     templates: &[TemplateStringInfo],
-    module_context: &ModuleContext,
 ) -> Result<Vec<LintDiagnostic>> {
-    if !source.contains("Annotated") || templates.is_empty() {
-        return Ok(Vec::new());
-    }
-
     let mut parser = Parser::new();
     parser
         .set_language(&tree_sitter_python::LANGUAGE.into())
-        .context("Failed to initialize Python parser")?;
-    let Some(tree) = parser.parse(source, None) else {
-        return Ok(Vec::new());
-    };
-    let markers = template_marker_languages(module_context);
-    let mut diagnostics = Vec::new();
+        .context("Failed to set Python language")?;
 
+    let mut diagnostics = Vec::new();
     for template in templates {
-        let Some(template_start) = location_to_byte_offset(
-            source,
-            template.location.start_line,
-            template.location.start_column,
-        ) else {
-            continue;
-        };
-        let Some(type_node) = type_annotation_for_template_start(tree.root_node(), template_start)
-        else {
-            continue;
-        };
-        let type_text = type_node.utf8_text(source.as_bytes())?;
-        let Some(metadata) = annotated_metadata_items(type_text) else {
-            continue;
-        };
+        let language = template.language.as_deref().and_then(normalize_language);
+        let mut seen_expressions = HashMap::<String, usize>::new();
+        let mut markup_slots = None;
+        for part in &template.parts {
+            let TemplatePart::Interpolation(interpolation) = part else {
+                continue;
+            };
+            let expression = interpolation.expression.trim();
+            let Some(tree) = parser.parse(expression, None) else {
+                continue;
+            };
+            let Some(node) = single_expression_node(&tree) else {
+                continue;
+            };
 
-        let string_languages = metadata
-            .iter()
-            .filter_map(|item| metadata_language_string(item.text).map(|language| (language, item)))
-            .collect::<Vec<_>>();
-        let marker_languages = metadata
-            .iter()
-            .filter_map(|item| marker_metadata_language(item.text, &markers))
-            .collect::<Vec<_>>();
+            if interpolation.debug_prefix.is_none()
+                && interpolation.conversion.is_none()
+                && interpolation.format_spec.is_empty()
+                && renders_interpolations_verbatim(language)
+                && is_constant_literal_node(node)
+            {
+                diagnostics.push(interpolation_usage_diagnostic(
+                    path,
+                    language,
+                    interpolation,
+                    RULE_INTERPOLATION_CONSTANT_LITERAL,
+                    format!(
+                        "Interpolation '{expression}' is a constant literal; write it as static template text"
+                    ),
+                    None,
+                    None,
+                ));
+                continue;
+            }
 
-        if marker_languages.len() > 1 {
-            diagnostics.push(metadata_diagnostic(
-                path,
-                type_node,
-                RULE_TEMPLATE_METADATA_CONFLICT,
-                LintSeverity::Error,
-                "Template metadata must contain at most one language marker.".to_string(),
-                Vec::new(),
-            ));
-            continue;
-        }
-        let Some(marker_language) = marker_languages.first() else {
-            continue;
-        };
+            if !is_plain_reference_node(node) {
+                let key = expression.split_whitespace().collect::<String>();
+                let count = seen_expressions.entry(key).or_default();
+                *count += 1;
+                if *count > 1 {
+                    diagnostics.push(interpolation_usage_diagnostic(
+                        path,
+                        language,
+                        interpolation,
+                        RULE_INTERPOLATION_DUPLICATE_EXPRESSION,
+                        format!(
+                            "Expression '{expression}' is already interpolated in this template; bind it to a local variable and interpolate the name"
+                        ),
+                        None,
+                        None,
+                    ));
+                }
+            }
 
-        let conflicting_language = string_languages
-            .iter()
-            .map(|(language, _)| language)
-            .find(|language| *language != marker_language);
-        if let Some(language) = conflicting_language {
-            diagnostics.push(metadata_diagnostic(
-                path,
-                type_node,
-                RULE_TEMPLATE_METADATA_CONFLICT,
-                LintSeverity::Error,
-                format!(
-                    "Template metadata declares language '{language}' and marker language '{marker_language}'."
-                ),
-                Vec::new(),
-            ));
-        } else if let Some((language, item)) = string_languages
-            .iter()
-            .find(|(language, _)| language == marker_language)
-        {
-            diagnostics.push(metadata_diagnostic(
-                path,
-                type_node,
-                RULE_TEMPLATE_METADATA_REDUNDANT_LANGUAGE,
-                LintSeverity::Warning,
-                format!(
-                    "Template metadata redundantly declares language '{language}' alongside a marker."
-                ),
-                redundant_language_edit(source, type_node, item),
-            ));
+            if node.kind() == "identifier"
+                && let Some(language) = language
+                && let Some(child_language) =
+                    prior_template_variable_language(templates, template, interpolation)
+            {
+                let slot = if is_markup_language(language) {
+                    markup_slots
+                        .get_or_insert_with(|| markup_interpolation_slots(template, language))
+                        .get(&interpolation.interpolation_index)
+                } else {
+                    None
+                };
+                let (compatible, expected, context) = match slot {
+                    Some(slot) => (
+                        slot.accepts(child_language),
+                        slot.expected_language(),
+                        format!("{} of a", slot.describe()),
+                    ),
+                    None => (
+                        language == child_language
+                            || (is_markup_language(language) && is_markup_language(child_language)),
+                        language,
+                        "a".to_string(),
+                    ),
+                };
+                if !compatible {
+                    diagnostics.push(interpolation_usage_diagnostic(
+                        path,
+                        Some(language),
+                        interpolation,
+                        RULE_INTERPOLATION_LANGUAGE_MISMATCH,
+                        format!(
+                            "Interpolation '{expression}' is a {child_language} template but is interpolated into {context} {language} template"
+                        ),
+                        Some(expected),
+                        Some(child_language),
+                    ));
+                }
+            }
         }
     }
-
     Ok(diagnostics)
 }
 
-#[derive(Debug)]
-struct MetadataItem<'a> {
-    text: &'a str,
-    start: usize,
-    end: usize,
-}
-
-fn annotated_metadata_items(type_text: &str) -> Option<Vec<MetadataItem<'_>>> {
-    let start = find_marker_followed_by(type_text, "Annotated", '[')?;
-    let bracket_start = start + "Annotated".len();
-    let contents = bracket_contents_at(&type_text[bracket_start..], '[', ']')?;
-    let args = split_top_level_type_tokens_with_offsets(contents, ',');
-    if args.len() < 2 {
+fn single_expression_node(tree: &Tree) -> Option<Node<'_>> {
+    let root = tree.root_node();
+    if root.has_error() || root.named_child_count() != 1 {
         return None;
     }
-    let contents_start = bracket_start + 1;
-    Some(
-        args.into_iter()
-            .skip(1)
-            .map(|item| MetadataItem {
-                text: item.text,
-                start: contents_start + item.start,
-                end: contents_start + item.end,
-            })
-            .collect(),
-    )
-}
-
-fn metadata_language_string(text: &str) -> Option<String> {
-    let language = parse_metadata_string_literal(text.trim())?;
-    if template_profile_text(&language).is_some() {
+    let statement = root.named_child(0)?;
+    if statement.kind() != "expression_statement" || statement.named_child_count() != 1 {
         return None;
     }
-    Some(language)
+    statement.named_child(0)
 }
 
-fn parse_metadata_string_literal(text: &str) -> Option<String> {
-    let mut chars = text.chars();
-    let quote = chars.next()?;
-    if !matches!(quote, '\'' | '"') {
-        return None;
+fn is_constant_literal_node(node: Node<'_>) -> bool {
+    match node.kind() {
+        "integer" | "float" | "true" | "false" | "none" => true,
+        "string" => {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .all(|child| child.kind() != "interpolation")
+        }
+        "concatenated_string" => {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor)
+                .all(is_constant_literal_node)
+        }
+        "unary_operator" => node
+            .child_by_field_name("argument")
+            .is_some_and(|argument| matches!(argument.kind(), "integer" | "float")),
+        "parenthesized_expression" => node.named_child(0).is_some_and(is_constant_literal_node),
+        _ => false,
     }
-    let suffix = text.strip_prefix(quote)?.strip_suffix(quote)?;
-    Some(suffix.to_string())
 }
 
-fn template_profile_text(value: &str) -> Option<&str> {
-    value
-        .strip_prefix("profile:")
-        .or_else(|| value.strip_prefix("profile="))
+/// Names and attribute chains are already bindings; repeating them is cheap.
+fn is_plain_reference_node(node: Node<'_>) -> bool {
+    match node.kind() {
+        "identifier" => true,
+        "attribute" => node
+            .child_by_field_name("object")
+            .is_some_and(is_plain_reference_node),
+        _ => is_constant_literal_node(node),
+    }
 }
 
-fn marker_metadata_language(text: &str, markers: &[(String, String)]) -> Option<String> {
-    let text = text.trim();
-    markers.iter().find_map(|(marker, language)| {
-        if text == marker
-            || find_marker_followed_by(text, marker, '(').is_some_and(|index| index == 0)
-            || find_marker_followed_by(text, marker, '[').is_some_and(|index| index == 0)
-        {
-            return Some(language.clone());
-        }
-        None
-    })
+/// Language of the closest template assigned to the interpolated name before
+/// the interpolation, as resolved by the template parser.
+fn prior_template_variable_language<'a>(
+    templates: &'a [TemplateStringInfo],
+    parent: &TemplateStringInfo,
+    interpolation: &crate::parser::InterpolationInfo,
+) -> Option<&'a str> {
+    let name = interpolation.expression.trim();
+    let position = (
+        interpolation.location.start_line,
+        interpolation.location.start_column,
+    );
+    templates
+        .iter()
+        .filter(|candidate| !std::ptr::eq(*candidate, parent))
+        .filter(|candidate| candidate.variable_name.as_deref() == Some(name))
+        .filter(|candidate| {
+            (
+                candidate.location.start_line,
+                candidate.location.start_column,
+            ) < position
+        })
+        .max_by_key(|candidate| {
+            (
+                candidate.location.start_line,
+                candidate.location.start_column,
+            )
+        })
+        .and_then(|candidate| candidate.language.as_deref())
+        .and_then(normalize_language)
 }
 
-fn metadata_diagnostic(
-    path: &Path,
-    node: Node<'_>,
-    rule: &str,
-    severity: LintSeverity,
-    message: String,
-    suggested_edits: Vec<DiagnosticEdit>,
-) -> LintDiagnostic {
-    let location = location_for_node(node);
-    LintDiagnostic {
-        rule: rule.to_string(),
-        severity,
-        language: None,
-        message,
-        file: path.to_path_buf(),
-        start_line: location.start_line,
-        start_column: location.start_column,
-        end_line: location.end_line,
-        end_column: location.end_column,
-        expected_type: None,
-        found_type: None,
-        schema_pointer: None,
-        source_of_truth: None,
-        suggested_edits,
-    }
+fn is_markup_language(language: &str) -> bool {
+    matches!(language, "html" | "thtml" | "tdom")
 }
 
-fn redundant_language_edit(
-    source: &str,
-    type_node: Node<'_>,
-    item: &MetadataItem<'_>,
-) -> Vec<DiagnosticEdit> {
-    let type_text = type_node.utf8_text(source.as_bytes()).unwrap_or("");
-    let (delete_start, delete_end) = redundant_language_delete_range(type_text, item);
-    let Some(location) = location_for_byte_range(
-        source,
-        type_node.start_byte() + delete_start,
-        type_node.start_byte() + delete_end,
-    ) else {
-        return Vec::new();
-    };
-    vec![DiagnosticEdit {
-        range: DiagnosticEditRange::from_location(&location),
-        new_text: String::new(),
-    }]
+/// Where an interpolation sits in a markup template, which decides the
+/// language a nested template may have there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MarkupSlot {
+    Text,
+    RawText {
+        element: String,
+        language: Option<&'static str>,
+    },
+    Attribute {
+        name: String,
+        language: Option<&'static str>,
+    },
+    Unchecked,
 }
 
-fn redundant_language_delete_range(type_text: &str, item: &MetadataItem<'_>) -> (usize, usize) {
-    let bytes = type_text.as_bytes();
-    let mut end = item.end;
-    while end < bytes.len() && bytes[end].is_ascii_whitespace() {
-        end += 1;
-    }
-    if end < bytes.len() && bytes[end] == b',' {
-        end += 1;
-        while end < bytes.len() && bytes[end].is_ascii_whitespace() {
-            end += 1;
+impl MarkupSlot {
+    fn accepts(&self, child: &str) -> bool {
+        match self {
+            Self::Text => is_markup_language(child),
+            Self::RawText { language, .. } | Self::Attribute { language, .. } => {
+                *language == Some(child)
+            }
+            Self::Unchecked => true,
         }
-        return (item.start, end);
     }
 
-    let mut start = item.start;
-    while start > 0 && bytes[start - 1].is_ascii_whitespace() {
-        start -= 1;
+    fn expected_language(&self) -> &'static str {
+        match self {
+            Self::Text => "html",
+            Self::RawText { language, .. } | Self::Attribute { language, .. } => {
+                language.unwrap_or("str")
+            }
+            Self::Unchecked => "Template",
+        }
     }
-    if let Some(comma) = type_text[..start].rfind(',') {
-        return (comma, item.end);
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Text => "text content".to_string(),
+            Self::RawText { element, .. } => format!("a <{element}> element"),
+            Self::Attribute { name, .. } => format!("the '{name}' attribute"),
+            Self::Unchecked => "a component or spread".to_string(),
+        }
     }
-    (item.start, item.end)
 }
 
-fn location_for_byte_range(source: &str, start: usize, end: usize) -> Option<crate::Location> {
-    if start > end || end > source.len() {
-        return None;
+/// Maps interpolation indexes to their slot in the parsed markup. Templates
+/// that do not parse yield no slots and fall back to a language-family check.
+fn markup_interpolation_slots(
+    template: &TemplateStringInfo,
+    language: &str,
+) -> HashMap<usize, MarkupSlot> {
+    let input = template.to_template_input();
+    let mut slots = HashMap::new();
+    if language == "tdom" {
+        if let Ok(document) = backend_tdom::parse_template(&input) {
+            collect_tdom_slots(&document.children, &mut slots);
+        }
+    } else if let Ok(document) = tstring_html::parse_template(&input) {
+        collect_html_slots(&document.children, &mut slots);
     }
-    let (start_line, start_column) = byte_position_to_line_column(source, start)?;
-    let (end_line, end_column) = byte_position_to_line_column(source, end)?;
-    Some(crate::Location {
-        start_line,
-        start_column,
-        end_line,
-        end_column,
-    })
+    slots
+}
+
+fn collect_html_slots(nodes: &[HtmlNode], slots: &mut HashMap<usize, MarkupSlot>) {
+    for node in nodes {
+        match node {
+            HtmlNode::Fragment(fragment) => collect_html_slots(&fragment.children, slots),
+            HtmlNode::Element(element) => {
+                collect_html_attribute_slots(&element.attributes, false, slots);
+                collect_html_slots(&element.children, slots);
+            }
+            HtmlNode::ComponentTag(component) => {
+                collect_html_attribute_slots(&component.attributes, true, slots);
+                collect_html_slots(&component.children, slots);
+            }
+            HtmlNode::RawTextElement(element) => {
+                collect_html_attribute_slots(&element.attributes, false, slots);
+                let type_attribute = element.attributes.iter().find_map(|attribute| {
+                    let AttributeLike::Attribute(attribute) = attribute else {
+                        return None;
+                    };
+                    if !attribute.name.eq_ignore_ascii_case("type") {
+                        return None;
+                    }
+                    attribute.value.as_ref().map(|value| {
+                        value
+                            .parts
+                            .iter()
+                            .filter_map(|part| match part {
+                                tstring_html::ValuePart::Text(text) => Some(text.as_str()),
+                                tstring_html::ValuePart::Interpolation(_) => None,
+                            })
+                            .collect::<String>()
+                    })
+                });
+                let language = raw_text_language(&element.name, type_attribute.as_deref());
+                for child in &element.children {
+                    if let HtmlNode::Interpolation(interpolation) = child {
+                        slots.insert(
+                            interpolation.interpolation_index,
+                            MarkupSlot::RawText {
+                                element: element.name.to_ascii_lowercase(),
+                                language,
+                            },
+                        );
+                    }
+                }
+            }
+            HtmlNode::Interpolation(interpolation) => {
+                slots.insert(interpolation.interpolation_index, MarkupSlot::Text);
+            }
+            HtmlNode::Text(_) | HtmlNode::Comment(_) | HtmlNode::Doctype(_) => {}
+        }
+    }
+}
+
+fn collect_html_attribute_slots(
+    attributes: &[AttributeLike],
+    component: bool,
+    slots: &mut HashMap<usize, MarkupSlot>,
+) {
+    for attribute in attributes {
+        match attribute {
+            AttributeLike::Attribute(attribute) => {
+                let Some(value) = &attribute.value else {
+                    continue;
+                };
+                for part in &value.parts {
+                    if let tstring_html::ValuePart::Interpolation(interpolation) = part {
+                        slots.insert(
+                            interpolation.interpolation_index,
+                            attribute_slot(&attribute.name, component),
+                        );
+                    }
+                }
+            }
+            AttributeLike::SpreadAttribute(spread) => {
+                slots.insert(
+                    spread.interpolation.interpolation_index,
+                    MarkupSlot::Unchecked,
+                );
+            }
+        }
+    }
+}
+
+fn collect_tdom_slots(nodes: &[backend_tdom::Node], slots: &mut HashMap<usize, MarkupSlot>) {
+    for node in nodes {
+        match node {
+            backend_tdom::Node::Fragment(fragment) => collect_tdom_slots(&fragment.children, slots),
+            backend_tdom::Node::Element(element) => {
+                collect_tdom_attribute_slots(&element.attributes, false, slots);
+                collect_tdom_slots(&element.children, slots);
+            }
+            backend_tdom::Node::ComponentTag(component) => {
+                slots.insert(
+                    component.start_tag.interpolation_index,
+                    MarkupSlot::Unchecked,
+                );
+                if let Some(end_tag) = &component.end_tag {
+                    slots.insert(end_tag.interpolation_index, MarkupSlot::Unchecked);
+                }
+                collect_tdom_attribute_slots(&component.attributes, true, slots);
+                collect_tdom_slots(&component.children, slots);
+            }
+            backend_tdom::Node::RawTextElement(element) => {
+                collect_tdom_attribute_slots(&element.attributes, false, slots);
+                let type_attribute =
+                    element
+                        .attributes
+                        .iter()
+                        .find_map(|attribute| match attribute {
+                            backend_tdom::AttributeLike::LiteralAttribute(attribute)
+                                if attribute.name.eq_ignore_ascii_case("type") =>
+                            {
+                                attribute.value.as_deref()
+                            }
+                            _ => None,
+                        });
+                let language = raw_text_language(&element.name, type_attribute);
+                for child in &element.children {
+                    if let backend_tdom::Node::Interpolation(interpolation) = child {
+                        slots.insert(
+                            interpolation.interpolation_index,
+                            MarkupSlot::RawText {
+                                element: element.name.to_ascii_lowercase(),
+                                language,
+                            },
+                        );
+                    }
+                }
+            }
+            backend_tdom::Node::Interpolation(interpolation) => {
+                slots.insert(interpolation.interpolation_index, MarkupSlot::Text);
+            }
+            backend_tdom::Node::Text(_)
+            | backend_tdom::Node::Comment(_)
+            | backend_tdom::Node::Doctype(_) => {}
+        }
+    }
+}
+
+fn collect_tdom_attribute_slots(
+    attributes: &[backend_tdom::AttributeLike],
+    component: bool,
+    slots: &mut HashMap<usize, MarkupSlot>,
+) {
+    for attribute in attributes {
+        match attribute {
+            backend_tdom::AttributeLike::LiteralAttribute(_) => {}
+            backend_tdom::AttributeLike::InterpolatedAttribute(attribute) => {
+                slots.insert(
+                    attribute.interpolation.interpolation_index,
+                    attribute_slot(&attribute.name, component),
+                );
+            }
+            backend_tdom::AttributeLike::TemplatedAttribute(attribute) => {
+                for part in &attribute.parts {
+                    if let backend_tdom::ValuePart::Interpolation(interpolation) = part {
+                        slots.insert(
+                            interpolation.interpolation_index,
+                            attribute_slot(&attribute.name, component),
+                        );
+                    }
+                }
+            }
+            backend_tdom::AttributeLike::SpreadAttribute(spread) => {
+                slots.insert(
+                    spread.interpolation.interpolation_index,
+                    MarkupSlot::Unchecked,
+                );
+            }
+        }
+    }
+}
+
+/// Component props are checked against the component signature instead.
+fn attribute_slot(name: &str, component: bool) -> MarkupSlot {
+    if component {
+        return MarkupSlot::Unchecked;
+    }
+    let lowered = name.to_ascii_lowercase();
+    let language = if lowered == "style" {
+        Some("css")
+    } else if lowered.starts_with("on") {
+        Some("javascript")
+    } else {
+        None
+    };
+    MarkupSlot::Attribute {
+        name: name.to_string(),
+        language,
+    }
+}
+
+fn raw_text_language(element: &str, type_attribute: Option<&str>) -> Option<&'static str> {
+    match element.to_ascii_lowercase().as_str() {
+        "style" => Some("css"),
+        "script" => match type_attribute.map(|value| value.trim().to_ascii_lowercase()) {
+            None => Some("javascript"),
+            Some(value)
+                if value.is_empty() || value == "module" || value.ends_with("javascript") =>
+            {
+                Some("javascript")
+            }
+            Some(value) if value.ends_with("json") => Some("json"),
+            Some(_) => None,
+        },
+        _ => None,
+    }
+}
+
+fn interpolation_usage_diagnostic(
+    path: &Path,
+    language: Option<&str>,
+    interpolation: &crate::parser::InterpolationInfo,
+    rule: &str,
+    message: String,
+    expected_type: Option<&str>,
+    found_type: Option<&str>,
+) -> LintDiagnostic {
+    LintDiagnostic {
+        rule: rule.to_string(),
+        severity: LintSeverity::Warning,
+        language: language.map(str::to_string),
+        message,
+        file: path.to_path_buf(),
+        start_line: interpolation.location.start_line,
+        start_column: interpolation.location.start_column,
+        end_line: interpolation.location.end_line,
+        end_column: interpolation.location.end_column,
+        expected_type: expected_type.map(str::to_string),
+        found_type: found_type.map(str::to_string),
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits: Vec::new(),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SchemaBinding {
+    reference: SchemaReference,
+    language: String,
+    location: crate::Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SchemaReference {
+    Model(String),
+    File(String),
+}
+
+impl SchemaReference {
+    fn name(&self) -> &str {
+        match self {
+            Self::Model(name) | Self::File(name) => name,
+        }
+    }
+}
+
+fn lint_template_schema_bindings(
+    path: &Path,
+    source: &str,
+    templates: &[TemplateStringInfo],
+    module_context: &ModuleContext,
+    config: &ProjectConfig,
+) -> Result<Vec<LintDiagnostic>> {
+    let has_schema_marker = SCHEMA_MARKERS.iter().any(|(name, _, _)| {
+        source.contains(name) && (source.contains("schema") || source.contains(&format!("{name}[")))
+    });
+    if !has_schema_marker {
+        return Ok(Vec::new());
+    }
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_python::LANGUAGE.into())
+        .context("Failed to initialize Python parser")?;
+    let Some(tree) = parser.parse(source, None) else {
+        return Ok(Vec::new());
+    };
+    let marker_names = schema_marker_names(module_context);
+    let type_aliases = collect_json_schema_type_aliases(source, tree.root_node(), module_context)?;
+    let mut registry = collect_schema_registry(source, tree.root_node())?;
+    extend_imported_schema_registry(path, module_context, &mut registry)?;
+
+    let mut diagnostics = Vec::new();
+    for template in templates {
+        let Some(binding) = schema_binding_for_template(
+            source,
+            tree.root_node(),
+            template,
+            &marker_names,
+            &type_aliases,
+        )?
+        else {
+            continue;
+        };
+        let model_key = match &binding.reference {
+            SchemaReference::Model(name) if registry.models.contains_key(name) => Ok(name.clone()),
+            SchemaReference::Model(name) => match config.schema_files.get(name) {
+                Some(file) => load_json_schema_model(&mut registry, &config.root, path, file),
+                None => Err(None),
+            },
+            SchemaReference::File(file) => {
+                load_json_schema_model(&mut registry, &config.root, path, file)
+            }
+        };
+        let model_key = match model_key {
+            Ok(model_key) => model_key,
+            Err(detail) => {
+                diagnostics.push(binding_unresolved_diagnostic(path, &binding, detail));
+                continue;
+            }
+        };
+        let Some(model) = registry.models.get(&model_key) else {
+            continue;
+        };
+        let Some(outline) = schema_root_outline(template, &binding.language) else {
+            continue;
+        };
+        let context = SchemaLintContext {
+            path,
+            registry: &registry,
+            source_of_truth: binding.reference.name(),
+            language: &binding.language,
+        };
+        lint_json_model_against_outline(&context, model, &outline, "", &mut diagnostics);
+    }
+
+    Ok(diagnostics)
+}
+
+fn schema_marker_names(module_context: &ModuleContext) -> BTreeMap<String, String> {
+    let mut names = BTreeMap::new();
+    for (name, target, language) in SCHEMA_MARKERS {
+        names.insert(name.to_string(), language.to_string());
+        names.insert(target.to_string(), language.to_string());
+    }
+    for (local, target) in &module_context.imports {
+        if let Some((_, _, language)) = SCHEMA_MARKERS
+            .iter()
+            .find(|(_, marker_target, _)| marker_target == target)
+        {
+            names.insert(local.clone(), language.to_string());
+        }
+    }
+    names
+}
+
+fn template_marker_languages(module_context: &ModuleContext) -> Vec<(String, String)> {
+    let mut markers = schema_marker_names(module_context);
+    for (name, language) in &module_context.template_language_markers {
+        markers.insert(name.clone(), language.clone());
+    }
+    let mut markers = markers.into_iter().collect::<Vec<_>>();
+    markers.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+    markers
+}
+
+fn lint_template_metadata_markers(
+    path: &Path,
+    source: &str,
+    templates: &[TemplateStringInfo],
+    module_context: &ModuleContext,
+) -> Result<Vec<LintDiagnostic>> {
+    if !source.contains("Annotated") || templates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_python::LANGUAGE.into())
+        .context("Failed to initialize Python parser")?;
+    let Some(tree) = parser.parse(source, None) else {
+        return Ok(Vec::new());
+    };
+    let markers = template_marker_languages(module_context);
+    let mut diagnostics = Vec::new();
+
+    for template in templates {
+        let Some(template_start) = location_to_byte_offset(
+            source,
+            template.location.start_line,
+            template.location.start_column,
+        ) else {
+            continue;
+        };
+        let Some(type_node) = type_annotation_for_template_start(tree.root_node(), template_start)
+        else {
+            continue;
+        };
+        let type_text = type_node.utf8_text(source.as_bytes())?;
+        let Some(metadata) = annotated_metadata_items(type_text) else {
+            continue;
+        };
+
+        let string_languages = metadata
+            .iter()
+            .filter_map(|item| metadata_language_string(item.text).map(|language| (language, item)))
+            .collect::<Vec<_>>();
+        let marker_languages = metadata
+            .iter()
+            .filter_map(|item| marker_metadata_language(item.text, &markers))
+            .collect::<Vec<_>>();
+
+        if marker_languages.len() > 1 {
+            diagnostics.push(metadata_diagnostic(
+                path,
+                type_node,
+                RULE_TEMPLATE_METADATA_CONFLICT,
+                LintSeverity::Error,
+                "Template metadata must contain at most one language marker.".to_string(),
+                Vec::new(),
+            ));
+            continue;
+        }
+        let Some(marker_language) = marker_languages.first() else {
+            continue;
+        };
+
+        let conflicting_language = string_languages
+            .iter()
+            .map(|(language, _)| language)
+            .find(|language| *language != marker_language);
+        if let Some(language) = conflicting_language {
+            diagnostics.push(metadata_diagnostic(
+                path,
+                type_node,
+                RULE_TEMPLATE_METADATA_CONFLICT,
+                LintSeverity::Error,
+                format!(
+                    "Template metadata declares language '{language}' and marker language '{marker_language}'."
+                ),
+                Vec::new(),
+            ));
+        } else if let Some((language, item)) = string_languages
+            .iter()
+            .find(|(language, _)| language == marker_language)
+        {
+            diagnostics.push(metadata_diagnostic(
+                path,
+                type_node,
+                RULE_TEMPLATE_METADATA_REDUNDANT_LANGUAGE,
+                LintSeverity::Warning,
+                format!(
+                    "Template metadata redundantly declares language '{language}' alongside a marker."
+                ),
+                redundant_language_edit(source, type_node, item),
+            ));
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+#[derive(Debug)]
+struct MetadataItem<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+fn annotated_metadata_items(type_text: &str) -> Option<Vec<MetadataItem<'_>>> {
+    let start = find_marker_followed_by(type_text, "Annotated", '[')?;
+    let bracket_start = start + "Annotated".len();
+    let contents = bracket_contents_at(&type_text[bracket_start..], '[', ']')?;
+    let args = split_top_level_type_tokens_with_offsets(contents, ',');
+    if args.len() < 2 {
+        return None;
+    }
+    let contents_start = bracket_start + 1;
+    Some(
+        args.into_iter()
+            .skip(1)
+            .map(|item| MetadataItem {
+                text: item.text,
+                start: contents_start + item.start,
+                end: contents_start + item.end,
+            })
+            .collect(),
+    )
+}
+
+fn metadata_language_string(text: &str) -> Option<String> {
+    let language = parse_metadata_string_literal(text.trim())?;
+    if template_profile_text(&language).is_some() {
+        return None;
+    }
+    Some(language)
+}
+
+fn parse_metadata_string_literal(text: &str) -> Option<String> {
+    let mut chars = text.chars();
+    let quote = chars.next()?;
+    if !matches!(quote, '\'' | '"') {
+        return None;
+    }
+    let suffix = text.strip_prefix(quote)?.strip_suffix(quote)?;
+    Some(suffix.to_string())
+}
+
+fn template_profile_text(value: &str) -> Option<&str> {
+    value
+        .strip_prefix("profile:")
+        .or_else(|| value.strip_prefix("profile="))
+}
+
+fn marker_metadata_language(text: &str, markers: &[(String, String)]) -> Option<String> {
+    let text = text.trim();
+    markers.iter().find_map(|(marker, language)| {
+        if text == marker
+            || find_marker_followed_by(text, marker, '(').is_some_and(|index| index == 0)
+            || find_marker_followed_by(text, marker, '[').is_some_and(|index| index == 0)
+        {
+            return Some(language.clone());
+        }
+        None
+    })
+}
+
+fn metadata_diagnostic(
+    path: &Path,
+    node: Node<'_>,
+    rule: &str,
+    severity: LintSeverity,
+    message: String,
+    suggested_edits: Vec<DiagnosticEdit>,
+) -> LintDiagnostic {
+    let location = location_for_node(node);
+    LintDiagnostic {
+        rule: rule.to_string(),
+        severity,
+        language: None,
+        message,
+        file: path.to_path_buf(),
+        start_line: location.start_line,
+        start_column: location.start_column,
+        end_line: location.end_line,
+        end_column: location.end_column,
+        expected_type: None,
+        found_type: None,
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits,
+    }
+}
+
+fn redundant_language_edit(
+    source: &str,
+    type_node: Node<'_>,
+    item: &MetadataItem<'_>,
+) -> Vec<DiagnosticEdit> {
+    let type_text = type_node.utf8_text(source.as_bytes()).unwrap_or("");
+    let (delete_start, delete_end) = redundant_language_delete_range(type_text, item);
+    let Some(location) = location_for_byte_range(
+        source,
+        type_node.start_byte() + delete_start,
+        type_node.start_byte() + delete_end,
+    ) else {
+        return Vec::new();
+    };
+    vec![DiagnosticEdit {
+        range: DiagnosticEditRange::from_location(&location),
+        new_text: String::new(),
+    }]
+}
+
+fn redundant_language_delete_range(type_text: &str, item: &MetadataItem<'_>) -> (usize, usize) {
+    let bytes = type_text.as_bytes();
+    let mut end = item.end;
+    while end < bytes.len() && bytes[end].is_ascii_whitespace() {
+        end += 1;
+    }
+    if end < bytes.len() && bytes[end] == b',' {
+        end += 1;
+        while end < bytes.len() && bytes[end].is_ascii_whitespace() {
+            end += 1;
+        }
+        return (item.start, end);
+    }
+
+    let mut start = item.start;
+    while start > 0 && bytes[start - 1].is_ascii_whitespace() {
+        start -= 1;
+    }
+    if let Some(comma) = type_text[..start].rfind(',') {
+        return (comma, item.end);
+    }
+    (item.start, item.end)
+}
+
+fn lint_template_string_expected(
+    path: &Path,
+    source: &str,
+    module_context: &ModuleContext,
+) -> Result<Vec<LintDiagnostic>> {
+    if !source.contains("Template")
+        && module_context.type_aliases.is_empty()
+        && module_context.callable_signatures.is_empty()
+    {
+        return Ok(Vec::new());
+    }
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_python::LANGUAGE.into())
+        .context("Failed to initialize Python parser")?;
+    let Some(tree) = parser.parse(source, None) else {
+        return Ok(Vec::new());
+    };
+
+    let mut diagnostics = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        match node.kind() {
+            "call" => {
+                lint_template_call_arguments(path, source, node, module_context, &mut diagnostics)?
+            }
+            "assignment" => {
+                if let Some(type_node) = node.child_by_field_name("type")
+                    && let Some(value) = node.child_by_field_name("right")
+                    && template_type_text_expects_template(
+                        type_node.utf8_text(source.as_bytes())?,
+                        module_context,
+                    )
+                    && let Some(kind) = non_template_string_kind(value, source)?
+                {
+                    let target = node
+                        .child_by_field_name("left")
+                        .map(|left| left.utf8_text(source.as_bytes()))
+                        .transpose()?
+                        .unwrap_or("value");
+                    diagnostics.push(template_string_expected_diagnostic(
+                        path,
+                        source,
+                        value,
+                        kind,
+                        format!("Variable '{target}' is annotated as a Template"),
+                        "assigned",
+                    )?);
+                }
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+
+    Ok(diagnostics)
+}
+
+fn lint_template_call_arguments(
+    path: &Path,
+    source: &str,
+    call: Node<'_>,
+    module_context: &ModuleContext,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) -> Result<()> {
+    let (Some(function), Some(arguments)) = (
+        call.child_by_field_name("function"),
+        call.child_by_field_name("arguments"),
+    ) else {
+        return Ok(());
+    };
+    if !matches!(function.kind(), "identifier" | "attribute") {
+        return Ok(());
+    }
+    let callee = function.utf8_text(source.as_bytes())?;
+    let Some(signature) = module_context.callable_signatures.get(callee).or_else(|| {
+        module_context
+            .callable_signatures
+            .get(&expand_lint_imported_name(callee, &module_context.imports))
+    }) else {
+        return Ok(());
+    };
+
+    let mut position = 0usize;
+    let mut cursor = arguments.walk();
+    for argument in arguments.named_children(&mut cursor) {
+        let (parameter, value) = match argument.kind() {
+            "keyword_argument" => {
+                let (Some(name), Some(value)) = (
+                    argument.child_by_field_name("name"),
+                    argument.child_by_field_name("value"),
+                ) else {
+                    continue;
+                };
+                let name = name.utf8_text(source.as_bytes())?;
+                let parameter = signature
+                    .parameters
+                    .iter()
+                    .find(|parameter| parameter.allows_keyword && parameter.name == name);
+                (parameter, value)
+            }
+            "list_splat" | "dictionary_splat" => break,
+            "comment" => continue,
+            _ => {
+                let parameter = signature
+                    .parameters
+                    .iter()
+                    .find(|parameter| !parameter.keyword_only && parameter.position == position);
+                position += 1;
+                (parameter, argument)
+            }
+        };
+        let Some(parameter) = parameter else {
+            continue;
+        };
+        // A parameter that also takes `str` accepts the formatted string.
+        if !parameter.accepts_template || parameter.value_types.contains(&CallableValueType::String)
+        {
+            continue;
+        }
+        let Some(kind) = non_template_string_kind(value, source)? else {
+            continue;
+        };
+        diagnostics.push(template_string_expected_diagnostic(
+            path,
+            source,
+            value,
+            kind,
+            format!(
+                "Parameter '{}' of '{callee}' expects a Template",
+                parameter.name
+            ),
+            "passed",
+        )?);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TemplateTypeMember {
+    Template,
+    String,
+    Other,
+}
+
+/// Whether a parameter or variable type accepts a `Template` but no plain
+/// string, so passing an f-string to it is a mistake.
+fn template_type_text_expects_template(type_text: &str, module_context: &ModuleContext) -> bool {
+    let mut members = Vec::new();
+    collect_template_type_members(type_text, module_context, &mut members);
+    members.contains(&TemplateTypeMember::Template)
+        && !members.contains(&TemplateTypeMember::String)
+}
+
+/// Flattens `|`, `Optional[...]`, `Union[...]`, and `Annotated[...]` into
+/// the union members of a type.
+fn collect_template_type_members(
+    type_text: &str,
+    module_context: &ModuleContext,
+    members: &mut Vec<TemplateTypeMember>,
+) {
+    for member in split_top_level_type_tokens(type_text, '|') {
+        let member = member
+            .trim()
+            .trim_matches(|ch| ch == '"' || ch == '\'')
+            .trim();
+        if let Some(open) = member.find('[')
+            && let Some(contents) = bracket_contents_at(&member[open..], '[', ']')
+        {
+            let base = expand_lint_imported_name(member[..open].trim(), &module_context.imports);
+            match base.rsplit('.').next() {
+                Some("Annotated") => {
+                    if let Some(first) = split_top_level_type_tokens(contents, ',').first() {
+                        collect_template_type_members(first, module_context, members);
+                    }
+                }
+                Some("Optional") => {
+                    collect_template_type_members(contents, module_context, members)
+                }
+                Some("Union") => {
+                    for item in split_top_level_type_tokens(contents, ',') {
+                        collect_template_type_members(item, module_context, members);
+                    }
+                }
+                _ => members.push(TemplateTypeMember::Other),
+            }
+            continue;
+        }
+
+        let expanded = expand_lint_imported_name(member, &module_context.imports);
+        members.push(
+            if module_context.type_aliases.contains_key(member)
+                || expanded == "string.templatelib.Template"
+            {
+                TemplateTypeMember::Template
+            } else if matches!(
+                expanded.as_str(),
+                "str"
+                    | "builtins.str"
+                    | "LiteralString"
+                    | "typing.LiteralString"
+                    | "typing_extensions.LiteralString"
+            ) {
+                TemplateTypeMember::String
+            } else {
+                TemplateTypeMember::Other
+            },
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NonTemplateStringKind {
+    FString,
+    PercentFormat,
+    FormatMethod,
+}
+
+impl NonTemplateStringKind {
+    fn description(self) -> &'static str {
+        match self {
+            Self::FString => "an f-string",
+            Self::PercentFormat => "a %-formatted string",
+            Self::FormatMethod => "a .format() string",
+        }
+    }
+}
+
+fn non_template_string_kind(node: Node<'_>, source: &str) -> Result<Option<NonTemplateStringKind>> {
+    match node.kind() {
+        "parenthesized_expression" => {
+            let mut cursor = node.walk();
+            let inner = node.named_children(&mut cursor).next();
+            match inner {
+                Some(inner) => non_template_string_kind(inner, source),
+                None => Ok(None),
+            }
+        }
+        "string" | "concatenated_string" => Ok(f_string_prefix_offsets(node, source)?
+            .iter()
+            .any(Option::is_some)
+            .then_some(NonTemplateStringKind::FString)),
+        "binary_operator" => {
+            let is_percent = node
+                .child_by_field_name("operator")
+                .is_some_and(|operator| operator.kind() == "%");
+            let left_is_string = node
+                .child_by_field_name("left")
+                .is_some_and(|left| matches!(left.kind(), "string" | "concatenated_string"));
+            Ok((is_percent && left_is_string).then_some(NonTemplateStringKind::PercentFormat))
+        }
+        "call" => {
+            let Some(function) = node.child_by_field_name("function") else {
+                return Ok(None);
+            };
+            if function.kind() != "attribute" {
+                return Ok(None);
+            }
+            let is_format = function
+                .child_by_field_name("attribute")
+                .map(|attribute| attribute.utf8_text(source.as_bytes()))
+                .transpose()?
+                == Some("format");
+            let object_is_string = function
+                .child_by_field_name("object")
+                .is_some_and(|object| matches!(object.kind(), "string" | "concatenated_string"));
+            Ok((is_format && object_is_string).then_some(NonTemplateStringKind::FormatMethod))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns the byte offset of the `f`/`F` prefix character of each part of a
+/// string or implicitly concatenated string literal that is not already a
+/// t-string, with `None` for plain string parts.
+fn f_string_prefix_offsets(node: Node<'_>, source: &str) -> Result<Vec<Option<usize>>> {
+    let strings = if node.kind() == "concatenated_string" {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .filter(|child| child.kind() == "string")
+            .collect::<Vec<_>>()
+    } else {
+        vec![node]
+    };
+
+    let mut offsets = Vec::new();
+    for string in strings {
+        let Some(start) = string
+            .child(0)
+            .filter(|child| child.kind() == "string_start")
+        else {
+            continue;
+        };
+        let prefix = start.utf8_text(source.as_bytes())?;
+        let prefix = prefix.trim_end_matches(['"', '\'']);
+        if prefix.contains(['t', 'T']) {
+            continue;
+        }
+        offsets.push(
+            prefix
+                .find(['f', 'F'])
+                .map(|index| start.start_byte() + index),
+        );
+    }
+    Ok(offsets)
+}
+
+fn template_string_expected_diagnostic(
+    path: &Path,
+    source: &str,
+    value: Node<'_>,
+    kind: NonTemplateStringKind,
+    subject: String,
+    verb: &str,
+) -> Result<LintDiagnostic> {
+    // Python rejects a t-string concatenated with a plain string literal, so
+    // a concatenation with plain parts gets no edit.
+    let offsets = if kind == NonTemplateStringKind::FString {
+        f_string_prefix_offsets(value, source)?
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+    } else {
+        None
+    };
+    let suggested_edits = if let Some(offsets) = offsets {
+        offsets
+            .into_iter()
+            .filter_map(|offset| {
+                let location = location_for_byte_range(source, offset, offset + 1)?;
+                let new_text = if source.as_bytes()[offset] == b'F' {
+                    "T"
+                } else {
+                    "t"
+                };
+                Some(DiagnosticEdit {
+                    range: DiagnosticEditRange::from_location(&location),
+                    new_text: new_text.to_string(),
+                })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut diagnostic = metadata_diagnostic(
+        path,
+        value,
+        RULE_TEMPLATE_STRING_EXPECTED,
+        LintSeverity::Error,
+        format!(
+            "{subject}, but {} is {verb}; it evaluates to str, not Template.",
+            kind.description()
+        ),
+        suggested_edits,
+    );
+    diagnostic.expected_type = Some("Template".to_string());
+    diagnostic.found_type = Some("str".to_string());
+    Ok(diagnostic)
+}
+
+fn location_for_byte_range(source: &str, start: usize, end: usize) -> Option<crate::Location> {
+    if start > end || end > source.len() {
+        return None;
+    }
+    let (start_line, start_column) = byte_position_to_line_column(source, start)?;
+    let (end_line, end_column) = byte_position_to_line_column(source, end)?;
+    Some(crate::Location {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    })
 }
 
 fn byte_position_to_line_column(source: &str, offset: usize) -> Option<(usize, usize)> {
     if offset > source.len() {
         return None;
     }
-    let mut line = 1usize;
-    let mut line_start = 0usize;
-    for (index, ch) in source.char_indices() {
-        if index >= offset {
-            break;
+    let mut line = 1usize;
+    let mut line_start = 0usize;
+    for (index, ch) in source.char_indices() {
+        if index >= offset {
+            break;
+        }
+        if ch == '\n' {
+            line += 1;
+            line_start = index + 1;
+        }
+    }
+    Some((line, offset - line_start + 1))
+}
+
+#[derive(Debug)]
+struct TypeToken<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+fn split_top_level_type_tokens_with_offsets(input: &str, separator: char) -> Vec<TypeToken<'_>> {
+    let mut parts = Vec::new();
+    let mut start = 0usize;
+    let mut bracket_depth = 0usize;
+    let mut paren_depth = 0usize;
+    let mut brace_depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, ch) in input.char_indices() {
+        if let Some(active_quote) = quote {
+            if escaped {
+                escaped = false;
+                continue;
+            }
+            match ch {
+                '\\' => escaped = true,
+                _ if ch == active_quote => quote = None,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '\'' | '"' => quote = Some(ch),
+            '[' => bracket_depth += 1,
+            ']' => bracket_depth = bracket_depth.saturating_sub(1),
+            '(' => paren_depth += 1,
+            ')' => paren_depth = paren_depth.saturating_sub(1),
+            '{' => brace_depth += 1,
+            '}' => brace_depth = brace_depth.saturating_sub(1),
+            _ if ch == separator && bracket_depth == 0 && paren_depth == 0 && brace_depth == 0 => {
+                push_type_token(input, start, index, &mut parts);
+                start = index + ch.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    push_type_token(input, start, input.len(), &mut parts);
+    parts
+}
+
+fn push_type_token<'a>(
+    input: &'a str,
+    raw_start: usize,
+    raw_end: usize,
+    parts: &mut Vec<TypeToken<'a>>,
+) {
+    let mut start = raw_start;
+    let mut end = raw_end;
+    while start < end && input.as_bytes()[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && input.as_bytes()[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    parts.push(TypeToken {
+        text: &input[start..end],
+        start,
+        end,
+    });
+}
+
+fn collect_json_schema_type_aliases(
+    source: &str,
+    root: Node<'_>,
+    module_context: &ModuleContext,
+) -> Result<BTreeMap<String, String>> {
+    let mut aliases = BTreeMap::new();
+    collect_pep695_json_schema_type_aliases(source, root, &mut aliases)?;
+    collect_typing_json_schema_type_aliases(source, root, module_context, &mut aliases)?;
+    Ok(aliases)
+}
+
+fn collect_pep695_json_schema_type_aliases(
+    source: &str,
+    root: Node<'_>,
+    aliases: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    let query = Query::new(
+        &tree_sitter_python::LANGUAGE.into(),
+        r#"
+        (type_alias_statement) @type_alias
+        "#,
+    )
+    .context("Failed to create JSON schema type alias query")?;
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, root, source.as_bytes());
+
+    while let Some(match_) = matches.next() {
+        for capture in match_.captures {
+            let type_alias_node = capture.node;
+            if !is_lint_module_level_statement(type_alias_node) {
+                continue;
+            }
+            let mut cursor = type_alias_node.walk();
+            let mut name_node = None;
+            let mut value_node = None;
+
+            for child in type_alias_node.children(&mut cursor) {
+                if child.kind() == "type" && name_node.is_none() {
+                    if child.utf8_text(source.as_bytes()).unwrap_or("") != "type" {
+                        name_node = Some(child);
+                    }
+                } else if child.kind() == "type" && name_node.is_some() {
+                    value_node = Some(child);
+                }
+            }
+
+            let (Some(name), Some(value)) = (name_node, value_node) else {
+                continue;
+            };
+            aliases.insert(
+                name.utf8_text(source.as_bytes())?.to_string(),
+                value.utf8_text(source.as_bytes())?.to_string(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn collect_typing_json_schema_type_aliases(
+    source: &str,
+    root: Node<'_>,
+    module_context: &ModuleContext,
+    aliases: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    let query = Query::new(
+        &tree_sitter_python::LANGUAGE.into(),
+        r#"
+        (assignment
+            left: (identifier) @alias_name
+            type: (_) @type_annotation
+            right: (_) @alias_value)
+        "#,
+    )
+    .context("Failed to create JSON schema typed alias query")?;
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, root, source.as_bytes());
+
+    while let Some(match_) = matches.next() {
+        let mut alias_name = None;
+        let mut alias_value = None;
+        let mut type_annotation = None;
+        for capture in match_.captures {
+            match query.capture_names()[capture.index as usize] {
+                "alias_name" => alias_name = Some(capture.node),
+                "alias_value" => alias_value = Some(capture.node),
+                "type_annotation" => type_annotation = Some(capture.node),
+                _ => {}
+            }
+        }
+        let (Some(name), Some(value), Some(annotation)) =
+            (alias_name, alias_value, type_annotation)
+        else {
+            continue;
+        };
+        if !is_lint_module_level_statement(name.parent().unwrap_or(name)) {
+            continue;
+        }
+        if !json_schema_type_annotation_is_type_alias(annotation, source, module_context)? {
+            continue;
+        }
+        aliases.insert(
+            name.utf8_text(source.as_bytes())?.to_string(),
+            value.utf8_text(source.as_bytes())?.to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+fn json_schema_type_annotation_is_type_alias(
+    annotation: Node<'_>,
+    source: &str,
+    module_context: &ModuleContext,
+) -> Result<bool> {
+    let raw = annotation.utf8_text(source.as_bytes())?.trim();
+    let resolved = expand_lint_imported_name(raw, &module_context.imports);
+    Ok(matches!(
+        resolved.as_str(),
+        "TypeAlias" | "typing.TypeAlias" | "typing_extensions.TypeAlias"
+    ))
+}
+
+fn expand_lint_imported_name(
+    name: &str,
+    imports: &std::collections::HashMap<String, String>,
+) -> String {
+    let Some((first, rest)) = name.split_once('.') else {
+        return imports
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string());
+    };
+    imports
+        .get(first)
+        .map(|target| format!("{target}.{rest}"))
+        .unwrap_or_else(|| name.to_string())
+}
+
+fn schema_binding_for_template(
+    source: &str,
+    root: Node<'_>,
+    template: &TemplateStringInfo,
+    marker_names: &BTreeMap<String, String>,
+    type_aliases: &BTreeMap<String, String>,
+) -> Result<Option<SchemaBinding>> {
+    let Some(template_start) = location_to_byte_offset(
+        source,
+        template.location.start_line,
+        template.location.start_column,
+    ) else {
+        return Ok(None);
+    };
+    let Some(type_node) = type_annotation_for_template_start(root, template_start) else {
+        return Ok(None);
+    };
+    let type_text = type_node.utf8_text(source.as_bytes())?;
+    let Some((reference, language)) =
+        schema_binding_from_type_text(type_text, marker_names, type_aliases)
+    else {
+        return Ok(None);
+    };
+    Ok(Some(SchemaBinding {
+        reference,
+        language,
+        location: location_for_node(type_node),
+    }))
+}
+
+fn type_annotation_for_template_start(root: Node<'_>, template_start: usize) -> Option<Node<'_>> {
+    let mut node = root.descendant_for_byte_range(template_start, template_start + 1)?;
+    loop {
+        if node.kind() == "assignment" {
+            let right = node.child_by_field_name("right")?;
+            if right.start_byte() <= template_start && template_start < right.end_byte() {
+                return node.child_by_field_name("type");
+            }
+        }
+        node = node.parent()?;
+    }
+}
+
+fn is_lint_module_level_statement(node: Node<'_>) -> bool {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        match parent.kind() {
+            "module" => return true,
+            "expression_statement" => current = parent,
+            _ => return false,
         }
-        if ch == '\n' {
-            line += 1;
-            line_start = index + 1;
+    }
+    false
+}
+
+fn schema_binding_from_type_text(
+    type_text: &str,
+    marker_names: &BTreeMap<String, String>,
+    type_aliases: &BTreeMap<String, String>,
+) -> Option<(SchemaReference, String)> {
+    schema_binding_from_type_text_inner(type_text, marker_names, type_aliases, &mut BTreeSet::new())
+}
+
+fn schema_binding_from_type_text_inner(
+    type_text: &str,
+    marker_names: &BTreeMap<String, String>,
+    type_aliases: &BTreeMap<String, String>,
+    seen_aliases: &mut BTreeSet<String>,
+) -> Option<(SchemaReference, String)> {
+    let markers = {
+        let mut markers = marker_names
+            .iter()
+            .map(|(marker, language)| (marker.as_str(), language.as_str()))
+            .collect::<Vec<_>>();
+        markers.sort_by_key(|(marker, _)| std::cmp::Reverse(marker.len()));
+        markers
+    };
+    for (marker, language) in markers {
+        if let Some(model) = generic_binding_arg(type_text, marker) {
+            return Some((
+                SchemaReference::Model(model.to_string()),
+                language.to_string(),
+            ));
+        }
+        if let Some(reference) = marker_call_schema_reference(type_text, marker) {
+            return Some((reference, language.to_string()));
         }
     }
-    Some((line, offset - line_start + 1))
+    let alias_name = type_text.trim();
+    if seen_aliases.insert(alias_name.to_string())
+        && let Some(alias_text) = type_aliases.get(alias_name)
+    {
+        return schema_binding_from_type_text_inner(
+            alias_text,
+            marker_names,
+            type_aliases,
+            seen_aliases,
+        );
+    }
+    None
 }
 
-#[derive(Debug)]
-struct TypeToken<'a> {
-    text: &'a str,
-    start: usize,
-    end: usize,
+fn generic_binding_arg<'a>(type_text: &'a str, marker: &str) -> Option<&'a str> {
+    let start = find_marker_followed_by(type_text, marker, '[')?;
+    let args = bracket_contents_at(&type_text[start + marker.len()..], '[', ']')?;
+    split_top_level_type_tokens(args, ',')
+        .first()
+        .map(|arg| arg.trim())
+        .filter(|arg| !arg.is_empty())
 }
 
-fn split_top_level_type_tokens_with_offsets(input: &str, separator: char) -> Vec<TypeToken<'_>> {
-    let mut parts = Vec::new();
-    let mut start = 0usize;
-    let mut bracket_depth = 0usize;
-    let mut paren_depth = 0usize;
-    let mut brace_depth = 0usize;
+fn marker_call_schema_reference(type_text: &str, marker: &str) -> Option<SchemaReference> {
+    let start = find_marker_followed_by(type_text, marker, '(')?;
+    let args = bracket_contents_at(&type_text[start + marker.len()..], '(', ')')?;
+    split_top_level_type_tokens(args, ',')
+        .into_iter()
+        .filter_map(|arg| arg.split_once('='))
+        .find_map(|(name, value)| match (name.trim(), value.trim()) {
+            (_, "") => None,
+            ("schema", value) => Some(SchemaReference::Model(value.to_string())),
+            ("schema_file", value) => {
+                parse_metadata_string_literal(value).map(SchemaReference::File)
+            }
+            _ => None,
+        })
+}
+
+fn find_marker_followed_by(type_text: &str, marker: &str, next: char) -> Option<usize> {
+    let mut search_start = 0usize;
+    while let Some(relative) = type_text[search_start..].find(marker) {
+        let start = search_start + relative;
+        let end = start + marker.len();
+        let before_ok = start == 0
+            || !type_text[..start]
+                .chars()
+                .next_back()
+                .is_some_and(is_identifier_continue);
+        let after_ok = type_text[end..].starts_with(next);
+        if before_ok && after_ok {
+            return Some(start);
+        }
+        search_start = end;
+    }
+    None
+}
+
+fn bracket_contents_at(text: &str, open: char, close: char) -> Option<&str> {
+    if !text.starts_with(open) {
+        return None;
+    }
+    let mut depth = 0usize;
     let mut quote = None;
     let mut escaped = false;
-    for (index, ch) in input.char_indices() {
+    for (index, ch) in text.char_indices() {
         if let Some(active_quote) = quote {
             if escaped {
                 escaped = false;
@@ -1062,564 +2881,1207 @@ fn split_top_level_type_tokens_with_offsets(input: &str, separator: char) -> Vec
         }
         match ch {
             '\'' | '"' => quote = Some(ch),
-            '[' => bracket_depth += 1,
-            ']' => bracket_depth = bracket_depth.saturating_sub(1),
-            '(' => paren_depth += 1,
-            ')' => paren_depth = paren_depth.saturating_sub(1),
-            '{' => brace_depth += 1,
-            '}' => brace_depth = brace_depth.saturating_sub(1),
-            _ if ch == separator && bracket_depth == 0 && paren_depth == 0 && brace_depth == 0 => {
-                push_type_token(input, start, index, &mut parts);
-                start = index + ch.len_utf8();
+            _ if ch == open => depth += 1,
+            _ if ch == close => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return Some(&text[1..index]);
+                }
             }
             _ => {}
         }
     }
-    push_type_token(input, start, input.len(), &mut parts);
-    parts
-}
-
-fn push_type_token<'a>(
-    input: &'a str,
-    raw_start: usize,
-    raw_end: usize,
-    parts: &mut Vec<TypeToken<'a>>,
-) {
-    let mut start = raw_start;
-    let mut end = raw_end;
-    while start < end && input.as_bytes()[start].is_ascii_whitespace() {
-        start += 1;
-    }
-    while end > start && input.as_bytes()[end - 1].is_ascii_whitespace() {
-        end -= 1;
-    }
-    parts.push(TypeToken {
-        text: &input[start..end],
-        start,
-        end,
-    });
-}
-
-fn collect_json_schema_type_aliases(
-    source: &str,
-    root: Node<'_>,
-    module_context: &ModuleContext,
-) -> Result<BTreeMap<String, String>> {
-    let mut aliases = BTreeMap::new();
-    collect_pep695_json_schema_type_aliases(source, root, &mut aliases)?;
-    collect_typing_json_schema_type_aliases(source, root, module_context, &mut aliases)?;
-    Ok(aliases)
+    None
 }
 
-fn collect_pep695_json_schema_type_aliases(
-    source: &str,
-    root: Node<'_>,
-    aliases: &mut BTreeMap<String, String>,
-) -> Result<()> {
-    let query = Query::new(
-        &tree_sitter_python::LANGUAGE.into(),
-        r#"
-        (type_alias_statement) @type_alias
-        "#,
-    )
-    .context("Failed to create JSON schema type alias query")?;
-    let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, root, source.as_bytes());
-
-    while let Some(match_) = matches.next() {
-        for capture in match_.captures {
-            let type_alias_node = capture.node;
-            if !is_lint_module_level_statement(type_alias_node) {
+fn split_top_level_type_tokens(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0usize;
+    let mut bracket_depth = 0usize;
+    let mut paren_depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, ch) in input.char_indices() {
+        if let Some(active_quote) = quote {
+            if escaped {
+                escaped = false;
                 continue;
             }
-            let mut cursor = type_alias_node.walk();
-            let mut name_node = None;
-            let mut value_node = None;
-
-            for child in type_alias_node.children(&mut cursor) {
-                if child.kind() == "type" && name_node.is_none() {
-                    if child.utf8_text(source.as_bytes()).unwrap_or("") != "type" {
-                        name_node = Some(child);
-                    }
-                } else if child.kind() == "type" && name_node.is_some() {
-                    value_node = Some(child);
-                }
+            match ch {
+                '\\' => escaped = true,
+                _ if ch == active_quote => quote = None,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '\'' | '"' => quote = Some(ch),
+            '[' => bracket_depth += 1,
+            ']' => bracket_depth = bracket_depth.saturating_sub(1),
+            '(' => paren_depth += 1,
+            ')' => paren_depth = paren_depth.saturating_sub(1),
+            _ if ch == separator && bracket_depth == 0 && paren_depth == 0 => {
+                parts.push(input[start..index].trim());
+                start = index + ch.len_utf8();
             }
-
-            let (Some(name), Some(value)) = (name_node, value_node) else {
-                continue;
-            };
-            aliases.insert(
-                name.utf8_text(source.as_bytes())?.to_string(),
-                value.utf8_text(source.as_bytes())?.to_string(),
-            );
+            _ => {}
         }
     }
-
-    Ok(())
+    parts.push(input[start..].trim());
+    parts
 }
 
-fn collect_typing_json_schema_type_aliases(
-    source: &str,
-    root: Node<'_>,
-    module_context: &ModuleContext,
-    aliases: &mut BTreeMap<String, String>,
-) -> Result<()> {
+fn collect_schema_registry(source: &str, root: Node<'_>) -> Result<SchemaRegistry> {
     let query = Query::new(
         &tree_sitter_python::LANGUAGE.into(),
         r#"
-        (assignment
-            left: (identifier) @alias_name
-            type: (_) @type_annotation
-            right: (_) @alias_value)
+        (class_definition
+            name: (identifier) @name
+            body: (block) @body) @class
         "#,
     )
-    .context("Failed to create JSON schema typed alias query")?;
+    .context("Failed to create schema model query")?;
     let mut cursor = QueryCursor::new();
     let mut matches = cursor.matches(&query, root, source.as_bytes());
+    let mut registry = SchemaRegistry::default();
 
     while let Some(match_) = matches.next() {
-        let mut alias_name = None;
-        let mut alias_value = None;
-        let mut type_annotation = None;
+        let mut class_node = None;
+        let mut name_node = None;
+        let mut body_node = None;
         for capture in match_.captures {
             match query.capture_names()[capture.index as usize] {
-                "alias_name" => alias_name = Some(capture.node),
-                "alias_value" => alias_value = Some(capture.node),
-                "type_annotation" => type_annotation = Some(capture.node),
+                "class" => class_node = Some(capture.node),
+                "name" => name_node = Some(capture.node),
+                "body" => body_node = Some(capture.node),
                 _ => {}
             }
         }
-        let (Some(name), Some(value), Some(annotation)) =
-            (alias_name, alias_value, type_annotation)
+        let (Some(class_node), Some(name_node), Some(body_node)) =
+            (class_node, name_node, body_node)
         else {
             continue;
         };
-        if !is_lint_module_level_statement(name.parent().unwrap_or(name)) {
+        let name = name_node.utf8_text(source.as_bytes())?.to_string();
+        if is_enum_class(source, class_node) {
+            registry
+                .enums
+                .insert(name, parse_schema_enum_body(source, body_node));
             continue;
         }
-        if !json_schema_type_annotation_is_type_alias(annotation, source, module_context)? {
+        let header = &source[class_node.start_byte()..body_node.start_byte()];
+        let bases = class_base_names(source, class_node);
+        let parents = bases
+            .iter()
+            .filter_map(|base| registry.model(base))
+            .collect::<Vec<_>>();
+        let Some(kind) = schema_model_kind(source, class_node, header, &bases, &parents) else {
             continue;
+        };
+        let mut model = SchemaModel {
+            kind,
+            fields: BTreeMap::new(),
+            allows_extra: kind == SchemaModelKind::Pydantic,
+            populate_by_name: false,
+            origin: None,
+        };
+        for parent in parents {
+            model.fields.extend(parent.fields.clone());
+            model.allows_extra = parent.allows_extra;
+            model.populate_by_name = parent.populate_by_name;
         }
-        aliases.insert(
-            name.utf8_text(source.as_bytes())?.to_string(),
-            value.utf8_text(source.as_bytes())?.to_string(),
-        );
+        if kind == SchemaModelKind::Pydantic
+            && let Some(superclasses) = class_node.child_by_field_name("superclasses")
+        {
+            apply_pydantic_model_config(source, &mut model, superclasses);
+        }
+        let total = !header.contains("total=False");
+        parse_schema_model_body(source, body_node, total, &mut model);
+        registry.models.insert(name, model);
     }
+    Ok(registry)
+}
 
-    Ok(())
+fn is_enum_class(source: &str, class_node: Node<'_>) -> bool {
+    class_base_names(source, class_node).iter().any(|base| {
+        base.rsplit('.')
+            .next()
+            .is_some_and(|name| name.ends_with("Enum"))
+    })
 }
 
-fn json_schema_type_annotation_is_type_alias(
-    annotation: Node<'_>,
+fn class_base_names(source: &str, class_node: Node<'_>) -> Vec<String> {
+    let Some(superclasses) = class_node.child_by_field_name("superclasses") else {
+        return Vec::new();
+    };
+    let mut cursor = superclasses.walk();
+    superclasses
+        .named_children(&mut cursor)
+        .filter(|base| base.kind() != "keyword_argument")
+        .filter_map(|base| base.utf8_text(source.as_bytes()).ok())
+        .map(str::to_string)
+        .collect()
+}
+
+fn schema_model_kind(
     source: &str,
-    module_context: &ModuleContext,
-) -> Result<bool> {
-    let raw = annotation.utf8_text(source.as_bytes())?.trim();
-    let resolved = expand_lint_imported_name(raw, &module_context.imports);
-    Ok(matches!(
-        resolved.as_str(),
-        "TypeAlias" | "typing.TypeAlias" | "typing_extensions.TypeAlias"
-    ))
+    class_node: Node<'_>,
+    header: &str,
+    bases: &[String],
+    parents: &[&SchemaModel],
+) -> Option<SchemaModelKind> {
+    let decorators = class_node
+        .parent()
+        .filter(|parent| parent.kind() == "decorated_definition")
+        .map(|parent| {
+            let mut cursor = parent.walk();
+            parent
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() == "decorator")
+                .filter_map(|decorator| decorator.utf8_text(source.as_bytes()).ok())
+                .map(|text| {
+                    let text = text.trim_start_matches('@').trim();
+                    text.split_once('(')
+                        .map_or(text, |(callee, _)| callee)
+                        .trim()
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let is_base_model = bases.iter().any(|base| {
+        let base = base.split_once('[').map_or(base.as_str(), |(base, _)| base);
+        base.rsplit('.').next() == Some("BaseModel")
+    });
+    if header.contains("TypedDict") {
+        return Some(SchemaModelKind::TypedDict);
+    }
+    if is_base_model {
+        return Some(SchemaModelKind::Pydantic);
+    }
+    if decorators
+        .iter()
+        .any(|decorator| is_attrs_decorator(decorator))
+    {
+        return Some(SchemaModelKind::Attrs);
+    }
+    if decorators
+        .iter()
+        .any(|decorator| decorator.rsplit('.').next() == Some("dataclass"))
+    {
+        return Some(SchemaModelKind::Dataclass);
+    }
+    parents.first().map(|parent| parent.kind)
 }
 
-fn expand_lint_imported_name(
-    name: &str,
-    imports: &std::collections::HashMap<String, String>,
-) -> String {
-    let Some((first, rest)) = name.split_once('.') else {
-        return imports
-            .get(name)
-            .cloned()
-            .unwrap_or_else(|| name.to_string());
+fn is_attrs_decorator(decorator: &str) -> bool {
+    match decorator.rsplit_once('.') {
+        Some((module, name)) => {
+            matches!(module, "attr" | "attrs")
+                && matches!(name, "define" | "frozen" | "mutable" | "s" | "attrs")
+        }
+        None => matches!(decorator, "define" | "frozen" | "mutable"),
+    }
+}
+
+/// Applies `extra` and `populate_by_name` from class keywords
+/// (`class M(BaseModel, extra="forbid")`), a `ConfigDict(...)` call, or a
+/// `{"extra": "forbid"}` dict.
+fn apply_pydantic_model_config(source: &str, model: &mut SchemaModel, config: Node<'_>) {
+    let arguments = match config.kind() {
+        "call" => match config.child_by_field_name("arguments") {
+            Some(arguments) => arguments,
+            None => return,
+        },
+        _ => config,
     };
-    imports
-        .get(first)
-        .map(|target| format!("{target}.{rest}"))
-        .unwrap_or_else(|| name.to_string())
+    let mut cursor = arguments.walk();
+    for entry in arguments.named_children(&mut cursor) {
+        let (key, value) = match entry.kind() {
+            "keyword_argument" => (
+                entry
+                    .child_by_field_name("name")
+                    .and_then(|name| name.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string),
+                entry.child_by_field_name("value"),
+            ),
+            "pair" => (
+                entry
+                    .child_by_field_name("key")
+                    .and_then(|key| key.utf8_text(source.as_bytes()).ok())
+                    .and_then(parse_metadata_string_literal),
+                entry.child_by_field_name("value"),
+            ),
+            _ => continue,
+        };
+        let (Some(key), Some(value)) = (key, value) else {
+            continue;
+        };
+        let Ok(value) = value.utf8_text(source.as_bytes()) else {
+            continue;
+        };
+        match key.as_str() {
+            "extra" => {
+                let extra = parse_metadata_string_literal(value).or_else(|| {
+                    value
+                        .strip_prefix("Extra.")
+                        .map(|member| member.trim().to_string())
+                });
+                match extra.as_deref() {
+                    Some("forbid") => model.allows_extra = false,
+                    Some("allow" | "ignore") => model.allows_extra = true,
+                    _ => {}
+                }
+            }
+            "populate_by_name" | "validate_by_name" if value == "True" => {
+                model.populate_by_name = true;
+            }
+            _ => {}
+        }
+    }
 }
 
-fn json_schema_binding_for_template(
-    source: &str,
-    root: Node<'_>,
-    template: &TemplateStringInfo,
-    marker_names: &BTreeSet<String>,
-    type_aliases: &BTreeMap<String, String>,
-) -> Result<Option<JsonSchemaBinding>> {
-    let Some(template_start) = location_to_byte_offset(
-        source,
-        template.location.start_line,
-        template.location.start_column,
-    ) else {
-        return Ok(None);
+fn parse_schema_enum_body(source: &str, body: Node<'_>) -> SchemaEnum {
+    let mut schema_enum = SchemaEnum {
+        values: Vec::new(),
+        complete: true,
     };
-    let Some(type_node) = type_annotation_for_template_start(root, template_start) else {
-        return Ok(None);
+    let mut cursor = body.walk();
+    for statement in body.named_children(&mut cursor) {
+        if statement.kind() != "expression_statement" {
+            continue;
+        }
+        let Some(assignment) = statement
+            .named_child(0)
+            .filter(|child| child.kind() == "assignment")
+        else {
+            continue;
+        };
+        let (Some(left), Some(right)) = (
+            assignment.child_by_field_name("left"),
+            assignment.child_by_field_name("right"),
+        ) else {
+            continue;
+        };
+        let Ok(member) = left.utf8_text(source.as_bytes()) else {
+            continue;
+        };
+        if left.kind() != "identifier" || member.starts_with('_') {
+            continue;
+        }
+        match right
+            .utf8_text(source.as_bytes())
+            .ok()
+            .and_then(schema_literal_from_python)
+        {
+            Some(value) => schema_enum.values.push(value),
+            None => schema_enum.complete = false,
+        }
+    }
+    schema_enum
+}
+
+fn extend_imported_schema_registry(
+    path: &Path,
+    module_context: &ModuleContext,
+    registry: &mut SchemaRegistry,
+) -> Result<()> {
+    let Some(root) = path.parent() else {
+        return Ok(());
     };
-    let type_text = type_node.utf8_text(source.as_bytes())?;
-    let Some(model_name) =
-        json_schema_binding_from_type_text(type_text, marker_names, type_aliases)
-    else {
-        return Ok(None);
+    let mut imported_registries = BTreeMap::<PathBuf, SchemaRegistry>::new();
+    for target in module_context.imports.values() {
+        let Some((module, symbol)) = target.rsplit_once('.') else {
+            continue;
+        };
+        if registry.models.contains_key(symbol) || registry.enums.contains_key(symbol) {
+            continue;
+        }
+        let Some(module_path) = imported_module_file(root, module) else {
+            continue;
+        };
+        if !imported_registries.contains_key(&module_path) {
+            let Ok(source) = std::fs::read_to_string(&module_path) else {
+                continue;
+            };
+            let mut parser = Parser::new();
+            parser
+                .set_language(&tree_sitter_python::LANGUAGE.into())
+                .context("Failed to initialize Python parser")?;
+            let Some(tree) = parser.parse(&source, None) else {
+                continue;
+            };
+            let imported = collect_schema_registry(&source, tree.root_node())?;
+            imported_registries.insert(module_path.clone(), imported);
+        }
+        let imported = &imported_registries[&module_path];
+        if let Some(model) = imported.models.get(symbol) {
+            registry.models.insert(symbol.to_string(), model.clone());
+            registry
+                .models
+                .insert(format!("{module}.{symbol}"), model.clone());
+        }
+        if let Some(schema_enum) = imported.enums.get(symbol) {
+            registry
+                .enums
+                .insert(symbol.to_string(), schema_enum.clone());
+            registry
+                .enums
+                .insert(format!("{module}.{symbol}"), schema_enum.clone());
+        }
+    }
+    // Nested fields of imported models may reference other names from the
+    // same module without those names being imported here.
+    for imported in imported_registries.into_values() {
+        for (name, model) in imported.models {
+            registry.models.entry(name).or_insert(model);
+        }
+        for (name, schema_enum) in imported.enums {
+            registry.enums.entry(name).or_insert(schema_enum);
+        }
+    }
+    Ok(())
+}
+
+/// Loads a JSON Schema document into `registry` and returns the key of its root model.
+fn load_json_schema_model(
+    registry: &mut SchemaRegistry,
+    root: &Path,
+    path: &Path,
+    file: &str,
+) -> std::result::Result<String, Option<String>> {
+    let root_key = format!("{file}#");
+    if registry.models.contains_key(&root_key) {
+        return Ok(root_key);
+    }
+    let candidates = [
+        Some(root.join(file)),
+        path.parent().map(|parent| parent.join(file)),
+    ];
+    let Some(schema_path) = candidates.into_iter().flatten().find(|path| path.is_file()) else {
+        return Err(Some(format!("Could not find JSON Schema file '{file}'.")));
     };
-    Ok(Some(JsonSchemaBinding {
-        model_name,
-        location: location_for_node(type_node),
-    }))
+    let document = std::fs::read_to_string(&schema_path)
+        .map_err(|error| error.to_string())
+        .and_then(|text| {
+            serde_json::from_str::<serde_json::Value>(&text).map_err(|error| error.to_string())
+        })
+        .map_err(|error| {
+            Some(format!(
+                "Could not load JSON Schema file '{file}': {error}."
+            ))
+        })?;
+    let mut translator = JsonSchemaTranslator {
+        document: &document,
+        label: file,
+        registry,
+        visiting: BTreeSet::new(),
+    };
+    match translator.translate(&document, "") {
+        SchemaType::Named(key) => Ok(key),
+        _ => Err(Some(format!(
+            "JSON Schema file '{file}' does not describe an object."
+        ))),
+    }
 }
 
-fn type_annotation_for_template_start(root: Node<'_>, template_start: usize) -> Option<Node<'_>> {
-    let mut node = root.descendant_for_byte_range(template_start, template_start + 1)?;
-    loop {
-        if node.kind() == "assignment" {
-            let right = node.child_by_field_name("right")?;
-            if right.start_byte() <= template_start && template_start < right.end_byte() {
-                return node.child_by_field_name("type");
+/// Translates the JSON Schema subset that maps onto `SchemaType`; other keywords are ignored.
+struct JsonSchemaTranslator<'a> {
+    document: &'a serde_json::Value,
+    label: &'a str,
+    registry: &'a mut SchemaRegistry,
+    visiting: BTreeSet<String>,
+}
+
+impl JsonSchemaTranslator<'_> {
+    fn translate(&mut self, schema: &serde_json::Value, pointer: &str) -> SchemaType {
+        let Some(object) = schema.as_object() else {
+            return SchemaType::Any;
+        };
+        if let Some(reference) = object.get("$ref").and_then(serde_json::Value::as_str) {
+            return self.translate_reference(reference);
+        }
+        if let Some(value) = object.get("const") {
+            return schema_literal_from_json(value)
+                .map_or(SchemaType::Any, |value| SchemaType::Literal(vec![value]));
+        }
+        if let Some(values) = object.get("enum").and_then(serde_json::Value::as_array) {
+            return values
+                .iter()
+                .map(schema_literal_from_json)
+                .collect::<Option<Vec<_>>>()
+                .map_or(SchemaType::Any, SchemaType::Literal);
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(alternatives) = object.get(keyword).and_then(serde_json::Value::as_array) {
+                let alternatives = alternatives
+                    .iter()
+                    .enumerate()
+                    .map(|(index, alternative)| {
+                        self.translate(alternative, &format!("{pointer}/{keyword}/{index}"))
+                    })
+                    .collect();
+                return schema_union(alternatives);
             }
         }
-        node = node.parent()?;
+        if let Some([only]) = object
+            .get("allOf")
+            .and_then(serde_json::Value::as_array)
+            .map(Vec::as_slice)
+        {
+            return self.translate(only, &format!("{pointer}/allOf/0"));
+        }
+        match object.get("type") {
+            Some(serde_json::Value::String(kind)) => self.translate_typed(object, kind, pointer),
+            Some(serde_json::Value::Array(kinds)) => {
+                let alternatives = kinds
+                    .iter()
+                    .filter_map(serde_json::Value::as_str)
+                    .map(|kind| self.translate_typed(object, kind, pointer))
+                    .collect();
+                schema_union(alternatives)
+            }
+            _ if object.contains_key("properties") => {
+                self.translate_typed(object, "object", pointer)
+            }
+            _ => SchemaType::Any,
+        }
     }
-}
 
-fn is_lint_module_level_statement(node: Node<'_>) -> bool {
-    let mut current = node;
-    while let Some(parent) = current.parent() {
-        match parent.kind() {
-            "module" => return true,
-            "expression_statement" => current = parent,
-            _ => return false,
+    fn translate_reference(&mut self, reference: &str) -> SchemaType {
+        // Only document-local references are followed.
+        let Some(target_pointer) = reference.strip_prefix('#') else {
+            return SchemaType::Any;
+        };
+        let key = format!("{}#{target_pointer}", self.label);
+        if self.registry.models.contains_key(&key) || self.visiting.contains(&key) {
+            return SchemaType::Named(key);
+        }
+        let reference_key = format!("$ref:{key}");
+        if !self.visiting.insert(reference_key.clone()) {
+            return SchemaType::Any;
+        }
+        let document = self.document;
+        let schema = document
+            .pointer(target_pointer)
+            .map_or(SchemaType::Any, |target| {
+                self.translate(target, target_pointer)
+            });
+        self.visiting.remove(&reference_key);
+        schema
+    }
+
+    fn translate_typed(
+        &mut self,
+        object: &serde_json::Map<String, serde_json::Value>,
+        kind: &str,
+        pointer: &str,
+    ) -> SchemaType {
+        let schema = match kind {
+            "object" => return self.translate_object(object, pointer),
+            "array" => SchemaType::Array(Box::new(
+                object.get("items").map_or(SchemaType::Any, |items| {
+                    self.translate(items, &format!("{pointer}/items"))
+                }),
+            )),
+            "string" => SchemaType::Scalar(JsonScalarKind::String),
+            "integer" => SchemaType::Scalar(JsonScalarKind::Integer),
+            "number" => SchemaType::Scalar(JsonScalarKind::Number),
+            "boolean" => SchemaType::Scalar(JsonScalarKind::Boolean),
+            "null" => SchemaType::Scalar(JsonScalarKind::Null),
+            _ => return SchemaType::Any,
+        };
+        let mut constraints = SchemaConstraints::default();
+        for (keyword, key) in [
+            ("exclusiveMinimum", "gt"),
+            ("minimum", "ge"),
+            ("exclusiveMaximum", "lt"),
+            ("maximum", "le"),
+            ("minLength", "min_length"),
+            ("minItems", "min_length"),
+            ("maxLength", "max_length"),
+            ("maxItems", "max_length"),
+        ] {
+            if let Some(value) = object.get(keyword).filter(|value| value.is_number()) {
+                constraints.apply(key, &value.to_string());
+            }
+        }
+        if constraints.is_empty() {
+            schema
+        } else {
+            SchemaType::Constrained(Box::new(schema), constraints)
         }
     }
-    false
+
+    fn translate_object(
+        &mut self,
+        object: &serde_json::Map<String, serde_json::Value>,
+        pointer: &str,
+    ) -> SchemaType {
+        let additional = object.get("additionalProperties");
+        let Some(properties) = object
+            .get("properties")
+            .and_then(serde_json::Value::as_object)
+        else {
+            return match additional {
+                Some(schema @ serde_json::Value::Object(_)) => SchemaType::Map(Box::new(
+                    self.translate(schema, &format!("{pointer}/additionalProperties")),
+                )),
+                _ => SchemaType::Map(Box::new(SchemaType::Any)),
+            };
+        };
+        let key = format!("{}#{pointer}", self.label);
+        if self.registry.models.contains_key(&key) || !self.visiting.insert(key.clone()) {
+            return SchemaType::Named(key);
+        }
+        let required = object
+            .get("required")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(serde_json::Value::as_str)
+            .collect::<BTreeSet<_>>();
+        let closed = serde_json::Value::Bool(false);
+        let mut model = SchemaModel {
+            kind: SchemaModelKind::JsonSchema,
+            fields: BTreeMap::new(),
+            allows_extra: additional != Some(&closed)
+                && object.get("unevaluatedProperties") != Some(&closed),
+            populate_by_name: false,
+            origin: Some(key.clone()),
+        };
+        for (name, property) in properties {
+            let property_pointer = json_pointer_child(&format!("{pointer}/properties"), name);
+            let schema = self.translate(property, &property_pointer);
+            model.fields.insert(
+                name.clone(),
+                SchemaField {
+                    required: required.contains(name.as_str()),
+                    schema,
+                    aliases: Vec::new(),
+                    origin: Some(format!("{}#{property_pointer}", self.label)),
+                },
+            );
+        }
+        self.registry.models.insert(key.clone(), model);
+        SchemaType::Named(key)
+    }
+}
+
+fn schema_union(alternatives: Vec<SchemaType>) -> SchemaType {
+    if alternatives.is_empty() {
+        SchemaType::Any
+    } else {
+        SchemaType::Union(alternatives)
+    }
+}
+
+fn schema_literal_from_json(value: &serde_json::Value) -> Option<SchemaLiteral> {
+    match value {
+        serde_json::Value::Null => Some(SchemaLiteral::Null),
+        serde_json::Value::Bool(value) => Some(SchemaLiteral::Boolean(*value)),
+        serde_json::Value::Number(number) => number
+            .as_i64()
+            .map(SchemaLiteral::Integer)
+            .or_else(|| number.as_f64().map(SchemaLiteral::Number)),
+        serde_json::Value::String(value) => Some(SchemaLiteral::String(value.clone())),
+        _ => None,
+    }
 }
 
-fn json_schema_binding_from_type_text(
-    type_text: &str,
-    marker_names: &BTreeSet<String>,
-    type_aliases: &BTreeMap<String, String>,
-) -> Option<String> {
-    json_schema_binding_from_type_text_inner(
-        type_text,
-        marker_names,
-        type_aliases,
-        &mut BTreeSet::new(),
-    )
+fn imported_module_file(root: &Path, module: &str) -> Option<PathBuf> {
+    let relative = module
+        .split('.')
+        .fold(PathBuf::new(), |path, part| path.join(part));
+    let file = root.join(&relative).with_extension("py");
+    if file.is_file() {
+        return Some(file);
+    }
+    let init = root.join(relative).join("__init__.py");
+    init.is_file().then_some(init)
 }
 
-fn json_schema_binding_from_type_text_inner(
-    type_text: &str,
-    marker_names: &BTreeSet<String>,
-    type_aliases: &BTreeMap<String, String>,
-    seen_aliases: &mut BTreeSet<String>,
-) -> Option<String> {
-    let markers = {
-        let mut markers = marker_names.iter().map(String::as_str).collect::<Vec<_>>();
-        markers.sort_by_key(|marker| std::cmp::Reverse(marker.len()));
-        markers
-    };
-    for marker in markers {
-        if let Some(model) = generic_binding_arg(type_text, marker) {
-            return Some(model.to_string());
+fn parse_schema_model_body(source: &str, body: Node<'_>, total: bool, model: &mut SchemaModel) {
+    let mut cursor = body.walk();
+    for statement in body.named_children(&mut cursor) {
+        if statement.kind() != "expression_statement" {
+            continue;
+        }
+        let Some(assignment) = statement
+            .named_child(0)
+            .filter(|child| child.kind() == "assignment")
+        else {
+            continue;
+        };
+        let Some(name) = assignment
+            .child_by_field_name("left")
+            .filter(|left| left.kind() == "identifier")
+            .and_then(|left| left.utf8_text(source.as_bytes()).ok())
+        else {
+            continue;
+        };
+        let default = assignment
+            .child_by_field_name("right")
+            .and_then(|right| right.utf8_text(source.as_bytes()).ok());
+        if model.kind == SchemaModelKind::Pydantic {
+            if name == "model_config" {
+                if let Some(config) = assignment.child_by_field_name("right") {
+                    apply_pydantic_model_config(source, model, config);
+                }
+                continue;
+            }
+            if name.starts_with('_') {
+                continue;
+            }
         }
-        if let Some(model) = marker_call_schema_arg(type_text, marker) {
-            return Some(model.to_string());
+        let type_text = match assignment.child_by_field_name("type") {
+            Some(type_node) => type_node.utf8_text(source.as_bytes()).unwrap_or_default(),
+            // attrs also declares fields with unannotated `x = attr.ib()`.
+            None if model.kind == SchemaModelKind::Attrs
+                && default.is_some_and(|default| {
+                    schema_field_call(default)
+                        .is_some_and(|(callee, _)| matches!(callee, "ib" | "attrib"))
+                }) =>
+            {
+                "Any"
+            }
+            None => continue,
+        };
+        let (required_override, inner_type) = requiredness_from_type_text(type_text);
+        if inner_type.starts_with("ClassVar") || inner_type.starts_with("typing.ClassVar") {
+            continue;
         }
-    }
-    let alias_name = type_text.trim();
-    if seen_aliases.insert(alias_name.to_string())
-        && let Some(alias_text) = type_aliases.get(alias_name)
-    {
-        return json_schema_binding_from_type_text_inner(
-            alias_text,
-            marker_names,
-            type_aliases,
-            seen_aliases,
+        let mut schema = schema_type_from_text(inner_type);
+        let mut has_default = default.is_some();
+        let mut alias = None;
+        if let Some(default) = default {
+            if default.trim() == "..." {
+                has_default = false;
+            } else if let Some((callee, arguments)) = schema_field_call(default) {
+                has_default = schema_field_call_has_default(callee, &arguments);
+                if model.kind == SchemaModelKind::Pydantic {
+                    alias = ["validation_alias", "alias"].iter().find_map(|key| {
+                        keyword_argument(&arguments, key).and_then(parse_metadata_string_literal)
+                    });
+                    let constraints = schema_constraints_from_metadata(&[default.trim()]);
+                    if !constraints.is_empty() {
+                        schema = SchemaType::Constrained(Box::new(schema), constraints);
+                    }
+                }
+            }
+        }
+        let required = match model.kind {
+            SchemaModelKind::TypedDict => required_override.unwrap_or(total),
+            _ => !has_default,
+        };
+        let (key, aliases) = match alias {
+            Some(alias) if model.populate_by_name => (alias, vec![name.to_string()]),
+            Some(alias) => (alias, Vec::new()),
+            None => (name.to_string(), Vec::new()),
+        };
+        model
+            .fields
+            .retain(|_, field| !field.aliases.iter().any(|alias| alias == name));
+        model.fields.remove(name);
+        model.fields.insert(
+            key,
+            SchemaField {
+                required,
+                schema,
+                aliases,
+                origin: None,
+            },
         );
     }
-    None
 }
 
-fn generic_binding_arg<'a>(type_text: &'a str, marker: &str) -> Option<&'a str> {
-    let start = find_marker_followed_by(type_text, marker, '[')?;
-    let args = bracket_contents_at(&type_text[start + marker.len()..], '[', ']')?;
-    split_top_level_type_tokens(args, ',')
+/// Splits `Field(...)`, `field(...)`, and `attr.ib(...)` defaults into callee and arguments.
+fn schema_field_call(default: &str) -> Option<(&str, Vec<&str>)> {
+    let default = default.trim();
+    let open = default.find('(')?;
+    let callee = default[..open].trim();
+    let callee = callee.rsplit('.').next().unwrap_or(callee);
+    if !matches!(callee, "Field" | "field" | "ib" | "attrib") {
+        return None;
+    }
+    let arguments = bracket_contents_at(&default[open..], '(', ')')?;
+    Some((
+        callee,
+        split_top_level_type_tokens(arguments, ',')
+            .into_iter()
+            .filter(|argument| !argument.is_empty())
+            .collect(),
+    ))
+}
+
+fn schema_field_call_has_default(callee: &str, arguments: &[&str]) -> bool {
+    let positional_default = arguments
         .first()
-        .map(|arg| arg.trim())
-        .filter(|arg| !arg.is_empty())
+        .filter(|argument| !is_keyword_argument(argument))
+        .is_some_and(|argument| !matches!(*argument, "..." | "Ellipsis" | "PydanticUndefined"));
+    let keyword_default = ["default", "default_factory", "factory"]
+        .iter()
+        .any(|key| keyword_argument(arguments, key).is_some());
+    // `dataclasses.field()` takes no positional default.
+    keyword_default || (callee != "field" && positional_default)
 }
 
-fn marker_call_schema_arg<'a>(type_text: &'a str, marker: &str) -> Option<&'a str> {
-    let start = find_marker_followed_by(type_text, marker, '(')?;
-    let args = bracket_contents_at(&type_text[start + marker.len()..], '(', ')')?;
-    split_top_level_type_tokens(args, ',')
-        .into_iter()
-        .filter_map(|arg| arg.split_once('='))
-        .find_map(|(name, value)| (name.trim() == "schema").then(|| value.trim()))
-        .filter(|arg| !arg.is_empty())
+fn keyword_argument<'a>(arguments: &[&'a str], key: &str) -> Option<&'a str> {
+    arguments.iter().find_map(|argument| {
+        let (name, value) = argument.split_once('=')?;
+        (name.trim() == key).then_some(value.trim())
+    })
 }
 
-fn find_marker_followed_by(type_text: &str, marker: &str, next: char) -> Option<usize> {
-    let mut search_start = 0usize;
-    while let Some(relative) = type_text[search_start..].find(marker) {
-        let start = search_start + relative;
-        let end = start + marker.len();
-        let before_ok = start == 0
-            || !type_text[..start]
-                .chars()
-                .next_back()
-                .is_some_and(is_identifier_continue);
-        let after_ok = type_text[end..].starts_with(next);
-        if before_ok && after_ok {
-            return Some(start);
+fn is_keyword_argument(argument: &str) -> bool {
+    argument
+        .split_once('=')
+        .is_some_and(|(name, _)| is_identifier_expression(name.trim()))
+}
+
+fn requiredness_from_type_text(type_text: &str) -> (Option<bool>, &str) {
+    for (prefix, required) in [("Required[", true), ("typing.Required[", true)] {
+        if let Some(inner) = type_text
+            .strip_prefix(prefix)
+            .and_then(|text| text.strip_suffix(']'))
+        {
+            return (Some(required), inner.trim());
         }
-        search_start = end;
     }
-    None
+    for (prefix, required) in [("NotRequired[", false), ("typing.NotRequired[", false)] {
+        if let Some(inner) = type_text
+            .strip_prefix(prefix)
+            .and_then(|text| text.strip_suffix(']'))
+        {
+            return (Some(required), inner.trim());
+        }
+    }
+    (None, type_text)
 }
 
-fn bracket_contents_at(text: &str, open: char, close: char) -> Option<&str> {
-    if !text.starts_with(open) {
-        return None;
+fn schema_type_from_text(type_text: &str) -> SchemaType {
+    let text = type_text.trim();
+    if let Some(forward_reference) = parse_metadata_string_literal(text) {
+        return schema_type_from_text(&forward_reference);
     }
-    let mut depth = 0usize;
-    let mut quote = None;
-    let mut escaped = false;
-    for (index, ch) in text.char_indices() {
-        if let Some(active_quote) = quote {
-            if escaped {
-                escaped = false;
-                continue;
+    let alternatives = split_top_level_type_tokens(text, '|');
+    if alternatives.len() > 1 {
+        return SchemaType::Union(
+            alternatives
+                .into_iter()
+                .map(schema_type_from_text)
+                .collect(),
+        );
+    }
+    if let Some(open) = text.find('[')
+        && let Some(arguments) = text[open + 1..].strip_suffix(']')
+    {
+        let base = text[..open].trim();
+        let base = base.rsplit('.').next().unwrap_or(base);
+        let arguments = split_top_level_type_tokens(arguments, ',');
+        let first = || schema_type_from_text(arguments[0]);
+        return match base {
+            "Optional" => {
+                SchemaType::Union(vec![first(), SchemaType::Scalar(JsonScalarKind::Null)])
             }
-            match ch {
-                '\\' => escaped = true,
-                _ if ch == active_quote => quote = None,
-                _ => {}
+            "Union" => SchemaType::Union(
+                arguments
+                    .iter()
+                    .copied()
+                    .map(schema_type_from_text)
+                    .collect(),
+            ),
+            "Required" | "NotRequired" | "ReadOnly" | "Final" => first(),
+            "list" | "List" | "Sequence" | "MutableSequence" | "Iterable" | "Collection"
+            | "set" | "Set" | "MutableSet" | "frozenset" | "FrozenSet" => {
+                SchemaType::Array(Box::new(first()))
             }
-            continue;
-        }
-        match ch {
-            '\'' | '"' => quote = Some(ch),
-            _ if ch == open => depth += 1,
-            _ if ch == close => {
-                depth = depth.saturating_sub(1);
-                if depth == 0 {
-                    return Some(&text[1..index]);
+            "tuple" | "Tuple" => match arguments.as_slice() {
+                [item] | [item, "..."] => SchemaType::Array(Box::new(schema_type_from_text(item))),
+                _ => SchemaType::Array(Box::new(SchemaType::Any)),
+            },
+            "dict" | "Dict" | "Mapping" | "MutableMapping" => SchemaType::Map(Box::new(
+                arguments
+                    .get(1)
+                    .map_or(SchemaType::Any, |value| schema_type_from_text(value)),
+            )),
+            "Literal" => arguments
+                .iter()
+                .map(|argument| schema_literal_from_python(argument))
+                .collect::<Option<Vec<_>>>()
+                .map_or(SchemaType::Any, SchemaType::Literal),
+            "Annotated" => {
+                let constraints = schema_constraints_from_metadata(&arguments[1..]);
+                if constraints.is_empty() {
+                    first()
+                } else {
+                    SchemaType::Constrained(Box::new(first()), constraints)
                 }
             }
-            _ => {}
+            _ => SchemaType::Any,
+        };
+    }
+    match text {
+        "int" | "builtins.int" => SchemaType::Scalar(JsonScalarKind::Integer),
+        "float" | "builtins.float" => SchemaType::Scalar(JsonScalarKind::Number),
+        "str" | "builtins.str" => SchemaType::Scalar(JsonScalarKind::String),
+        "bool" | "builtins.bool" => SchemaType::Scalar(JsonScalarKind::Boolean),
+        "None" | "NoneType" => SchemaType::Scalar(JsonScalarKind::Null),
+        "list" | "List" | "typing.List" | "tuple" | "Tuple" | "typing.Tuple" | "set" => {
+            SchemaType::Array(Box::new(SchemaType::Any))
         }
+        "dict" | "Dict" | "typing.Dict" => SchemaType::Map(Box::new(SchemaType::Any)),
+        _ if !text.is_empty() && text.split('.').all(is_identifier_expression) => {
+            SchemaType::Named(text.to_string())
+        }
+        _ => SchemaType::Any,
     }
-    None
 }
 
-fn split_top_level_type_tokens(input: &str, separator: char) -> Vec<&str> {
-    let mut parts = Vec::new();
-    let mut start = 0usize;
-    let mut bracket_depth = 0usize;
-    let mut paren_depth = 0usize;
-    let mut quote = None;
-    let mut escaped = false;
-    for (index, ch) in input.char_indices() {
-        if let Some(active_quote) = quote {
-            if escaped {
-                escaped = false;
-                continue;
-            }
-            match ch {
-                '\\' => escaped = true,
-                _ if ch == active_quote => quote = None,
+fn schema_constraints_from_metadata(items: &[&str]) -> SchemaConstraints {
+    let mut constraints = SchemaConstraints::default();
+    for item in items {
+        let Some(open) = item.find('(') else {
+            continue;
+        };
+        let Some(arguments) = item[open + 1..].strip_suffix(')') else {
+            continue;
+        };
+        let name = item[..open].trim();
+        let name = name.rsplit('.').next().unwrap_or(name);
+        let positional_keys: &[&str] = match name {
+            "Gt" => &["gt"],
+            "Ge" => &["ge"],
+            "Lt" => &["lt"],
+            "Le" => &["le"],
+            "MinLen" => &["min_length"],
+            "MaxLen" => &["max_length"],
+            "Len" => &["min_length", "max_length"],
+            "Interval" | "Field" => &[],
+            _ => continue,
+        };
+        let mut positional = positional_keys.iter();
+        for argument in split_top_level_type_tokens(arguments, ',') {
+            match argument.split_once('=') {
+                Some((key, value)) if is_identifier_expression(key.trim()) => {
+                    constraints.apply(key.trim(), value);
+                }
+                _ if !argument.is_empty() => {
+                    if let Some(key) = positional.next() {
+                        constraints.apply(key, argument);
+                    }
+                }
                 _ => {}
             }
-            continue;
         }
-        match ch {
-            '\'' | '"' => quote = Some(ch),
-            '[' => bracket_depth += 1,
-            ']' => bracket_depth = bracket_depth.saturating_sub(1),
-            '(' => paren_depth += 1,
-            ')' => paren_depth = paren_depth.saturating_sub(1),
-            _ if ch == separator && bracket_depth == 0 && paren_depth == 0 => {
-                parts.push(input[start..index].trim());
-                start = index + ch.len_utf8();
-            }
+    }
+    constraints
+}
+
+impl SchemaConstraints {
+    fn apply(&mut self, key: &str, value: &str) {
+        let value = value.trim().replace('_', "");
+        match key {
+            "gt" => self.gt = value.parse().ok(),
+            "ge" => self.ge = value.parse().ok(),
+            "lt" => self.lt = value.parse().ok(),
+            "le" => self.le = value.parse().ok(),
+            "min_length" => self.min_length = value.parse().ok(),
+            "max_length" => self.max_length = value.parse().ok(),
             _ => {}
         }
     }
-    parts.push(input[start..].trim());
-    parts
+
+    fn is_empty(&self) -> bool {
+        self.gt.is_none()
+            && self.ge.is_none()
+            && self.lt.is_none()
+            && self.le.is_none()
+            && self.min_length.is_none()
+            && self.max_length.is_none()
+    }
+
+    fn numeric_violation(&self, value: f64) -> Option<String> {
+        let bounds = [
+            (
+                self.gt,
+                "greater than",
+                value > self.gt.unwrap_or(f64::NEG_INFINITY),
+            ),
+            (
+                self.ge,
+                "greater than or equal to",
+                value >= self.ge.unwrap_or(f64::NEG_INFINITY),
+            ),
+            (
+                self.lt,
+                "less than",
+                value < self.lt.unwrap_or(f64::INFINITY),
+            ),
+            (
+                self.le,
+                "less than or equal to",
+                value <= self.le.unwrap_or(f64::INFINITY),
+            ),
+        ];
+        bounds
+            .into_iter()
+            .find_map(|(bound, description, satisfied)| {
+                bound
+                    .filter(|_| !satisfied)
+                    .map(|bound| format!("a value {description} {bound}"))
+            })
+    }
+
+    fn length_violation(&self, length: usize) -> Option<String> {
+        if let Some(min_length) = self.min_length
+            && length < min_length
+        {
+            return Some(format!("a length of at least {min_length}"));
+        }
+        if let Some(max_length) = self.max_length
+            && length > max_length
+        {
+            return Some(format!("a length of at most {max_length}"));
+        }
+        None
+    }
 }
 
-fn collect_schema_models(
-    source: &str,
-    root: Node<'_>,
-) -> Result<std::collections::BTreeMap<String, SchemaModel>> {
-    let query = Query::new(
-        &tree_sitter_python::LANGUAGE.into(),
-        r#"
-        (class_definition
-            name: (identifier) @name
-            body: (block) @body) @class
-        "#,
-    )
-    .context("Failed to create schema model query")?;
-    let mut cursor = QueryCursor::new();
-    let mut matches = cursor.matches(&query, root, source.as_bytes());
-    let mut models = std::collections::BTreeMap::new();
+fn schema_literal_from_python(text: &str) -> Option<SchemaLiteral> {
+    let text = text.trim();
+    match text {
+        "None" => return Some(SchemaLiteral::Null),
+        "True" => return Some(SchemaLiteral::Boolean(true)),
+        "False" => return Some(SchemaLiteral::Boolean(false)),
+        _ => {}
+    }
+    if let Some(value) = unquote_python_string(text) {
+        return Some(SchemaLiteral::String(value));
+    }
+    schema_number_literal(text)
+}
 
-    while let Some(match_) = matches.next() {
-        let mut class_node = None;
-        let mut name_node = None;
-        let mut body_node = None;
-        for capture in match_.captures {
-            match query.capture_names()[capture.index as usize] {
-                "class" => class_node = Some(capture.node),
-                "name" => name_node = Some(capture.node),
-                "body" => body_node = Some(capture.node),
-                _ => {}
+fn schema_number_literal(text: &str) -> Option<SchemaLiteral> {
+    let text = text.trim().replace('_', "");
+    if let Ok(value) = text.parse::<i64>() {
+        return Some(SchemaLiteral::Integer(value));
+    }
+    text.parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .map(SchemaLiteral::Number)
+}
+
+impl SchemaLiteral {
+    fn kind(&self) -> JsonScalarKind {
+        match self {
+            Self::String(_) => JsonScalarKind::String,
+            Self::Integer(_) => JsonScalarKind::Integer,
+            Self::Number(_) => JsonScalarKind::Number,
+            Self::Boolean(_) => JsonScalarKind::Boolean,
+            Self::Null => JsonScalarKind::Null,
+        }
+    }
+
+    fn matches(&self, other: &SchemaLiteral) -> bool {
+        match (self, other) {
+            (Self::Integer(left), Self::Number(right))
+            | (Self::Number(right), Self::Integer(left)) => *left as f64 == *right,
+            _ => self == other,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Integer(value) => Some(*value as f64),
+            Self::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaLiteral {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(value) => {
+                write!(formatter, "{}", serde_json::Value::String(value.clone()))
             }
+            Self::Integer(value) => write!(formatter, "{value}"),
+            Self::Number(value) => write!(formatter, "{value}"),
+            Self::Boolean(value) => write!(formatter, "{value}"),
+            Self::Null => formatter.write_str("null"),
         }
-        let (Some(class_node), Some(name_node), Some(body_node)) =
-            (class_node, name_node, body_node)
-        else {
-            continue;
-        };
-        let header = &source[class_node.start_byte()..body_node.start_byte()];
-        let decorated_prefix = class_node
-            .parent()
-            .filter(|parent| parent.kind() == "decorated_definition")
-            .map(|parent| &source[parent.start_byte()..class_node.start_byte()])
-            .unwrap_or("");
-        let is_typed_dict = header.contains("TypedDict");
-        let is_dataclass = decorated_prefix.contains("dataclass");
-        if !is_typed_dict && !is_dataclass {
-            continue;
+    }
+}
+
+impl<'a> SchemaLintContext<'a> {
+    /// Reports diagnostics against `origin` (a JSON Schema location) when one is known.
+    fn with_origin(&self, origin: Option<&'a str>) -> Self {
+        Self {
+            source_of_truth: origin.unwrap_or(self.source_of_truth),
+            ..*self
         }
-        let total = !header.contains("total=False");
-        let name = name_node.utf8_text(source.as_bytes())?.to_string();
-        let body = body_node.utf8_text(source.as_bytes())?;
-        let model = parse_schema_model_body(body, total, is_dataclass);
-        models.insert(name, model);
     }
-    Ok(models)
 }
 
-fn extend_imported_schema_models(
-    path: &Path,
-    module_context: &ModuleContext,
-    models: &mut std::collections::BTreeMap<String, SchemaModel>,
-) -> Result<()> {
-    let Some(root) = path.parent() else {
-        return Ok(());
-    };
-    for target in module_context.imports.values() {
-        let Some((module, symbol)) = target.rsplit_once('.') else {
-            continue;
-        };
-        if models.contains_key(symbol) {
-            continue;
+impl SchemaModel {
+    fn field(&self, key: &str) -> Option<&SchemaField> {
+        self.fields.get(key).or_else(|| {
+            self.fields
+                .values()
+                .find(|field| field.aliases.iter().any(|alias| alias == key))
+        })
+    }
+}
+
+impl SchemaRegistry {
+    fn model(&self, name: &str) -> Option<&SchemaModel> {
+        self.models.get(name).or_else(|| {
+            name.rsplit_once('.')
+                .and_then(|(_, short)| self.models.get(short))
+        })
+    }
+
+    fn schema_enum(&self, name: &str) -> Option<&SchemaEnum> {
+        self.enums
+            .get(name)
+            .or_else(|| {
+                name.rsplit_once('.')
+                    .and_then(|(_, short)| self.enums.get(short))
+            })
+            .filter(|schema_enum| schema_enum.complete && !schema_enum.values.is_empty())
+    }
+
+    /// Returns the value shapes accepted by `schema`, or `None` when any shape is allowed.
+    fn shape_kinds(&self, schema: &SchemaType) -> Option<Vec<JsonScalarKind>> {
+        match schema {
+            SchemaType::Any => None,
+            SchemaType::Scalar(kind) => Some(vec![*kind]),
+            SchemaType::Named(name) => {
+                if self.model(name).is_some() {
+                    Some(vec![JsonScalarKind::Object])
+                } else {
+                    self.schema_enum(name).map(|schema_enum| {
+                        schema_enum.values.iter().map(SchemaLiteral::kind).collect()
+                    })
+                }
+            }
+            SchemaType::Array(_) => Some(vec![JsonScalarKind::Array]),
+            SchemaType::Map(_) => Some(vec![JsonScalarKind::Object]),
+            SchemaType::Literal(values) => Some(values.iter().map(SchemaLiteral::kind).collect()),
+            SchemaType::Union(alternatives) => {
+                let mut kinds = Vec::new();
+                for alternative in alternatives {
+                    kinds.extend(self.shape_kinds(alternative)?);
+                }
+                Some(kinds)
+            }
+            SchemaType::Constrained(inner, _) => self.shape_kinds(inner),
         }
-        let Some(module_path) = imported_module_file(root, module) else {
-            continue;
-        };
-        let Ok(source) = std::fs::read_to_string(&module_path) else {
-            continue;
-        };
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_python::LANGUAGE.into())
-            .context("Failed to initialize Python parser")?;
-        let Some(tree) = parser.parse(&source, None) else {
-            continue;
-        };
-        let imported = collect_schema_models(&source, tree.root_node())?;
-        if let Some(model) = imported.get(symbol).cloned() {
-            models.insert(symbol.to_string(), model.clone());
-            models.insert(format!("{module}.{symbol}"), model);
+    }
+
+    fn choices<'a>(&'a self, schema: &'a SchemaType) -> Option<&'a [SchemaLiteral]> {
+        match schema {
+            SchemaType::Literal(values) => Some(values),
+            SchemaType::Named(name) => self
+                .schema_enum(name)
+                .map(|schema_enum| schema_enum.values.as_slice()),
+            _ => None,
         }
     }
-    Ok(())
 }
 
-fn imported_module_file(root: &Path, module: &str) -> Option<PathBuf> {
-    let relative = module
-        .split('.')
-        .fold(PathBuf::new(), |path, part| path.join(part));
-    let file = root.join(&relative).with_extension("py");
-    if file.is_file() {
-        return Some(file);
+fn describe_shape_kinds(kinds: &[JsonScalarKind]) -> String {
+    kinds
+        .iter()
+        .map(ToString::to_string)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+fn schema_root_outline(template: &TemplateStringInfo, language: &str) -> Option<JsonObjectOutline> {
+    match language {
+        "json" => json_root_outline(template),
+        "yaml" => yaml_root_outline(template),
+        "toml" => toml_root_outline(template),
+        _ => None,
     }
-    let init = root.join(relative).join("__init__.py");
-    init.is_file().then_some(init)
 }
 
-fn parse_schema_model_body(body: &str, total: bool, is_dataclass: bool) -> SchemaModel {
-    let mut fields = std::collections::BTreeMap::new();
-    for line in body.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "pass" {
-            continue;
+impl<'a> OutlineContext<'a> {
+    fn new(template: &'a TemplateStringInfo, processed: &'a ProcessedTemplate) -> Self {
+        Self {
+            template,
+            processed,
+            interpolation_ranges: interpolation_content_ranges(template),
         }
-        let Some((name, mut type_text)) = trimmed.split_once(':') else {
-            continue;
-        };
-        let name = name.trim();
-        if !is_identifier_expression(name) {
-            continue;
+    }
+
+    fn location(&self, node: Node<'_>) -> crate::Location {
+        processed_node_location(self.template, self.processed, node)
+    }
+
+    fn is_interpolated(&self, node: Node<'_>) -> bool {
+        ranges_overlap_usize(
+            &processed_original_range(self.processed, node),
+            &self.interpolation_ranges,
+        )
+    }
+
+    fn text(&self, node: Node<'_>) -> Option<&'a str> {
+        node.utf8_text(self.processed.content.as_bytes()).ok()
+    }
+
+    fn object(&self, node: Node<'_>) -> JsonObjectOutline {
+        JsonObjectOutline {
+            location: Some(self.location(node)),
+            ..JsonObjectOutline::default()
         }
-        type_text = type_text.split('#').next().unwrap_or(type_text).trim();
-        let has_default = type_text.contains('=');
-        type_text = type_text.split('=').next().unwrap_or(type_text).trim();
-        let (required_override, inner_type) = requiredness_from_type_text(type_text);
-        fields.insert(
-            name.to_string(),
-            SchemaField {
-                required: required_override.unwrap_or(if is_dataclass {
-                    !has_default
-                } else {
-                    total
-                }),
-                scalar: json_scalar_from_type(inner_type),
-            },
-        );
     }
-    SchemaModel { fields }
 }
 
-fn requiredness_from_type_text(type_text: &str) -> (Option<bool>, &str) {
-    for (prefix, required) in [("Required[", true), ("typing.Required[", true)] {
-        if let Some(inner) = type_text
-            .strip_prefix(prefix)
-            .and_then(|text| text.strip_suffix(']'))
-        {
-            return (Some(required), inner.trim());
+impl JsonValueOutline {
+    fn unknown(location: crate::Location) -> Self {
+        Self {
+            location,
+            kind: None,
+            literal: None,
+            quoted: false,
+            object: None,
+            items: None,
         }
     }
-    for (prefix, required) in [("NotRequired[", false), ("typing.NotRequired[", false)] {
-        if let Some(inner) = type_text
-            .strip_prefix(prefix)
-            .and_then(|text| text.strip_suffix(']'))
-        {
-            return (Some(required), inner.trim());
+
+    fn scalar(
+        location: crate::Location,
+        kind: Option<JsonScalarKind>,
+        literal: Option<SchemaLiteral>,
+        quoted: bool,
+    ) -> Self {
+        Self {
+            kind,
+            literal,
+            quoted,
+            ..Self::unknown(location)
         }
     }
-    (None, type_text)
-}
 
-fn json_scalar_from_type(type_text: &str) -> Option<JsonScalarKind> {
-    let text = type_text.trim();
-    match text {
-        "int" | "builtins.int" => Some(JsonScalarKind::Integer),
-        "float" | "builtins.float" => Some(JsonScalarKind::Number),
-        "str" | "builtins.str" => Some(JsonScalarKind::String),
-        "bool" | "builtins.bool" => Some(JsonScalarKind::Boolean),
-        "None" | "NoneType" => Some(JsonScalarKind::Null),
-        _ if text.starts_with("list[") || text.starts_with("typing.List[") => {
-            Some(JsonScalarKind::Array)
+    fn object(location: crate::Location, object: JsonObjectOutline) -> Self {
+        Self {
+            kind: Some(JsonScalarKind::Object),
+            object: Some(object),
+            ..Self::unknown(location)
         }
-        _ if text.starts_with("dict[") || text.starts_with("typing.Dict[") => {
-            Some(JsonScalarKind::Object)
+    }
+
+    fn array(location: crate::Location, items: Vec<JsonValueOutline>) -> Self {
+        Self {
+            kind: Some(JsonScalarKind::Array),
+            items: Some(items),
+            ..Self::unknown(location)
         }
-        _ => None,
     }
 }
 
@@ -1630,20 +4092,15 @@ fn json_root_outline(template: &TemplateStringInfo) -> Option<JsonObjectOutline>
         return None;
     }
     let root = tree.root_node().named_child(0)?;
-    (root.kind() == "object").then(|| collect_json_object_outline(template, &processed, root, ""))
+    let context = OutlineContext::new(template, &processed);
+    (root.kind() == "object").then(|| collect_json_object_outline(&context, root))
 }
 
 fn collect_json_object_outline(
-    template: &TemplateStringInfo,
-    processed: &ProcessedTemplate,
+    context: &OutlineContext<'_>,
     object: Node<'_>,
-    pointer: &str,
 ) -> JsonObjectOutline {
-    let interpolation_ranges = interpolation_content_ranges(template);
-    let mut outline = JsonObjectOutline {
-        pointer: pointer.to_string(),
-        ..JsonObjectOutline::default()
-    };
+    let mut outline = context.object(object);
     let mut cursor = object.walk();
     for child in object.named_children(&mut cursor) {
         if child.kind() != "pair" {
@@ -1655,35 +4112,430 @@ fn collect_json_object_outline(
         let Some(value_node) = child.child_by_field_name("value") else {
             continue;
         };
-        let key_original_range = processed_original_range(processed, key_node);
-        if ranges_overlap_usize(&key_original_range, &interpolation_ranges) {
+        if context.is_interpolated(key_node) {
+            outline.has_interpolation_key = true;
+            continue;
+        }
+        let Some(key) = context
+            .text(key_node)
+            .and_then(|text| serde_json::from_str::<String>(text).ok())
+        else {
+            continue;
+        };
+        outline.static_keys.insert(
+            key,
+            JsonKeyOutline {
+                key_location: context.location(key_node),
+                quoted_key: true,
+                value: json_value_outline(context, value_node),
+            },
+        );
+    }
+    outline
+}
+
+fn json_value_outline(context: &OutlineContext<'_>, node: Node<'_>) -> JsonValueOutline {
+    let location = context.location(node);
+    match node.kind() {
+        "object" => JsonValueOutline::object(location, collect_json_object_outline(context, node)),
+        "array" => {
+            let mut cursor = node.walk();
+            let items = node
+                .named_children(&mut cursor)
+                .filter(|item| item.kind() != "comment")
+                .map(|item| json_value_outline(context, item))
+                .collect();
+            JsonValueOutline::array(location, items)
+        }
+        _ if context.is_interpolated(node) => JsonValueOutline::unknown(location),
+        _ => {
+            let text = context.text(node).unwrap_or_default();
+            let literal = match node.kind() {
+                "string" => serde_json::from_str::<String>(text)
+                    .ok()
+                    .map(SchemaLiteral::String),
+                "number" => schema_number_literal(text),
+                "true" => Some(SchemaLiteral::Boolean(true)),
+                "false" => Some(SchemaLiteral::Boolean(false)),
+                "null" => Some(SchemaLiteral::Null),
+                _ => None,
+            };
+            JsonValueOutline::scalar(
+                location,
+                json_scalar_from_value_node(node, &context.processed.content),
+                literal,
+                true,
+            )
+        }
+    }
+}
+
+fn yaml_root_outline(template: &TemplateStringInfo) -> Option<JsonObjectOutline> {
+    let processed = prepare_template_for_lint(template, "yaml");
+    let tree = parse_embedded("yaml", &processed.content).ok()?;
+    if tree.root_node().has_error() {
+        return None;
+    }
+    let mut cursor = tree.root_node().walk();
+    let document = tree
+        .root_node()
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "document")?;
+    let mut cursor = document.walk();
+    let root = document
+        .named_children(&mut cursor)
+        .find(|child| matches!(child.kind(), "block_node" | "flow_node"))?;
+    let mapping = yaml_node_value(root)?;
+    let context = OutlineContext::new(template, &processed);
+    matches!(mapping.kind(), "block_mapping" | "flow_mapping")
+        .then(|| collect_yaml_mapping_outline(&context, mapping))
+}
+
+fn collect_yaml_mapping_outline(
+    context: &OutlineContext<'_>,
+    mapping: Node<'_>,
+) -> JsonObjectOutline {
+    let mut outline = context.object(mapping);
+    let mut cursor = mapping.walk();
+    for child in mapping.named_children(&mut cursor) {
+        if !matches!(child.kind(), "block_mapping_pair" | "flow_pair") {
+            continue;
+        }
+        let Some(key_node) = child.child_by_field_name("key") else {
+            continue;
+        };
+        if context.is_interpolated(key_node) {
             outline.has_interpolation_key = true;
             continue;
         }
-        let Ok(key_text) = key_node.utf8_text(processed.content.as_bytes()) else {
+        let Some(key_scalar) = yaml_node_value(key_node) else {
             continue;
         };
-        let Ok(key) = serde_json::from_str::<String>(key_text) else {
+        let Some(key) = context.text(key_scalar).and_then(unquote_schema_key) else {
             continue;
         };
-        let value_original_range = processed_original_range(processed, value_node);
-        let value_kind = if ranges_overlap_usize(&value_original_range, &interpolation_ranges) {
-            None
-        } else {
-            json_scalar_from_value_node(value_node, &processed.content)
+        let value = match child.child_by_field_name("value") {
+            Some(value_node) => yaml_value_outline(context, value_node),
+            None => JsonValueOutline::scalar(
+                context.location(key_node),
+                Some(JsonScalarKind::Null),
+                Some(SchemaLiteral::Null),
+                false,
+            ),
         };
         outline.static_keys.insert(
             key,
             JsonKeyOutline {
-                key_location: processed_node_location(template, processed, key_node),
-                value_location: processed_node_location(template, processed, value_node),
-                value_kind,
+                key_location: context.location(key_node),
+                quoted_key: matches!(
+                    key_scalar.kind(),
+                    "double_quote_scalar" | "single_quote_scalar"
+                ),
+                value,
             },
         );
     }
     outline
 }
 
+fn yaml_value_outline(context: &OutlineContext<'_>, node: Node<'_>) -> JsonValueOutline {
+    let location = context.location(node);
+    let Some(value) = yaml_node_value(node) else {
+        return JsonValueOutline::unknown(location);
+    };
+    match value.kind() {
+        "block_mapping" | "flow_mapping" => {
+            JsonValueOutline::object(location, collect_yaml_mapping_outline(context, value))
+        }
+        "block_sequence" => {
+            let mut cursor = value.walk();
+            let items = value
+                .named_children(&mut cursor)
+                .filter(|item| item.kind() == "block_sequence_item")
+                .map(|item| {
+                    let mut cursor = item.walk();
+                    let item_value = item
+                        .named_children(&mut cursor)
+                        .find(|child| child.kind() != "comment");
+                    match item_value {
+                        Some(item_value) => yaml_value_outline(context, item_value),
+                        None => JsonValueOutline::scalar(
+                            context.location(item),
+                            Some(JsonScalarKind::Null),
+                            Some(SchemaLiteral::Null),
+                            false,
+                        ),
+                    }
+                })
+                .collect();
+            JsonValueOutline::array(location, items)
+        }
+        "flow_sequence" => {
+            let mut cursor = value.walk();
+            let items = value
+                .named_children(&mut cursor)
+                .filter(|item| item.kind() != "comment")
+                .map(|item| yaml_value_outline(context, item))
+                .collect();
+            JsonValueOutline::array(location, items)
+        }
+        _ if context.is_interpolated(value) => JsonValueOutline::unknown(location),
+        _ => JsonValueOutline::scalar(
+            location,
+            yaml_scalar_from_value_node(value),
+            yaml_literal_from_value_node(context, value),
+            value.kind() != "plain_scalar",
+        ),
+    }
+}
+
+fn yaml_node_value(node: Node<'_>) -> Option<Node<'_>> {
+    if !matches!(node.kind(), "block_node" | "flow_node") {
+        return Some(node);
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .find(|child| !matches!(child.kind(), "tag" | "anchor" | "comment"))
+}
+
+fn yaml_scalar_from_value_node(node: Node<'_>) -> Option<JsonScalarKind> {
+    match node.kind() {
+        "plain_scalar" => match node.named_child(0)?.kind() {
+            "integer_scalar" => Some(JsonScalarKind::Integer),
+            "float_scalar" => Some(JsonScalarKind::Number),
+            "boolean_scalar" => Some(JsonScalarKind::Boolean),
+            "null_scalar" => Some(JsonScalarKind::Null),
+            "string_scalar" => Some(JsonScalarKind::String),
+            _ => None,
+        },
+        "double_quote_scalar" | "single_quote_scalar" | "block_scalar" => {
+            Some(JsonScalarKind::String)
+        }
+        "block_sequence" | "flow_sequence" => Some(JsonScalarKind::Array),
+        "block_mapping" | "flow_mapping" => Some(JsonScalarKind::Object),
+        _ => None,
+    }
+}
+
+fn yaml_literal_from_value_node(
+    context: &OutlineContext<'_>,
+    node: Node<'_>,
+) -> Option<SchemaLiteral> {
+    match node.kind() {
+        "plain_scalar" => {
+            let scalar = node.named_child(0)?;
+            let text = context.text(scalar)?;
+            match scalar.kind() {
+                "integer_scalar" | "float_scalar" => schema_number_literal(text),
+                "boolean_scalar" => Some(SchemaLiteral::Boolean(text.eq_ignore_ascii_case("true"))),
+                "null_scalar" => Some(SchemaLiteral::Null),
+                "string_scalar" => Some(SchemaLiteral::String(text.to_string())),
+                _ => None,
+            }
+        }
+        "double_quote_scalar" | "single_quote_scalar" => context
+            .text(node)
+            .and_then(unquote_schema_key)
+            .map(SchemaLiteral::String),
+        _ => None,
+    }
+}
+
+fn toml_root_outline(template: &TemplateStringInfo) -> Option<JsonObjectOutline> {
+    let processed = prepare_template_for_lint(template, "toml");
+    let tree = parse_embedded("toml", &processed.content).ok()?;
+    if tree.root_node().has_error() {
+        return None;
+    }
+    let context = OutlineContext::new(template, &processed);
+    let document = tree.root_node();
+    let mut outline = context.object(document);
+    let mut cursor = document.walk();
+    for child in document.named_children(&mut cursor) {
+        match child.kind() {
+            "pair" => insert_toml_pair(&context, &mut outline, child),
+            "table" | "table_array_element" => insert_toml_table(&context, &mut outline, child),
+            _ => {}
+        }
+    }
+    Some(outline)
+}
+
+fn insert_toml_table(
+    context: &OutlineContext<'_>,
+    outline: &mut JsonObjectOutline,
+    table: Node<'_>,
+) {
+    let Some(header) = table.named_child(0) else {
+        return;
+    };
+    let segments = toml_key_segments(header);
+    let Some((last, parents)) = segments.split_last() else {
+        return;
+    };
+    let Some(parent) = toml_descend(context, outline, parents, table) else {
+        return;
+    };
+    let location = context.location(table);
+    let is_array = table.kind() == "table_array_element";
+    let Some(entry) = toml_key_entry(context, parent, *last, || match is_array {
+        true => JsonValueOutline::array(location.clone(), Vec::new()),
+        false => JsonValueOutline::object(location.clone(), context.object(table)),
+    }) else {
+        return;
+    };
+    let target = match (is_array, entry.value.items.as_mut()) {
+        (true, Some(items)) => {
+            items.push(JsonValueOutline::object(
+                context.location(table),
+                context.object(table),
+            ));
+            items.last_mut().and_then(|item| item.object.as_mut())
+        }
+        (true, None) => None,
+        (false, _) => entry.value.object.as_mut(),
+    };
+    let Some(target) = target else {
+        return;
+    };
+    let mut cursor = table.walk();
+    for pair in table.named_children(&mut cursor).skip(1) {
+        if pair.kind() == "pair" {
+            insert_toml_pair(context, target, pair);
+        }
+    }
+}
+
+fn insert_toml_pair(context: &OutlineContext<'_>, object: &mut JsonObjectOutline, pair: Node<'_>) {
+    let (Some(key_node), Some(value_node)) = (pair.named_child(0), pair.named_child(1)) else {
+        return;
+    };
+    let segments = toml_key_segments(key_node);
+    let Some((last, parents)) = segments.split_last() else {
+        return;
+    };
+    let Some(parent) = toml_descend(context, object, parents, pair) else {
+        return;
+    };
+    let value = toml_value_outline(context, value_node);
+    toml_key_entry(context, parent, *last, || value);
+}
+
+/// Walks dotted key `segments` below `object`, creating implicit tables along the way.
+fn toml_descend<'o>(
+    context: &OutlineContext<'_>,
+    object: &'o mut JsonObjectOutline,
+    segments: &[Node<'_>],
+    container: Node<'_>,
+) -> Option<&'o mut JsonObjectOutline> {
+    let Some((first, rest)) = segments.split_first() else {
+        return Some(object);
+    };
+    let entry = toml_key_entry(context, object, *first, || {
+        JsonValueOutline::object(context.location(container), context.object(container))
+    })?;
+    let next = match entry.value.items.as_mut() {
+        Some(items) => items.last_mut()?.object.as_mut()?,
+        None => entry.value.object.as_mut()?,
+    };
+    toml_descend(context, next, rest, container)
+}
+
+fn toml_key_entry<'o>(
+    context: &OutlineContext<'_>,
+    object: &'o mut JsonObjectOutline,
+    key_node: Node<'_>,
+    value: impl FnOnce() -> JsonValueOutline,
+) -> Option<&'o mut JsonKeyOutline> {
+    if context.is_interpolated(key_node) {
+        object.has_interpolation_key = true;
+        return None;
+    }
+    let key = context.text(key_node).and_then(unquote_schema_key)?;
+    Some(
+        object
+            .static_keys
+            .entry(key)
+            .or_insert_with(|| JsonKeyOutline {
+                key_location: context.location(key_node),
+                quoted_key: key_node.kind() == "quoted_key",
+                value: value(),
+            }),
+    )
+}
+
+fn toml_key_segments(key: Node<'_>) -> Vec<Node<'_>> {
+    if key.kind() != "dotted_key" {
+        return vec![key];
+    }
+    let mut cursor = key.walk();
+    key.named_children(&mut cursor)
+        .flat_map(toml_key_segments)
+        .collect()
+}
+
+fn toml_value_outline(context: &OutlineContext<'_>, node: Node<'_>) -> JsonValueOutline {
+    let location = context.location(node);
+    match node.kind() {
+        "inline_table" => {
+            let mut object = context.object(node);
+            let mut cursor = node.walk();
+            for pair in node.named_children(&mut cursor) {
+                if pair.kind() == "pair" {
+                    insert_toml_pair(context, &mut object, pair);
+                }
+            }
+            JsonValueOutline::object(location, object)
+        }
+        "array" => {
+            let mut cursor = node.walk();
+            let items = node
+                .named_children(&mut cursor)
+                .filter(|item| item.kind() != "comment")
+                .map(|item| toml_value_outline(context, item))
+                .collect();
+            JsonValueOutline::array(location, items)
+        }
+        _ if context.is_interpolated(node) => JsonValueOutline::unknown(location),
+        _ => {
+            let text = context.text(node).unwrap_or_default();
+            let literal = match node.kind() {
+                "string" => unquote_schema_key(text).map(SchemaLiteral::String),
+                "integer" | "float" => schema_number_literal(text),
+                "boolean" => Some(SchemaLiteral::Boolean(text == "true")),
+                _ => None,
+            };
+            JsonValueOutline::scalar(location, toml_scalar_from_value_node(node), literal, true)
+        }
+    }
+}
+
+fn toml_scalar_from_value_node(node: Node<'_>) -> Option<JsonScalarKind> {
+    match node.kind() {
+        "string" => Some(JsonScalarKind::String),
+        "integer" => Some(JsonScalarKind::Integer),
+        "float" => Some(JsonScalarKind::Number),
+        "boolean" => Some(JsonScalarKind::Boolean),
+        "array" => Some(JsonScalarKind::Array),
+        "inline_table" => Some(JsonScalarKind::Object),
+        _ => None,
+    }
+}
+
+fn unquote_schema_key(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.starts_with('"') {
+        return serde_json::from_str::<String>(text).ok();
+    }
+    if let Some(inner) = text
+        .strip_prefix('\'')
+        .and_then(|text| text.strip_suffix('\''))
+    {
+        return Some(inner.replace("''", "'"));
+    }
+    Some(text.to_string())
+}
+
 fn interpolation_content_ranges(template: &TemplateStringInfo) -> Vec<std::ops::Range<usize>> {
     let mut ranges = Vec::new();
     let mut offset = 0usize;
@@ -1755,24 +4607,36 @@ fn json_scalar_from_value_node(node: Node<'_>, source: &str) -> Option<JsonScala
 }
 
 fn lint_json_model_against_outline(
-    path: &Path,
+    context: &SchemaLintContext<'_>,
     model: &SchemaModel,
     outline: &JsonObjectOutline,
-    source_of_truth: &str,
-) -> Vec<LintDiagnostic> {
-    let mut diagnostics = Vec::new();
+    pointer: &str,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let context = &context.with_origin(model.origin.as_deref());
+    let label = schema_language_label(context.language);
+    let source_of_truth = context.source_of_truth;
     if !outline.has_interpolation_key {
         for (field_name, field) in &model.fields {
-            if field.required && !outline.static_keys.contains_key(field_name) {
+            if field.required
+                && !outline.static_keys.contains_key(field_name)
+                && !field
+                    .aliases
+                    .iter()
+                    .any(|alias| outline.static_keys.contains_key(alias))
+            {
                 diagnostics.push(schema_diagnostic(
-                    path,
-                    RULE_TEMPLATE_SCHEMA_MISSING_KEY,
-                    format!("JSON template is missing required key '{field_name}'."),
-                    None,
-                    None,
-                    Some(json_pointer_child(&outline.pointer, field_name)),
-                    source_of_truth,
-                    outline_location(outline),
+                    context.path,
+                    format!("{label} template is missing required key '{field_name}'."),
+                    SchemaFinding {
+                        rule: RULE_TEMPLATE_SCHEMA_MISSING_KEY,
+                        language: context.language,
+                        source_of_truth,
+                        location: outline_location(outline),
+                        expected_type: None,
+                        found_type: None,
+                        schema_pointer: Some(json_pointer_child(pointer, field_name)),
+                    },
                     Vec::new(),
                 ));
             }
@@ -1780,10 +4644,17 @@ fn lint_json_model_against_outline(
     }
 
     for (key, key_outline) in &outline.static_keys {
-        let Some(field) = model.fields.get(key) else {
+        let key_pointer = json_pointer_child(pointer, key);
+        let Some(field) = model.field(key) else {
+            if model.allows_extra {
+                continue;
+            }
             let suggestion = closest_key(key, model.fields.keys().map(String::as_str));
             let edits = suggestion
-                .and_then(|suggestion| serde_json::to_string(suggestion).ok())
+                .and_then(|suggestion| match key_outline.quoted_key {
+                    true => serde_json::to_string(suggestion).ok(),
+                    false => Some(suggestion.to_string()),
+                })
                 .map(|new_text| DiagnosticEdit {
                     range: DiagnosticEditRange::from_location(&key_outline.key_location),
                     new_text,
@@ -1791,37 +4662,248 @@ fn lint_json_model_against_outline(
                 .into_iter()
                 .collect();
             diagnostics.push(schema_diagnostic(
-                path,
-                RULE_TEMPLATE_SCHEMA_UNKNOWN_KEY,
-                format!("JSON template key '{key}' is not present in schema '{source_of_truth}'."),
-                None,
-                None,
-                Some(json_pointer_child(&outline.pointer, key)),
-                source_of_truth,
-                key_outline.key_location.clone(),
+                context.path,
+                format!(
+                    "{label} template key '{key}' is not present in schema '{source_of_truth}'."
+                ),
+                SchemaFinding {
+                    rule: RULE_TEMPLATE_SCHEMA_UNKNOWN_KEY,
+                    language: context.language,
+                    source_of_truth,
+                    location: key_outline.key_location.clone(),
+                    expected_type: None,
+                    found_type: None,
+                    schema_pointer: Some(key_pointer),
+                },
                 edits,
             ));
             continue;
         };
-        if let (Some(expected), Some(found)) = (field.scalar, key_outline.value_kind)
-            && json_type_shape_mismatch(expected, found)
-        {
-            diagnostics.push(schema_diagnostic(
-                path,
-                RULE_TEMPLATE_SCHEMA_TYPE_SHAPE,
-                format!(
-                    "JSON template key '{key}' has static {found} value but schema '{source_of_truth}' expects {expected}."
-                ),
-                Some(expected.to_string()),
-                Some(found.to_string()),
-                Some(json_pointer_child(&outline.pointer, key)),
-                source_of_truth,
-                key_outline.value_location.clone(),
-                Vec::new(),
-            ));
+        lint_schema_value(
+            &context.with_origin(field.origin.as_deref()),
+            &field.schema,
+            &key_outline.value,
+            &key_pointer,
+            &format!("key '{key}'"),
+            diagnostics,
+        );
+    }
+}
+
+fn lint_schema_value(
+    context: &SchemaLintContext<'_>,
+    schema: &SchemaType,
+    value: &JsonValueOutline,
+    pointer: &str,
+    subject: &str,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let Some(found) = value.kind else {
+        return;
+    };
+    let registry = context.registry;
+    let Some(expected) = registry.shape_kinds(schema) else {
+        return;
+    };
+    if expected
+        .iter()
+        .all(|expected| json_type_shape_mismatch(*expected, found))
+    {
+        let label = schema_language_label(context.language);
+        let expected = describe_shape_kinds(&expected);
+        let message = format!(
+            "{label} template {subject} has static {found} value but schema '{}' expects {expected}.",
+            context.source_of_truth
+        );
+        diagnostics.push(schema_diagnostic(
+            context.path,
+            message,
+            SchemaFinding {
+                rule: RULE_TEMPLATE_SCHEMA_TYPE_SHAPE,
+                language: context.language,
+                source_of_truth: context.source_of_truth,
+                location: value.location.clone(),
+                expected_type: Some(expected),
+                found_type: Some(found.to_string()),
+                schema_pointer: Some(pointer.to_string()),
+            },
+            Vec::new(),
+        ));
+        return;
+    }
+
+    match schema {
+        SchemaType::Any | SchemaType::Scalar(_) => {}
+        SchemaType::Named(name) => {
+            if let Some(model) = registry.model(name) {
+                if let Some(object) = &value.object {
+                    lint_json_model_against_outline(context, model, object, pointer, diagnostics);
+                }
+            } else if let Some(choices) = registry.choices(schema) {
+                lint_schema_choice(context, choices, value, pointer, subject, diagnostics);
+            }
+        }
+        SchemaType::Literal(choices) => {
+            lint_schema_choice(context, choices, value, pointer, subject, diagnostics);
+        }
+        SchemaType::Array(item_schema) => {
+            for (index, item) in value.items.iter().flatten().enumerate() {
+                let item_pointer = json_pointer_child(pointer, &index.to_string());
+                let item_subject = format!("item '{item_pointer}'");
+                lint_schema_value(
+                    context,
+                    item_schema,
+                    item,
+                    &item_pointer,
+                    &item_subject,
+                    diagnostics,
+                );
+            }
+        }
+        SchemaType::Map(value_schema) => {
+            for (key, entry) in value.object.iter().flat_map(|object| &object.static_keys) {
+                lint_schema_value(
+                    context,
+                    value_schema,
+                    &entry.value,
+                    &json_pointer_child(pointer, key),
+                    &format!("key '{key}'"),
+                    diagnostics,
+                );
+            }
+        }
+        SchemaType::Union(alternatives) => {
+            let matching = alternatives
+                .iter()
+                .filter(|alternative| {
+                    registry.shape_kinds(alternative).is_none_or(|kinds| {
+                        kinds
+                            .iter()
+                            .any(|kind| !json_type_shape_mismatch(*kind, found))
+                    })
+                })
+                .collect::<Vec<_>>();
+            if let [alternative] = matching.as_slice() {
+                lint_schema_value(context, alternative, value, pointer, subject, diagnostics);
+            } else if let Some(choices) = matching
+                .iter()
+                .map(|alternative| registry.choices(alternative))
+                .collect::<Option<Vec<_>>>()
+            {
+                let choices = choices.concat();
+                lint_schema_choice(context, &choices, value, pointer, subject, diagnostics);
+            }
+        }
+        SchemaType::Constrained(inner, constraints) => {
+            lint_schema_constraints(context, constraints, value, pointer, subject, diagnostics);
+            lint_schema_value(context, inner, value, pointer, subject, diagnostics);
         }
     }
-    diagnostics
+}
+
+fn lint_schema_choice(
+    context: &SchemaLintContext<'_>,
+    choices: &[SchemaLiteral],
+    value: &JsonValueOutline,
+    pointer: &str,
+    subject: &str,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let Some(literal) = &value.literal else {
+        return;
+    };
+    if choices.iter().any(|choice| choice.matches(literal)) {
+        return;
+    }
+    let label = schema_language_label(context.language);
+    let allowed = choices
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let edits = match literal {
+        SchemaLiteral::String(found) => closest_key(
+            found,
+            choices.iter().filter_map(|choice| match choice {
+                SchemaLiteral::String(choice) => Some(choice.as_str()),
+                _ => None,
+            }),
+        ),
+        _ => None,
+    }
+    .and_then(|suggestion| match value.quoted {
+        true => serde_json::to_string(suggestion).ok(),
+        false => Some(suggestion.to_string()),
+    })
+    .map(|new_text| DiagnosticEdit {
+        range: DiagnosticEditRange::from_location(&value.location),
+        new_text,
+    })
+    .into_iter()
+    .collect();
+    diagnostics.push(schema_diagnostic(
+        context.path,
+        format!(
+            "{label} template {subject} has value {literal} but schema '{}' allows {allowed}.",
+            context.source_of_truth
+        ),
+        SchemaFinding {
+            rule: RULE_TEMPLATE_SCHEMA_INVALID_VALUE,
+            language: context.language,
+            source_of_truth: context.source_of_truth,
+            location: value.location.clone(),
+            expected_type: Some(allowed.clone()),
+            found_type: Some(literal.to_string()),
+            schema_pointer: Some(pointer.to_string()),
+        },
+        edits,
+    ));
+}
+
+fn lint_schema_constraints(
+    context: &SchemaLintContext<'_>,
+    constraints: &SchemaConstraints,
+    value: &JsonValueOutline,
+    pointer: &str,
+    subject: &str,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let found = match (&value.literal, &value.items) {
+        (Some(SchemaLiteral::String(text)), _) => constraints
+            .length_violation(text.chars().count())
+            .map(|requirement| (requirement, value.literal.as_ref().map(ToString::to_string))),
+        (Some(literal), _) => literal
+            .as_number()
+            .and_then(|number| constraints.numeric_violation(number))
+            .map(|requirement| (requirement, Some(literal.to_string()))),
+        (None, Some(items)) => constraints
+            .length_violation(items.len())
+            .map(|requirement| (requirement, Some(format!("{} items", items.len())))),
+        (None, None) => None,
+    };
+    let Some((requirement, found)) = found else {
+        return;
+    };
+    let label = schema_language_label(context.language);
+    let found_text = found.clone().unwrap_or_default();
+    let message = format!(
+        "{label} template {subject} has value {found_text} but schema '{}' requires {requirement}.",
+        context.source_of_truth
+    );
+    diagnostics.push(schema_diagnostic(
+        context.path,
+        message,
+        SchemaFinding {
+            rule: RULE_TEMPLATE_SCHEMA_INVALID_VALUE,
+            language: context.language,
+            source_of_truth: context.source_of_truth,
+            location: value.location.clone(),
+            expected_type: Some(requirement),
+            found_type: found,
+            schema_pointer: Some(pointer.to_string()),
+        },
+        Vec::new(),
+    ));
 }
 
 fn outline_location(outline: &JsonObjectOutline) -> crate::Location {
@@ -1830,6 +4912,7 @@ fn outline_location(outline: &JsonObjectOutline) -> crate::Location {
         .values()
         .next()
         .map(|key| key.key_location.clone())
+        .or_else(|| outline.location.clone())
         .unwrap_or(crate::Location {
             start_line: 1,
             start_column: 1,
@@ -1838,48 +4921,73 @@ fn outline_location(outline: &JsonObjectOutline) -> crate::Location {
         })
 }
 
-fn binding_unresolved_diagnostic(path: &Path, binding: &JsonSchemaBinding) -> LintDiagnostic {
+fn binding_unresolved_diagnostic(
+    path: &Path,
+    binding: &SchemaBinding,
+    detail: Option<String>,
+) -> LintDiagnostic {
+    let message = detail.unwrap_or_else(|| {
+        format!(
+            "Could not resolve {} schema binding '{}'.",
+            schema_language_label(&binding.language),
+            binding.reference.name()
+        )
+    });
     schema_diagnostic(
         path,
-        RULE_BINDING_UNRESOLVED,
-        format!(
-            "Could not resolve JSON schema binding '{}'.",
-            binding.model_name
-        ),
-        None,
-        None,
-        None,
-        &binding.model_name,
-        binding.location.clone(),
+        message,
+        SchemaFinding {
+            rule: RULE_BINDING_UNRESOLVED,
+            language: &binding.language,
+            source_of_truth: binding.reference.name(),
+            location: binding.location.clone(),
+            expected_type: None,
+            found_type: None,
+            schema_pointer: None,
+        },
         Vec::new(),
     )
 }
 
-fn schema_diagnostic(
-    path: &Path,
-    rule: &str,
-    message: String,
+fn schema_language_label(language: &str) -> &'static str {
+    match language {
+        "yaml" => "YAML",
+        "toml" => "TOML",
+        _ => "JSON",
+    }
+}
+
+/// Where a schema mismatch was found and what the schema expected there.
+struct SchemaFinding<'a> {
+    rule: &'a str,
+    language: &'a str,
+    source_of_truth: &'a str,
+    location: crate::Location,
     expected_type: Option<String>,
     found_type: Option<String>,
     schema_pointer: Option<String>,
-    source_of_truth: &str,
-    location: crate::Location,
+}
+
+fn schema_diagnostic(
+    path: &Path,
+    message: String,
+    finding: SchemaFinding<'_>,
     suggested_edits: Vec<DiagnosticEdit>,
 ) -> LintDiagnostic {
     LintDiagnostic {
-        rule: rule.to_string(),
+        rule: finding.rule.to_string(),
         severity: LintSeverity::Error,
-        language: Some("json".to_string()),
+        language: Some(finding.language.to_string()),
         message,
         file: path.to_path_buf(),
-        start_line: location.start_line,
-        start_column: location.start_column,
-        end_line: location.end_line,
-        end_column: location.end_column,
-        expected_type,
-        found_type,
-        schema_pointer,
-        source_of_truth: Some(source_of_truth.to_string()),
+        start_line: finding.location.start_line,
+        start_column: finding.location.start_column,
+        end_line: finding.location.end_line,
+        end_column: finding.location.end_column,
+        expected_type: finding.expected_type,
+        found_type: finding.found_type,
+        schema_pointer: finding.schema_pointer,
+        source_of_truth: Some(finding.source_of_truth.to_string()),
         suggested_edits,
     }
 }
@@ -1907,7 +5015,10 @@ fn json_pointer_child(parent: &str, key: &str) -> String {
     }
 }
 
-fn closest_key<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+pub(crate) fn closest_key<'a>(
+    key: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
     candidates
         .map(|candidate| (levenshtein(key, candidate), candidate))
         .min_by_key(|(distance, _)| *distance)
@@ -1967,6 +5078,7 @@ fn parse_embedded(language: &str, source: &str) -> Result<Tree> {
         "json" => parser.set_language(&tree_sitter_json::LANGUAGE.into())?,
         "yaml" => parser.set_language(&tree_sitter_yaml::LANGUAGE.into())?,
         "toml" => parser.set_language(&tree_sitter_toml_ng::LANGUAGE.into())?,
+        "shell" => parser.set_language(&tree_sitter_bash::LANGUAGE.into())?,
         #[cfg(feature = "sql")]
         "sql" => parser.set_language(&tree_sitter_sequel::LANGUAGE.into())?,
         #[cfg(not(feature = "sql"))]
@@ -2000,6 +5112,13 @@ fn collect_error_nodes_inner<'tree>(node: Node<'tree>, nodes: &mut Vec<Node<'tre
 
 fn prepare_template_for_lint(template: &TemplateStringInfo, language: &str) -> ProcessedTemplate {
     let placeholder = placeholder_for_language(language);
+    prepare_template_with_placeholders(template, |_| placeholder)
+}
+
+fn prepare_template_with_placeholders<'a>(
+    template: &TemplateStringInfo,
+    placeholder: impl Fn(&crate::parser::InterpolationInfo) -> &'a str,
+) -> ProcessedTemplate {
     let mut content = String::new();
     let mut processed_to_original = vec![0];
     let mut original_offset = 0;
@@ -2028,7 +5147,7 @@ fn prepare_template_for_lint(template: &TemplateStringInfo, language: &str) -> P
                 append_placeholder_segment(
                     &mut content,
                     &mut processed_to_original,
-                    placeholder,
+                    placeholder(part),
                     original_offset,
                     original_offset + 2,
                 );
@@ -2043,6 +5162,27 @@ fn prepare_template_for_lint(template: &TemplateStringInfo, language: &str) -> P
     }
 }
 
+fn interpolation_original_ranges(
+    template: &TemplateStringInfo,
+) -> Vec<(usize, std::ops::Range<usize>)> {
+    let mut ranges = Vec::new();
+    let mut original_offset = 0;
+    for part in &template.parts {
+        match part {
+            TemplatePart::Static(part) => original_offset += part.text.len(),
+            TemplatePart::Interpolation(part) => {
+                original_offset += part.debug_prefix.as_ref().map_or(0, String::len);
+                ranges.push((
+                    part.interpolation_index,
+                    original_offset..original_offset + 2,
+                ));
+                original_offset += 2;
+            }
+        }
+    }
+    ranges
+}
+
 fn append_original_segment(
     processed: &mut String,
     processed_to_original: &mut Vec<usize>,
@@ -2107,6 +5247,15 @@ fn placeholder_for_language(language: &str) -> &'static str {
     }
 }
 
+/// Markup escapes interpolated values, SQL binds them as parameters, and
+/// shell quotes them, so a constant there is not the same as static text.
+fn renders_interpolations_verbatim(language: Option<&str>) -> bool {
+    !matches!(
+        language,
+        Some("html" | "thtml" | "tdom" | "xml" | "svg" | "sql" | "shell")
+    )
+}
+
 fn normalize_language(language: &str) -> Option<&str> {
     match language.to_ascii_lowercase().as_str() {
         "html" => Some("html"),
@@ -2118,6 +5267,12 @@ fn normalize_language(language: &str) -> Option<&str> {
         "yaml" | "yml" => Some("yaml"),
         "toml" => Some("toml"),
         "sql" => Some("sql"),
+        "xml" => Some("xml"),
+        "svg" => Some("svg"),
+        "graphql" | "gql" => Some("graphql"),
+        "markdown" | "md" => Some("markdown"),
+        "shell" | "sh" | "bash" => Some("shell"),
+        "regex" | "re" => Some("regex"),
         _ => None,
     }
 }
@@ -2344,6 +5499,25 @@ fn lint_component_signature(
         return;
     };
 
+    let has_children = component.children.iter().any(|child| match child {
+        HtmlNode::Text(text) => !text.value.trim().is_empty(),
+        HtmlNode::Comment(_) => false,
+        _ => true,
+    });
+    lint_component_usage(
+        path,
+        template,
+        "thtml",
+        signature,
+        ComponentUsage {
+            name: &component.name,
+            has_children,
+            self_closing: component.self_closing,
+            span: component.span.as_ref(),
+        },
+        diagnostics,
+    );
+
     let mut provided_names = BTreeSet::new();
     let mut resolved_props = std::collections::BTreeMap::<String, ResolvedPropValue>::new();
     let mut has_unknown_spread = false;
@@ -2451,6 +5625,127 @@ fn lint_component_signature(
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct ComponentUsage<'a> {
+    name: &'a str,
+    has_children: bool,
+    self_closing: bool,
+    span: Option<&'a tstring_syntax::SourceSpan>,
+}
+
+/// Checks how a component tag is used against its callable: child content
+/// needs a `children` parameter (or `**kwargs`), a required `children`
+/// parameter needs child content, and the callable should return something
+/// the renderer can insert.
+fn lint_component_usage(
+    path: &Path,
+    template: &TemplateStringInfo,
+    language: &str,
+    signature: &crate::parser::CallableSignature,
+    usage: ComponentUsage<'_>,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let (name, span) = (usage.name, usage.span);
+    let children = signature
+        .parameters
+        .iter()
+        .find(|parameter| parameter.name == "children" && parameter.allows_keyword);
+    let accepts_children = children.is_some() || signature.accepts_kwargs;
+
+    if usage.has_children && !accepts_children {
+        diagnostics.push(make_component_diagnostic(
+            path,
+            template,
+            language,
+            RULE_COMPONENT_UNEXPECTED_CHILDREN,
+            format!(
+                "Component '{name}' does not accept children; add a 'children' parameter or remove the child content."
+            ),
+            span,
+        ));
+    } else if !usage.has_children && children.is_some_and(|parameter| parameter.required) {
+        let usage_text = if usage.self_closing {
+            "is used as a self-closing tag"
+        } else {
+            "has no child content"
+        };
+        diagnostics.push(make_component_diagnostic(
+            path,
+            template,
+            language,
+            RULE_COMPONENT_MISSING_CHILDREN,
+            format!("Component '{name}' requires children but {usage_text}."),
+            span,
+        ));
+    } else if !usage.has_children && !usage.self_closing && !accepts_children {
+        let mut diagnostic = make_component_diagnostic(
+            path,
+            template,
+            language,
+            RULE_COMPONENT_SELF_CLOSING,
+            format!("Component '{name}' does not accept children; write it as a self-closing tag."),
+            span,
+        );
+        diagnostic.severity = LintSeverity::Warning;
+        diagnostics.push(diagnostic);
+    }
+
+    if let Some(annotation) = signature.return_annotation.as_deref()
+        && !is_component_return_annotation(annotation)
+    {
+        let mut diagnostic = make_component_diagnostic(
+            path,
+            template,
+            language,
+            RULE_COMPONENT_RETURN_TYPE,
+            format!(
+                "Component '{name}' returns '{annotation}', which is not a Template or Node the renderer can insert."
+            ),
+            span,
+        );
+        diagnostic.severity = LintSeverity::Warning;
+        diagnostic.expected_type = Some("Template | Node".to_string());
+        diagnostic.found_type = Some(annotation.to_string());
+        diagnostics.push(diagnostic);
+    }
+}
+
+/// Accepts annotations with at least one renderable union member: templates,
+/// nodes and markup types, `str`, `object`/`Any`, or iterables of those.
+fn is_component_return_annotation(annotation: &str) -> bool {
+    let annotation = annotation.trim().trim_matches(['"', '\'']).trim();
+    split_top_level_type_tokens(annotation, '|')
+        .into_iter()
+        .any(|member| {
+            let member = member.trim();
+            let (head, arguments) = match member.split_once('[') {
+                Some((head, rest)) => (head.trim(), rest.strip_suffix(']')),
+                None => (member, None),
+            };
+            let head = head.rsplit('.').next().unwrap_or(head);
+            match head {
+                "Optional" | "Union" | "Annotated" | "Iterable" | "Iterator" | "Generator"
+                | "Sequence" | "list" | "List" | "tuple" | "Tuple" => {
+                    arguments.is_some_and(|arguments| {
+                        let arguments = split_top_level_type_tokens(arguments, ',');
+                        let candidates = if head == "Annotated" {
+                            &arguments[..arguments.len().min(1)]
+                        } else {
+                            &arguments[..]
+                        };
+                        candidates
+                            .iter()
+                            .any(|argument| is_component_return_annotation(argument))
+                    })
+                }
+                "str" | "object" | "Any" | "Template" | "Markup" => true,
+                _ => ["Node", "Element", "Fragment", "Template", "Markup"]
+                    .iter()
+                    .any(|suffix| head.ends_with(suffix)),
+            }
+        })
+}
+
 fn lint_component_attribute_type(
     path: &Path,
     template: &TemplateStringInfo,
@@ -2489,7 +5784,23 @@ fn lint_component_attribute_type(
 
     match value_kind {
         ComponentAttributeValueKind::BareBoolean if accepts_bool => None,
-        ComponentAttributeValueKind::StringLike if accepts_string => None,
+        ComponentAttributeValueKind::StringLike if accepts_string => {
+            let ResolvedPropValue::Explicit(attribute) = resolved_prop else {
+                return None;
+            };
+            let mismatch = string_choice_mismatch(&static_attribute_text(attribute)?, parameter)?;
+            Some(make_component_diagnostic(
+                path,
+                template,
+                "thtml",
+                RULE_COMPONENT_PROP_TYPE_ERROR,
+                format!(
+                    "Component '{}' prop '{}' {mismatch}",
+                    component.name, prop_name
+                ),
+                span,
+            ))
+        }
         ComponentAttributeValueKind::BareBoolean => Some(make_component_diagnostic(
             path,
             template,
@@ -2534,6 +5845,7 @@ enum TdomComponentValueKind {
 struct TdomResolvedPropValue {
     name: String,
     value_kind: TdomComponentValueKind,
+    static_value: Option<String>,
     span: Option<tstring_syntax::SourceSpan>,
 }
 
@@ -2573,6 +5885,29 @@ fn lint_tdom_component_signature(
         return;
     }
 
+    let has_children = component.children.iter().any(|child| match child {
+        backend_tdom::Node::Text(text) => !text.value.trim().is_empty(),
+        backend_tdom::Node::Comment(_) => false,
+        _ => true,
+    });
+    lint_component_usage(
+        path,
+        template,
+        "tdom",
+        signature,
+        ComponentUsage {
+            name: &component.start_tag.expression,
+            has_children,
+            self_closing: component.self_closing,
+            span: component
+                .start_tag
+                .span
+                .as_ref()
+                .or(component.span.as_ref()),
+        },
+        diagnostics,
+    );
+
     let mut resolved_props = std::collections::BTreeMap::<String, TdomResolvedPropValue>::new();
     let mut has_unknown_spread = false;
 
@@ -2589,6 +5924,7 @@ fn lint_tdom_component_signature(
                         } else {
                             TdomComponentValueKind::BareBoolean
                         },
+                        static_value: attribute.value.clone(),
                         span: attribute.span.clone(),
                     },
                 );
@@ -2610,6 +5946,7 @@ fn lint_tdom_component_signature(
                     TdomResolvedPropValue {
                         name: normalized_name,
                         value_kind: TdomComponentValueKind::StringLike,
+                        static_value: None,
                         span: attribute.span.clone(),
                     },
                 );
@@ -2633,6 +5970,7 @@ fn lint_tdom_component_signature(
                                     accepts_none: entry.accepts_none,
                                     known: entry.value_type.is_some() || entry.accepts_none,
                                 },
+                                static_value: None,
                                 span: spread
                                     .interpolation
                                     .span
@@ -2779,6 +6117,7 @@ fn extend_tdom_interpolated_attr_values(
                     TdomResolvedPropValue {
                         name: normalized_name,
                         value_kind,
+                        static_value: None,
                         span: attribute
                             .interpolation
                             .span
@@ -2795,6 +6134,12 @@ fn extend_tdom_interpolated_attr_values(
     }
 
     let normalized_name = normalize_tdom_prop_name(&attribute.name);
+    let static_value = match parse_static_value_expression(&attribute.interpolation.expression) {
+        Some((Some(CallableValueType::String), false)) => {
+            unquote_python_string(&attribute.interpolation.expression)
+        }
+        _ => None,
+    };
     let value_kind = if matches!(attribute.name.as_str(), "class" | "style") {
         TdomComponentValueKind::StringLike
     } else if let Some((value_type, accepts_none)) =
@@ -2817,6 +6162,7 @@ fn extend_tdom_interpolated_attr_values(
         TdomResolvedPropValue {
             name: normalized_name,
             value_kind,
+            static_value,
             span: attribute
                 .interpolation
                 .span
@@ -2860,7 +6206,25 @@ fn lint_tdom_component_attribute_type(
             ),
             span,
         )),
-        TdomComponentValueKind::StringLike if accepts_string => None,
+        TdomComponentValueKind::StringLike
+        | TdomComponentValueKind::Typed {
+            value_type: Some(CallableValueType::String),
+            ..
+        } if accepts_string => {
+            let mismatch =
+                string_choice_mismatch(resolved_prop.static_value.as_deref()?, parameter)?;
+            Some(make_component_diagnostic(
+                path,
+                template,
+                "tdom",
+                RULE_COMPONENT_PROP_TYPE_ERROR,
+                format!(
+                    "Component '{}' prop '{}' {mismatch}",
+                    component.start_tag.expression, resolved_prop.name
+                ),
+                span,
+            ))
+        }
         TdomComponentValueKind::StringLike => Some(make_component_diagnostic(
             path,
             template,
@@ -2903,6 +6267,38 @@ fn lint_tdom_component_attribute_type(
     }
 }
 
+fn static_attribute_text(attribute: &Attribute) -> Option<String> {
+    attribute
+        .value
+        .as_ref()?
+        .parts
+        .iter()
+        .map(|part| match part {
+            tstring_html::ValuePart::Text(text) => Some(text.as_str()),
+            tstring_html::ValuePart::Interpolation(_) => None,
+        })
+        .collect()
+}
+
+/// Describes a static string prop value that is not one of the parameter's
+/// `Literal` or `Enum` choices, suggesting the closest choice.
+fn string_choice_mismatch(value: &str, parameter: &CallableParameter) -> Option<String> {
+    let choices = parameter.string_choices.as_ref()?;
+    if choices.iter().any(|choice| choice == value) {
+        return None;
+    }
+    let allowed = choices
+        .iter()
+        .map(|choice| format!("'{choice}'"))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let mut message = format!("expects {allowed}, but got '{value}'.");
+    if let Some(suggestion) = closest_key(value, choices.iter().map(String::as_str)) {
+        message.push_str(&format!(" Did you mean '{suggestion}'?"));
+    }
+    Some(message)
+}
+
 fn normalize_tdom_prop_name(name: &str) -> String {
     backend_tdom::normalize_component_prop_name(name).into_owned()
 }
@@ -3476,4 +6872,52 @@ value: Annotated[Template, "json"] = t'{{"literal": "{{}}", "value": {value}}}'
             ]
         );
     }
+
+    #[test]
+    fn standard_format_specs_follow_python_mini_language() {
+        for spec in [
+            ">10", "*^20", "+.2f", "08.3e", ",d", "_x", "#o", "z.1%", ".2_f", "s",
+        ] {
+            assert!(is_standard_format_spec(spec), "expected {spec} to be valid");
+        }
+        for spec in ["upper", "10q", ".f", "safe", "10.2.3"] {
+            assert!(
+                !is_standard_format_spec(spec),
+                "expected {spec} to be invalid"
+            );
+        }
+        assert!(is_strftime_format_spec("%Y-%m-%d"));
+        assert!(!is_strftime_format_spec("100%"));
+    }
+
+    #[test]
+    fn component_return_annotations_accept_templates_and_nodes() {
+        for annotation in [
+            "Template",
+            "object",
+            "tdom.Node",
+            "Element | None",
+            "Optional[Template]",
+            "Iterable[Node]",
+            "\"Markup\"",
+            "Annotated[Template, 'html']",
+        ] {
+            assert!(
+                is_component_return_annotation(annotation),
+                "expected {annotation} to be accepted"
+            );
+        }
+        for annotation in [
+            "None",
+            "int",
+            "dict[str, str]",
+            "Optional[int]",
+            "list[int]",
+        ] {
+            assert!(
+                !is_component_return_annotation(annotation),
+                "expected {annotation} to be rejected"
+            );
+        }
+    }
 }
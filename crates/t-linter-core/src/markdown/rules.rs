@@ -0,0 +1,193 @@
+use std::ops::Range;
+use std::path::Path;
+
+use crate::lint::{LintDiagnostic, LintSeverity};
+use crate::parser::{InterpolationInfo, Location, TemplatePart, TemplateStringInfo};
+
+use super::syntax::{self, MarkdownSource, ProblemKind};
+
+const RULE_BROKEN_LINK: &str = "markdown-broken-link";
+const RULE_UNBALANCED_EMPHASIS: &str = "markdown-unbalanced-emphasis";
+const RULE_TABLE_ROW: &str = "markdown-table-row";
+const RULE_INTERPOLATED_URL: &str = "markdown-interpolated-url";
+
+/// Reports link syntax that will not render as a link, emphasis delimiters
+/// without a partner, table rows that do not match their header, and
+/// interpolations spliced into link URLs without URL escaping.
+pub fn lint_rules(path: &Path, template: &TemplateStringInfo) -> Vec<LintDiagnostic> {
+    let source = MarkdownSource::from_template(template);
+    let document = syntax::parse(&source);
+    let mut diagnostics = Vec::new();
+
+    for problem in &document.problems {
+        let rule = match problem.kind {
+            ProblemKind::BrokenLink => RULE_BROKEN_LINK,
+            ProblemKind::UnbalancedEmphasis => RULE_UNBALANCED_EMPHASIS,
+            ProblemKind::TableRow => RULE_TABLE_ROW,
+        };
+        diagnostics.push(diagnostic(
+            path,
+            rule,
+            LintSeverity::Warning,
+            problem.message.clone(),
+            content_location(template, &problem.range),
+        ));
+    }
+
+    let interpolations = template
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            TemplatePart::Interpolation(interpolation) => Some(interpolation),
+            TemplatePart::Static(_) => None,
+        })
+        .collect::<Vec<_>>();
+    for destination in &document.destinations {
+        for (&start, _) in source.atoms.range(destination.clone()) {
+            let index = source.atoms.range(..start).count();
+            let Some(interpolation) = interpolations.get(index) else {
+                continue;
+            };
+            if is_url_escaped(interpolation) {
+                continue;
+            }
+            diagnostics.push(diagnostic(
+                path,
+                RULE_INTERPOLATED_URL,
+                LintSeverity::Error,
+                format!(
+                    "Interpolation '{}' is inserted into a link URL without escaping; a space, `)`, or `javascript:` scheme in the value can break or hijack the link. Wrap it in `urllib.parse.quote(...)`",
+                    interpolation.expression
+                ),
+                interpolation.location.clone(),
+            ));
+        }
+    }
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.start_line, diagnostic.start_column));
+    diagnostics
+}
+
+/// Whether the interpolation calls a URL quoting or escaping helper, such as
+/// `quote(path)`, `urlencode(params)`, or `escape_url(link)`.
+fn is_url_escaped(interpolation: &InterpolationInfo) -> bool {
+    let expression = interpolation.expression.trim();
+    let Some((callee, _)) = expression.split_once('(') else {
+        return false;
+    };
+    if !expression.ends_with(')') {
+        return false;
+    }
+    let name = callee.trim().rsplit('.').next().unwrap_or_default();
+    matches!(name, "quote" | "quote_plus" | "urlencode") || name.contains("escape")
+}
+
+fn content_location(template: &TemplateStringInfo, range: &Range<usize>) -> Location {
+    let ((start_line, start_column), (end_line, end_column)) =
+        template.map_content_range_to_document(range.start, range.end);
+    Location {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    }
+}
+
+fn diagnostic(
+    path: &Path,
+    rule: &str,
+    severity: LintSeverity,
+    message: String,
+    location: Location,
+) -> LintDiagnostic {
+    LintDiagnostic {
+        rule: rule.to_string(),
+        severity,
+        language: Some("markdown".to_string()),
+        message,
+        file: path.to_path_buf(),
+        start_line: location.start_line,
+        start_column: location.start_column,
+        end_line: location.end_line,
+        end_column: location.end_column,
+        expected_type: None,
+        found_type: None,
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::lint::lint_source;
+
+    fn markdown_diagnostics(body: &str) -> Vec<(String, String)> {
+        let source = format!(
+            "from typing import Annotated\nfrom string.templatelib import Template\nfrom urllib.parse import quote\n\nname = \"x\"\nurl = \"https://example.com\"\nnote: Annotated[Template, \"markdown\"] = t\"\"\"{body}\"\"\"\n"
+        );
+        lint_source(Path::new("notes.py"), &source)
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn reports_broken_links_unbalanced_emphasis_and_table_rows() {
+        assert_eq!(
+            markdown_diagnostics(
+                "# Release **{name}**\n\nSee [the docs] (https://example.com) and [guide](https://example.com\n\nRead [the notes][notse] for **details.\n\n| a | b |\n|---|---|\n| 1 | 2 | 3 |\n\n[notes]: https://example.com/notes\n"
+            ),
+            vec![
+                (
+                    "markdown-broken-link".to_string(),
+                    "Remove the space between `]` and `(`; Markdown does not treat `[text] (url)` as a link".to_string()
+                ),
+                (
+                    "markdown-broken-link".to_string(),
+                    "Link is missing the `)` that closes its destination".to_string()
+                ),
+                (
+                    "markdown-broken-link".to_string(),
+                    "Link reference `[notse]` has no definition such as `[notse]: https://example.com`; did you mean `[notes]`?".to_string()
+                ),
+                (
+                    "markdown-unbalanced-emphasis".to_string(),
+                    "Emphasis `**` is opened here but never closed".to_string()
+                ),
+                (
+                    "markdown-table-row".to_string(),
+                    "Table row has 3 cells but the header row has 2".to_string()
+                ),
+            ]
+        );
+        assert!(
+            markdown_diagnostics(
+                "Use `a * b` and snake_case, 2 * 3, *{name}*, ~~old~~, <https://example.com>.\n\n- [ ] task [ref]\n\n```python\nx = a**b [y](\n```\n\n[ref]: <https://example.com/ref> \"Ref\"\n"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn reports_unescaped_interpolations_in_link_urls() {
+        assert_eq!(
+            markdown_diagnostics(
+                "[home]({url}) ![logo](https://cdn.example.com/{name}.png) <{url}>\n[ok](https://example.com/u/{quote(name)})\n\n[ref]: {url}\n[next]:\n  {url} \"Next\"\n"
+            )
+            .into_iter()
+            .map(|(rule, message)| (rule, message.split(';').next().unwrap().to_string()))
+            .collect::<Vec<_>>(),
+            ["url", "name", "url", "url", "url"]
+                .map(|name| (
+                    "markdown-interpolated-url".to_string(),
+                    format!("Interpolation '{name}' is inserted into a link URL without escaping")
+                ))
+                .to_vec()
+        );
+    }
+}
@@ -0,0 +1,1243 @@
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
+
+use crate::parser::{TemplatePart, TemplateStringInfo};
+
+/// The template content, where every interpolation takes the two bytes of
+/// its `{}` placeholder.
+pub(crate) struct MarkdownSource {
+    pub(crate) text: String,
+    /// The start of each interpolation mapped to its end.
+    pub(crate) atoms: BTreeMap<usize, usize>,
+}
+
+impl MarkdownSource {
+    pub(crate) fn from_template(template: &TemplateStringInfo) -> Self {
+        let mut atoms = BTreeMap::new();
+        let mut offset = 0;
+        for part in &template.parts {
+            match part {
+                TemplatePart::Static(part) => offset += part.text.len(),
+                TemplatePart::Interpolation(_) => {
+                    atoms.insert(offset, offset + 2);
+                    offset += 2;
+                }
+            }
+        }
+        Self {
+            text: template.content.clone(),
+            atoms,
+        }
+    }
+
+    pub(crate) fn text(&self, range: &Range<usize>) -> &str {
+        &self.text[range.clone()]
+    }
+
+    /// Whether an interpolation starts inside `range`.
+    pub(crate) fn has_atom(&self, range: &Range<usize>) -> bool {
+        self.atoms.range(range.clone()).next().is_some()
+    }
+
+    fn atom_ending_at(&self, offset: usize) -> bool {
+        self.atoms
+            .range(..offset)
+            .next_back()
+            .is_some_and(|(_, &end)| end == offset)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SpanKind {
+    /// Heading markers, setext underlines, thematic breaks, list and quote
+    /// markers, code fences, and table delimiter rows.
+    Marker,
+    EmphasisDelimiter,
+    CodeSpan,
+    /// An indented code block line.
+    Code,
+    FenceInfo,
+    LinkBracket,
+    Destination,
+    Title,
+    /// The label of a reference link or link reference definition.
+    Label,
+    Html,
+    Escape,
+    TablePipe,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProblemKind {
+    BrokenLink,
+    UnbalancedEmphasis,
+    TableRow,
+}
+
+#[derive(Debug)]
+pub(crate) struct Problem {
+    pub(crate) kind: ProblemKind,
+    pub(crate) message: String,
+    pub(crate) range: Range<usize>,
+}
+
+/// A fenced code block, with the language named by its info string.
+#[derive(Debug)]
+pub(crate) struct CodeBlock {
+    pub(crate) info: Option<Range<usize>>,
+    pub(crate) content: Range<usize>,
+    /// Whether the block sits outside block quotes and list items, so its
+    /// content is the code byte for byte.
+    pub(crate) top_level: bool,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Document {
+    pub(crate) spans: Vec<(Range<usize>, SpanKind)>,
+    pub(crate) code_blocks: Vec<CodeBlock>,
+    /// The URLs of links, images, autolinks, and link reference
+    /// definitions.
+    pub(crate) destinations: Vec<Range<usize>>,
+    pub(crate) problems: Vec<Problem>,
+}
+
+/// Scans the blocks of a CommonMark document with GitHub tables, then the
+/// inline content of its paragraphs, headings, and table cells once every
+/// link reference definition is known.
+pub(crate) fn parse(source: &MarkdownSource) -> Document {
+    let mut blocks = BlockScanner {
+        source,
+        bytes: source.text.as_bytes(),
+        document: Document::default(),
+        inlines: Vec::new(),
+        labels: HashSet::new(),
+        dynamic_labels: false,
+    };
+    blocks.run();
+    let BlockScanner {
+        mut document,
+        inlines,
+        labels,
+        dynamic_labels,
+        ..
+    } = blocks;
+
+    let mut inline = InlineScanner {
+        source,
+        bytes: source.text.as_bytes(),
+        document: &mut document,
+        labels: &labels,
+        dynamic_labels,
+    };
+    for range in inlines {
+        inline.scan(range);
+    }
+    document.spans.sort_by_key(|(range, _)| range.start);
+    document.problems.sort_by_key(|problem| problem.range.start);
+    document
+}
+
+struct Fence {
+    marker: u8,
+    marker_start: usize,
+    len: usize,
+    quoted: bool,
+    info: Option<Range<usize>>,
+    content_start: usize,
+    top_level: bool,
+}
+
+struct BlockScanner<'a> {
+    source: &'a MarkdownSource,
+    bytes: &'a [u8],
+    document: Document,
+    /// Ranges whose inline content is scanned after the blocks.
+    inlines: Vec<Range<usize>>,
+    labels: HashSet<String>,
+    /// Whether an interpolation supplies a definition label, so any
+    /// reference might be defined.
+    dynamic_labels: bool,
+}
+
+impl BlockScanner<'_> {
+    fn run(&mut self) {
+        let lines = self.lines();
+        let mut paragraph: Option<Range<usize>> = None;
+        let mut list_open = false;
+        let mut previous_blank = true;
+        let mut index = 0;
+        while index < lines.len() {
+            let line = lines[index].clone();
+            index += 1;
+            let (quote_start, quoted) = self.strip_quotes(line.start, line.end);
+            let mut start = quote_start;
+
+            if self.is_blank(start..line.end) {
+                self.close(&mut paragraph);
+                previous_blank = true;
+                continue;
+            }
+            let indent = self.indent(start, line.end);
+            if previous_blank && indent == 0 && !self.starts_list_item(start, line.end) {
+                list_open = false;
+            }
+            previous_blank = false;
+
+            if indent >= 4 && paragraph.is_none() && !list_open {
+                let code = start + self.whitespace_len(start, line.end);
+                self.document.spans.push((code..line.end, SpanKind::Code));
+                continue;
+            }
+
+            loop {
+                start += self.whitespace_len(start, line.end);
+                let rest = start..line.end;
+
+                if let Some(range) = paragraph.clone()
+                    && self.is_setext_underline(rest.clone())
+                {
+                    self.inlines.push(range);
+                    paragraph = None;
+                    self.document
+                        .spans
+                        .push((rest.start..self.trim_end(rest.clone()), SpanKind::Marker));
+                    break;
+                }
+                if self.is_thematic_break(rest.clone()) {
+                    self.close(&mut paragraph);
+                    self.document
+                        .spans
+                        .push((rest.start..self.trim_end(rest.clone()), SpanKind::Marker));
+                    break;
+                }
+                if let Some(fence) =
+                    self.fence_open(rest.clone(), quoted, start == line.start && !list_open)
+                {
+                    self.close(&mut paragraph);
+                    index = self.fence(fence, &lines, index);
+                    break;
+                }
+                if self.heading(rest.clone()) {
+                    self.close(&mut paragraph);
+                    break;
+                }
+                if self.is_html_block(rest.clone()) {
+                    self.close(&mut paragraph);
+                    self.document.spans.push((rest.clone(), SpanKind::Html));
+                    while index < lines.len() {
+                        let (next, _) = self.quote_prefix(lines[index].start, lines[index].end);
+                        if self.is_blank(next..lines[index].end) {
+                            break;
+                        }
+                        self.strip_quotes(lines[index].start, lines[index].end);
+                        self.document
+                            .spans
+                            .push((next..lines[index].end, SpanKind::Html));
+                        index += 1;
+                    }
+                    break;
+                }
+                if let Some(marker) = self.list_marker(rest.clone()) {
+                    self.close(&mut paragraph);
+                    list_open = true;
+                    self.document.spans.push((marker.clone(), SpanKind::Marker));
+                    start = marker.end;
+                    continue;
+                }
+                if let Some(next) = lines.get(index)
+                    && self.has_pipe(rest.clone())
+                {
+                    let (delimiter_start, _) = self.quote_prefix(next.start, next.end);
+                    if self.is_delimiter_row(delimiter_start..next.end) {
+                        self.close(&mut paragraph);
+                        index = self.table(rest, &lines, index);
+                        break;
+                    }
+                }
+                if paragraph.is_none() && self.definition(rest.clone()) {
+                    break;
+                }
+                // The destination of a definition may sit on the next line.
+                if paragraph.is_none()
+                    && !quoted
+                    && let Some(next) = lines.get(index)
+                    && self.source.text[rest.clone()].trim_end().ends_with("]:")
+                    && !self.is_blank(next.clone())
+                    && self.definition(rest.start..next.end)
+                {
+                    index += 1;
+                    break;
+                }
+                match &mut paragraph {
+                    Some(range) => range.end = line.end,
+                    None => paragraph = Some(rest),
+                }
+                break;
+            }
+        }
+        self.close(&mut paragraph);
+    }
+
+    fn lines(&self) -> Vec<Range<usize>> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (offset, byte) in self.bytes.iter().enumerate() {
+            if *byte == b'\n' {
+                let end = if offset > start && self.bytes[offset - 1] == b'\r' {
+                    offset - 1
+                } else {
+                    offset
+                };
+                lines.push(start..end);
+                start = offset + 1;
+            }
+        }
+        if start < self.bytes.len() {
+            lines.push(start..self.bytes.len());
+        }
+        lines
+    }
+
+    fn close(&mut self, paragraph: &mut Option<Range<usize>>) {
+        if let Some(range) = paragraph.take() {
+            self.inlines.push(range);
+        }
+    }
+
+    /// Skips block quote markers, returning where the line's content starts
+    /// and whether it was quoted.
+    fn strip_quotes(&mut self, start: usize, end: usize) -> (usize, bool) {
+        let (content, markers) = self.quote_prefix(start, end);
+        for marker in &markers {
+            self.document
+                .spans
+                .push((*marker..marker + 1, SpanKind::Marker));
+        }
+        (content, !markers.is_empty())
+    }
+
+    /// The content start of a line and its block quote markers, without
+    /// recording them.
+    fn quote_prefix(&self, mut start: usize, end: usize) -> (usize, Vec<usize>) {
+        let mut markers = Vec::new();
+        loop {
+            let marker = start + self.whitespace_len(start, end).min(3);
+            if marker < end && self.bytes[marker] == b'>' {
+                markers.push(marker);
+                start = marker + 1;
+                if start < end && self.bytes[start] == b' ' {
+                    start += 1;
+                }
+            } else {
+                return (start, markers);
+            }
+        }
+    }
+
+    fn whitespace_len(&self, start: usize, end: usize) -> usize {
+        self.bytes[start..end]
+            .iter()
+            .take_while(|byte| matches!(byte, b' ' | b'\t'))
+            .count()
+    }
+
+    fn indent(&self, start: usize, end: usize) -> usize {
+        self.bytes[start..end]
+            .iter()
+            .take_while(|byte| matches!(byte, b' ' | b'\t'))
+            .map(|byte| if *byte == b'\t' { 4 } else { 1 })
+            .sum()
+    }
+
+    fn is_blank(&self, range: Range<usize>) -> bool {
+        self.bytes[range]
+            .iter()
+            .all(|byte| matches!(byte, b' ' | b'\t'))
+    }
+
+    fn trim_end(&self, range: Range<usize>) -> usize {
+        range.start + self.source.text[range].trim_end().len()
+    }
+
+    fn is_setext_underline(&self, range: Range<usize>) -> bool {
+        let text = self.source.text[range].trim_end();
+        !text.is_empty()
+            && (text.bytes().all(|byte| byte == b'=') || text.bytes().all(|byte| byte == b'-'))
+    }
+
+    fn is_thematic_break(&self, range: Range<usize>) -> bool {
+        let text = &self.bytes[range];
+        let Some(&marker) = text.first() else {
+            return false;
+        };
+        matches!(marker, b'*' | b'-' | b'_')
+            && text.iter().filter(|&&byte| byte == marker).count() >= 3
+            && text
+                .iter()
+                .all(|&byte| byte == marker || matches!(byte, b' ' | b'\t'))
+    }
+
+    fn fence_open(&self, range: Range<usize>, quoted: bool, top_level: bool) -> Option<Fence> {
+        let text = &self.bytes[range.clone()];
+        let marker = *text.first()?;
+        if !matches!(marker, b'`' | b'~') {
+            return None;
+        }
+        let len = text.iter().take_while(|&&byte| byte == marker).count();
+        if len < 3 {
+            return None;
+        }
+        let info_start = range.start + len + self.whitespace_len(range.start + len, range.end);
+        let info_text = &self.source.text[info_start..range.end];
+        if marker == b'`' && info_text.contains('`') {
+            return None;
+        }
+        let info_len = info_text
+            .find(|character: char| character.is_whitespace() || character == '{')
+            .unwrap_or(info_text.len());
+        Some(Fence {
+            marker,
+            marker_start: range.start,
+            len,
+            quoted,
+            info: (info_len > 0).then(|| info_start..info_start + info_len),
+            content_start: 0,
+            top_level: top_level && !quoted,
+        })
+    }
+
+    /// Consumes the lines of a fenced code block, returning the index of the
+    /// line after its closing fence.
+    fn fence(&mut self, mut fence: Fence, lines: &[Range<usize>], mut index: usize) -> usize {
+        self.document.spans.push((
+            fence.marker_start..fence.marker_start + fence.len,
+            SpanKind::Marker,
+        ));
+        if let Some(info) = &fence.info {
+            self.document
+                .spans
+                .push((info.clone(), SpanKind::FenceInfo));
+        }
+        fence.content_start = lines.get(index).map_or(self.bytes.len(), |line| line.start);
+
+        let mut content_end = self.bytes.len();
+        while index < lines.len() {
+            let line = lines[index].clone();
+            let start = if fence.quoted {
+                self.strip_quotes(line.start, line.end).0
+            } else {
+                line.start
+            };
+            let marker = start + self.whitespace_len(start, line.end);
+            let run = self.bytes[marker..line.end]
+                .iter()
+                .take_while(|&&byte| byte == fence.marker)
+                .count();
+            index += 1;
+            if run >= fence.len && self.is_blank(marker + run..line.end) {
+                self.document
+                    .spans
+                    .push((marker..marker + run, SpanKind::Marker));
+                content_end = line.start;
+                break;
+            }
+        }
+        self.document.code_blocks.push(CodeBlock {
+            info: fence.info,
+            content: fence.content_start..content_end.max(fence.content_start),
+            top_level: fence.top_level,
+        });
+        index
+    }
+
+    /// Records an ATX heading's markers and queues its text.
+    fn heading(&mut self, range: Range<usize>) -> bool {
+        let level = self.bytes[range.clone()]
+            .iter()
+            .take_while(|&&byte| byte == b'#')
+            .count();
+        let after = range.start + level;
+        if level == 0
+            || level > 6
+            || (after < range.end && !matches!(self.bytes[after], b' ' | b'\t'))
+        {
+            return false;
+        }
+        self.document
+            .spans
+            .push((range.start..after, SpanKind::Marker));
+        let mut end = self.trim_end(after..range.end);
+        let closing = self.bytes[after..end]
+            .iter()
+            .rev()
+            .take_while(|&&byte| byte == b'#')
+            .count();
+        if closing > 0 && matches!(self.bytes[end - closing - 1], b' ' | b'\t') {
+            self.document
+                .spans
+                .push((end - closing..end, SpanKind::Marker));
+            end -= closing;
+        }
+        self.inlines.push(after..end);
+        true
+    }
+
+    fn is_html_block(&self, range: Range<usize>) -> bool {
+        let text = &self.source.text[range];
+        if text.starts_with("<!--") || text.starts_with("<?") || text.starts_with("<!") {
+            return true;
+        }
+        let name = text
+            .strip_prefix("</")
+            .or_else(|| text.strip_prefix('<'))
+            .unwrap_or("");
+        let len = name
+            .bytes()
+            .take_while(|byte| byte.is_ascii_alphanumeric() || *byte == b'-')
+            .count();
+        len > 0
+            && name.as_bytes()[0].is_ascii_alphabetic()
+            && matches!(
+                name.as_bytes().get(len),
+                None | Some(b' ' | b'\t' | b'>' | b'/')
+            )
+    }
+
+    fn starts_list_item(&self, start: usize, end: usize) -> bool {
+        let marker = start + self.whitespace_len(start, end);
+        !self.is_thematic_break(marker..end) && self.list_marker(marker..end).is_some()
+    }
+
+    /// The bullet or ordinal that starts a list item, with a task list
+    /// checkbox after it.
+    fn list_marker(&self, range: Range<usize>) -> Option<Range<usize>> {
+        let text = &self.bytes[range.clone()];
+        let len = match text.first()? {
+            b'-' | b'*' | b'+' => 1,
+            byte if byte.is_ascii_digit() => {
+                let digits = text.iter().take_while(|byte| byte.is_ascii_digit()).count();
+                if digits > 9 || !matches!(text.get(digits), Some(b'.' | b')')) {
+                    return None;
+                }
+                digits + 1
+            }
+            _ => return None,
+        };
+        if !matches!(text.get(len), None | Some(b' ' | b'\t')) {
+            return None;
+        }
+        let mut end = range.start + len;
+        let checkbox = end + self.whitespace_len(end, range.end);
+        if let Some(box_text) = self.bytes.get(checkbox..checkbox + 3)
+            && matches!(box_text, b"[ ]" | b"[x]" | b"[X]")
+            && matches!(self.bytes.get(checkbox + 3), None | Some(b' ' | b'\t'))
+            && checkbox + 3 <= range.end
+        {
+            end = checkbox + 3;
+        }
+        Some(range.start..end)
+    }
+
+    fn has_pipe(&self, range: Range<usize>) -> bool {
+        !self.cells(range).1.is_empty()
+    }
+
+    fn is_delimiter_row(&self, range: Range<usize>) -> bool {
+        let text = &self.bytes[range];
+        text.contains(&b'-')
+            && text.contains(&b'|')
+            && text
+                .iter()
+                .all(|byte| matches!(byte, b'|' | b':' | b'-' | b' ' | b'\t'))
+    }
+
+    /// Splits a table row at its unescaped pipes outside code spans,
+    /// returning the cells and the pipes.
+    fn cells(&self, range: Range<usize>) -> (Vec<Range<usize>>, Vec<usize>) {
+        let start = range.start + self.whitespace_len(range.start, range.end);
+        let end = self.trim_end(start..range.end);
+        let mut pipes = Vec::new();
+        let mut offset = start;
+        while offset < end {
+            match self.bytes[offset] {
+                b'\\' => offset += 1,
+                b'`' => {
+                    let run = self.bytes[offset..end]
+                        .iter()
+                        .take_while(|&&byte| byte == b'`')
+                        .count();
+                    let fence = &self.source.text[offset..offset + run];
+                    if let Some(close) = self.source.text[offset + run..end].find(fence) {
+                        offset += run + close + run;
+                        continue;
+                    }
+                    offset += run - 1;
+                }
+                b'|' => pipes.push(offset),
+                _ => {}
+            }
+            offset += 1;
+        }
+
+        let mut cells = Vec::new();
+        let mut cell_start = start;
+        for &pipe in &pipes {
+            cells.push(cell_start..pipe);
+            cell_start = pipe + 1;
+        }
+        cells.push(cell_start..end);
+        if pipes.first() == Some(&start) {
+            cells.remove(0);
+        }
+        if pipes.last().is_some_and(|&pipe| pipe + 1 == end) {
+            cells.pop();
+        }
+        (cells, pipes)
+    }
+
+    /// Checks a table's delimiter row and body rows against its header,
+    /// returning the index of the line after the table.
+    fn table(&mut self, header: Range<usize>, lines: &[Range<usize>], mut index: usize) -> usize {
+        let (header_cells, header_pipes) = self.cells(header);
+        let columns = header_cells.len();
+        for pipe in header_pipes {
+            self.document
+                .spans
+                .push((pipe..pipe + 1, SpanKind::TablePipe));
+        }
+        self.inlines.extend(header_cells);
+
+        let (delimiter_start, _) = self.strip_quotes(lines[index].start, lines[index].end);
+        let delimiter_start =
+            delimiter_start + self.whitespace_len(delimiter_start, lines[index].end);
+        let delimiter = delimiter_start..lines[index].end;
+        index += 1;
+        let delimiter_end = self.trim_end(delimiter.clone());
+        self.document
+            .spans
+            .push((delimiter.start..delimiter_end, SpanKind::Marker));
+        let (delimiter_cells, _) = self.cells(delimiter.clone());
+        for cell in &delimiter_cells {
+            let text = self.source.text[cell.clone()].trim();
+            let dashes = text.strip_prefix(':').unwrap_or(text);
+            let dashes = dashes.strip_suffix(':').unwrap_or(dashes);
+            if dashes.is_empty() || !dashes.bytes().all(|byte| byte == b'-') {
+                self.problem(
+                    ProblemKind::TableRow,
+                    format!(
+                        "Table delimiter cell `{text}` must be dashes with optional colons, such as `---` or `:---:`"
+                    ),
+                    delimiter.start..delimiter_end,
+                );
+                break;
+            }
+        }
+        if delimiter_cells.len() != columns {
+            self.problem(
+                ProblemKind::TableRow,
+                format!(
+                    "Table delimiter row has {} cells but the header row has {columns}; the table will not render",
+                    delimiter_cells.len()
+                ),
+                delimiter.start..delimiter_end,
+            );
+        }
+
+        while index < lines.len() {
+            let line = lines[index].clone();
+            let (start, _) = self.quote_prefix(line.start, line.end);
+            let start = start + self.whitespace_len(start, line.end);
+            let row = start..line.end;
+            if self.is_blank(row.clone()) || !self.has_pipe(row.clone()) {
+                break;
+            }
+            self.strip_quotes(line.start, line.end);
+            index += 1;
+            let (cells, pipes) = self.cells(row.clone());
+            for pipe in pipes {
+                self.document
+                    .spans
+                    .push((pipe..pipe + 1, SpanKind::TablePipe));
+            }
+            if cells.len() != columns && !cells.iter().any(|cell| self.source.has_atom(cell)) {
+                self.problem(
+                    ProblemKind::TableRow,
+                    format!(
+                        "Table row has {} cells but the header row has {columns}",
+                        cells.len()
+                    ),
+                    start..self.trim_end(row),
+                );
+            }
+            self.inlines.extend(cells);
+        }
+        index
+    }
+
+    /// Records a link reference definition such as
+    /// `[label]: https://example.com "Title"`, whose destination may follow
+    /// on the next line.
+    fn definition(&mut self, range: Range<usize>) -> bool {
+        let text = &self.source.text[range.clone()];
+        if !text.starts_with('[') {
+            return false;
+        }
+        let Some(close) = text.find("]:") else {
+            return false;
+        };
+        let label = range.start + 1..range.start + close;
+        if label.is_empty() || self.source.text[label.clone()].contains(['[', ']']) {
+            return false;
+        }
+        let mut offset = range.start + close + 2;
+        offset += self.whitespace_len(offset, range.end);
+        let rest = &self.source.text[offset..range.end];
+        if let Some(next_line) = rest
+            .strip_prefix("\r\n")
+            .or_else(|| rest.strip_prefix('\n'))
+        {
+            offset = range.end - next_line.len();
+        }
+        offset += self.whitespace_len(offset, range.end);
+        if offset == range.end {
+            return false;
+        }
+        let destination = if self.bytes[offset] == b'<' {
+            let Some(end) = self.source.text[offset..range.end].find('>') else {
+                return false;
+            };
+            let destination = offset + 1..offset + end;
+            offset += end + 1;
+            destination
+        } else {
+            let start = offset;
+            while offset < range.end && !matches!(self.bytes[offset], b' ' | b'\t') {
+                offset += 1;
+            }
+            start..offset
+        };
+        let title_start = offset + self.whitespace_len(offset, range.end);
+        let title_end = self.trim_end(title_start..range.end);
+        let title = title_start..title_end;
+        if !title.is_empty() {
+            let title_text = self.source.text[title.clone()].as_bytes();
+            let closes = match title_text[0] {
+                b'"' => b'"',
+                b'\'' => b'\'',
+                b'(' => b')',
+                _ => return false,
+            };
+            if title_text.len() < 2 || title_text[title_text.len() - 1] != closes {
+                return false;
+            }
+        }
+
+        self.document
+            .spans
+            .push((range.start..range.start + 1, SpanKind::LinkBracket));
+        self.document.spans.push((label.clone(), SpanKind::Label));
+        self.document
+            .spans
+            .push((label.end..label.end + 1, SpanKind::LinkBracket));
+        self.document
+            .spans
+            .push((destination.clone(), SpanKind::Destination));
+        if !title.is_empty() {
+            self.document.spans.push((title, SpanKind::Title));
+        }
+        self.document.destinations.push(destination);
+        if self.source.has_atom(&label) {
+            self.dynamic_labels = true;
+        } else {
+            self.labels
+                .insert(normalize_label(&self.source.text[label]));
+        }
+        true
+    }
+
+    fn problem(&mut self, kind: ProblemKind, message: String, range: Range<usize>) {
+        self.document.problems.push(Problem {
+            kind,
+            message,
+            range,
+        });
+    }
+}
+
+/// A run of emphasis delimiter characters.
+struct Delimiter {
+    marker: u8,
+    start: usize,
+    len: usize,
+    original_len: usize,
+    can_open: bool,
+    can_close: bool,
+    /// Set once a closer matched an opener before this one, which leaves this
+    /// run as literal text.
+    skipped: bool,
+}
+
+/// The parts of an inline link after its text.
+struct InlineLink {
+    destination: Range<usize>,
+    title: Option<Range<usize>>,
+    /// The offset of the closing `)`.
+    close: usize,
+}
+
+struct InlineScanner<'a> {
+    source: &'a MarkdownSource,
+    bytes: &'a [u8],
+    document: &'a mut Document,
+    labels: &'a HashSet<String>,
+    dynamic_labels: bool,
+}
+
+impl InlineScanner<'_> {
+    fn scan(&mut self, range: Range<usize>) {
+        let end = range.end;
+        let mut delimiters = Vec::new();
+        let mut brackets: Vec<(usize, bool)> = Vec::new();
+        let mut offset = range.start;
+        while offset < end {
+            if let Some(&atom_end) = self.source.atoms.get(&offset) {
+                offset = atom_end;
+                continue;
+            }
+            match self.bytes[offset] {
+                b'\\'
+                    if offset + 1 < end
+                        && self.bytes[offset + 1].is_ascii_punctuation()
+                        && !self.source.atoms.contains_key(&(offset + 1)) =>
+                {
+                    self.span(offset..offset + 2, SpanKind::Escape);
+                    offset += 2;
+                }
+                b'`' => {
+                    let run = self.run(offset, end, b'`');
+                    let fence = &self.source.text[offset..offset + run];
+                    let mut search = offset + run;
+                    let mut close = None;
+                    while let Some(found) = self.source.text[search..end].find(fence) {
+                        let at = search + found;
+                        let len = self.run(at, end, b'`');
+                        if len == run {
+                            close = Some(at + run);
+                            break;
+                        }
+                        search = at + len;
+                    }
+                    match close {
+                        Some(close) => {
+                            self.span(offset..close, SpanKind::CodeSpan);
+                            offset = close;
+                        }
+                        None => offset += run,
+                    }
+                }
+                b'<' => offset = self.angle(offset, end),
+                b'!' if offset + 1 < end && self.bytes[offset + 1] == b'[' => {
+                    brackets.push((offset, true));
+                    offset += 2;
+                }
+                b'[' => {
+                    brackets.push((offset, false));
+                    offset += 1;
+                }
+                b']' => match brackets.pop() {
+                    Some((open, image)) => {
+                        let (next, is_link) = self.link(open, image, offset, end);
+                        if is_link && !image {
+                            brackets.retain(|(_, image)| *image);
+                        }
+                        offset = next;
+                    }
+                    None => offset += 1,
+                },
+                marker @ (b'*' | b'_' | b'~') => {
+                    let run = self.run(offset, end, marker);
+                    if marker != b'~' || run == 2 {
+                        delimiters.push(self.delimiter(marker, offset, run));
+                    }
+                    offset += run;
+                }
+                _ => offset += 1,
+            }
+        }
+        self.emphasis(delimiters);
+    }
+
+    fn run(&self, start: usize, end: usize, marker: u8) -> usize {
+        self.bytes[start..end]
+            .iter()
+            .take_while(|&&byte| byte == marker)
+            .count()
+    }
+
+    fn span(&mut self, range: Range<usize>, kind: SpanKind) {
+        self.document.spans.push((range, kind));
+    }
+
+    /// Classifies a delimiter run by what surrounds it, treating an
+    /// interpolation as a word.
+    fn delimiter(&self, marker: u8, start: usize, len: usize) -> Delimiter {
+        let end = start + len;
+        let before = if self.source.atom_ending_at(start) {
+            Some('a')
+        } else {
+            self.source.text[..start].chars().next_back()
+        };
+        let after = if self.source.atoms.contains_key(&end) {
+            Some('a')
+        } else {
+            self.source.text[end..].chars().next()
+        };
+        let is_space = |character: Option<char>| character.is_none_or(char::is_whitespace);
+        let is_punctuation = |character: Option<char>| {
+            character.is_some_and(|character| character.is_ascii_punctuation())
+        };
+        let left_flanking = !is_space(after)
+            && (!is_punctuation(after) || is_space(before) || is_punctuation(before));
+        let right_flanking = !is_space(before)
+            && (!is_punctuation(before) || is_space(after) || is_punctuation(after));
+        let (can_open, can_close) = if marker == b'_' {
+            (
+                left_flanking && (!right_flanking || is_punctuation(before)),
+                right_flanking && (!left_flanking || is_punctuation(after)),
+            )
+        } else {
+            (left_flanking, right_flanking)
+        };
+        Delimiter {
+            marker,
+            start,
+            len,
+            original_len: len,
+            can_open,
+            can_close,
+            skipped: false,
+        }
+    }
+
+    /// Matches closers to the nearest compatible opener as CommonMark does,
+    /// then reports runs that can only open or only close and found no
+    /// partner.
+    fn emphasis(&mut self, mut delimiters: Vec<Delimiter>) {
+        for closer in 0..delimiters.len() {
+            if !delimiters[closer].can_close {
+                continue;
+            }
+            while delimiters[closer].len > 0 {
+                let Some(opener) = (0..closer).rev().find(|&index| {
+                    let opener = &delimiters[index];
+                    let candidate = &delimiters[closer];
+                    opener.marker == candidate.marker
+                        && opener.can_open
+                        && opener.len > 0
+                        && !opener.skipped
+                        && !((opener.can_close || candidate.can_open)
+                            && (opener.original_len + candidate.original_len).is_multiple_of(3)
+                            && !(opener.original_len.is_multiple_of(3)
+                                && candidate.original_len.is_multiple_of(3)))
+                }) else {
+                    break;
+                };
+                let used = if delimiters[opener].len >= 2 && delimiters[closer].len >= 2 {
+                    2
+                } else {
+                    1
+                };
+                let opener_end = delimiters[opener].start + delimiters[opener].len;
+                self.span(opener_end - used..opener_end, SpanKind::EmphasisDelimiter);
+                let closer_start = delimiters[closer].start;
+                self.span(
+                    closer_start..closer_start + used,
+                    SpanKind::EmphasisDelimiter,
+                );
+                delimiters[opener].len -= used;
+                delimiters[closer].start += used;
+                delimiters[closer].len -= used;
+                for between in &mut delimiters[opener + 1..closer] {
+                    between.skipped = true;
+                }
+            }
+        }
+
+        for delimiter in delimiters {
+            if delimiter.len == 0 || delimiter.can_open == delimiter.can_close {
+                continue;
+            }
+            let range = delimiter.start..delimiter.start + delimiter.len;
+            let run = &self.source.text[range.clone()];
+            let message = if delimiter.can_open {
+                format!("Emphasis `{run}` is opened here but never closed")
+            } else {
+                format!("Emphasis `{run}` is closed here but was never opened")
+            };
+            self.document.problems.push(Problem {
+                kind: ProblemKind::UnbalancedEmphasis,
+                message,
+                range,
+            });
+        }
+    }
+
+    /// Scans an autolink or inline HTML tag, returning the offset after it.
+    fn angle(&mut self, start: usize, end: usize) -> usize {
+        let rest = &self.source.text[start + 1..end];
+        let Some(close) = rest.find('>') else {
+            return start + 1;
+        };
+        let inner = &rest[..close];
+        let scheme = inner.find(':').map_or(0, |colon| {
+            let scheme = &inner[..colon];
+            let valid = (2..=32).contains(&scheme.len())
+                && scheme.as_bytes()[0].is_ascii_alphabetic()
+                && scheme
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'.' | b'-'));
+            if valid { colon } else { 0 }
+        });
+        let is_url = !inner.is_empty()
+            && !inner.contains(|character: char| character.is_whitespace() || character == '<');
+        if is_url && (scheme > 0 || self.source.atoms.contains_key(&(start + 1))) {
+            let destination = start + 1..start + 1 + close;
+            self.span(start..start + 1, SpanKind::LinkBracket);
+            self.span(destination.clone(), SpanKind::Destination);
+            self.span(destination.end..destination.end + 1, SpanKind::LinkBracket);
+            self.document.destinations.push(destination);
+            return start + close + 2;
+        }
+        let tag = inner.strip_prefix('/').unwrap_or(inner);
+        if tag.starts_with(|character: char| character.is_ascii_alphabetic())
+            || inner.starts_with("!--")
+        {
+            self.span(start..start + close + 2, SpanKind::Html);
+            return start + close + 2;
+        }
+        start + 1
+    }
+
+    /// Resolves the `]` at `close` against the bracket opened at `open`,
+    /// returning the offset to continue from and whether it made a link.
+    fn link(&mut self, open: usize, image: bool, close: usize, end: usize) -> (usize, bool) {
+        let text = if image { open + 2 } else { open + 1 }..close;
+        let after = close + 1;
+        let bracket = |scanner: &mut Self| {
+            scanner.span(open..text.start, SpanKind::LinkBracket);
+            scanner.span(close..after, SpanKind::LinkBracket);
+        };
+
+        if after < end && self.bytes[after] == b'(' {
+            return match self.inline_destination(after, end) {
+                Ok(InlineLink {
+                    destination,
+                    title,
+                    close: paren,
+                }) => {
+                    bracket(self);
+                    self.span(after..after + 1, SpanKind::LinkBracket);
+                    if !destination.is_empty() {
+                        self.span(destination.clone(), SpanKind::Destination);
+                    }
+                    if let Some(title) = title {
+                        self.span(title, SpanKind::Title);
+                    }
+                    self.span(paren..paren + 1, SpanKind::LinkBracket);
+                    self.document.destinations.push(destination);
+                    (paren + 1, true)
+                }
+                Err(message) => {
+                    self.document.problems.push(Problem {
+                        kind: ProblemKind::BrokenLink,
+                        message,
+                        range: open..after + 1,
+                    });
+                    (after + 1, false)
+                }
+            };
+        }
+
+        if after < end && self.bytes[after] == b'[' {
+            let Some(label_end) = self.source.text[after + 1..end]
+                .find([']', '['])
+                .map(|found| after + 1 + found)
+                .filter(|&found| self.bytes[found] == b']')
+            else {
+                return (after, false);
+            };
+            let label = if label_end == after + 1 {
+                text.clone()
+            } else {
+                after + 1..label_end
+            };
+            if self.is_defined(&label) {
+                bracket(self);
+                if label != text {
+                    self.span(after..after + 1, SpanKind::LinkBracket);
+                    self.span(label.clone(), SpanKind::Label);
+                }
+                self.span(label_end..label_end + 1, SpanKind::LinkBracket);
+                return (label_end + 1, true);
+            }
+            let name = self.source.text(&label).to_string();
+            let mut message = format!(
+                "Link reference `[{name}]` has no definition such as `[{name}]: https://example.com`"
+            );
+            if let Some(suggestion) = crate::lint::closest_key(
+                &normalize_label(&name),
+                self.labels.iter().map(String::as_str),
+            ) {
+                message.push_str(&format!("; did you mean `[{suggestion}]`?"));
+            }
+            self.document.problems.push(Problem {
+                kind: ProblemKind::BrokenLink,
+                message,
+                range: open..label_end + 1,
+            });
+            return (label_end + 1, false);
+        }
+
+        if self.is_defined(&text) {
+            bracket(self);
+            return (after, true);
+        }
+
+        let paren = after
+            + self.bytes[after..end]
+                .iter()
+                .take_while(|byte| matches!(byte, b' ' | b'\t'))
+                .count();
+        if paren > after
+            && paren < end
+            && self.bytes[paren] == b'('
+            && self.looks_like_url(paren + 1, end)
+        {
+            self.document.problems.push(Problem {
+                kind: ProblemKind::BrokenLink,
+                message: "Remove the space between `]` and `(`; Markdown does not treat `[text] (url)` as a link".to_string(),
+                range: open..paren + 1,
+            });
+        }
+        (after, false)
+    }
+
+    /// Parses `(destination "title")` from the `(` at `start`.
+    fn inline_destination(&self, start: usize, end: usize) -> Result<InlineLink, String> {
+        let missing_paren = || "Link is missing the `)` that closes its destination".to_string();
+        let skip_space = |mut offset: usize| {
+            while offset < end && self.bytes[offset].is_ascii_whitespace() {
+                offset += 1;
+            }
+            offset
+        };
+
+        let mut offset = skip_space(start + 1);
+        let bracketed = offset < end && self.bytes[offset] == b'<';
+        let destination = if bracketed {
+            let Some(close) = self.source.text[offset..end]
+                .find(['>', '\n'])
+                .filter(|&close| self.bytes[offset + close] == b'>')
+            else {
+                return Err(
+                    "Link destination opened with `<` is missing its closing `>`".to_string(),
+                );
+            };
+            let destination = offset + 1..offset + close;
+            offset += close + 1;
+            destination
+        } else {
+            let destination_start = offset;
+            let mut depth = 0usize;
+            while offset < end {
+                if let Some(&atom_end) = self.source.atoms.get(&offset) {
+                    offset = atom_end;
+                    continue;
+                }
+                match self.bytes[offset] {
+                    b'\\' => offset += 1,
+                    b'(' => depth += 1,
+                    b')' if depth == 0 => break,
+                    b')' => depth -= 1,
+                    byte if byte.is_ascii_whitespace() => break,
+                    _ => {}
+                }
+                offset += 1;
+            }
+            destination_start..offset.min(end)
+        };
+
+        let after_destination = offset;
+        offset = skip_space(offset);
+        if offset >= end {
+            return Err(missing_paren());
+        }
+        let mut title = None;
+        if offset > after_destination || destination.is_empty() {
+            let closes = match self.bytes[offset] {
+                b'"' => Some(b'"'),
+                b'\'' => Some(b'\''),
+                b'(' => Some(b')'),
+                _ => None,
+            };
+            if let Some(closes) = closes {
+                let mut close = offset + 1;
+                while close < end && self.bytes[close] != closes {
+                    if self.bytes[close] == b'\\' {
+                        close += 1;
+                    }
+                    close += 1;
+                }
+                if close >= end {
+                    return Err(format!(
+                        "Link title is missing its closing `{}`",
+                        closes as char
+                    ));
+                }
+                title = Some(offset..close + 1);
+                offset = skip_space(close + 1);
+            }
+        }
+        if offset < end && self.bytes[offset] == b')' {
+            return Ok(InlineLink {
+                destination,
+                title,
+                close: offset,
+            });
+        }
+        if !bracketed && offset > after_destination && offset < end {
+            return Err(
+                "Link destination is followed by text instead of `)`; close the link, or write a destination containing spaces as `<...>`"
+                    .to_string(),
+            );
+        }
+        Err(missing_paren())
+    }
+
+    fn looks_like_url(&self, start: usize, end: usize) -> bool {
+        let rest = &self.source.text[start..end];
+        let url = &rest[..rest.find([')', ' ', '\n']).unwrap_or(rest.len())];
+        rest[url.len()..].starts_with(')')
+            && (self.source.atoms.contains_key(&start)
+                || ["http://", "https://", "mailto:", "/", "#", "./", "../"]
+                    .iter()
+                    .any(|prefix| url.starts_with(prefix)))
+    }
+
+    fn is_defined(&self, label: &Range<usize>) -> bool {
+        self.dynamic_labels
+            || self.source.has_atom(label)
+            || self
+                .labels
+                .contains(&normalize_label(self.source.text(label)))
+    }
+}
+
+/// Matches labels case-insensitively with runs of whitespace collapsed.
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
@@ -39,6 +39,7 @@ pub struct CallableSignature {
     pub parameters: Vec<CallableParameter>,
     pub accepts_kwargs: bool,
     pub requires_positional: bool,
+    pub return_annotation: Option<String>,
 }
 
 impl CallableSignature {
@@ -57,6 +58,9 @@ pub struct CallableParameter {
     pub template_profile: Option<String>,
     pub value_types: Vec<CallableValueType>,
     pub accepts_none: bool,
+    pub accepts_template: bool,
+    /// The only string values accepted, from `Literal[...]` or a string `Enum`.
+    pub string_choices: Option<Vec<String>>,
     pub required: bool,
     pub allows_keyword: bool,
     pub keyword_only: bool,
@@ -218,6 +222,8 @@ struct ResolvedTypeInfo {
     template_profile: Option<String>,
     value_types: Vec<CallableValueType>,
     accepts_none: bool,
+    accepts_template: bool,
+    string_choices: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -231,6 +237,8 @@ struct ModuleTypeData {
     local_callable_signature_names: HashSet<String>,
     imported_module_paths: HashSet<String>,
     scoped_import_bindings: Vec<ScopedImportBinding>,
+    typed_dict_fields: HashMap<String, Vec<CallableParameter>>,
+    string_enum_values: HashMap<String, Vec<String>>,
 }
 
 impl Default for ModuleTypeData {
@@ -245,6 +253,8 @@ impl Default for ModuleTypeData {
             local_callable_signature_names: HashSet::new(),
             imported_module_paths: HashSet::new(),
             scoped_import_bindings: Vec::new(),
+            typed_dict_fields: HashMap::new(),
+            string_enum_values: HashMap::new(),
         }
     }
 }
@@ -259,6 +269,7 @@ pub struct TemplateStringParser {
     last_module_cache: HashMap<PathBuf, ModuleTypeData>,
     module_load_stack: Vec<ModuleCacheKey>,
     modules_with_incomplete_dependencies: HashSet<ModuleCacheKey>,
+    rebuilt_incomplete_modules: HashSet<ModuleCacheKey>,
 }
 
 impl TemplateStringParser {
@@ -278,6 +289,7 @@ impl TemplateStringParser {
             last_module_cache: HashMap::new(),
             module_load_stack: Vec::new(),
             modules_with_incomplete_dependencies: HashSet::new(),
+            rebuilt_incomplete_modules: HashSet::new(),
         })
     }
 
@@ -340,6 +352,7 @@ impl TemplateStringParser {
         self.last_module_cache.clear();
         self.module_load_stack.clear();
         self.modules_with_incomplete_dependencies.clear();
+        self.rebuilt_incomplete_modules.clear();
         if self.runtime_python_search_roots.is_none() {
             self.runtime_python_search_roots = Some(discover_runtime_python_search_roots());
         }
@@ -456,6 +469,12 @@ impl TemplateStringParser {
                     continue;
                 }
                 let node = capture.node;
+                if is_f_string_node(node, source)?
+                    && let Some(function_node) = call_function_for_string_node(node)
+                {
+                    // Resolve f-string callees too so lint can flag Template parameters.
+                    push_root_identifier_for_node(&mut roots, function_node, source)?;
+                }
                 if !is_template_string_node(node, source)? {
                     continue;
                 }
@@ -568,6 +587,8 @@ impl TemplateStringParser {
                 import_resolution_filter,
             )?;
             self.collect_type_aliases(tree, source, &mut module_type_data)?;
+            collect_string_enum_values(tree, source, &mut module_type_data)?;
+            self.collect_typed_dict_fields(tree, source, &mut module_type_data, module_cache)?;
             self.collect_local_callable_signatures(
                 tree,
                 source,
@@ -980,13 +1001,18 @@ impl TemplateStringParser {
                         continue;
                     };
                     let name = name_node.utf8_text(source.as_bytes())?.to_string();
-                    let signature = self.extract_callable_signature(
+                    let mut signature = self.extract_callable_signature(
                         params_node,
                         source,
                         module_type_data,
                         module_cache,
                         false,
                     )?;
+                    signature.return_annotation = definition
+                        .child_by_field_name("return_type")
+                        .map(|node| node.utf8_text(source.as_bytes()))
+                        .transpose()?
+                        .map(str::to_string);
                     if !signature.is_empty() {
                         module_type_data
                             .local_callable_signature_names
@@ -1130,6 +1156,8 @@ impl TemplateStringParser {
                 template_profile: type_hints.template_profile,
                 value_types: type_hints.value_types,
                 accepts_none: type_hints.accepts_none,
+                accepts_template: type_hints.accepts_template,
+                string_choices: type_hints.string_choices,
                 required,
                 allows_keyword: true,
                 keyword_only,
@@ -1189,8 +1217,25 @@ impl TemplateStringParser {
                             };
                             match name_node.kind() {
                                 "dictionary_splat_pattern" => {
-                                    signature.accepts_kwargs = true;
                                     receiver_skipped = true;
+                                    let unpacked = match child.child_by_field_name("type") {
+                                        Some(type_node) => self.resolve_unpacked_typed_dict(
+                                            type_node.utf8_text(source.as_bytes())?,
+                                            module_type_data,
+                                            module_cache,
+                                        )?,
+                                        None => None,
+                                    };
+                                    let Some(fields) = unpacked else {
+                                        signature.accepts_kwargs = true;
+                                        continue;
+                                    };
+                                    for field in fields {
+                                        signature
+                                            .parameters
+                                            .push(CallableParameter { position, ..field });
+                                        position += 1;
+                                    }
                                     continue;
                                 }
                                 "list_splat_pattern" => {
@@ -1241,6 +1286,8 @@ impl TemplateStringParser {
                         template_profile: type_hints.template_profile,
                         value_types: type_hints.value_types,
                         accepts_none: type_hints.accepts_none,
+                        accepts_template: type_hints.accepts_template,
+                        string_choices: type_hints.string_choices,
                         required,
                         allows_keyword: true,
                         keyword_only,
@@ -1255,6 +1302,198 @@ impl TemplateStringParser {
         Ok(signature)
     }
 
+    /// Collects the keys of module-level `TypedDict` classes as keyword-only
+    /// parameters, including keys inherited from other `TypedDict`s.
+    fn collect_typed_dict_fields(
+        &mut self,
+        tree: &Tree,
+        source: &str,
+        module_type_data: &mut ModuleTypeData,
+        module_cache: &mut HashMap<PathBuf, ModuleTypeData>,
+    ) -> Result<()> {
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+
+        for child in root.children(&mut cursor) {
+            let Some(definition) = definition_node_for_statement(child) else {
+                continue;
+            };
+            if definition.kind() != "class_definition" {
+                continue;
+            }
+            let (Some(name_node), Some(body_node), Some(superclasses)) = (
+                definition.child_by_field_name("name"),
+                definition.child_by_field_name("body"),
+                definition.child_by_field_name("superclasses"),
+            ) else {
+                continue;
+            };
+
+            let mut is_typed_dict = false;
+            let mut total = true;
+            let mut fields = Vec::<CallableParameter>::new();
+            let mut base_cursor = superclasses.walk();
+            for base in superclasses.named_children(&mut base_cursor) {
+                if base.kind() == "keyword_argument" {
+                    let keyword = base
+                        .child_by_field_name("name")
+                        .map(|node| node.utf8_text(source.as_bytes()))
+                        .transpose()?;
+                    let value = base
+                        .child_by_field_name("value")
+                        .map(|node| node.utf8_text(source.as_bytes()))
+                        .transpose()?;
+                    if keyword == Some("total") && value == Some("False") {
+                        total = false;
+                    }
+                    continue;
+                }
+                let base_expr = parse_type_expr(base.utf8_text(source.as_bytes())?);
+                let TypeExpr::Name(base_name) = &base_expr else {
+                    continue;
+                };
+                if qualified_name_resolves_to(
+                    base_name,
+                    module_type_data,
+                    &[
+                        "TypedDict",
+                        "typing.TypedDict",
+                        "typing_extensions.TypedDict",
+                    ],
+                ) {
+                    is_typed_dict = true;
+                } else if let Some(inherited) = self.resolve_typed_dict_fields(
+                    &base_expr,
+                    module_type_data,
+                    module_cache,
+                    false,
+                )? {
+                    is_typed_dict = true;
+                    for field in inherited {
+                        fields.retain(|existing| existing.name != field.name);
+                        fields.push(field);
+                    }
+                }
+            }
+            if !is_typed_dict {
+                continue;
+            }
+
+            let mut body_cursor = body_node.walk();
+            for statement in body_node.children(&mut body_cursor) {
+                let Some(assignment) = dataclass_field_assignment_node(statement) else {
+                    continue;
+                };
+                let (Some(left_node), Some(type_node)) = (
+                    assignment.child_by_field_name("left"),
+                    assignment.child_by_field_name("type"),
+                ) else {
+                    continue;
+                };
+                if left_node.kind() != "identifier"
+                    || assignment.child_by_field_name("right").is_some()
+                {
+                    continue;
+                }
+                let type_text = type_node.utf8_text(source.as_bytes())?;
+                let type_expr = parse_type_expr(type_text);
+                let (field_expr, required) =
+                    typed_dict_field_requiredness(&type_expr, module_type_data);
+                let mut visited = HashSet::new();
+                let type_hints = self.resolve_type_expr(
+                    field_expr,
+                    module_type_data,
+                    module_cache,
+                    &mut visited,
+                )?;
+                let name = left_node.utf8_text(source.as_bytes())?.to_string();
+                fields.retain(|existing| existing.name != name);
+                fields.push(CallableParameter {
+                    position: 0,
+                    name,
+                    type_annotation: checker_type_annotation_from_expr(field_expr)
+                        .or_else(|| checker_type_annotation_from_text(type_text)),
+                    type_annotation_module: None,
+                    template_language: type_hints.template_language,
+                    template_profile: type_hints.template_profile,
+                    value_types: type_hints.value_types,
+                    accepts_none: type_hints.accepts_none,
+                    accepts_template: type_hints.accepts_template,
+                    string_choices: type_hints.string_choices,
+                    required: required.unwrap_or(total),
+                    allows_keyword: true,
+                    keyword_only: true,
+                });
+            }
+
+            module_type_data
+                .typed_dict_fields
+                .insert(name_node.utf8_text(source.as_bytes())?.to_string(), fields);
+        }
+
+        Ok(())
+    }
+
+    /// Expands `Unpack[SomeTypedDict]` to the keys of `SomeTypedDict`.
+    fn resolve_unpacked_typed_dict(
+        &mut self,
+        type_text: &str,
+        module_type_data: &ModuleTypeData,
+        module_cache: &mut HashMap<PathBuf, ModuleTypeData>,
+    ) -> Result<Option<Vec<CallableParameter>>> {
+        let TypeExpr::Generic { base, args } = parse_type_expr(type_text) else {
+            return Ok(None);
+        };
+        if args.len() != 1
+            || !qualified_name_resolves_to(
+                &base,
+                module_type_data,
+                &["Unpack", "typing.Unpack", "typing_extensions.Unpack"],
+            )
+        {
+            return Ok(None);
+        }
+        self.resolve_typed_dict_fields(&args[0], module_type_data, module_cache, true)
+    }
+
+    /// Class bases only consult modules that are already loaded, so ordinary
+    /// base classes from installed packages are not parsed just to rule them out.
+    fn resolve_typed_dict_fields(
+        &mut self,
+        expr: &TypeExpr,
+        module_type_data: &ModuleTypeData,
+        module_cache: &mut HashMap<PathBuf, ModuleTypeData>,
+        load_imports: bool,
+    ) -> Result<Option<Vec<CallableParameter>>> {
+        let name = match expr {
+            TypeExpr::Name(name) => name.clone(),
+            TypeExpr::StringLiteral(text) => match parse_type_expr(text) {
+                TypeExpr::Name(name) => name,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        if let Some(fields) = module_type_data.typed_dict_fields.get(&name.as_string()) {
+            return Ok(Some(fields.clone()));
+        }
+        let Some(import_path) = expand_qualified_name(&name, &module_type_data.imports) else {
+            return Ok(None);
+        };
+        let Some((module_name, symbol_name)) = import_path.rsplit_once('.') else {
+            return Ok(None);
+        };
+        if !load_imports {
+            return Ok(self
+                .resolve_python_module_path(module_name)
+                .and_then(|path| module_cache.get(&path))
+                .and_then(|imported| imported.typed_dict_fields.get(symbol_name).cloned()));
+        }
+        let Some(imported) = self.load_imported_module_type_data(module_name, module_cache)? else {
+            return Ok(None);
+        };
+        Ok(imported.typed_dict_fields.get(symbol_name).cloned())
+    }
+
     fn collect_imported_callable_signatures(
         &mut self,
         module_type_data: &mut ModuleTypeData,
@@ -1379,7 +1618,11 @@ impl TemplateStringParser {
         }
 
         if let Some(module_type_data) = module_cache.get(&module_path) {
-            if module_type_data.is_complete {
+            // Modules in import cycles stay incomplete; rebuilding them on every
+            // lookup is exponential for large cyclic packages, so retry once.
+            if module_type_data.is_complete
+                || !self.rebuilt_incomplete_modules.insert(module_key.clone())
+            {
                 return Ok(Some(module_type_data.clone()));
             }
         }
@@ -2751,6 +2994,8 @@ impl TemplateStringParser {
                 template_profile: None,
                 value_types: Vec::new(),
                 accepts_none: true,
+                accepts_template: false,
+                string_choices: None,
             }),
             TypeExpr::Call { function, .. } => {
                 if let Some(language) = self.resolve_template_marker_language_name(
@@ -2957,6 +3202,9 @@ impl TemplateStringParser {
 
         if name.parts.len() == 1 {
             let alias_name = &name.parts[0];
+            if let Some(values) = module_type_data.string_enum_values.get(alias_name) {
+                return Ok(string_enum_type_info(values));
+            }
             if let Some(resolved) = self.resolve_module_alias_type_info(
                 module_type_data,
                 alias_name,
@@ -3030,6 +3278,10 @@ impl TemplateStringParser {
             return Ok(resolved_type_info_for_special_name(kind));
         }
 
+        if let Some(values) = module_type_data.string_enum_values.get(symbol_name) {
+            return Ok(string_enum_type_info(values));
+        }
+
         if let Some(resolved) = self.resolve_module_alias_type_info(
             module_type_data,
             symbol_name,
@@ -3088,6 +3340,14 @@ impl TemplateStringParser {
         if resolved.template_profile.is_none() {
             resolved.template_profile = metadata.iter().find_map(template_profile_metadata);
         }
+        if resolved.template_profile.is_none()
+            && resolved
+                .template_language
+                .as_deref()
+                .is_some_and(|language| language.eq_ignore_ascii_case("sql"))
+        {
+            resolved.template_profile = sql_dialect_metadata(metadata);
+        }
 
         Ok(resolved)
     }
@@ -3621,6 +3881,7 @@ fn canonical_special_name(name: &str) -> Option<&'static str> {
         "int" | "builtins.int" => Some("int"),
         "float" | "builtins.float" => Some("float"),
         "str" | "builtins.str" => Some("str"),
+        "LiteralString" | "typing.LiteralString" | "typing_extensions.LiteralString" => Some("str"),
         "None" | "NoneType" | "builtins.NoneType" => Some("None"),
         _ => None,
     }
@@ -3634,6 +3895,7 @@ fn resolved_type_info_for_special_name(kind: &str) -> ResolvedTypeInfo {
         "float" => push_value_type(&mut resolved.value_types, CallableValueType::Float),
         "str" => push_value_type(&mut resolved.value_types, CallableValueType::String),
         "None" => resolved.accepts_none = true,
+        "Template" => resolved.accepts_template = true,
         _ => {}
     }
     resolved
@@ -3786,6 +4048,35 @@ fn dataclass_annotation_kind<'a>(
     }
 }
 
+/// Unwraps `Required[...]`, `NotRequired[...]` and `ReadOnly[...]`, returning
+/// the key's value type and its explicit requiredness, if any.
+fn typed_dict_field_requiredness<'a>(
+    expr: &'a TypeExpr,
+    module_type_data: &ModuleTypeData,
+) -> (&'a TypeExpr, Option<bool>) {
+    let mut current = expr;
+    let mut required = None;
+    while let TypeExpr::Generic { base, args } = current
+        && let [inner] = args.as_slice()
+    {
+        let resolves_to =
+            |names: [&str; 3]| qualified_name_resolves_to(base, module_type_data, &names);
+        if resolves_to(["Required", "typing.Required", "typing_extensions.Required"]) {
+            required = Some(true);
+        } else if resolves_to([
+            "NotRequired",
+            "typing.NotRequired",
+            "typing_extensions.NotRequired",
+        ]) {
+            required = Some(false);
+        } else if !resolves_to(["ReadOnly", "typing.ReadOnly", "typing_extensions.ReadOnly"]) {
+            break;
+        }
+        current = inner;
+    }
+    (current, required)
+}
+
 fn dataclass_field_requiredness(
     assignment: Node,
     source: &str,
@@ -3905,9 +4196,24 @@ fn template_profile_metadata(arg: &TypeExpr) -> Option<String> {
         .map(str::to_string)
 }
 
+/// A bare dialect after the language, as in `Annotated[Template, "sql", "sqlite"]`.
+fn sql_dialect_metadata(metadata: &[TypeExpr]) -> Option<String> {
+    metadata
+        .iter()
+        .filter_map(|arg| match arg {
+            TypeExpr::StringLiteral(value) => Some(value),
+            _ => None,
+        })
+        .skip(1)
+        .find(|value| value.parse::<crate::project_config::SqlDialect>().is_ok())
+        .cloned()
+}
+
 fn builtin_template_marker_language(target: &str) -> Option<&'static str> {
     match target {
         "json_tstring.Json" => Some("json"),
+        "yaml_tstring.Yaml" => Some("yaml"),
+        "toml_tstring.Toml" => Some("toml"),
         _ => None,
     }
 }
@@ -3941,8 +4247,9 @@ fn resolve_literal_type_info(args: &[TypeExpr]) -> ResolvedTypeInfo {
     let mut resolved = ResolvedTypeInfo::default();
     for arg in args {
         match arg {
-            TypeExpr::StringLiteral(_) => {
-                push_value_type(&mut resolved.value_types, CallableValueType::String)
+            TypeExpr::StringLiteral(value) => {
+                push_value_type(&mut resolved.value_types, CallableValueType::String);
+                push_string_choice(&mut resolved.string_choices, value);
             }
             TypeExpr::NoneLiteral => resolved.accepts_none = true,
             TypeExpr::Name(name) => {
@@ -3967,11 +4274,24 @@ fn resolve_literal_type_info(args: &[TypeExpr]) -> ResolvedTypeInfo {
 fn resolved_template_language_marker_type_info(language: String) -> ResolvedTypeInfo {
     ResolvedTypeInfo {
         template_language: Some(language),
+        accepts_template: true,
         ..ResolvedTypeInfo::default()
     }
 }
 
 fn merge_resolved_type_info(target: &mut ResolvedTypeInfo, other: ResolvedTypeInfo) {
+    // A plain `str` member accepts any string, so choices only survive when
+    // every string-accepting member is restricted.
+    let accepts_any_string = |info: &ResolvedTypeInfo| {
+        info.value_types.contains(&CallableValueType::String) && info.string_choices.is_none()
+    };
+    if accepts_any_string(target) || accepts_any_string(&other) {
+        target.string_choices = None;
+    } else if let Some(choices) = &other.string_choices {
+        for choice in choices {
+            push_string_choice(&mut target.string_choices, choice);
+        }
+    }
     if target.template_language.is_none() {
         target.template_language = other.template_language;
     }
@@ -3982,6 +4302,7 @@ fn merge_resolved_type_info(target: &mut ResolvedTypeInfo, other: ResolvedTypeIn
         push_value_type(&mut target.value_types, value_type);
     }
     target.accepts_none |= other.accepts_none;
+    target.accepts_template |= other.accepts_template;
 }
 
 fn mark_signature_type_annotation_module(signature: &mut CallableSignature, module_name: &str) {
@@ -4106,6 +4427,12 @@ fn is_template_string_node(node: Node, source: &str) -> Result<bool> {
     Ok(is_template_string_start(start_text))
 }
 
+fn is_f_string_node(node: Node, source: &str) -> Result<bool> {
+    let start_text = string_start_node(node)?.utf8_text(source.as_bytes())?;
+    Ok(string_prefix(start_text)
+        .is_some_and(|prefix| prefix.contains(['f', 'F']) && !prefix.contains(['t', 'T'])))
+}
+
 fn node_is_template_or_template_concat(node: Node, source: &str) -> Result<bool> {
     if node.kind() == "string" {
         return is_template_string_node(node, source);
@@ -4592,6 +4919,109 @@ fn resolve_callable_parameter<'a>(
         .find(|parameter| parameter.position == position && !parameter.keyword_only)
 }
 
+/// Records module-level `Enum` classes whose members all have string values
+/// (`StrEnum` members declared with `auto()` use their lowercased name).
+fn collect_string_enum_values(
+    tree: &Tree,
+    source: &str,
+    module_type_data: &mut ModuleTypeData,
+) -> Result<()> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+
+    for child in root.children(&mut cursor) {
+        let Some(definition) = definition_node_for_statement(child) else {
+            continue;
+        };
+        if definition.kind() != "class_definition" {
+            continue;
+        }
+        let (Some(name_node), Some(body_node), Some(superclasses)) = (
+            definition.child_by_field_name("name"),
+            definition.child_by_field_name("body"),
+            definition.child_by_field_name("superclasses"),
+        ) else {
+            continue;
+        };
+
+        let mut is_enum = false;
+        let mut is_str_enum = false;
+        let mut base_cursor = superclasses.walk();
+        for base in superclasses.named_children(&mut base_cursor) {
+            let TypeExpr::Name(base_name) = parse_type_expr(base.utf8_text(source.as_bytes())?)
+            else {
+                continue;
+            };
+            if qualified_name_resolves_to(
+                &base_name,
+                module_type_data,
+                &["StrEnum", "enum.StrEnum"],
+            ) {
+                is_enum = true;
+                is_str_enum = true;
+            } else if qualified_name_resolves_to(
+                &base_name,
+                module_type_data,
+                &["Enum", "enum.Enum"],
+            ) {
+                is_enum = true;
+            }
+        }
+        if !is_enum {
+            continue;
+        }
+
+        let mut values = Vec::new();
+        let mut all_strings = true;
+        let mut body_cursor = body_node.walk();
+        for statement in body_node.children(&mut body_cursor) {
+            let Some(assignment) = dataclass_field_assignment_node(statement) else {
+                continue;
+            };
+            let (Some(left_node), Some(right_node)) = (
+                assignment.child_by_field_name("left"),
+                assignment.child_by_field_name("right"),
+            ) else {
+                continue;
+            };
+            let member = left_node.utf8_text(source.as_bytes())?;
+            if left_node.kind() != "identifier" || member.starts_with('_') {
+                continue;
+            }
+            let value_text = right_node.utf8_text(source.as_bytes())?;
+            if let Some(value) = parse_string_literal(value_text) {
+                values.push(value);
+            } else if is_str_enum && value_text.replace(' ', "").ends_with("auto()") {
+                values.push(member.to_ascii_lowercase());
+            } else {
+                all_strings = false;
+            }
+        }
+        if all_strings && !values.is_empty() {
+            module_type_data
+                .string_enum_values
+                .insert(name_node.utf8_text(source.as_bytes())?.to_string(), values);
+        }
+    }
+
+    Ok(())
+}
+
+fn string_enum_type_info(values: &[String]) -> ResolvedTypeInfo {
+    ResolvedTypeInfo {
+        value_types: vec![CallableValueType::String],
+        string_choices: Some(values.to_vec()),
+        ..ResolvedTypeInfo::default()
+    }
+}
+
+fn push_string_choice(choices: &mut Option<Vec<String>>, value: &str) {
+    let choices = choices.get_or_insert_with(Vec::new);
+    if !choices.iter().any(|choice| choice == value) {
+        choices.push(value.to_string());
+    }
+}
+
 fn push_value_type(types: &mut Vec<CallableValueType>, value_type: CallableValueType) {
     if !types.contains(&value_type) {
         types.push(value_type);
@@ -7443,6 +7873,24 @@ config: Annotated[Template, "toml", "profile:1.0"] = t"title = {title}"
         assert_eq!(templates[0].profile, Some("1.0".to_string()));
     }
 
+    #[test]
+    fn test_annotated_sql_dialect_metadata_detection() {
+        let source = r#"
+lite: Annotated[Template, "sql", "sqlite"] = t"SELECT * FROM users WHERE id = {user_id}"
+note: Annotated[Template, "sql", "cached"] = t"SELECT 1"
+html: Annotated[Template, "html", "sqlite"] = t"<p></p>"
+"#;
+
+        let mut parser = TemplateStringParser::new().unwrap();
+        let templates = parser.find_template_strings(source).unwrap();
+
+        let profiles = templates
+            .iter()
+            .map(|template| template.profile.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(profiles, vec![Some("sqlite"), None, None]);
+    }
+
     #[test]
     fn test_annotated_template_marker_metadata_detection() {
         let source = r#"
@@ -7579,6 +8027,32 @@ payload: Json[Order] = t'{"id": {order_id}}'
         );
     }
 
+    #[test]
+    fn test_yaml_and_toml_template_marker_detection() {
+        let source = r#"
+from typing import Annotated, TypedDict
+from string.templatelib import Template
+from toml_tstring import Toml
+from yaml_tstring import Yaml
+
+class Order(TypedDict):
+    id: int
+
+yaml_payload: Annotated[Template, Yaml(schema=Order)] = t"id: {order_id}"
+toml_payload: Toml[Order] = t"id = {order_id}"
+"#;
+
+        let mut parser = TemplateStringParser::new().unwrap();
+        let templates = parser.find_template_strings(source).unwrap();
+
+        assert_eq!(templates.len(), 2);
+        assert_eq!(templates[0].language, Some("yaml".to_string()));
+        assert_eq!(templates[1].language, Some("toml".to_string()));
+        assert!(templates.iter().all(|template| {
+            template.language_detection == Some(LanguageDetection::Annotation)
+        }));
+    }
+
     #[test]
     fn test_imported_template_language_marker_attribute_detection() {
         let dir = parser_test_dir("template-language-marker-attribute");
@@ -10436,6 +10910,41 @@ page: Marker = t"<div><"
         assert_eq!(templates[0].language, Some("html".to_string()));
     }
 
+    #[test]
+    fn test_densely_cyclic_package_resolves_without_rebuilding_each_module_per_lookup() {
+        let dir = parser_test_dir("dense-cycle");
+        let modules = 12;
+        for index in 0..modules {
+            let imports = (0..modules)
+                .filter(|other| *other != index)
+                .map(|other| format!("from cycle_{other} import render_{other}\n"))
+                .collect::<String>();
+            fs::write(
+                dir.join(format!("cycle_{index}.py")),
+                format!(
+                    "{imports}from typing import Annotated\nfrom string.templatelib import Template\n\ntype HtmlTemplate = Annotated[Template, \"html\"]\n\ndef render_{index}(value: str) -> str:\n    return value\n"
+                ),
+            )
+            .unwrap();
+        }
+
+        let source = r#"
+from cycle_0 import HtmlTemplate, render_0
+
+page: HtmlTemplate = t"<div>{render_0('x')}</div>"
+"#;
+
+        let mut parser = TemplateStringParser::new().unwrap();
+        let templates = parser
+            .find_template_strings_in_file(source, &dir.join("app.py"))
+            .unwrap();
+
+        let _ = fs::remove_dir_all(dir);
+
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].language, Some("html".to_string()));
+    }
+
     #[test]
     fn test_entry_file_back_edge_short_circuits_cycle_without_losing_alias_resolution() {
         let dir = parser_test_dir("entry-file-back-edge");
@@ -10591,6 +11100,132 @@ class Card:
         assert_eq!(card.parameters[1].type_annotation, Some("User".to_string()));
     }
 
+    #[test]
+    fn test_callable_signatures_track_template_parameters() {
+        let source = r#"
+from typing import Annotated, Optional
+from string.templatelib import Template as T
+
+type Html = Annotated[T, "html"]
+
+def render(body: T, title: Optional[T], page: Html | None, label: str) -> None:
+    return None
+"#;
+
+        let mut parser = TemplateStringParser::new().unwrap();
+        parser.find_template_strings(source).unwrap();
+
+        let render = parser
+            .module_context()
+            .callable_signatures
+            .get("render")
+            .unwrap();
+        let accepts_template = render
+            .parameters
+            .iter()
+            .map(|parameter| parameter.accepts_template)
+            .collect::<Vec<_>>();
+        assert_eq!(accepts_template, vec![true, true, true, false]);
+    }
+
+    #[test]
+    fn test_callable_signatures_expand_unpacked_typed_dict_kwargs() {
+        let source = r#"
+from typing import NotRequired, Required, TypedDict, Unpack
+
+class BaseProps(TypedDict, total=False):
+    id: str
+
+class CardProps(BaseProps):
+    title: Required[str]
+    count: int
+    tone: NotRequired[str]
+
+def Card(**props: Unpack[CardProps]) -> object:
+    return None
+
+def Loose(**props: object) -> object:
+    return None
+"#;
+
+        let mut parser = TemplateStringParser::new().unwrap();
+        parser.find_template_strings(source).unwrap();
+        let signatures = &parser.module_context().callable_signatures;
+
+        let card = signatures.get("Card").unwrap();
+        assert!(!card.accepts_kwargs);
+        let parameters = card
+            .parameters
+            .iter()
+            .map(|parameter| {
+                (
+                    parameter.name.as_str(),
+                    parameter.required,
+                    parameter.keyword_only,
+                    parameter.value_types.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parameters,
+            vec![
+                ("id", false, true, vec![CallableValueType::String]),
+                ("title", true, true, vec![CallableValueType::String]),
+                ("count", true, true, vec![CallableValueType::Int]),
+                ("tone", false, true, vec![CallableValueType::String]),
+            ]
+        );
+        assert!(signatures.get("Loose").unwrap().accepts_kwargs);
+    }
+
+    #[test]
+    fn test_callable_signatures_collect_literal_and_enum_string_choices() {
+        let source = r#"
+from enum import Enum, StrEnum, auto
+from typing import Literal
+
+class Size(StrEnum):
+    SMALL = auto()
+    LARGE = auto()
+
+class Shape(Enum):
+    ROUND = "round"
+    SQUARE = "square"
+
+def Button(
+    *,
+    tone: Literal["primary", "secondary"],
+    size: Size,
+    shape: Shape | None = None,
+    label: Literal["ok"] | str = "ok",
+) -> object:
+    return None
+"#;
+
+        let mut parser = TemplateStringParser::new().unwrap();
+        parser.find_template_strings(source).unwrap();
+        let button = parser
+            .module_context()
+            .callable_signatures
+            .get("Button")
+            .unwrap();
+        let choices = button
+            .parameters
+            .iter()
+            .map(|parameter| (parameter.name.as_str(), parameter.string_choices.clone()))
+            .collect::<Vec<_>>();
+        let strings = |values: &[&str]| Some(values.iter().map(ToString::to_string).collect());
+        assert_eq!(
+            choices,
+            vec![
+                ("tone", strings(&["primary", "secondary"])),
+                ("size", strings(&["small", "large"])),
+                ("shape", strings(&["round", "square"])),
+                ("label", None),
+            ]
+        );
+    }
+
     #[test]
     fn test_imported_callable_signature_tracks_type_annotation_module() {
         let dir = parser_test_dir("imported-callable-type-annotation-module");
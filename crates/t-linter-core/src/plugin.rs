@@ -0,0 +1,670 @@
+//! Language plugins: external commands that check, format, and highlight
+//! templates in languages t-linter does not ship, configured under
+//! `[tool.t-linter.plugins.<language>]`.
+//!
+//! A plugin reads one JSON request per line on stdin and answers each with
+//! one JSON line on stdout carrying the request's `id`. It is started on first
+//! use, kept running for later requests, and should exit when stdin closes.
+//! Ranges in requests and responses are byte offsets into the template text
+//! where every interpolation counts as the two bytes `{}`.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::lint::{LintDiagnostic, LintSeverity};
+use crate::parser::{InterpolationInfo, Location, TemplatePart, TemplateStringInfo};
+use crate::project_config::{PluginConfig, ProjectConfig};
+
+const RULE_PLUGIN_ERROR: &str = "plugin-error";
+const DEFAULT_TIMEOUT_SECONDS: u64 = 10;
+
+/// A highlighted content range and the highlight name a plugin gave it.
+pub type PluginSpan = (Range<usize>, String);
+
+/// The language plugins configured for a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguagePlugins {
+    root: PathBuf,
+    plugins: HashMap<String, PluginConfig>,
+}
+
+impl LanguagePlugins {
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        Self {
+            root: config.root.clone(),
+            plugins: config.plugins.clone(),
+        }
+    }
+
+    pub fn supports_language(&self, language: &str) -> bool {
+        self.plugin(language).is_some()
+    }
+
+    fn plugin(&self, language: &str) -> Option<&PluginConfig> {
+        self.plugins
+            .get(&language.trim().to_ascii_lowercase())
+            .filter(|plugin| !plugin.command.is_empty())
+    }
+
+    /// Checks a template with its language's plugin, or returns `None` when
+    /// no plugin handles the language. A plugin that cannot answer is
+    /// reported as a `plugin-error` diagnostic on the template.
+    pub(crate) fn lint_template(
+        &self,
+        path: &Path,
+        template: &TemplateStringInfo,
+    ) -> Option<Vec<LintDiagnostic>> {
+        let language = template.language.as_deref()?.trim().to_ascii_lowercase();
+        let plugin = self.plugin(&language)?;
+        let request = PluginRequest::new("check", template, &language, Some(path), None);
+        let diagnostics = self.send(plugin, request).and_then(|response| {
+            response
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| {
+                    let location = content_location(template, diagnostic.start..diagnostic.end)?;
+                    Ok(lint_diagnostic(
+                        path,
+                        &language,
+                        &diagnostic.rule,
+                        diagnostic.severity.unwrap_or(LintSeverity::Error),
+                        diagnostic.message,
+                        location,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()
+        });
+        Some(diagnostics.unwrap_or_else(|error| {
+            vec![lint_diagnostic(
+                path,
+                &language,
+                RULE_PLUGIN_ERROR,
+                LintSeverity::Error,
+                format!("Language plugin for '{language}' failed: {error:#}"),
+                template.location.clone(),
+            )]
+        }))
+    }
+
+    /// Formats a template with its language's plugin, returning the new
+    /// template content. Returns `None` when no plugin handles the language
+    /// or the plugin leaves the template unformatted.
+    pub(crate) fn format_template(
+        &self,
+        template: &TemplateStringInfo,
+        line_length: usize,
+    ) -> Option<Result<String>> {
+        let language = template.language.as_deref()?.trim().to_ascii_lowercase();
+        let plugin = self.plugin(&language)?;
+        let request = PluginRequest::new("format", template, &language, None, Some(line_length));
+        match self.send(plugin, request) {
+            Ok(response) => response
+                .segments
+                .map(|segments| render_formatted(template, &segments)),
+            Err(error) => Some(Err(error)),
+        }
+    }
+
+    /// Highlights a template with its language's plugin, returning content
+    /// ranges and their highlight names. Returns `None` when no plugin
+    /// handles the language. This blocks until the plugin answers or its
+    /// timeout passes.
+    pub fn highlight_template(
+        &self,
+        template: &TemplateStringInfo,
+    ) -> Option<Result<Vec<PluginSpan>>> {
+        let language = template.language.as_deref()?.trim().to_ascii_lowercase();
+        let plugin = self.plugin(&language)?;
+        let request = PluginRequest::new("highlight", template, &language, None, None);
+        Some(self.send(plugin, request).and_then(|response| {
+            response
+                .tokens
+                .into_iter()
+                .map(|token| {
+                    check_range(template, &(token.start..token.end))?;
+                    Ok((token.start..token.end, token.kind))
+                })
+                .collect()
+        }))
+    }
+
+    fn send(&self, plugin: &PluginConfig, request: PluginRequest<'_>) -> Result<PluginResponse> {
+        let slot = processes()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry((self.root.clone(), plugin.command.clone()))
+            .or_default()
+            .clone();
+        let mut process = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut running = match process.take() {
+            Some(mut running) => {
+                if running.is_running() {
+                    running
+                } else {
+                    running.stop();
+                    PluginProcess::start(&self.root, &plugin.command)?
+                }
+            }
+            None => PluginProcess::start(&self.root, &plugin.command)?,
+        };
+        let timeout = Duration::from_secs(plugin.timeout.unwrap_or(DEFAULT_TIMEOUT_SECONDS).max(1));
+        let response = match running.request(request, timeout) {
+            Ok(response) => response,
+            Err(error) => {
+                running.stop();
+                return Err(error);
+            }
+        };
+        *process = Some(running);
+        if let Some(error) = response.error {
+            return Err(anyhow::anyhow!(error.message));
+        }
+        Ok(response)
+    }
+}
+
+type PluginKey = (PathBuf, Vec<String>);
+type PluginSlot = Arc<Mutex<Option<PluginProcess>>>;
+
+/// The running plugins, shared so a plugin serves every file of a run.
+fn processes() -> &'static Mutex<HashMap<PluginKey, PluginSlot>> {
+    static PROCESSES: OnceLock<Mutex<HashMap<PluginKey, PluginSlot>>> = OnceLock::new();
+    PROCESSES.get_or_init(Default::default)
+}
+
+struct PluginProcess {
+    command: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Receiver<std::result::Result<String, String>>,
+    next_id: u64,
+}
+
+impl PluginProcess {
+    fn start(root: &Path, command: &[String]) -> Result<Self> {
+        let (program, args) = command
+            .split_first()
+            .context("Language plugin command is empty")?;
+        // A program given as a relative path is found from the project root,
+        // like the files it is run against.
+        let executable = if Path::new(program).components().count() > 1 {
+            root.join(program)
+        } else {
+            PathBuf::from(program)
+        };
+        let mut child = Command::new(&executable)
+            .args(args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to start {program}"))?;
+        let stdin = child
+            .stdin
+            .take()
+            .context("Language plugin stdin is not available")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("Language plugin stdout is not available")?;
+        Ok(Self {
+            command: program.clone(),
+            child,
+            stdin,
+            stdout: start_stdout_reader(stdout),
+            next_id: 1,
+        })
+    }
+
+    fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    fn request(
+        &mut self,
+        mut request: PluginRequest<'_>,
+        timeout: Duration,
+    ) -> Result<PluginResponse> {
+        let id = self.next_id;
+        self.next_id += 1;
+        request.id = id;
+        let payload =
+            serde_json::to_string(&request).context("Failed to serialize plugin request")?;
+        self.stdin
+            .write_all(payload.as_bytes())
+            .and_then(|()| self.stdin.write_all(b"\n"))
+            .and_then(|()| self.stdin.flush())
+            .with_context(|| format!("Failed to write request to {}", self.command))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match self.stdout.recv_timeout(remaining) {
+                Ok(Ok(line)) => line,
+                Ok(Err(error)) => {
+                    return Err(anyhow::anyhow!(
+                        "Failed to read response from {}: {error}",
+                        self.command
+                    ));
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(anyhow::anyhow!(
+                        "{} did not respond within {}s",
+                        self.command,
+                        timeout.as_secs()
+                    ));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow::anyhow!(
+                        "{} exited without a response",
+                        self.command
+                    ));
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response: PluginResponse = serde_json::from_str(&line)
+                .with_context(|| format!("Failed to parse response from {}", self.command))?;
+            if response.id == Some(id) {
+                return Ok(response);
+            }
+        }
+    }
+
+    fn stop(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn start_stdout_reader(stdout: ChildStdout) -> Receiver<std::result::Result<String, String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdout = BufReader::new(stdout);
+        loop {
+            let mut line = String::new();
+            match stdout.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if sender.send(Ok(line)).is_err() {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    let _ = sender.send(Err(error.to_string()));
+                    break;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+#[derive(Debug, Serialize)]
+struct PluginRequest<'a> {
+    id: u64,
+    op: &'static str,
+    language: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a Path>,
+    segments: Vec<RequestSegment<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_length: Option<usize>,
+}
+
+impl<'a> PluginRequest<'a> {
+    fn new(
+        op: &'static str,
+        template: &'a TemplateStringInfo,
+        language: &'a str,
+        path: Option<&'a Path>,
+        line_length: Option<usize>,
+    ) -> Self {
+        let mut index = 0;
+        let segments = template
+            .parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Static(part) => RequestSegment::Text { text: &part.text },
+                TemplatePart::Interpolation(interpolation) => {
+                    index += 1;
+                    RequestSegment::Interpolation {
+                        index: index - 1,
+                        expression: &interpolation.expression,
+                        conversion: interpolation.conversion.as_deref(),
+                        format_spec: &interpolation.format_spec,
+                    }
+                }
+            })
+            .collect();
+        Self {
+            id: 0,
+            op,
+            language,
+            profile: template.profile.as_deref(),
+            path,
+            segments,
+            line_length,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RequestSegment<'a> {
+    Text {
+        text: &'a str,
+    },
+    Interpolation {
+        index: usize,
+        expression: &'a str,
+        conversion: Option<&'a str>,
+        format_spec: &'a str,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginResponse {
+    id: Option<u64>,
+    #[serde(default)]
+    diagnostics: Vec<PluginDiagnostic>,
+    /// The formatted template, or `None` to leave it as written.
+    #[serde(default)]
+    segments: Option<Vec<FormattedSegment>>,
+    #[serde(default)]
+    tokens: Vec<PluginToken>,
+    #[serde(default)]
+    error: Option<PluginError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginDiagnostic {
+    rule: String,
+    #[serde(default)]
+    severity: Option<LintSeverity>,
+    message: String,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FormattedSegment {
+    Text { text: String },
+    Interpolation { index: usize },
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginToken {
+    start: usize,
+    end: usize,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginError {
+    message: String,
+}
+
+/// Writes formatted segments as template content, keeping each
+/// interpolation's source text and escaping braces in the static text.
+fn render_formatted(
+    template: &TemplateStringInfo,
+    segments: &[FormattedSegment],
+) -> Result<String> {
+    let interpolations = template
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            TemplatePart::Interpolation(interpolation) => Some(interpolation),
+            TemplatePart::Static(_) => None,
+        })
+        .collect::<Vec<&InterpolationInfo>>();
+    let mut next = 0;
+    let mut content = String::new();
+    for segment in segments {
+        match segment {
+            FormattedSegment::Text { text } => {
+                for character in text.chars() {
+                    match character {
+                        '{' => content.push_str("{{"),
+                        '}' => content.push_str("}}"),
+                        _ => content.push(character),
+                    }
+                }
+            }
+            FormattedSegment::Interpolation { index } if *index == next => {
+                let Some(interpolation) = interpolations.get(next) else {
+                    return Err(reordered_interpolations());
+                };
+                content.push_str(&interpolation.raw_source);
+                next += 1;
+            }
+            FormattedSegment::Interpolation { .. } => return Err(reordered_interpolations()),
+        }
+    }
+    if next != interpolations.len() {
+        return Err(reordered_interpolations());
+    }
+    Ok(content)
+}
+
+fn reordered_interpolations() -> anyhow::Error {
+    anyhow::anyhow!("Formatted template must keep every interpolation once, in its original order")
+}
+
+fn check_range(template: &TemplateStringInfo, range: &Range<usize>) -> Result<()> {
+    if range.start > range.end
+        || !template.content.is_char_boundary(range.start)
+        || !template.content.is_char_boundary(range.end)
+    {
+        return Err(anyhow::anyhow!(
+            "Range {}..{} is outside the template",
+            range.start,
+            range.end
+        ));
+    }
+    Ok(())
+}
+
+fn content_location(template: &TemplateStringInfo, range: Range<usize>) -> Result<Location> {
+    check_range(template, &range)?;
+    let ((start_line, start_column), (end_line, end_column)) =
+        template.map_content_range_to_document(range.start, range.end);
+    Ok(Location {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    })
+}
+
+fn lint_diagnostic(
+    path: &Path,
+    language: &str,
+    rule: &str,
+    severity: LintSeverity,
+    message: String,
+    location: Location,
+) -> LintDiagnostic {
+    LintDiagnostic {
+        rule: rule.to_string(),
+        severity,
+        language: Some(language.to_string()),
+        message,
+        file: path.to_path_buf(),
+        start_line: location.start_line,
+        start_column: location.start_column,
+        end_line: location.end_line,
+        end_column: location.end_column,
+        expected_type: None,
+        found_type: None,
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+    use crate::formatting::{FormatOptions, apply_template_edits, format_document_with_options};
+    use crate::highlighter::TemplateHighlighter;
+    use crate::lint::lint_source_with_config;
+    use crate::parser::TemplateStringParser;
+
+    /// A plugin for a tiny access-rule language: lines start with `allow` or
+    /// `deny`, which it highlights, and it formats by collapsing spaces.
+    const RULES_PLUGIN: &str = r#"import json, re, sys
+
+for line in sys.stdin:
+    request = json.loads(line)
+    segments = request["segments"]
+    text = "".join(s["text"] if s["type"] == "text" else "{}" for s in segments).encode()
+    response = {"id": request["id"]}
+    if request["op"] == "check":
+        response["diagnostics"] = [
+            {"rule": "rules-unknown-action", "severity": "warning",
+             "message": "Unknown action " + m.group(1).decode(), "start": m.start(1), "end": m.end(1)}
+            for m in re.finditer(rb"^ *(?!allow\b|deny\b)(\w+)", text, re.M)
+        ]
+    elif request["op"] == "format":
+        response["segments"] = [
+            {"type": "text", "text": re.sub(" +", " ", s["text"])} if s["type"] == "text"
+            else {"type": "interpolation", "index": s["index"]}
+            for s in segments
+        ]
+    else:
+        response["tokens"] = [
+            {"start": m.start(), "end": m.end(), "type": "keyword"}
+            for m in re.finditer(rb"\b(allow|deny)\b", text)
+        ]
+    print(json.dumps(response), flush=True)
+"#;
+
+    const SOURCE: &str = "from typing import Annotated\nfrom string.templatelib import Template\n\nuser = \"ada\"\nacl: Annotated[Template, \"rules\"] = t\"\"\"allow  {user}  read\ngrant {user}\n\"\"\"\n";
+
+    fn project(root: &Path) -> ProjectConfig {
+        fs::write(root.join("rules_plugin.py"), RULES_PLUGIN).unwrap();
+        let mut config = ProjectConfig {
+            root: root.to_path_buf(),
+            ..ProjectConfig::default()
+        };
+        config.plugins.insert(
+            "rules".to_string(),
+            PluginConfig {
+                command: vec!["python3".to_string(), "rules_plugin.py".to_string()],
+                timeout: None,
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn plugin_checks_formats_and_highlights_templates() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = project(temp.path());
+
+        let diagnostics = lint_source_with_config(Path::new("acl.py"), SOURCE, &config)
+            .unwrap()
+            .diagnostics;
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (
+                    diagnostic.rule.as_str(),
+                    diagnostic.message.as_str(),
+                    diagnostic.start_line,
+                    diagnostic.start_column,
+                    diagnostic.severity,
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "rules-unknown-action",
+                "Unknown action grant",
+                6,
+                1,
+                LintSeverity::Warning
+            )]
+        );
+
+        let options = FormatOptions {
+            plugins: LanguagePlugins::from_config(&config),
+            ..FormatOptions::default()
+        };
+        let edits = format_document_with_options(SOURCE, &options).unwrap();
+        assert_eq!(
+            apply_template_edits(SOURCE, &edits).unwrap(),
+            SOURCE.replace("allow  {user}  read", "allow {user} read")
+        );
+
+        let template = TemplateStringParser::new()
+            .unwrap()
+            .find_template_strings(SOURCE)
+            .unwrap()
+            .remove(0);
+        let mut highlighter = TemplateHighlighter::new().unwrap();
+        assert!(!highlighter.supports_language("rules"));
+        highlighter.set_plugins(LanguagePlugins::from_config(&config));
+        assert!(highlighter.supports_language("rules"));
+        let ranges = highlighter.highlight_template(&template).unwrap();
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| (
+                    range.start_byte,
+                    range.end_byte,
+                    range.highlight_name.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 5, "keyword"),
+                (7, 9, "variable.parameter"),
+                (22, 24, "variable.parameter")
+            ]
+        );
+    }
+
+    #[test]
+    fn plugin_failures_are_reported_on_the_template() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut config = project(temp.path());
+        config.plugins.get_mut("rules").unwrap().command = vec!["./missing-plugin".to_string()];
+
+        let diagnostics = lint_source_with_config(Path::new("acl.py"), SOURCE, &config)
+            .unwrap()
+            .diagnostics;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "plugin-error");
+        assert!(
+            diagnostics[0].message.starts_with(
+                "Language plugin for 'rules' failed: Failed to start ./missing-plugin"
+            )
+        );
+        assert_eq!(diagnostics[0].start_line, 5);
+
+        let options = FormatOptions {
+            plugins: LanguagePlugins::from_config(&config),
+            ..FormatOptions::default()
+        };
+        assert!(format_document_with_options(SOURCE, &options).is_err());
+    }
+}
@@ -16,6 +16,12 @@ pub struct ProjectConfig {
     pub severity: HashMap<String, RuleSeverity>,
     pub per_file_ignores: HashMap<String, Vec<String>>,
     pub sql: SqlConfig,
+    pub graphql: GraphqlConfig,
+    pub format: FormatConfig,
+    pub schema_files: HashMap<String, String>,
+    pub interpolation: HashMap<String, InterpolationConfig>,
+    pub plugins: HashMap<String, PluginConfig>,
+    pub grammars: HashMap<String, GrammarConfig>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
@@ -41,10 +47,50 @@ impl<'de> Deserialize<'de> for RuleSeverity {
     }
 }
 
+/// The SQL dialect used to parse and highlight `sql` templates, selecting the
+/// bind placeholders and dialect-only syntax that are accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SqlDialect {
+    Postgres,
+    Sqlite,
+    Mysql,
+    #[default]
+    Generic,
+}
+
+impl std::str::FromStr for SqlDialect {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            "sqlite" => Ok(Self::Sqlite),
+            "mysql" | "mariadb" => Ok(Self::Mysql),
+            "generic" => Ok(Self::Generic),
+            _ => Err(format!(
+                "invalid SQL dialect `{value}`; expected `postgres`, `sqlite`, `mysql`, or `generic`"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SqlDialect {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct SqlConfig {
     pub library: Option<String>,
+    pub dialect: Option<SqlDialect>,
     #[serde(alias = "databaseUrl")]
     pub database_url: Option<String>,
     #[serde(alias = "searchPath")]
@@ -53,6 +99,255 @@ pub struct SqlConfig {
     pub extra_param_types: Vec<String>,
 }
 
+/// Settings for `graphql` templates, read from `[tool.t-linter.graphql]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GraphqlConfig {
+    /// A GraphQL SDL file that fields, arguments, and fragment types are
+    /// checked against, relative to the project root or the linted file.
+    pub schema: Option<String>,
+}
+
+/// Per-language formatter settings, read from `[tool.t-linter.format.<language>]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct FormatConfig {
+    pub css: CssFormatConfig,
+    pub javascript: JavascriptFormatConfig,
+    pub sql: SqlFormatConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CssFormatConfig {
+    pub indent_width: usize,
+    pub quote_style: QuoteStyle,
+    pub declaration_order: DeclarationOrder,
+}
+
+impl Default for CssFormatConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            quote_style: QuoteStyle::Double,
+            declaration_order: DeclarationOrder::Preserve,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct JavascriptFormatConfig {
+    pub indent_width: usize,
+    pub semicolons: Semicolons,
+    pub quote_style: QuoteStyle,
+}
+
+impl Default for JavascriptFormatConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: 2,
+            semicolons: Semicolons::Always,
+            quote_style: QuoteStyle::Double,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Semicolons {
+    #[default]
+    Always,
+    AsNeeded,
+}
+
+impl<'de> Deserialize<'de> for Semicolons {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "always" => Ok(Self::Always),
+            "as-needed" => Ok(Self::AsNeeded),
+            _ => Err(serde::de::Error::custom(format!(
+                "invalid semicolons setting `{value}`; expected `always` or `as-needed`"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuoteStyle {
+    #[default]
+    Double,
+    Single,
+    Preserve,
+}
+
+impl<'de> Deserialize<'de> for QuoteStyle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "double" => Ok(Self::Double),
+            "single" => Ok(Self::Single),
+            "preserve" => Ok(Self::Preserve),
+            _ => Err(serde::de::Error::custom(format!(
+                "invalid quote style `{value}`; expected `double`, `single`, or `preserve`"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DeclarationOrder {
+    #[default]
+    Preserve,
+    Alphabetical,
+}
+
+impl<'de> Deserialize<'de> for DeclarationOrder {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "preserve" => Ok(Self::Preserve),
+            "alphabetical" => Ok(Self::Alphabetical),
+            _ => Err(serde::de::Error::custom(format!(
+                "invalid declaration order `{value}`; expected `preserve` or `alphabetical`"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SqlFormatConfig {
+    pub keyword_case: KeywordCase,
+    pub indent_width: usize,
+    pub comma_style: CommaStyle,
+}
+
+impl Default for SqlFormatConfig {
+    fn default() -> Self {
+        Self {
+            keyword_case: KeywordCase::Upper,
+            indent_width: 4,
+            comma_style: CommaStyle::Trailing,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeywordCase {
+    #[default]
+    Upper,
+    Lower,
+    Preserve,
+}
+
+impl<'de> Deserialize<'de> for KeywordCase {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "upper" => Ok(Self::Upper),
+            "lower" => Ok(Self::Lower),
+            "preserve" => Ok(Self::Preserve),
+            _ => Err(serde::de::Error::custom(format!(
+                "invalid keyword case `{value}`; expected `upper`, `lower`, or `preserve`"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommaStyle {
+    #[default]
+    Trailing,
+    Leading,
+}
+
+impl<'de> Deserialize<'de> for CommaStyle {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "trailing" => Ok(Self::Trailing),
+            "leading" => Ok(Self::Leading),
+            _ => Err(serde::de::Error::custom(format!(
+                "invalid comma style `{value}`; expected `trailing` or `leading`"
+            ))),
+        }
+    }
+}
+
+/// Per-language allowlist for interpolation conversions, debug specifiers and
+/// format specs, read from `[tool.t-linter.interpolation.<language>]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct InterpolationConfig {
+    pub allowed_conversions: Vec<String>,
+    pub allow_debug: bool,
+    pub allowed_format_specs: Vec<String>,
+}
+
+impl InterpolationConfig {
+    pub fn allows_conversion(&self, conversion: &str) -> bool {
+        self.allowed_conversions
+            .iter()
+            .any(|allowed| allowed.trim_start_matches('!') == conversion)
+    }
+
+    pub fn allows_format_spec(&self, spec: &str) -> bool {
+        self.allowed_format_specs
+            .iter()
+            .any(|allowed| allowed == spec)
+    }
+}
+
+/// An external command that checks, formats, and highlights templates of one
+/// language, read from `[tool.t-linter.plugins.<language>]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PluginConfig {
+    /// The program and its arguments, run from the project root.
+    pub command: Vec<String>,
+    /// Seconds to wait for each response before the plugin is stopped.
+    pub timeout: Option<u64>,
+}
+
+/// A Tree-sitter grammar loaded from a shared library at runtime, read from
+/// `[tool.t-linter.grammars.<language>]`. Paths are relative to the project
+/// root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GrammarConfig {
+    /// The compiled grammar (`.so`, `.dylib`, or `.dll`).
+    pub library: Option<String>,
+    /// The exported language function, `tree_sitter_<language>` by default.
+    pub symbol: Option<String>,
+    /// A `highlights.scm` query file.
+    pub highlights: Option<String>,
+    /// An `injections.scm` query file.
+    pub injections: Option<String>,
+    /// Text parsed in place of each interpolation, `t_linter_expr` by default.
+    pub placeholder: Option<String>,
+}
+
 #[derive(Debug, Default, serde::Deserialize)]
 struct PyprojectToml {
     tool: Option<ToolSection>,
@@ -82,6 +377,13 @@ struct TLinterConfig {
     #[serde(rename = "per-file-ignores")]
     per_file_ignores: Option<HashMap<String, Vec<String>>>,
     sql: Option<SqlConfig>,
+    graphql: Option<GraphqlConfig>,
+    format: Option<FormatConfig>,
+    #[serde(rename = "schema-files")]
+    schema_files: Option<HashMap<String, String>>,
+    interpolation: Option<HashMap<String, InterpolationConfig>>,
+    plugins: Option<HashMap<String, PluginConfig>>,
+    grammars: Option<HashMap<String, GrammarConfig>>,
 }
 
 pub fn load_project_config_for_path(path: &Path) -> Result<ProjectConfig> {
@@ -120,6 +422,27 @@ pub fn load_project_config(root: &Path) -> Result<ProjectConfig> {
         severity: config.severity.unwrap_or_default(),
         per_file_ignores: config.per_file_ignores.unwrap_or_default(),
         sql: config.sql.unwrap_or_default(),
+        graphql: config.graphql.unwrap_or_default(),
+        format: config.format.unwrap_or_default(),
+        schema_files: config.schema_files.unwrap_or_default(),
+        interpolation: config
+            .interpolation
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(language, config)| (language.to_ascii_lowercase(), config))
+            .collect(),
+        plugins: config
+            .plugins
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(language, config)| (language.to_ascii_lowercase(), config))
+            .collect(),
+        grammars: config
+            .grammars
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(language, config)| (language.to_ascii_lowercase(), config))
+            .collect(),
     })
 }
 
@@ -178,6 +501,201 @@ mod tests {
         assert_eq!(config.sql.database_url.as_deref(), Some("env:DATABASE_URL"));
         assert_eq!(config.sql.search_path.as_deref(), Some("public"));
         assert_eq!(config.sql.extra_param_types, vec!["myapp.Money"]);
+        assert_eq!(config.sql.dialect, None);
+    }
+
+    #[test]
+    fn load_project_config_reads_graphql_schema() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.graphql]\nschema = \"api/schema.graphql\"\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+
+        assert_eq!(config.graphql.schema.as_deref(), Some("api/schema.graphql"));
+    }
+
+    #[test]
+    fn load_project_config_reads_language_plugins() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.plugins.RuleDSL]\ncommand = [\"python\", \"tools/ruledsl.py\"]\ntimeout = 5\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+
+        assert_eq!(
+            config.plugins.get("ruledsl"),
+            Some(&PluginConfig {
+                command: vec!["python".to_string(), "tools/ruledsl.py".to_string()],
+                timeout: Some(5),
+            })
+        );
+    }
+
+    #[test]
+    fn load_project_config_reads_runtime_grammars() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.grammars.Lua]\nlibrary = \"grammars/lua.so\"\nhighlights = \"grammars/lua/highlights.scm\"\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+
+        assert_eq!(
+            config.grammars.get("lua"),
+            Some(&GrammarConfig {
+                library: Some("grammars/lua.so".to_string()),
+                highlights: Some("grammars/lua/highlights.scm".to_string()),
+                ..GrammarConfig::default()
+            })
+        );
+    }
+
+    #[test]
+    fn load_project_config_reads_sql_dialect() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.sql]\ndialect = \"postgresql\"\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+        assert_eq!(config.sql.dialect, Some(SqlDialect::Postgres));
+    }
+
+    #[test]
+    fn load_project_config_reads_sql_format_settings() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.format.sql]\nkeyword-case = \"lower\"\ncomma-style = \"leading\"\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+
+        assert_eq!(
+            config.format.sql,
+            SqlFormatConfig {
+                keyword_case: KeywordCase::Lower,
+                indent_width: 4,
+                comma_style: CommaStyle::Leading,
+            }
+        );
+        assert_eq!(
+            load_project_config(tempfile::tempdir().expect("tempdir").path())
+                .expect("load config")
+                .format
+                .sql,
+            SqlFormatConfig::default()
+        );
+    }
+
+    #[test]
+    fn load_project_config_reads_css_format_settings() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.format.css]\nquote-style = \"single\"\ndeclaration-order = \"alphabetical\"\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+
+        assert_eq!(
+            config.format.css,
+            CssFormatConfig {
+                indent_width: 2,
+                quote_style: QuoteStyle::Single,
+                declaration_order: DeclarationOrder::Alphabetical,
+            }
+        );
+
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.format.css]\ndeclaration-order = \"random\"\n",
+        )
+        .expect("write pyproject");
+        let error = load_project_config(temp.path()).expect_err("invalid order");
+        assert!(format!("{error:#}").contains("invalid declaration order `random`"));
+    }
+
+    #[test]
+    fn load_project_config_reads_javascript_format_settings() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.format.javascript]\nindent-width = 4\nsemicolons = \"as-needed\"\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+
+        assert_eq!(
+            config.format.javascript,
+            JavascriptFormatConfig {
+                indent_width: 4,
+                semicolons: Semicolons::AsNeeded,
+                quote_style: QuoteStyle::Double,
+            }
+        );
+    }
+
+    #[test]
+    fn load_project_config_rejects_unknown_sql_dialect() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.sql]\ndialect = \"oracle\"\n",
+        )
+        .expect("write pyproject");
+
+        let error = load_project_config(temp.path()).expect_err("invalid dialect");
+        assert!(format!("{error:#}").contains("invalid SQL dialect `oracle`"));
+    }
+
+    #[test]
+    fn load_project_config_reads_schema_files() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.schema-files]\nOrder = \"schemas/order.schema.json\"\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+
+        assert_eq!(
+            config.schema_files.get("Order").map(String::as_str),
+            Some("schemas/order.schema.json")
+        );
+    }
+
+    #[test]
+    fn load_project_config_reads_interpolation_allowlists() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.interpolation.HTML]\nallowed-conversions = [\"!r\"]\nallow-debug = true\nallowed-format-specs = [\"upper\"]\n",
+        )
+        .expect("write pyproject");
+
+        let config = load_project_config(temp.path()).expect("load config");
+        let html = config.interpolation.get("html").expect("html allowlist");
+
+        assert!(html.allows_conversion("r"));
+        assert!(!html.allows_conversion("a"));
+        assert!(html.allow_debug);
+        assert!(html.allows_format_spec("upper"));
     }
 
     #[test]
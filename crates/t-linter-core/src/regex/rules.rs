@@ -0,0 +1,162 @@
+use std::ops::Range;
+use std::path::Path;
+
+use crate::lint::{LintDiagnostic, LintSeverity, RULE_EMBEDDED_PARSE_ERROR};
+use crate::parser::{InterpolationInfo, Location, TemplatePart, TemplateStringInfo};
+
+use super::syntax::{self, RegexSource, TokenKind};
+
+const RULE_UNESCAPED_INTERPOLATION: &str = "regex-unescaped-interpolation";
+
+/// Reports patterns `re.compile` would reject, and interpolations spliced
+/// into the pattern without `re.escape`, whose metacharacters would change
+/// what the pattern matches.
+pub fn lint_rules(path: &Path, template: &TemplateStringInfo) -> Vec<LintDiagnostic> {
+    let source = RegexSource::from_template(template);
+    let pattern = syntax::parse(&source);
+    let mut diagnostics = Vec::new();
+
+    if let Some(error) = &pattern.error {
+        diagnostics.push(diagnostic(
+            path,
+            RULE_EMBEDDED_PARSE_ERROR,
+            LintSeverity::Error,
+            format!("Invalid regex syntax in template string: {}", error.message),
+            content_location(template, &error.range),
+        ));
+    }
+
+    let counts = pattern
+        .tokens
+        .iter()
+        .filter(|(_, kind)| *kind == TokenKind::Quantifier)
+        .map(|(range, _)| range)
+        .collect::<Vec<_>>();
+    let interpolations = template.parts.iter().filter_map(|part| match part {
+        TemplatePart::Interpolation(interpolation) => Some(interpolation),
+        TemplatePart::Static(_) => None,
+    });
+    for (&start, interpolation) in source.atoms.keys().zip(interpolations) {
+        let in_count = counts
+            .iter()
+            .any(|range| range.start < start && start < range.end);
+        if in_count || is_regex_escaped(interpolation) {
+            continue;
+        }
+        diagnostics.push(diagnostic(
+            path,
+            RULE_UNESCAPED_INTERPOLATION,
+            LintSeverity::Warning,
+            format!(
+                "Interpolation '{}' is spliced into the pattern unescaped, so `.`, `*`, `(` and other metacharacters in the value change what it matches; wrap it in `re.escape(...)` or use the `:escape` format spec",
+                interpolation.expression
+            ),
+            interpolation.location.clone(),
+        ));
+    }
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.start_line, diagnostic.start_column));
+    diagnostics
+}
+
+/// Whether the interpolation is escaped with the `:escape` format spec or a
+/// call such as `re.escape(name)`.
+fn is_regex_escaped(interpolation: &InterpolationInfo) -> bool {
+    if interpolation.format_spec.trim() == "escape" {
+        return true;
+    }
+    let expression = interpolation.expression.trim();
+    let Some((callee, _)) = expression.split_once('(') else {
+        return false;
+    };
+    expression.ends_with(')') && callee.trim().rsplit('.').next() == Some("escape")
+}
+
+fn content_location(template: &TemplateStringInfo, range: &Range<usize>) -> Location {
+    let ((start_line, start_column), (end_line, end_column)) =
+        template.map_content_range_to_document(range.start, range.end);
+    Location {
+        start_line,
+        start_column,
+        end_line,
+        end_column,
+    }
+}
+
+fn diagnostic(
+    path: &Path,
+    rule: &str,
+    severity: LintSeverity,
+    message: String,
+    location: Location,
+) -> LintDiagnostic {
+    LintDiagnostic {
+        rule: rule.to_string(),
+        severity,
+        language: Some("regex".to_string()),
+        message,
+        file: path.to_path_buf(),
+        start_line: location.start_line,
+        start_column: location.start_column,
+        end_line: location.end_line,
+        end_column: location.end_column,
+        expected_type: None,
+        found_type: None,
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::lint::lint_source;
+
+    fn regex_diagnostics(body: &str) -> Vec<(String, String)> {
+        let source = format!(
+            "import re\nfrom typing import Annotated\nfrom string.templatelib import Template\n\nword = suffix = \"x\"\ncount = 3\npattern: Annotated[Template, \"regex\"] = t\"\"\"{body}\"\"\"\n"
+        );
+        lint_source(Path::new("patterns.py"), &source)
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn reports_python_re_syntax_errors() {
+        assert_eq!(
+            regex_diagnostics(r"(?P<year>\d{{4}})-(?P<year>\d{{2}})"),
+            vec![(
+                "embedded-parse-error".to_string(),
+                "Invalid regex syntax in template string: redefinition of group name 'year' as group 2; was group 1".to_string()
+            )]
+        );
+        assert_eq!(
+            regex_diagnostics(r"(?<=\w+)x")
+                .into_iter()
+                .map(|(_, message)| message)
+                .collect::<Vec<_>>(),
+            vec![
+                "Invalid regex syntax in template string: look-behind requires fixed-width pattern"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_interpolations_without_escaping() {
+        assert_eq!(
+            regex_diagnostics(r"^{word}\.{re.escape(suffix)}(?:{suffix:escape})\d{{{count}}}$")
+                .into_iter()
+                .map(|(rule, message)| (rule, message.split(',').next().unwrap().to_string()))
+                .collect::<Vec<_>>(),
+            vec![(
+                "regex-unescaped-interpolation".to_string(),
+                "Interpolation 'word' is spliced into the pattern unescaped".to_string()
+            )]
+        );
+    }
+}
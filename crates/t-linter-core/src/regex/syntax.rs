@@ -0,0 +1,1037 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use crate::parser::{TemplatePart, TemplateStringInfo};
+
+/// The template content, where every interpolation takes the two bytes of
+/// its `{}` placeholder.
+pub(crate) struct RegexSource {
+    pub(crate) text: String,
+    /// The start of each interpolation mapped to its end.
+    pub(crate) atoms: BTreeMap<usize, usize>,
+}
+
+impl RegexSource {
+    pub(crate) fn from_template(template: &TemplateStringInfo) -> Self {
+        let mut atoms = BTreeMap::new();
+        let mut offset = 0;
+        for part in &template.parts {
+            match part {
+                TemplatePart::Static(part) => offset += part.text.len(),
+                TemplatePart::Interpolation(_) => {
+                    atoms.insert(offset, offset + 2);
+                    offset += 2;
+                }
+            }
+        }
+        Self {
+            text: template.content.clone(),
+            atoms,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SyntaxError {
+    pub(crate) message: String,
+    pub(crate) range: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// Escapes such as `\d`, `\n`, and `\x41`.
+    Escape,
+    /// `^`, `$`, `.`, and zero-width escapes such as `\b` and `\A`.
+    Anchor,
+    Quantifier,
+    Alternation,
+    GroupBracket,
+    GroupName,
+    Backreference,
+    ClassBracket,
+    /// The `^` that negates a character class and the `-` of a range.
+    ClassOperator,
+    Flags,
+    Comment,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Pattern {
+    pub(crate) tokens: Vec<(Range<usize>, TokenKind)>,
+    pub(crate) error: Option<SyntaxError>,
+}
+
+/// Parses a pattern the way Python's `re` module does, stopping at the first
+/// error with the message `re.compile` would raise. Interpolations stand for
+/// any subpattern.
+pub(crate) fn parse(source: &RegexSource) -> Pattern {
+    let mut parser = Parser {
+        source,
+        bytes: source.text.as_bytes(),
+        offset: 0,
+        verbose: false,
+        at_start: true,
+        groups: 0,
+        open_groups: Vec::new(),
+        names: HashMap::new(),
+        conditional_groups: Vec::new(),
+        lookbehind_groups: None,
+        width_error: None,
+        tokens: Vec::new(),
+    };
+    let error = parser.pattern().err();
+    let mut tokens = parser.tokens;
+    tokens.sort_by_key(|(range, _)| range.start);
+    Pattern { tokens, error }
+}
+
+type ParseResult<T> = Result<T, SyntaxError>;
+
+/// The number of characters a subpattern matches, for checking that
+/// look-behind assertions have a fixed width.
+#[derive(Debug, Clone, Copy)]
+struct Width {
+    min: usize,
+    max: Option<usize>,
+    /// False when an interpolation or backreference makes the width
+    /// unknowable.
+    known: bool,
+}
+
+impl Width {
+    const ZERO: Self = Self {
+        min: 0,
+        max: Some(0),
+        known: true,
+    };
+    const ONE: Self = Self {
+        min: 1,
+        max: Some(1),
+        known: true,
+    };
+    const UNKNOWN: Self = Self {
+        min: 0,
+        max: None,
+        known: false,
+    };
+
+    fn then(self, next: Self) -> Self {
+        Self {
+            min: self.min + next.min,
+            max: self.max.zip(next.max).map(|(left, right)| left + right),
+            known: self.known && next.known,
+        }
+    }
+
+    fn or(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.zip(other.max).map(|(left, right)| left.max(right)),
+            known: self.known && other.known,
+        }
+    }
+
+    fn repeat(self, min: usize, max: Option<usize>) -> Self {
+        Self {
+            min: self.min.saturating_mul(min),
+            max: match (self.max, max) {
+                (Some(0), _) | (_, Some(0)) => Some(0),
+                (Some(width), Some(max)) => Some(width.saturating_mul(max)),
+                _ => None,
+            },
+            known: self.known,
+        }
+    }
+}
+
+/// A parsed `{m,n}` style repeat.
+struct Repeat {
+    min: usize,
+    max: Option<usize>,
+    len: usize,
+}
+
+struct Parser<'a> {
+    source: &'a RegexSource,
+    bytes: &'a [u8],
+    offset: usize,
+    /// Whether `(?x)` lets whitespace and `#` comments through.
+    verbose: bool,
+    /// Whether nothing but global flags has been parsed yet.
+    at_start: bool,
+    groups: usize,
+    open_groups: Vec<usize>,
+    names: HashMap<String, usize>,
+    /// Group numbers used by conditionals, which may refer to groups that
+    /// are defined later in the pattern.
+    conditional_groups: Vec<(usize, Range<usize>)>,
+    /// The number of groups opened before the outermost look-behind being
+    /// parsed, if any.
+    lookbehind_groups: Option<usize>,
+    /// The first look-behind without a fixed width, which Python only
+    /// reports once the whole pattern has parsed.
+    width_error: Option<SyntaxError>,
+    tokens: Vec<(Range<usize>, TokenKind)>,
+}
+
+impl Parser<'_> {
+    fn pattern(&mut self) -> ParseResult<()> {
+        self.alternation()?;
+        if self.offset < self.bytes.len() {
+            return Err(self.error("unbalanced parenthesis", self.offset..self.offset + 1));
+        }
+        for (group, range) in &self.conditional_groups {
+            if *group > self.groups {
+                return Err(self.error(format!("invalid group reference {group}"), range.clone()));
+            }
+        }
+        match self.width_error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn alternation(&mut self) -> ParseResult<Width> {
+        let mut width = self.sequence()?;
+        while self.peek() == Some(b'|') {
+            self.token(self.offset..self.offset + 1, TokenKind::Alternation);
+            self.offset += 1;
+            self.at_start = false;
+            width = width.or(self.sequence()?);
+        }
+        Ok(width)
+    }
+
+    fn sequence(&mut self) -> ParseResult<Width> {
+        let mut width = Width::ZERO;
+        loop {
+            self.skip_verbose();
+            match self.peek() {
+                None | Some(b'|' | b')') => return Ok(width),
+                Some(_) => {}
+            }
+            let (item, repeatable) = self.item()?;
+            width = width.then(self.quantifiers(item, repeatable)?);
+        }
+    }
+
+    /// Applies the quantifier after an item, if any.
+    fn quantifiers(&mut self, item: Width, repeatable: bool) -> ParseResult<Width> {
+        self.skip_verbose();
+        let Some(repeat) = self.repeat_at(self.offset) else {
+            return Ok(item);
+        };
+        let start = self.offset;
+        self.check_repeat(&repeat, start)?;
+        if !repeatable {
+            return Err(self.error("nothing to repeat", start..start + repeat.len));
+        }
+        self.offset += repeat.len;
+        if matches!(self.peek(), Some(b'?' | b'+')) {
+            self.offset += 1;
+        }
+        self.token(start..self.offset, TokenKind::Quantifier);
+        self.skip_verbose();
+        if let Some(next) = self.repeat_at(self.offset) {
+            self.check_repeat(&next, self.offset)?;
+            return Err(self.error("multiple repeat", self.offset..self.offset + next.len));
+        }
+        Ok(item.repeat(repeat.min, repeat.max))
+    }
+
+    fn check_repeat(&self, repeat: &Repeat, start: usize) -> ParseResult<()> {
+        if repeat.max.is_some_and(|max| max < repeat.min) {
+            return Err(self.error(
+                "min repeat greater than max repeat",
+                start..start + repeat.len,
+            ));
+        }
+        Ok(())
+    }
+
+    /// The quantifier starting at `offset`. A `{` that does not start a
+    /// valid `{m,n}` repeat is a literal.
+    fn repeat_at(&self, offset: usize) -> Option<Repeat> {
+        match self.bytes.get(offset)? {
+            b'*' => Some(Repeat {
+                min: 0,
+                max: None,
+                len: 1,
+            }),
+            b'+' => Some(Repeat {
+                min: 1,
+                max: None,
+                len: 1,
+            }),
+            b'?' => Some(Repeat {
+                min: 0,
+                max: Some(1),
+                len: 1,
+            }),
+            b'{' if !self.source.atoms.contains_key(&offset) => {
+                let mut end = offset + 1;
+                let mut dynamic = false;
+                let mut bound = |end: &mut usize| {
+                    let start = *end;
+                    loop {
+                        if let Some(&atom_end) = self.source.atoms.get(end) {
+                            dynamic = true;
+                            *end = atom_end;
+                        } else if self.bytes.get(*end).is_some_and(u8::is_ascii_digit) {
+                            *end += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    self.source.text[start..*end].parse::<usize>().ok()
+                };
+                let low = bound(&mut end);
+                let (high, comma) = if self.bytes.get(end) == Some(&b',') {
+                    end += 1;
+                    (bound(&mut end), true)
+                } else {
+                    (low, false)
+                };
+                if self.bytes.get(end) != Some(&b'}') || (end == offset + 1) {
+                    return None;
+                }
+                if dynamic {
+                    return Some(Repeat {
+                        min: 0,
+                        max: None,
+                        len: end + 1 - offset,
+                    });
+                }
+                Some(Repeat {
+                    min: low.unwrap_or(0),
+                    max: if comma { high } else { low },
+                    len: end + 1 - offset,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses one item, returning its width and whether a quantifier may
+    /// follow it.
+    fn item(&mut self) -> ParseResult<(Width, bool)> {
+        let start = self.offset;
+        if let Some(&end) = self.source.atoms.get(&start) {
+            self.offset = end;
+            self.at_start = false;
+            return Ok((Width::UNKNOWN, true));
+        }
+        let byte = self.bytes[start];
+        if byte != b'(' {
+            self.at_start = false;
+        }
+        match byte {
+            b'(' => self.group(),
+            b'[' => self.class().map(|()| (Width::ONE, true)),
+            b'\\' => self.escape(),
+            b'.' => {
+                self.offset += 1;
+                self.token(start..self.offset, TokenKind::Anchor);
+                Ok((Width::ONE, true))
+            }
+            b'^' | b'$' => {
+                self.offset += 1;
+                self.token(start..self.offset, TokenKind::Anchor);
+                Ok((Width::ZERO, false))
+            }
+            _ => match self.repeat_at(start) {
+                Some(repeat) => {
+                    self.check_repeat(&repeat, start)?;
+                    Err(self.error("nothing to repeat", start..start + repeat.len))
+                }
+                None => {
+                    self.offset += self.char_len(start);
+                    Ok((Width::ONE, true))
+                }
+            },
+        }
+    }
+
+    fn escape(&mut self) -> ParseResult<(Width, bool)> {
+        let start = self.offset;
+        let Some(&next) = self.bytes.get(start + 1) else {
+            return Err(self.error("bad escape (end of pattern)", start..start + 1));
+        };
+        if let Some(&end) = self.source.atoms.get(&(start + 1)) {
+            self.offset = end;
+            return Ok((Width::UNKNOWN, true));
+        }
+        let width = match next {
+            b'1'..=b'9' => {
+                if let Some(end) = self.octal_escape(start)? {
+                    self.offset = end;
+                    Width::ONE
+                } else {
+                    let digits =
+                        1 + usize::from(self.bytes.get(start + 2).is_some_and(u8::is_ascii_digit));
+                    let end = start + 1 + digits;
+                    let group = self.source.text[start + 1..end]
+                        .parse::<usize>()
+                        .unwrap_or(0);
+                    self.check_group_reference(group, start..end)?;
+                    self.offset = end;
+                    self.token(start..end, TokenKind::Backreference);
+                    return Ok((Width::UNKNOWN, true));
+                }
+            }
+            b'b' | b'B' | b'A' | b'Z' | b'z' => {
+                self.offset = start + 2;
+                self.token(start..self.offset, TokenKind::Anchor);
+                return Ok((Width::ZERO, false));
+            }
+            _ => {
+                self.offset = self.char_escape(start, false)?.1;
+                Width::ONE
+            }
+        };
+        self.token(start..self.offset, TokenKind::Escape);
+        Ok((width, true))
+    }
+
+    /// An escape that stands for characters, returning the character, if it
+    /// is a single one, and where the escape ends.
+    fn char_escape(&self, start: usize, in_class: bool) -> ParseResult<(Option<char>, usize)> {
+        let next = self.bytes[start + 1];
+        let simple = |character: char| Ok((Some(character), start + 2));
+        match next {
+            b'd' | b'D' | b's' | b'S' | b'w' | b'W' => Ok((None, start + 2)),
+            b'a' => simple('\x07'),
+            b'f' => simple('\x0c'),
+            b'n' => simple('\n'),
+            b'r' => simple('\r'),
+            b't' => simple('\t'),
+            b'v' => simple('\x0b'),
+            b'b' if in_class => simple('\x08'),
+            b'x' => self.hex_escape(start, 2),
+            b'u' => self.hex_escape(start, 4),
+            b'U' => self.hex_escape(start, 8),
+            b'N' => self.named_escape(start),
+            b'0'..=b'7' => {
+                let end = start
+                    + 2
+                    + self.bytes[start + 2..]
+                        .iter()
+                        .take(2)
+                        .take_while(|byte| matches!(byte, b'0'..=b'7'))
+                        .count();
+                let value = u32::from_str_radix(&self.source.text[start + 1..end], 8).unwrap_or(0);
+                if value > 0o377 {
+                    return Err(self.error(
+                        format!(
+                            "octal escape value {} outside of range 0-0o377",
+                            &self.source.text[start..end]
+                        ),
+                        start..end,
+                    ));
+                }
+                Ok((char::from_u32(value), end))
+            }
+            byte if byte.is_ascii_alphanumeric() => {
+                Err(self.error(format!("bad escape \\{}", byte as char), start..start + 2))
+            }
+            _ => {
+                let end = start + 1 + self.char_len(start + 1);
+                Ok((self.source.text[start + 1..end].chars().next(), end))
+            }
+        }
+    }
+
+    /// A three-digit octal escape such as `\101` outside a class, where
+    /// shorter digit runs are group references.
+    fn octal_escape(&self, start: usize) -> ParseResult<Option<usize>> {
+        let digits = &self.bytes[start + 1..(start + 4).min(self.bytes.len())];
+        if digits.len() == 3 && digits.iter().all(|byte| matches!(byte, b'0'..=b'7')) {
+            return self.char_escape(start, false).map(|(_, end)| Some(end));
+        }
+        Ok(None)
+    }
+
+    fn hex_escape(&self, start: usize, len: usize) -> ParseResult<(Option<char>, usize)> {
+        let digits = self.bytes[start + 2..]
+            .iter()
+            .take(len)
+            .take_while(|byte| byte.is_ascii_hexdigit())
+            .count();
+        let end = start + 2 + digits;
+        if digits < len {
+            return Err(self.error(
+                format!("incomplete escape {}", &self.source.text[start..end]),
+                start..end,
+            ));
+        }
+        let value = u32::from_str_radix(&self.source.text[start + 2..end], 16).unwrap_or(0);
+        match char::from_u32(value) {
+            Some(character) => Ok((Some(character), end)),
+            None if value > 0x10FFFF => Err(self.error(
+                format!("bad escape {}", &self.source.text[start..end]),
+                start..end,
+            )),
+            None => Ok((None, end)),
+        }
+    }
+
+    fn named_escape(&self, start: usize) -> ParseResult<(Option<char>, usize)> {
+        if self.bytes.get(start + 2) != Some(&b'{') {
+            return Err(self.error("missing {", start..start + 2));
+        }
+        let Some(close) = self.source.text[start + 3..].find('}') else {
+            return Err(self.error("missing }", start..self.bytes.len()));
+        };
+        let end = start + 3 + close + 1;
+        let name = &self.source.text[start + 3..end - 1];
+        if name.is_empty() {
+            return Err(self.error("missing character name", start..end));
+        }
+        if self.source.atoms.range(start..end).next().is_some() {
+            return Ok((None, end));
+        }
+        match unicode_names2::character(name) {
+            Some(character) => Ok((Some(character), end)),
+            None => Err(self.error(format!("undefined character name '{name}'"), start..end)),
+        }
+    }
+
+    fn class(&mut self) -> ParseResult<()> {
+        let start = self.offset;
+        self.token(start..start + 1, TokenKind::ClassBracket);
+        self.offset += 1;
+        if self.peek() == Some(b'^') {
+            self.token(self.offset..self.offset + 1, TokenKind::ClassOperator);
+            self.offset += 1;
+        }
+        let mut first = true;
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(self.error("unterminated character set", start..start + 1));
+            };
+            if byte == b']' && !first {
+                self.token(self.offset..self.offset + 1, TokenKind::ClassBracket);
+                self.offset += 1;
+                return Ok(());
+            }
+            first = false;
+            let item_start = self.offset;
+            let Some(low) = self.class_item()? else {
+                continue;
+            };
+            if self.peek() == Some(b'-')
+                && self
+                    .bytes
+                    .get(self.offset + 1)
+                    .is_some_and(|&byte| byte != b']')
+            {
+                let dash = self.offset;
+                self.offset += 1;
+                let high = self.class_item()?;
+                let range_text = &self.source.text[item_start..self.offset];
+                match (low, high) {
+                    (ClassItem::Char(low), Some(ClassItem::Char(high))) if low > high => {
+                        return Err(self.error(
+                            format!("bad character range {range_text}"),
+                            item_start..self.offset,
+                        ));
+                    }
+                    (ClassItem::Set, _) | (_, Some(ClassItem::Set)) => {
+                        return Err(self.error(
+                            format!("bad character range {range_text}"),
+                            item_start..self.offset,
+                        ));
+                    }
+                    _ => self.token(dash..dash + 1, TokenKind::ClassOperator),
+                }
+            }
+        }
+    }
+
+    /// One member of a character class. Interpolations are `None`, since
+    /// they may stand for any members.
+    fn class_item(&mut self) -> ParseResult<Option<ClassItem>> {
+        let start = self.offset;
+        if let Some(&end) = self.source.atoms.get(&start) {
+            self.offset = end;
+            return Ok(None);
+        }
+        if self.bytes[start] != b'\\' {
+            self.offset += self.char_len(start);
+            return Ok(self.source.text[start..self.offset]
+                .chars()
+                .next()
+                .map(ClassItem::Char));
+        }
+        let Some(&next) = self.bytes.get(start + 1) else {
+            return Err(self.error("bad escape (end of pattern)", start..start + 1));
+        };
+        if next.is_ascii_digit() && !matches!(next, b'0'..=b'7') {
+            return Err(self.error(format!("bad escape \\{}", next as char), start..start + 2));
+        }
+        let (character, end) = self.char_escape(start, true)?;
+        self.offset = end;
+        self.token(start..end, TokenKind::Escape);
+        Ok(Some(match character {
+            Some(character) => ClassItem::Char(character),
+            None => ClassItem::Set,
+        }))
+    }
+
+    fn group(&mut self) -> ParseResult<(Width, bool)> {
+        let start = self.offset;
+        self.offset += 1;
+        if self.peek() != Some(b'?') {
+            self.at_start = false;
+            self.groups += 1;
+            let group = self.groups;
+            self.token(start..start + 1, TokenKind::GroupBracket);
+            self.open_groups.push(group);
+            let width = self.alternation()?;
+            self.open_groups.pop();
+            self.close_group(start)?;
+            return Ok((width, true));
+        }
+
+        self.offset += 1;
+        let Some(kind) = self.peek() else {
+            return Err(self.error("unexpected end of pattern", start..self.offset));
+        };
+        if !matches!(kind, b'a' | b'i' | b'L' | b'm' | b's' | b'u' | b'x' | b'-') {
+            self.at_start = false;
+        }
+        match kind {
+            b':' | b'>' => {
+                self.offset += 1;
+                self.token(start..self.offset, TokenKind::GroupBracket);
+                let width = self.alternation()?;
+                self.close_group(start)?;
+                Ok((width, true))
+            }
+            b'=' | b'!' => {
+                self.offset += 1;
+                self.token(start..self.offset, TokenKind::GroupBracket);
+                self.alternation()?;
+                self.close_group(start)?;
+                Ok((Width::ZERO, true))
+            }
+            b'<' if matches!(self.bytes.get(self.offset + 1), Some(b'=' | b'!')) => {
+                self.offset += 2;
+                self.token(start..self.offset, TokenKind::GroupBracket);
+                let outermost = self.lookbehind_groups.is_none();
+                if outermost {
+                    self.lookbehind_groups = Some(self.groups);
+                }
+                let width = self.alternation()?;
+                if outermost {
+                    self.lookbehind_groups = None;
+                }
+                self.close_group(start)?;
+                if width.known && width.max != Some(width.min) && self.width_error.is_none() {
+                    self.width_error = Some(self.error(
+                        "look-behind requires fixed-width pattern",
+                        start..self.offset,
+                    ));
+                }
+                Ok((Width::ZERO, true))
+            }
+            b'P' => match self.bytes.get(self.offset + 1) {
+                Some(b'<') => {
+                    self.offset += 2;
+                    self.token(start..self.offset, TokenKind::GroupBracket);
+                    let name = self.group_name(b'>', "missing >, unterminated name", false)?;
+                    self.groups += 1;
+                    let group = self.groups;
+                    if let Some(name) = name
+                        && let Some(previous) = self.names.insert(name.clone(), group)
+                    {
+                        return Err(self.error(
+                            format!(
+                                "redefinition of group name '{name}' as group {group}; was group {previous}"
+                            ),
+                            start..self.offset,
+                        ));
+                    }
+                    self.open_groups.push(group);
+                    let width = self.alternation()?;
+                    self.open_groups.pop();
+                    self.close_group(start)?;
+                    Ok((width, true))
+                }
+                Some(b'=') => {
+                    self.offset += 2;
+                    self.token(start..self.offset, TokenKind::GroupBracket);
+                    let name_start = self.offset;
+                    if let Some(name) =
+                        self.group_name(b')', "missing ), unterminated name", false)?
+                    {
+                        let range = name_start..self.offset - 1;
+                        match self.names.get(&name) {
+                            Some(&group) => self.check_group_reference(group, range)?,
+                            None => {
+                                return Err(
+                                    self.error(format!("unknown group name '{name}'"), range)
+                                );
+                            }
+                        }
+                    }
+                    self.offset -= 1;
+                    self.close_group(start)?;
+                    Ok((Width::UNKNOWN, true))
+                }
+                _ => {
+                    let end = (self.offset + 2).min(self.bytes.len());
+                    Err(self.error(
+                        format!("unknown extension ?{}", &self.source.text[self.offset..end]),
+                        start..end,
+                    ))
+                }
+            },
+            b'#' => {
+                let Some(close) = self.source.text[self.offset..].find(')') else {
+                    return Err(self.error("missing ), unterminated comment", start..start + 1));
+                };
+                self.offset += close + 1;
+                self.token(start..self.offset, TokenKind::Comment);
+                Ok((Width::ZERO, false))
+            }
+            b'(' => self.conditional(start),
+            b'a' | b'i' | b'L' | b'm' | b's' | b'u' | b'x' | b'-' => self.flags(start),
+            _ => {
+                let mut end = self.offset + self.char_len(self.offset);
+                if kind == b'<' && end < self.bytes.len() {
+                    end += self.char_len(end);
+                }
+                Err(self.error(
+                    format!("unknown extension ?{}", &self.source.text[self.offset..end]),
+                    start..end,
+                ))
+            }
+        }
+    }
+
+    /// Reads a group name up to `close`, returning it unless an
+    /// interpolation supplies part of it. Conditionals also accept a group
+    /// number when `numeric` is set.
+    fn group_name(
+        &mut self,
+        close: u8,
+        unterminated: &str,
+        numeric: bool,
+    ) -> ParseResult<Option<String>> {
+        let start = self.offset;
+        let Some(length) = self.bytes[start..].iter().position(|&byte| byte == close) else {
+            return Err(self.error(unterminated, start..self.bytes.len()));
+        };
+        let end = start + length;
+        self.offset = end + 1;
+        if length == 0 {
+            return Err(self.error("missing group name", start..end));
+        }
+        self.token(start..end, TokenKind::GroupName);
+        if self.source.atoms.range(start..end).next().is_some() {
+            return Ok(None);
+        }
+        let name = &self.source.text[start..end];
+        if numeric && name.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Ok(Some(name.to_string()));
+        }
+        let is_identifier = name
+            .chars()
+            .next()
+            .is_some_and(|character| character == '_' || character.is_alphabetic())
+            && name
+                .chars()
+                .all(|character| character == '_' || character.is_alphanumeric());
+        if !is_identifier {
+            return Err(self.error(format!("bad character in group name '{name}'"), start..end));
+        }
+        Ok(Some(name.to_string()))
+    }
+
+    /// Parses `(?(group)yes|no)`.
+    fn conditional(&mut self, start: usize) -> ParseResult<(Width, bool)> {
+        self.offset += 1;
+        self.token(start..self.offset, TokenKind::GroupBracket);
+        let name_start = self.offset;
+        if let Some(name) = self.group_name(b')', "missing ), unterminated name", true)? {
+            let range = name_start..self.offset - 1;
+            if let Ok(group) = name.parse::<usize>() {
+                if group == 0 {
+                    return Err(self.error("bad group number", range));
+                }
+                self.conditional_groups.push((group, range.clone()));
+                self.check_lookbehind_reference(group, range)?;
+            } else {
+                let Some(&group) = self.names.get(&name) else {
+                    return Err(self.error(format!("unknown group name '{name}'"), range));
+                };
+                self.check_lookbehind_reference(group, range)?;
+            }
+        }
+        let mut width = self.sequence()?;
+        if self.peek() == Some(b'|') {
+            self.token(self.offset..self.offset + 1, TokenKind::Alternation);
+            self.offset += 1;
+            width = width.or(self.sequence()?);
+            if self.peek() == Some(b'|') {
+                return Err(self.error(
+                    "conditional backref with more than two branches",
+                    self.offset..self.offset + 1,
+                ));
+            }
+        } else {
+            width = width.or(Width::ZERO);
+        }
+        self.close_group(start)?;
+        Ok((width, true))
+    }
+
+    /// Parses inline flags: global ones such as `(?i)` or scoped ones such
+    /// as `(?i-s:...)`.
+    fn flags(&mut self, start: usize) -> ParseResult<(Width, bool)> {
+        let mut add = String::new();
+        let mut remove = String::new();
+        let mut removing = false;
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(self.error("missing -, : or )", start..self.offset));
+            };
+            match byte {
+                b'a' | b'i' | b'L' | b'm' | b's' | b'u' | b'x' if !removing => {
+                    add.push(byte as char)
+                }
+                b'i' | b'm' | b's' | b'x' if removing => remove.push(byte as char),
+                b'-' if !removing => removing = true,
+                b':' | b')' => break,
+                _ => {
+                    let message = if byte.is_ascii_alphabetic() {
+                        if removing {
+                            "bad inline flags: cannot turn off flags 'a', 'u' and 'L'"
+                        } else {
+                            "unknown flag"
+                        }
+                    } else if removing {
+                        "missing flag"
+                    } else {
+                        "missing -, : or )"
+                    };
+                    return Err(self.error(message, self.offset..self.offset + 1));
+                }
+            }
+            self.offset += 1;
+        }
+        if add.contains('L') {
+            return Err(self.error(
+                "bad inline flags: cannot use 'L' flag with a str pattern",
+                start..self.offset,
+            ));
+        }
+        if add.contains('a') && add.contains('u') {
+            return Err(self.error(
+                "bad inline flags: flags 'a', 'u' and 'L' are incompatible",
+                start..self.offset,
+            ));
+        }
+        if removing && remove.is_empty() {
+            return Err(self.error("missing flag", start..self.offset));
+        }
+
+        if self.peek() == Some(b')') {
+            self.offset += 1;
+            self.token(start..self.offset, TokenKind::Flags);
+            if removing {
+                return Err(self.error("missing :", start..self.offset));
+            }
+            if !self.at_start {
+                return Err(self.error(
+                    "global flags not at the start of the expression",
+                    start..self.offset,
+                ));
+            }
+            if add.contains('x') {
+                self.verbose = true;
+            }
+            return Ok((Width::ZERO, false));
+        }
+
+        self.offset += 1;
+        self.at_start = false;
+        self.token(start..self.offset, TokenKind::Flags);
+        let verbose = self.verbose;
+        if add.contains('x') {
+            self.verbose = true;
+        } else if remove.contains('x') {
+            self.verbose = false;
+        }
+        let width = self.alternation();
+        self.verbose = verbose;
+        let width = width?;
+        self.close_group(start)?;
+        Ok((width, true))
+    }
+
+    fn close_group(&mut self, start: usize) -> ParseResult<()> {
+        if self.peek() != Some(b')') {
+            return Err(self.error("missing ), unterminated subpattern", start..start + 1));
+        }
+        self.token(self.offset..self.offset + 1, TokenKind::GroupBracket);
+        self.offset += 1;
+        Ok(())
+    }
+
+    fn check_group_reference(&self, group: usize, range: Range<usize>) -> ParseResult<()> {
+        if group == 0 || group > self.groups {
+            return Err(self.error(format!("invalid group reference {group}"), range));
+        }
+        if self.open_groups.contains(&group) {
+            return Err(self.error("cannot refer to an open group", range));
+        }
+        self.check_lookbehind_reference(group, range)
+    }
+
+    /// Checks a reference inside a look-behind, which can only use groups
+    /// closed before the look-behind starts.
+    fn check_lookbehind_reference(&self, group: usize, range: Range<usize>) -> ParseResult<()> {
+        let Some(lookbehind_groups) = self.lookbehind_groups else {
+            return Ok(());
+        };
+        if group > self.groups || self.open_groups.contains(&group) {
+            return Err(self.error("cannot refer to an open group", range));
+        }
+        if group > lookbehind_groups {
+            return Err(self.error(
+                "cannot refer to group defined in the same lookbehind subpattern",
+                range,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Skips whitespace and `#` comments in verbose patterns.
+    fn skip_verbose(&mut self) {
+        if !self.verbose {
+            return;
+        }
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_whitespace() {
+                self.offset += 1;
+            } else if byte == b'#' {
+                let end = self.source.text[self.offset..]
+                    .find('\n')
+                    .map_or(self.bytes.len(), |newline| self.offset + newline);
+                self.token(self.offset..end, TokenKind::Comment);
+                self.offset = end;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        if self.source.atoms.contains_key(&self.offset) {
+            return Some(0);
+        }
+        self.bytes.get(self.offset).copied()
+    }
+
+    fn char_len(&self, offset: usize) -> usize {
+        self.source.text[offset..]
+            .chars()
+            .next()
+            .map_or(1, char::len_utf8)
+    }
+
+    fn token(&mut self, range: Range<usize>, kind: TokenKind) {
+        self.tokens.push((range, kind));
+    }
+
+    fn error(&self, message: impl Into<String>, range: Range<usize>) -> SyntaxError {
+        SyntaxError {
+            message: message.into(),
+            range,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ClassItem {
+    Char(char),
+    /// A class escape such as `\d`, which cannot bound a range.
+    Set,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(pattern: &str) -> Option<String> {
+        let source = RegexSource {
+            text: pattern.to_string(),
+            atoms: BTreeMap::new(),
+        };
+        parse(&source).error.map(|error| error.message)
+    }
+
+    #[test]
+    fn reports_the_errors_python_re_raises() {
+        for (pattern, message) in [
+            ("^*", "nothing to repeat"),
+            ("a**", "multiple repeat"),
+            ("a{3,2}", "min repeat greater than max repeat"),
+            ("(a", "missing ), unterminated subpattern"),
+            ("a)", "unbalanced parenthesis"),
+            ("[a", "unterminated character set"),
+            ("[z-a]", "bad character range z-a"),
+            (r"[\d-z]", r"bad character range \d-z"),
+            (r"\q", r"bad escape \q"),
+            (r"[\A]", r"bad escape \A"),
+            (r"\x4", r"incomplete escape \x4"),
+            (r"\N{FOO}", "undefined character name 'FOO'"),
+            (r"\400", r"octal escape value \400 outside of range 0-0o377"),
+            ("(?<n>a)", "unknown extension ?<n"),
+            ("(?P<1>a)", "bad character in group name '1'"),
+            ("(?P<>x)", "missing group name"),
+            (
+                "(?P<a>x)(?P<a>y)",
+                "redefinition of group name 'a' as group 2; was group 1",
+            ),
+            ("(?P=b)", "unknown group name 'b'"),
+            (r"\2(a)", "invalid group reference 2"),
+            (r"(a\1)", "cannot refer to an open group"),
+            ("(?<=a+)b", "look-behind requires fixed-width pattern"),
+            ("a(?i)b", "global flags not at the start of the expression"),
+            (
+                "(?au:a)",
+                "bad inline flags: flags 'a', 'u' and 'L' are incompatible",
+            ),
+            ("(?-i)a", "missing :"),
+            ("(?z)a", "unknown extension ?z"),
+            ("(?#x", "missing ), unterminated comment"),
+            (
+                "(?(1)a|b|c)",
+                "conditional backref with more than two branches",
+            ),
+            ("(?(2)a)(b)", "invalid group reference 2"),
+            ("(?(0)a)", "bad group number"),
+            ("(?", "unexpected end of pattern"),
+            ("\\", "bad escape (end of pattern)"),
+        ] {
+            assert_eq!(error(pattern).as_deref(), Some(message), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn accepts_valid_python_patterns() {
+        for pattern in [
+            r"^(?P<year>\d{4})-(?P<month>\d{2})(?:-(?P=month))?$",
+            r"(?i)(?m)^\s*#\s*(?!include)(?<=\s)\w+?",
+            r"(?x) a \  b  # comment",
+            r"(?(1)b|c)(a)\1[\]\-^a-z\d]{,3}x{}",
+            r"(?>a++)(?s-i:.)\N{LATIN SMALL LETTER A}é\101\0",
+            r"(?<=ab|cd)x(?<!\bq)",
+        ] {
+            assert_eq!(error(pattern), None, "{pattern}");
+        }
+    }
+}
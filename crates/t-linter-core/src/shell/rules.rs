@@ -0,0 +1,215 @@
+use std::ops::Range;
+
+use tree_sitter::Node;
+
+use crate::lint::{EmbeddedDocument, LintDiagnostic, LintSeverity};
+use crate::parser::InterpolationInfo;
+
+const RULE_UNQUOTED_INTERPOLATION: &str = "shell-unquoted-interpolation";
+const RULE_QUOTED_INTERPOLATION: &str = "shell-quoted-interpolation";
+const RULE_INTERPOLATED_COMMAND: &str = "shell-interpolated-command";
+
+/// Where an interpolation lands in the shell command.
+enum Position {
+    CommandName,
+    DoubleQuoted,
+    SingleQuoted,
+    /// The body of a here-document with an unquoted delimiter, which the
+    /// shell expands like double-quoted text.
+    HereDocument,
+    Unquoted,
+    /// Comments and the bodies of here-documents with a quoted delimiter,
+    /// such as `<<'EOF'`, which the shell does not expand.
+    Ignored,
+}
+
+/// Reports interpolations the shell would reinterpret: unquoted ones, which
+/// are split into words, globbed, and can end the command, ones inside
+/// quotes they can break out of, and ones that choose the command to run.
+pub fn lint_rules(document: &EmbeddedDocument<'_>) -> Vec<LintDiagnostic> {
+    let root = document.tree.root_node();
+    let mut diagnostics = Vec::new();
+    for (interpolation, range) in &document.interpolations {
+        let node = innermost_node(document, root, range);
+        let (rule, message) = match position(document, node) {
+            Position::CommandName => (
+                RULE_INTERPOLATED_COMMAND,
+                format!(
+                    "Interpolation '{}' is used as the command name, so the value chooses the program that runs; use a fixed command and pass the value as an argument",
+                    interpolation.expression
+                ),
+            ),
+            Position::DoubleQuoted => (
+                RULE_QUOTED_INTERPOLATION,
+                format!(
+                    "Interpolation '{}' is inside double quotes, where `$`, backticks, and `\"` in the value are still interpreted; move it outside the quotes and wrap it in `shlex.quote(...)`",
+                    interpolation.expression
+                ),
+            ),
+            Position::SingleQuoted => (
+                RULE_QUOTED_INTERPOLATION,
+                format!(
+                    "Interpolation '{}' is inside single quotes, which a `'` in the value ends; move it outside the quotes and wrap it in `shlex.quote(...)`",
+                    interpolation.expression
+                ),
+            ),
+            Position::HereDocument => (
+                RULE_QUOTED_INTERPOLATION,
+                format!(
+                    "Interpolation '{}' is inside a here-document, where `$` and backticks in the value are still interpreted; quote the delimiter as `<<'EOF'` to keep the body literal",
+                    interpolation.expression
+                ),
+            ),
+            Position::Unquoted if !is_shell_quoted(interpolation) => (
+                RULE_UNQUOTED_INTERPOLATION,
+                format!(
+                    "Interpolation '{}' is spliced into the command unquoted; spaces, globs, `;`, `|`, and `$(...)` in the value are interpreted by the shell. Wrap it in `shlex.quote(...)`",
+                    interpolation.expression
+                ),
+            ),
+            Position::Unquoted | Position::Ignored => continue,
+        };
+        let mut diagnostic = document.diagnostic(node, rule, LintSeverity::Error, message);
+        diagnostic.start_line = interpolation.location.start_line;
+        diagnostic.start_column = interpolation.location.start_column;
+        diagnostic.end_line = interpolation.location.end_line;
+        diagnostic.end_column = interpolation.location.end_column;
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
+
+/// The deepest node whose text covers the interpolation's placeholder.
+fn innermost_node<'tree>(
+    document: &EmbeddedDocument<'_>,
+    root: Node<'tree>,
+    range: &Range<usize>,
+) -> Node<'tree> {
+    let mut node = root;
+    loop {
+        let mut cursor = node.walk();
+        let child = node.children(&mut cursor).find(|child| {
+            let child_range = document.original_range(*child);
+            child_range.start < range.end && range.start < child_range.end
+        });
+        match child {
+            Some(child) => node = child,
+            None => return node,
+        }
+    }
+}
+
+fn position(document: &EmbeddedDocument<'_>, node: Node<'_>) -> Position {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        match node.kind() {
+            "command_name" => return Position::CommandName,
+            "string" | "translated_string" => return Position::DoubleQuoted,
+            "raw_string" | "ansi_c_string" => return Position::SingleQuoted,
+            "heredoc_body" if !has_quoted_delimiter(document, node) => {
+                return Position::HereDocument;
+            }
+            "comment" | "heredoc_body" | "heredoc_start" | "heredoc_end" => {
+                return Position::Ignored;
+            }
+            "command" | "variable_assignment" | "redirected_statement" | "program" => {
+                return Position::Unquoted;
+            }
+            _ => {}
+        }
+        current = node.parent();
+    }
+    Position::Unquoted
+}
+
+/// Whether the here-document's delimiter is quoted or escaped, as in
+/// `<<'EOF'`, `<<"EOF"`, or `<<\EOF`, which keeps the body literal.
+fn has_quoted_delimiter(document: &EmbeddedDocument<'_>, body: Node<'_>) -> bool {
+    let Some(redirect) = body.parent() else {
+        return true;
+    };
+    let mut cursor = redirect.walk();
+    redirect
+        .children(&mut cursor)
+        .find(|child| child.kind() == "heredoc_start")
+        .is_none_or(|start| document.text(start).contains(['\'', '"', '\\']))
+}
+
+/// Whether the interpolation already renders a single shell word, such as
+/// `shlex.quote(path)` or `shlex.join(args)`.
+fn is_shell_quoted(interpolation: &InterpolationInfo) -> bool {
+    let expression = interpolation.expression.trim();
+    let Some((callee, _)) = expression.split_once('(') else {
+        return false;
+    };
+    expression.ends_with(')')
+        && matches!(
+            callee.trim().rsplit('.').next(),
+            Some("quote" | "join" | "shell_quote")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::lint_source;
+
+    fn shell_diagnostics(command: &str) -> Vec<(String, String)> {
+        let source = format!(
+            "import shlex\nfrom typing import Annotated\nfrom string.templatelib import Template\n\nhost = path = tool = msg = tag = \"x\"\ncommand: Annotated[Template, \"bash\"] = t\"\"\"{command}\"\"\"\n"
+        );
+        lint_source(std::path::Path::new("deploy.py"), &source)
+            .unwrap()
+            .diagnostics
+            .into_iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn reports_unquoted_quoted_and_command_position_interpolations() {
+        assert_eq!(
+            shell_diagnostics(
+                "ssh {host} && {tool} --message \"{msg}\" '{tag}' {shlex.quote(path)} # {host}"
+            )
+            .into_iter()
+            .map(|(rule, message)| (rule, message.split([';', ',']).next().unwrap().to_string()))
+            .collect::<Vec<_>>(),
+            vec![
+                (
+                    "shell-unquoted-interpolation".to_string(),
+                    "Interpolation 'host' is spliced into the command unquoted".to_string()
+                ),
+                (
+                    "shell-interpolated-command".to_string(),
+                    "Interpolation 'tool' is used as the command name".to_string()
+                ),
+                (
+                    "shell-quoted-interpolation".to_string(),
+                    "Interpolation 'msg' is inside double quotes".to_string()
+                ),
+                (
+                    "shell-quoted-interpolation".to_string(),
+                    "Interpolation 'tag' is inside single quotes".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_command_substitutions_assignments_and_here_documents() {
+        assert_eq!(
+            shell_diagnostics(
+                "TARGET={shlex.quote(path)} echo \"$(cat {path})\"; export DIR={path}\ncat <<'EOF'\n{msg}\nEOF\ncat <<EOF\n{tag} $(ls {host})\nEOF\n"
+            )
+            .into_iter()
+            .map(|(rule, _)| rule)
+            .collect::<Vec<_>>(),
+            vec![
+                "shell-unquoted-interpolation",
+                "shell-unquoted-interpolation",
+                "shell-quoted-interpolation",
+                "shell-unquoted-interpolation"
+            ]
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use std::ops::Range;
+
+use super::positions::SqlPosition;
+use crate::TemplateStringInfo;
+use crate::project_config::{SqlConfig, SqlDialect};
+
+const CONSTRUCT_KEYWORDS: &[&str] = &[
+    "ABORT",
+    "AUTOINCREMENT",
+    "CONFLICT",
+    "DO",
+    "FAIL",
+    "IGNORE",
+    "NOTHING",
+    "ON",
+    "OR",
+    "PRAGMA",
+    "REPLACE",
+    "ROLLBACK",
+    "SET",
+    "UPDATE",
+    "WHERE",
+];
+
+/// SQL rewritten so tree-sitter-sequel accepts the dialect's syntax. Every
+/// rewrite keeps byte length, so offsets still map onto the template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedSql {
+    pub content: String,
+    /// Ranges blanked out because the generic grammar cannot parse them.
+    pub blanked: Vec<Range<usize>>,
+    /// Dialect keywords inside blanked ranges, for highlighting.
+    pub keywords: Vec<Range<usize>>,
+}
+
+impl NormalizedSql {
+    pub fn is_blanked(&self, range: &Range<usize>) -> bool {
+        self.blanked
+            .iter()
+            .any(|blanked| blanked.start <= range.start && range.end <= blanked.end)
+    }
+}
+
+/// The dialect for a template: its profile, then `[tool.t-linter.sql]
+/// dialect`, then `postgres` for psycopg templates, then `generic`.
+pub fn resolve(template: &TemplateStringInfo, config: &SqlConfig) -> SqlDialect {
+    if let Some(dialect) = profile_dialect(template.profile.as_deref()) {
+        return dialect;
+    }
+    if let Some(dialect) = config.dialect {
+        return dialect;
+    }
+    if super::psycopg::is_enabled(config, template) {
+        SqlDialect::Postgres
+    } else {
+        SqlDialect::Generic
+    }
+}
+
+pub fn profile_dialect(profile: Option<&str>) -> Option<SqlDialect> {
+    profile.and_then(|profile| profile.parse().ok())
+}
+
+pub fn normalize(content: &str, dialect: SqlDialect) -> NormalizedSql {
+    let tokens = tokenize(content);
+    let mut bytes = content.as_bytes().to_vec();
+    let mut blanked = Vec::new();
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        if let Some(width) = placeholder_width(content, token, dialect) {
+            bytes[token.range.start] = b'1';
+            bytes[token.range.start + 1..token.range.start + width].fill(b' ');
+            index += 1;
+            continue;
+        }
+        let Some(construct) = tolerated_construct(content, &tokens, index, dialect) else {
+            index += 1;
+            continue;
+        };
+        let end = construct.end;
+        let range = tokens[construct.start].range.start..tokens[end - 1].range.end;
+        for byte in &mut bytes[range.clone()] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+        blanked.push(range);
+        index = end;
+    }
+
+    let keywords = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::Word)
+        .filter(|token| {
+            blanked
+                .iter()
+                .any(|range| range.start <= token.range.start && token.range.end <= range.end)
+        })
+        .filter(|token| {
+            CONSTRUCT_KEYWORDS
+                .iter()
+                .any(|keyword| keyword.eq_ignore_ascii_case(&content[token.range.clone()]))
+        })
+        .map(|token| token.range.clone())
+        .collect();
+
+    NormalizedSql {
+        content: String::from_utf8(bytes).unwrap_or_else(|_| content.to_string()),
+        blanked,
+        keywords,
+    }
+}
+
+/// Where a slot inside a blanked construct sits, read from the tokens since
+/// the grammar never sees the construct: conflict targets, `SET` targets and
+/// qualified names such as `excluded.{col}` are identifiers, `PRAGMA` names
+/// too, and the count in `LIMIT n, {m}` is a keyword position.
+pub fn blanked_position(
+    content: &str,
+    normalized: &NormalizedSql,
+    slot: Range<usize>,
+) -> Option<SqlPosition> {
+    let blanked = normalized
+        .blanked
+        .iter()
+        .find(|blanked| blanked.start <= slot.start && slot.end <= blanked.end)?;
+    let construct = &content[blanked.clone()];
+    let tokens = tokenize(construct);
+    let slot = slot.start - blanked.start..slot.end - blanked.start;
+    let index = tokens.iter().position(|token| token.range == slot)?;
+    let text = |position: usize| {
+        tokens
+            .get(position)
+            .map(|token| construct[token.range.clone()].to_ascii_uppercase())
+            .unwrap_or_default()
+    };
+    let previous = index.checked_sub(1).map(text).unwrap_or_default();
+    let next = text(index + 1);
+    if previous == "." || next == "." {
+        return Some(SqlPosition::Identifier("column name"));
+    }
+
+    Some(match text(0).as_str() {
+        "PRAGMA" if index == 1 => SqlPosition::Identifier("pragma name"),
+        "," => SqlPosition::Keyword("LIMIT"),
+        "ON" => {
+            let mut clause = String::new();
+            let mut depth = 0usize;
+            for position in 1..index {
+                let word = text(position);
+                match word.as_str() {
+                    "(" => depth += 1,
+                    ")" => depth = depth.saturating_sub(1),
+                    "CONFLICT" | "CONSTRAINT" | "DO" | "SET" | "WHERE" if depth == 0 => {
+                        clause = word;
+                    }
+                    _ => {}
+                }
+            }
+            match clause.as_str() {
+                "CONFLICT" if depth > 0 => SqlPosition::Identifier("column name"),
+                "CONSTRAINT" if previous == "CONSTRAINT" => {
+                    SqlPosition::Identifier("constraint name")
+                }
+                "DO" if previous == "DO" => SqlPosition::Keyword("keyword"),
+                "SET" if depth == 0 && matches!(previous.as_str(), "SET" | ",") && next == "=" => {
+                    SqlPosition::Identifier("column name")
+                }
+                _ => SqlPosition::Value,
+            }
+        }
+        _ => SqlPosition::Value,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Number,
+    Punctuation,
+    Quoted,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    range: Range<usize>,
+}
+
+/// Splits SQL into words, numbers, quoted text and punctuation, skipping
+/// whitespace and comments.
+fn tokenize(content: &str) -> Vec<Token> {
+    let bytes = content.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        let byte = bytes[index];
+        let kind = match byte {
+            b if b.is_ascii_whitespace() => {
+                index += 1;
+                continue;
+            }
+            b'-' if bytes.get(index + 1) == Some(&b'-') => {
+                while index < bytes.len() && bytes[index] != b'\n' {
+                    index += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                index += 2;
+                while index < bytes.len() && !bytes[index..].starts_with(b"*/") {
+                    index += 1;
+                }
+                index = (index + 2).min(bytes.len());
+                continue;
+            }
+            b'\'' | b'"' | b'`' => {
+                index += 1;
+                while index < bytes.len() && bytes[index] != byte {
+                    index += 1;
+                }
+                index = (index + 1).min(bytes.len());
+                TokenKind::Quoted
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' || !b.is_ascii() => {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric()
+                        || bytes[index] == b'_'
+                        || !bytes[index].is_ascii())
+                {
+                    index += 1;
+                }
+                TokenKind::Word
+            }
+            b if b.is_ascii_digit() => {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'.')
+                {
+                    index += 1;
+                }
+                TokenKind::Number
+            }
+            _ => {
+                index += 1;
+                TokenKind::Punctuation
+            }
+        };
+        tokens.push(Token {
+            kind,
+            range: start..index,
+        });
+    }
+    tokens
+}
+
+/// The byte width of a bind placeholder the dialect's drivers accept but the
+/// generic grammar does not, such as `%s`, `%(name)s`, `:name` or `?1`.
+fn placeholder_width(content: &str, token: &Token, dialect: SqlDialect) -> Option<usize> {
+    if token.kind != TokenKind::Punctuation {
+        return None;
+    }
+    let start = token.range.start;
+    let rest = &content.as_bytes()[start..];
+    let name_width = |from: usize| {
+        rest[from..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_')
+            .count()
+    };
+    match (rest[0], dialect) {
+        (b'%', SqlDialect::Postgres | SqlDialect::Mysql) => {
+            if matches!(rest.get(1), Some(b's' | b'b' | b't')) {
+                return Some(2);
+            }
+            if rest.get(1) == Some(&b'(') {
+                let close = rest.iter().position(|byte| *byte == b')')?;
+                return matches!(rest.get(close + 1), Some(b's' | b'b' | b't'))
+                    .then_some(close + 2);
+            }
+            None
+        }
+        (b'?', SqlDialect::Sqlite) if rest.get(1).is_some_and(u8::is_ascii_digit) => Some(
+            1 + rest[1..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count(),
+        ),
+        (b':' | b'$' | b'@', SqlDialect::Sqlite)
+            if start
+                .checked_sub(1)
+                .is_none_or(|previous| content.as_bytes()[previous] != b':')
+                && rest
+                    .get(1)
+                    .is_some_and(|byte| byte.is_ascii_alphabetic() || *byte == b'_') =>
+        {
+            Some(1 + name_width(1))
+        }
+        _ => None,
+    }
+}
+
+/// The token indices of a dialect-only construct at `index`: upserts,
+/// `INSERT OR REPLACE`, `PRAGMA`, `AUTOINCREMENT` and the `, m` of `LIMIT n, m`.
+fn tolerated_construct(
+    content: &str,
+    tokens: &[Token],
+    index: usize,
+    dialect: SqlDialect,
+) -> Option<Range<usize>> {
+    let word = |offset: usize| {
+        tokens
+            .get(index + offset)
+            .filter(|token| token.kind == TokenKind::Word)
+            .map(|token| content[token.range.clone()].to_ascii_uppercase())
+    };
+    let text = |position: usize| &content[tokens[position].range.clone()];
+    let statement_end = |stop_at_returning: bool| {
+        (index..tokens.len())
+            .find(|&position| {
+                text(position) == ";"
+                    || (stop_at_returning && text(position).eq_ignore_ascii_case("RETURNING"))
+            })
+            .unwrap_or(tokens.len())
+    };
+    let upsert = matches!(dialect, SqlDialect::Postgres | SqlDialect::Sqlite);
+    let sqlite = dialect == SqlDialect::Sqlite;
+
+    match word(0).as_deref() {
+        Some("ON") if upsert && word(1).as_deref() == Some("CONFLICT") => {
+            Some(index..statement_end(true))
+        }
+        Some("OR")
+            if sqlite
+                && index
+                    .checked_sub(1)
+                    .is_some_and(|previous| text(previous).eq_ignore_ascii_case("INSERT"))
+                && matches!(
+                    word(1).as_deref(),
+                    Some("REPLACE" | "IGNORE" | "ABORT" | "FAIL" | "ROLLBACK")
+                ) =>
+        {
+            Some(index..index + 2)
+        }
+        Some("PRAGMA") if sqlite => Some(index..statement_end(false)),
+        Some("AUTOINCREMENT") if sqlite => Some(index..index + 1),
+        Some("LIMIT")
+            if matches!(dialect, SqlDialect::Sqlite | SqlDialect::Mysql)
+                && index + 3 < tokens.len()
+                && text(index + 2) == "," =>
+        {
+            Some(index + 2..index + 4)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_rewrites_placeholders_with_the_same_length() {
+        let postgres = normalize(
+            "SELECT * FROM t WHERE a = %s AND b = %(name)s AND c = '%s'",
+            SqlDialect::Postgres,
+        );
+        assert_eq!(
+            postgres.content,
+            "SELECT * FROM t WHERE a = 1  AND b = 1        AND c = '%s'"
+        );
+
+        let sqlite = normalize("SELECT a::int, :name, ?12, $x", SqlDialect::Sqlite);
+        assert_eq!(sqlite.content, "SELECT a::int, 1    , 1  , 1 ");
+
+        let generic = normalize("SELECT :name", SqlDialect::Generic);
+        assert_eq!(generic.content, "SELECT :name");
+    }
+
+    #[test]
+    fn normalize_blanks_dialect_only_constructs() {
+        let upsert = normalize(
+            "INSERT INTO t (a) VALUES (1) ON CONFLICT (a) DO NOTHING RETURNING id",
+            SqlDialect::Postgres,
+        );
+        assert_eq!(
+            upsert.content,
+            "INSERT INTO t (a) VALUES (1)                            RETURNING id"
+        );
+        assert_eq!(upsert.blanked, vec![29..55]);
+        assert_eq!(upsert.keywords.len(), 4);
+
+        let source =
+            "INSERT OR REPLACE INTO t (a) VALUES (1) LIMIT 5, 10; PRAGMA foreign_keys = ON";
+        let sqlite = normalize(source, SqlDialect::Sqlite);
+        assert_eq!(sqlite.content.len(), source.len());
+        assert_eq!(
+            sqlite.content.split_whitespace().collect::<Vec<_>>(),
+            vec![
+                "INSERT", "INTO", "t", "(a)", "VALUES", "(1)", "LIMIT", "5", ";"
+            ]
+        );
+    }
+}
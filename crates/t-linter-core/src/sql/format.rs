@@ -0,0 +1,1135 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use tstring_format_doc::{Doc, RenderOptions, render};
+use tstring_syntax::{BackendError, BackendResult, TemplateInput};
+
+use crate::project_config::{CommaStyle, KeywordCase, SqlFormatConfig};
+use crate::template_text::{self, keep_block_breaks, template_source};
+
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "AUTOINCREMENT",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CHECK",
+    "COLLATE",
+    "COLUMN",
+    "CONFLICT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "DROP",
+    "DUPLICATE",
+    "ELSE",
+    "END",
+    "ESCAPE",
+    "EXCEPT",
+    "EXISTS",
+    "FALSE",
+    "FETCH",
+    "FILTER",
+    "FOR",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "IGNORE",
+    "ILIKE",
+    "IN",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "LATERAL",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NATURAL",
+    "NOT",
+    "NOTHING",
+    "NULL",
+    "NULLS",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "PRAGMA",
+    "PRIMARY",
+    "RECURSIVE",
+    "REFERENCES",
+    "REPLACE",
+    "RETURNING",
+    "RIGHT",
+    "SELECT",
+    "SET",
+    "SIMILAR",
+    "SOME",
+    "TABLE",
+    "THEN",
+    "TRUE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+];
+
+/// Words that are keywords only after one of the listed words, so columns
+/// named `key` or `first` keep their spelling elsewhere.
+const CONTEXT_KEYWORDS: &[(&str, &[&str])] = &[
+    ("FIRST", &["NULLS"]),
+    ("KEY", &["PRIMARY", "FOREIGN", "DUPLICATE"]),
+    ("LAST", &["NULLS"]),
+];
+
+/// Keywords that are also function names when a `(` follows directly.
+const FUNCTION_KEYWORDS: &[&str] = &["IF", "LEFT", "REPLACE", "RIGHT"];
+
+/// Keywords after which a name followed by `(` is a column list, not a call.
+const COLUMN_LIST_KEYWORDS: &[&str] = &["INTO", "REFERENCES", "TABLE"];
+
+const OPERATORS: &[&str] = &[
+    "->>", "#>>", "!~*", "->", "#>", "<=", ">=", "<>", "!=", "||", "@>", "<@", "&&", "<<", ">>",
+    "!~", "~*", "==", ":=",
+];
+
+/// Formats a `sql` template: keywords are recased, each clause goes on its own
+/// line when the statement does not fit, and subqueries, `CASE` expressions
+/// and long parenthesized lists are indented. Interpolations are kept as
+/// opaque atoms and the text of literals, quoted names and comments is kept.
+pub fn format_template(
+    input: &TemplateInput,
+    config: &SqlFormatConfig,
+    line_length: usize,
+) -> BackendResult<String> {
+    let (source, atoms) = template_source(input, "sql")?;
+    let tokens = tokenize(&source, &atoms)?;
+    if tokens.is_empty() {
+        return Ok(source);
+    }
+
+    let formatter = SqlFormatter {
+        source: &source,
+        atoms: &atoms,
+        tokens,
+        config,
+    };
+    let doc = formatter.statements();
+    let formatted = render(
+        &doc,
+        RenderOptions {
+            line_length,
+            indent_width: config.indent_width,
+        },
+    );
+    Ok(keep_block_breaks(&source, formatted))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    Atom,
+    Quoted,
+    Number,
+    Parameter,
+    Operator,
+    Comma,
+    Open,
+    Close,
+    Semicolon,
+    Dot,
+    LineComment,
+    BlockComment,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    range: Range<usize>,
+    /// Whether the token directly follows the previous one without whitespace.
+    glued: bool,
+}
+
+/// Splits the template into tokens. `atoms` maps the start of each
+/// interpolation to its end, and an interpolation touching word characters
+/// forms a single word with them.
+fn tokenize(source: &str, atoms: &BTreeMap<usize, usize>) -> BackendResult<Vec<Token>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut brackets = Vec::new();
+    let mut glued = false;
+    let mut index = 0;
+
+    // Advances past a delimited body, stepping over interpolations whole so
+    // quotes inside their expressions do not end the literal.
+    let scan_until = |mut index: usize, closing: &[u8], doubled: bool| -> Option<usize> {
+        while index < bytes.len() {
+            if let Some(&end) = atoms.get(&index) {
+                index = end;
+                continue;
+            }
+            if bytes[index..].starts_with(closing) {
+                index += closing.len();
+                if doubled && bytes[index..].starts_with(closing) {
+                    index += closing.len();
+                    continue;
+                }
+                return Some(index);
+            }
+            index += 1;
+        }
+        None
+    };
+
+    while index < bytes.len() {
+        let start = index;
+        let byte = bytes[index];
+        let is_atom = atoms.contains_key(&index);
+        if !is_atom && byte.is_ascii_whitespace() {
+            index += 1;
+            glued = false;
+            continue;
+        }
+
+        let kind = if is_atom || (is_word_byte(byte) && !byte.is_ascii_digit() && byte != b'$') {
+            let mut has_atom = false;
+            while index < bytes.len() {
+                if let Some(&end) = atoms.get(&index) {
+                    index = end;
+                    has_atom = true;
+                } else if is_word_byte(bytes[index]) {
+                    index += 1;
+                } else {
+                    break;
+                }
+            }
+            if has_atom {
+                TokenKind::Atom
+            } else if matches!(byte, b'E' | b'e' | b'N' | b'n' | b'B' | b'b' | b'X' | b'x')
+                && index == start + 1
+                && bytes.get(index) == Some(&b'\'')
+            {
+                index = scan_until(index + 1, b"'", true)
+                    .ok_or_else(|| unterminated("string literal"))?;
+                TokenKind::Quoted
+            } else {
+                TokenKind::Word
+            }
+        } else if byte.is_ascii_digit()
+            || (byte == b'.' && bytes.get(index + 1).is_some_and(u8::is_ascii_digit))
+        {
+            index += 1;
+            while index < bytes.len() {
+                let current = bytes[index];
+                let exponent_sign = matches!(current, b'+' | b'-')
+                    && matches!(bytes[index - 1], b'e' | b'E')
+                    && !bytes[start..index].starts_with(b"0x");
+                if current.is_ascii_alphanumeric() || current == b'.' || exponent_sign {
+                    index += 1;
+                } else {
+                    break;
+                }
+            }
+            TokenKind::Number
+        } else {
+            match byte {
+                b'\'' => {
+                    index = scan_until(index + 1, b"'", true)
+                        .ok_or_else(|| unterminated("string literal"))?;
+                    TokenKind::Quoted
+                }
+                b'"' | b'`' => {
+                    index = scan_until(index + 1, &[byte], true)
+                        .ok_or_else(|| unterminated("quoted identifier"))?;
+                    TokenKind::Quoted
+                }
+                b'-' if bytes.get(index + 1) == Some(&b'-') => {
+                    while index < bytes.len() && !matches!(bytes[index], b'\n' | b'\r') {
+                        index += 1;
+                    }
+                    TokenKind::LineComment
+                }
+                b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                    index = scan_until(index + 2, b"*/", false)
+                        .ok_or_else(|| unterminated("block comment"))?;
+                    TokenKind::BlockComment
+                }
+                b'$' => {
+                    let tag_end = bytes[index + 1..]
+                        .iter()
+                        .position(|byte| !(byte.is_ascii_alphanumeric() || *byte == b'_'))
+                        .map(|offset| index + 1 + offset);
+                    let tag = tag_end
+                        .filter(|&end| bytes[end] == b'$' && !bytes[index + 1].is_ascii_digit())
+                        .map(|end| &bytes[index..=end]);
+                    if let Some(tag) = tag {
+                        index = scan_until(index + tag.len(), tag, false)
+                            .ok_or_else(|| unterminated("dollar-quoted string"))?;
+                        TokenKind::Quoted
+                    } else {
+                        index += 1 + word_width(&bytes[index + 1..]);
+                        TokenKind::Parameter
+                    }
+                }
+                b'?' => {
+                    index += 1;
+                    while index < bytes.len() && bytes[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                    TokenKind::Parameter
+                }
+                b':' if bytes.get(index + 1) == Some(&b':') => {
+                    index += 2;
+                    TokenKind::Operator
+                }
+                b':' | b'@'
+                    if bytes
+                        .get(index + 1)
+                        .is_some_and(|next| next.is_ascii_alphabetic() || *next == b'_') =>
+                {
+                    index += 1 + word_width(&bytes[index + 1..]);
+                    TokenKind::Parameter
+                }
+                b'%' if matches!(bytes.get(index + 1), Some(b's' | b'b' | b't'))
+                    && !bytes.get(index + 2).is_some_and(|next| is_word_byte(*next)) =>
+                {
+                    index += 2;
+                    TokenKind::Parameter
+                }
+                b'%' if bytes.get(index + 1) == Some(&b'(')
+                    && let Some(close) = bytes[index..].iter().position(|byte| *byte == b')')
+                    && matches!(bytes.get(index + close + 1), Some(b's' | b'b' | b't')) =>
+                {
+                    index += close + 2;
+                    TokenKind::Parameter
+                }
+                b'(' | b'[' => {
+                    brackets.push(byte);
+                    index += 1;
+                    TokenKind::Open
+                }
+                b')' | b']' => {
+                    let expected = if byte == b')' { b'(' } else { b'[' };
+                    if brackets.pop() != Some(expected) {
+                        return Err(unbalanced());
+                    }
+                    index += 1;
+                    TokenKind::Close
+                }
+                b',' => {
+                    index += 1;
+                    TokenKind::Comma
+                }
+                b';' => {
+                    index += 1;
+                    TokenKind::Semicolon
+                }
+                b'.' => {
+                    index += 1;
+                    TokenKind::Dot
+                }
+                _ => {
+                    let rest = &source[index..];
+                    index += OPERATORS
+                        .iter()
+                        .find(|operator| rest.starts_with(*operator))
+                        .map_or_else(
+                            || rest.chars().next().map_or(1, char::len_utf8),
+                            |operator| operator.len(),
+                        );
+                    TokenKind::Operator
+                }
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            range: start..index,
+            glued,
+        });
+        glued = true;
+    }
+
+    if !brackets.is_empty() {
+        return Err(unbalanced());
+    }
+    Ok(tokens)
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    template_text::is_word_byte(byte) || byte == b'$'
+}
+
+fn word_width(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_')
+        .count()
+}
+
+fn unterminated(what: &str) -> BackendError {
+    template_text::unterminated(what, "sql")
+}
+
+fn unbalanced() -> BackendError {
+    template_text::unbalanced("sql")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClauseKind {
+    /// Comma-separated items, one per line when broken.
+    List,
+    /// Conditions, broken before each top-level `AND` and `OR`.
+    Condition,
+    /// A single expression indented under the clause keyword.
+    Plain,
+}
+
+/// A formatted fragment and whether it ends in a line comment, which must be
+/// followed by a line break.
+struct Part {
+    doc: Doc,
+    ends_with_comment: bool,
+}
+
+struct SqlFormatter<'a> {
+    source: &'a str,
+    atoms: &'a BTreeMap<usize, usize>,
+    tokens: Vec<Token>,
+    config: &'a SqlFormatConfig,
+}
+
+impl SqlFormatter<'_> {
+    fn statements(&self) -> Doc {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for index in 0..=self.tokens.len() {
+            let terminated = self.kind(index) == Some(TokenKind::Semicolon);
+            if index < self.tokens.len() && !terminated {
+                continue;
+            }
+            if start < index || terminated {
+                let suffix = if terminated { ";" } else { "" };
+                parts.push(self.statement(start..index, suffix).doc);
+            }
+            start = index + 1;
+        }
+        join(parts, Doc::hard_line())
+    }
+
+    fn statement(&self, range: Range<usize>, suffix: &str) -> Part {
+        let mut clauses = Vec::new();
+        let mut head: Option<(Range<usize>, ClauseKind)> = None;
+        let mut body_start = range.start;
+        for index in self.top_level(range.clone()) {
+            if index < body_start {
+                continue;
+            }
+            let Some((length, kind)) = self.clause_head(index, range.start) else {
+                continue;
+            };
+            if head.is_some() || body_start < index {
+                clauses.push((head.take(), body_start..index));
+            }
+            head = Some((index..index + length, kind));
+            body_start = index + length;
+        }
+        clauses.push((head, body_start..range.end));
+
+        let count = clauses.len();
+        let parts = clauses
+            .into_iter()
+            .enumerate()
+            .map(|(position, (head, body))| {
+                let suffix = if position + 1 == count { suffix } else { "" };
+                self.clause(head, body, suffix)
+            })
+            .collect::<Vec<_>>();
+        let ends_with_comment = parts.last().is_some_and(|part| part.ends_with_comment);
+        Part {
+            doc: join_lines(parts).group(),
+            ends_with_comment,
+        }
+    }
+
+    fn clause(
+        &self,
+        head: Option<(Range<usize>, ClauseKind)>,
+        body: Range<usize>,
+        suffix: &str,
+    ) -> Part {
+        let Some((head, kind)) = head else {
+            return self.item(body, suffix);
+        };
+        let head_text = head
+            .map(|index| self.token_text(index))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if body.is_empty() {
+            return Part {
+                doc: Doc::text(format!("{head_text}{suffix}")),
+                ends_with_comment: false,
+            };
+        }
+
+        let (body, ends_with_comment) = match kind {
+            ClauseKind::List => {
+                let parts = self.list_items(body, suffix);
+                let ends_with_comment = parts.last().is_some_and(|part| part.ends_with_comment);
+                (self.join_list(parts), ends_with_comment)
+            }
+            ClauseKind::Condition => {
+                let conditions = self.split_conditions(body);
+                let count = conditions.len();
+                let parts = conditions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(position, range)| {
+                        self.item(range, if position + 1 == count { suffix } else { "" })
+                    })
+                    .collect::<Vec<_>>();
+                let ends_with_comment = parts.last().is_some_and(|part| part.ends_with_comment);
+                (join_lines(parts), ends_with_comment)
+            }
+            ClauseKind::Plain => {
+                let part = self.item(body, suffix);
+                (part.doc, part.ends_with_comment)
+            }
+        };
+        Part {
+            doc: Doc::concat(vec![
+                Doc::text(head_text),
+                Doc::concat(vec![Doc::line(), body]).indent(),
+            ])
+            .group(),
+            ends_with_comment,
+        }
+    }
+
+    /// Comma-separated items; with trailing commas each item but the last
+    /// carries its comma ahead of any trailing comment.
+    fn list_items(&self, range: Range<usize>, suffix: &str) -> Vec<Part> {
+        // A line comment right after a comma describes the item before it.
+        let mut items: Vec<(Range<usize>, Vec<usize>)> = Vec::new();
+        for mut item in self.split_commas(range) {
+            if let Some((_, comments)) = items.last_mut() {
+                while item.start < item.end && self.kind(item.start) == Some(TokenKind::LineComment)
+                {
+                    comments.push(item.start);
+                    item.start += 1;
+                }
+            }
+            items.push((item, Vec::new()));
+        }
+        let count = items.len();
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(position, (item, comments))| {
+                let suffix = match (position + 1 == count, self.config.comma_style) {
+                    (true, _) => suffix,
+                    (false, CommaStyle::Trailing) => ",",
+                    (false, CommaStyle::Leading) => "",
+                };
+                self.commented_item(item, comments, suffix)
+            })
+            .collect()
+    }
+
+    fn join_list(&self, parts: Vec<Part>) -> Doc {
+        let mut docs = Vec::new();
+        let mut previous_comment = false;
+        for (position, part) in parts.into_iter().enumerate() {
+            if position > 0 {
+                match (self.config.comma_style, previous_comment) {
+                    (CommaStyle::Trailing, false) => docs.push(Doc::line()),
+                    (CommaStyle::Trailing, true) => docs.push(Doc::hard_line()),
+                    (CommaStyle::Leading, false) => {
+                        docs.extend([Doc::soft_line(), Doc::text(", ")]);
+                    }
+                    (CommaStyle::Leading, true) => {
+                        docs.extend([Doc::hard_line(), Doc::text(", ")]);
+                    }
+                }
+            }
+            previous_comment = part.ends_with_comment;
+            docs.push(part.doc);
+        }
+        Doc::concat(docs)
+    }
+
+    /// An expression followed by `suffix`, with trailing line comments kept
+    /// after the suffix so they cannot swallow it.
+    fn item(&self, range: Range<usize>, suffix: &str) -> Part {
+        self.commented_item(range, Vec::new(), suffix)
+    }
+
+    /// Like [`Self::item`], followed by the `comments` that came after it.
+    fn commented_item(&self, range: Range<usize>, comments: Vec<usize>, suffix: &str) -> Part {
+        let mut end = range.end;
+        while end > range.start && self.kind(end - 1) == Some(TokenKind::LineComment) {
+            end -= 1;
+        }
+        let mut docs = vec![self.expression(range.start..end)];
+        if !suffix.is_empty() {
+            docs.push(Doc::text(suffix));
+        }
+        let comments = (end..range.end).chain(comments).collect::<Vec<_>>();
+        for (position, comment) in comments.iter().enumerate() {
+            if position > 0 {
+                docs.push(Doc::hard_line());
+            } else if end > range.start || !suffix.is_empty() {
+                docs.push(Doc::text(" "));
+            }
+            docs.push(Doc::text(self.token_text(*comment)));
+        }
+        Part {
+            doc: Doc::concat(docs),
+            ends_with_comment: !comments.is_empty(),
+        }
+    }
+
+    fn expression(&self, range: Range<usize>) -> Doc {
+        let mut docs = Vec::new();
+        let mut previous: Option<usize> = None;
+        let mut index = range.start;
+        while index < range.end {
+            if let Some(previous) = previous
+                && self.space_between(previous, index)
+            {
+                docs.push(Doc::text(" "));
+            }
+            let token = &self.tokens[index];
+            let last = match token.kind {
+                TokenKind::Open => {
+                    let close = self.matching_close(index);
+                    docs.push(self.bracketed(index, close));
+                    close
+                }
+                TokenKind::Word if self.is_word(index, "CASE") => {
+                    match self.matching_end(index).filter(|end| *end < range.end) {
+                        Some(end) => {
+                            docs.push(self.case_expression(index, end));
+                            end
+                        }
+                        None => {
+                            docs.push(Doc::text(self.token_text(index)));
+                            index
+                        }
+                    }
+                }
+                TokenKind::LineComment => {
+                    docs.push(Doc::text(self.token_text(index)));
+                    docs.push(Doc::hard_line());
+                    previous = None;
+                    index += 1;
+                    continue;
+                }
+                _ => {
+                    docs.push(Doc::text(self.token_text(index)));
+                    index
+                }
+            };
+            previous = Some(last);
+            index = last + 1;
+        }
+        Doc::concat(docs)
+    }
+
+    fn bracketed(&self, open: usize, close: usize) -> Doc {
+        let inner = open + 1..close;
+        if inner.is_empty() {
+            return Doc::text(format!("{}{}", self.text(open), self.text(close)));
+        }
+        let is_subquery = self.text(open) == "("
+            && ["SELECT", "WITH", "VALUES"]
+                .iter()
+                .any(|keyword| self.is_word(inner.start, keyword));
+        let (body, ends_with_comment) = if is_subquery {
+            let part = self.statement(inner, "");
+            (part.doc, part.ends_with_comment)
+        } else {
+            let parts = self.list_items(inner, "");
+            let ends_with_comment = parts.last().is_some_and(|part| part.ends_with_comment);
+            (self.join_list(parts), ends_with_comment)
+        };
+        Doc::concat(vec![
+            Doc::text(self.text(open)),
+            Doc::concat(vec![Doc::soft_line(), body]).indent(),
+            if ends_with_comment {
+                Doc::hard_line()
+            } else {
+                Doc::soft_line()
+            },
+            Doc::text(self.text(close)),
+        ])
+        .group()
+    }
+
+    /// `CASE` with each `WHEN` and `ELSE` branch on its own line when broken.
+    fn case_expression(&self, case: usize, end: usize) -> Doc {
+        let inner = case + 1..end;
+        let mut branches = self
+            .top_level(inner.clone())
+            .filter(|&index| self.is_word(index, "WHEN") || self.is_word(index, "ELSE"))
+            .collect::<Vec<_>>();
+        let operand_end = branches.first().copied().unwrap_or(end);
+        branches.push(end);
+
+        let mut head = vec![Doc::text(self.token_text(case))];
+        if case + 1 < operand_end {
+            head.push(Doc::text(" "));
+            head.push(self.expression(case + 1..operand_end));
+        }
+        let parts = branches
+            .windows(2)
+            .map(|window| self.item(window[0]..window[1], ""))
+            .collect::<Vec<_>>();
+        Doc::concat(vec![
+            Doc::concat(head),
+            Doc::concat(vec![Doc::line(), join_lines(parts)]).indent(),
+            Doc::line(),
+            Doc::text(self.token_text(end)),
+        ])
+        .group()
+    }
+
+    /// Recognises a clause keyword at `index`, returning how many tokens it
+    /// spans and how its body is laid out.
+    fn clause_head(&self, index: usize, statement_start: usize) -> Option<(usize, ClauseKind)> {
+        let word = |offset: usize| {
+            (self.kind(index + offset) == Some(TokenKind::Word))
+                .then(|| self.text(index + offset).to_ascii_uppercase())
+        };
+        let previous = index
+            .checked_sub(1)
+            .filter(|previous| *previous >= statement_start)
+            .filter(|previous| self.kind(*previous) == Some(TokenKind::Word))
+            .map(|previous| self.text(previous).to_ascii_uppercase());
+        let optional = |offset: usize, words: &[&str]| {
+            usize::from(word(offset).is_some_and(|word| words.contains(&word.as_str())))
+        };
+
+        let head = match word(0)?.as_str() {
+            "SELECT" => (1 + optional(1, &["DISTINCT", "ALL"]), ClauseKind::List),
+            "FROM" if previous.as_deref() != Some("DISTINCT") => (1, ClauseKind::List),
+            "WHERE" | "HAVING" => (1, ClauseKind::Condition),
+            "GROUP" | "ORDER" if word(1).as_deref() == Some("BY") => (2, ClauseKind::List),
+            "LIMIT" | "OFFSET" | "FETCH" => (1, ClauseKind::Plain),
+            "FOR" if matches!(word(1).as_deref(), Some("UPDATE" | "SHARE" | "NO" | "KEY")) => {
+                (1, ClauseKind::Plain)
+            }
+            "UNION" | "INTERSECT" | "EXCEPT" => {
+                (1 + optional(1, &["ALL", "DISTINCT"]), ClauseKind::Plain)
+            }
+            "INSERT" => {
+                let or = if word(1).as_deref() == Some("OR") {
+                    2
+                } else {
+                    0
+                };
+                (1 + or + optional(1 + or, &["INTO"]), ClauseKind::Plain)
+            }
+            "REPLACE" if word(1).as_deref() == Some("INTO") => (2, ClauseKind::Plain),
+            "VALUES" => (1, ClauseKind::List),
+            "UPDATE" if !matches!(previous.as_deref(), Some("DO" | "FOR" | "KEY")) => {
+                (1, ClauseKind::Plain)
+            }
+            "SET" => (1, ClauseKind::List),
+            "DELETE" => (1 + optional(1, &["FROM"]), ClauseKind::Plain),
+            "RETURNING" | "WINDOW" => (1, ClauseKind::List),
+            "ON" if word(1).as_deref() == Some("CONFLICT") => (2, ClauseKind::Plain),
+            "ON" if word(1).as_deref() == Some("DUPLICATE")
+                && word(2).as_deref() == Some("KEY")
+                && word(3).as_deref() == Some("UPDATE") =>
+            {
+                (4, ClauseKind::List)
+            }
+            "WITH" if index == statement_start => {
+                (1 + optional(1, &["RECURSIVE"]), ClauseKind::List)
+            }
+            _ => (self.join_head_length(index)?, ClauseKind::Plain),
+        };
+        Some(head)
+    }
+
+    /// The length of `[NATURAL] [LEFT | RIGHT | FULL [OUTER] | INNER | CROSS] JOIN`.
+    fn join_head_length(&self, index: usize) -> Option<usize> {
+        let mut length = 0;
+        for words in [
+            &["NATURAL"][..],
+            &["LEFT", "RIGHT", "FULL", "INNER", "CROSS"],
+            &["OUTER"],
+        ] {
+            if words.iter().any(|word| self.is_word(index + length, word)) {
+                length += 1;
+            }
+        }
+        self.is_word(index + length, "JOIN").then_some(length + 1)
+    }
+
+    fn split_commas(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut items = Vec::new();
+        let mut start = range.start;
+        for index in self.top_level(range.clone()) {
+            if self.kind(index) == Some(TokenKind::Comma) {
+                items.push(start..index);
+                start = index + 1;
+            }
+        }
+        items.push(start..range.end);
+        items
+    }
+
+    /// Splits before each top-level `AND`/`OR`, except the `AND` of `BETWEEN`.
+    fn split_conditions(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut conditions = Vec::new();
+        let mut start = range.start;
+        let mut in_between = false;
+        for index in self.top_level(range.clone()) {
+            if self.is_word(index, "BETWEEN") {
+                in_between = true;
+            } else if self.is_word(index, "AND") && in_between {
+                in_between = false;
+            } else if (self.is_word(index, "AND") || self.is_word(index, "OR")) && start < index {
+                conditions.push(start..index);
+                start = index;
+            }
+        }
+        conditions.push(start..range.end);
+        conditions
+    }
+
+    /// Indices in `range` outside brackets and `CASE` expressions.
+    fn top_level(&self, range: Range<usize>) -> impl Iterator<Item = usize> + '_ {
+        let mut depth = 0usize;
+        let mut case_depth = 0usize;
+        range.filter(move |&index| {
+            let top_level = depth == 0 && case_depth == 0;
+            match self.tokens[index].kind {
+                TokenKind::Open => depth += 1,
+                TokenKind::Close => depth = depth.saturating_sub(1),
+                TokenKind::Word if self.is_word(index, "CASE") => case_depth += 1,
+                TokenKind::Word if self.is_word(index, "END") && case_depth > 0 => case_depth -= 1,
+                _ => {}
+            }
+            top_level
+        })
+    }
+
+    fn matching_close(&self, open: usize) -> usize {
+        template_text::matching_close(
+            self.tokens.iter().map(|token| token.kind),
+            open,
+            (TokenKind::Open, TokenKind::Close),
+        )
+        .unwrap_or(self.tokens.len() - 1)
+    }
+
+    fn matching_end(&self, case: usize) -> Option<usize> {
+        let mut depth = 0usize;
+        let mut brackets = 0usize;
+        for index in case..self.tokens.len() {
+            match self.tokens[index].kind {
+                TokenKind::Open => brackets += 1,
+                TokenKind::Close => {
+                    if brackets == 0 {
+                        return None;
+                    }
+                    brackets -= 1;
+                }
+                TokenKind::Word if brackets == 0 && self.is_word(index, "CASE") => depth += 1,
+                TokenKind::Word if brackets == 0 && self.is_word(index, "END") => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(index);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn space_between(&self, previous: usize, next: usize) -> bool {
+        let previous_token = &self.tokens[previous];
+        let next_token = &self.tokens[next];
+        match (previous_token.kind, next_token.kind) {
+            (_, TokenKind::Comma | TokenKind::Semicolon | TokenKind::Close) => false,
+            (TokenKind::Open | TokenKind::Dot, _) | (_, TokenKind::Dot) => false,
+            _ if self.text(previous) == "::" || self.text(next) == "::" => false,
+            (_, TokenKind::Open) if self.text(next) == "[" => !next_token.glued,
+            (TokenKind::Word | TokenKind::Atom | TokenKind::Quoted, TokenKind::Open) => {
+                if self.is_keyword(previous) {
+                    return true;
+                }
+                let follows_column_list_keyword = previous.checked_sub(1).is_some_and(|before| {
+                    COLUMN_LIST_KEYWORDS
+                        .iter()
+                        .any(|word| self.is_word(before, word))
+                });
+                follows_column_list_keyword || !next_token.glued
+            }
+            _ => !self.is_unary_operator(previous),
+        }
+    }
+
+    /// A `-` or `+` with no operand on its left, as in `= -1`.
+    fn is_unary_operator(&self, index: usize) -> bool {
+        if !matches!(self.text(index), "-" | "+") {
+            return false;
+        }
+        let Some(before) = index.checked_sub(1) else {
+            return true;
+        };
+        match self.tokens[before].kind {
+            TokenKind::Operator
+            | TokenKind::Open
+            | TokenKind::Comma
+            | TokenKind::Semicolon
+            | TokenKind::LineComment => true,
+            TokenKind::Word => self.is_keyword(before),
+            _ => false,
+        }
+    }
+
+    fn is_keyword(&self, index: usize) -> bool {
+        if self.kind(index) != Some(TokenKind::Word)
+            || index
+                .checked_sub(1)
+                .is_some_and(|before| self.kind(before) == Some(TokenKind::Dot))
+        {
+            return false;
+        }
+        let word = self.text(index).to_ascii_uppercase();
+        let calls_function = self
+            .tokens
+            .get(index + 1)
+            .is_some_and(|next| next.glued && self.text(index + 1) == "(");
+        if calls_function && FUNCTION_KEYWORDS.contains(&word.as_str()) {
+            return false;
+        }
+        KEYWORDS.contains(&word.as_str())
+            || CONTEXT_KEYWORDS.iter().any(|(keyword, after)| {
+                *keyword == word
+                    && index.checked_sub(1).is_some_and(|before| {
+                        after
+                            .iter()
+                            .any(|candidate| self.is_word(before, candidate))
+                    })
+            })
+    }
+
+    fn token_text(&self, index: usize) -> String {
+        let text = self.text(index);
+        if !self.is_keyword(index) {
+            return self.template_text(self.tokens[index].range.clone());
+        }
+        match self.config.keyword_case {
+            KeywordCase::Upper => text.to_ascii_uppercase(),
+            KeywordCase::Lower => text.to_ascii_lowercase(),
+            KeywordCase::Preserve => text.to_string(),
+        }
+    }
+
+    fn template_text(&self, range: Range<usize>) -> String {
+        template_text::template_text(self.source, self.atoms, range)
+    }
+
+    fn is_word(&self, index: usize, word: &str) -> bool {
+        self.kind(index) == Some(TokenKind::Word) && self.text(index).eq_ignore_ascii_case(word)
+    }
+
+    fn kind(&self, index: usize) -> Option<TokenKind> {
+        self.tokens.get(index).map(|token| token.kind)
+    }
+
+    fn text(&self, index: usize) -> &str {
+        &self.source[self.tokens[index].range.clone()]
+    }
+}
+
+fn join(docs: Vec<Doc>, separator: Doc) -> Doc {
+    let mut joined = Vec::with_capacity(docs.len() * 2);
+    for (position, doc) in docs.into_iter().enumerate() {
+        if position > 0 {
+            joined.push(separator.clone());
+        }
+        joined.push(doc);
+    }
+    Doc::concat(joined)
+}
+
+/// Joins parts with line breaks that collapse to spaces when the enclosing
+/// group fits, except after a line comment.
+fn join_lines(parts: Vec<Part>) -> Doc {
+    let mut docs = Vec::new();
+    let mut previous_comment = false;
+    for (position, part) in parts.into_iter().enumerate() {
+        if position > 0 {
+            docs.push(if previous_comment {
+                Doc::hard_line()
+            } else {
+                Doc::line()
+            });
+        }
+        previous_comment = part.ends_with_comment;
+        docs.push(part.doc);
+    }
+    Doc::concat(docs)
+}
+
+#[cfg(test)]
+mod tests {
+    use tstring_syntax::{TemplateInterpolation, TemplateSegment};
+
+    use super::*;
+
+    fn format_sql(segments: &[&str], config: &SqlFormatConfig, line_length: usize) -> String {
+        let segments = segments
+            .iter()
+            .enumerate()
+            .map(|(index, text)| match text.strip_prefix('{') {
+                Some(expression) => TemplateSegment::Interpolation(TemplateInterpolation {
+                    expression: expression.trim_end_matches('}').to_string(),
+                    conversion: None,
+                    format_spec: String::new(),
+                    interpolation_index: index,
+                    raw_source: Some(text.to_string()),
+                }),
+                None => TemplateSegment::StaticText(text.to_string()),
+            })
+            .collect();
+        format_template(&TemplateInput::from_segments(segments), config, line_length)
+            .expect("sql template formats")
+    }
+
+    #[test]
+    fn formats_keywords_spacing_and_keeps_short_statements_on_one_line() {
+        assert_eq!(
+            format_sql(
+                &[
+                    "select a::int,-1, x->>'k', count(*) from t where a=-1 and b in(",
+                    "{ids}",
+                    ")"
+                ],
+                &SqlFormatConfig::default(),
+                80,
+            ),
+            "SELECT a::int, -1, x ->> 'k', count(*) FROM t WHERE a = -1 AND b IN ({ids})"
+        );
+        assert_eq!(
+            format_sql(
+                &["select '{}'::jsonb, ", "{data}", " -- {note}"],
+                &SqlFormatConfig::default(),
+                80,
+            ),
+            "SELECT '{{}}'::jsonb, {data} -- {{note}}"
+        );
+    }
+
+    #[test]
+    fn breaks_clauses_subqueries_and_case_expressions_that_do_not_fit() {
+        let formatted = format_sql(
+            &[
+                "\nselect id, case when active then 'yes' else 'no' end as state from users where id in (select user_id from vip where level between 1 and 3) and name like '%",
+                "{name}",
+                "%' order by id\n",
+            ],
+            &SqlFormatConfig::default(),
+            40,
+        );
+        assert_eq!(
+            formatted,
+            "
+SELECT
+    id,
+    CASE
+        WHEN active THEN 'yes'
+        ELSE 'no'
+    END AS state
+FROM users
+WHERE
+    id IN (
+        SELECT user_id
+        FROM vip
+        WHERE level BETWEEN 1 AND 3
+    )
+    AND name LIKE '%{name}%'
+ORDER BY id
+"
+        );
+    }
+
+    #[test]
+    fn honours_keyword_case_comma_style_and_comments() {
+        let config = SqlFormatConfig {
+            keyword_case: KeywordCase::Lower,
+            indent_width: 2,
+            comma_style: CommaStyle::Leading,
+        };
+        assert_eq!(
+            format_sql(
+                &["SELECT first_name, -- given\nlast_name FROM people; SELECT 1"],
+                &config,
+                80,
+            ),
+            "select\n  first_name -- given\n  , last_name\nfrom people;\nselect 1"
+        );
+    }
+
+    #[test]
+    fn keeps_dollar_quoted_bodies_verbatim() {
+        assert_eq!(
+            format_sql(
+                &[
+                    "create function f() returns void as $$ begin; select 1; end $$ language plpgsql"
+                ],
+                &SqlFormatConfig::default(),
+                200,
+            ),
+            "CREATE function f() returns void AS $$ begin; select 1; end $$ language plpgsql"
+        );
+        assert_eq!(
+            format_sql(
+                &["select $body$ a;b $body$, $1"],
+                &SqlFormatConfig::default(),
+                80
+            ),
+            "SELECT $body$ a;b $body$, $1"
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_literals_and_unbalanced_brackets() {
+        for source in ["SELECT 'open", "SELECT (1", "SELECT 1)", "SELECT /* open"] {
+            let input =
+                TemplateInput::from_segments(vec![TemplateSegment::StaticText(source.to_string())]);
+            assert!(format_template(&input, &SqlFormatConfig::default(), 80).is_err());
+        }
+    }
+}
@@ -1,2 +1,5 @@
 pub mod catalog;
+pub mod dialect;
+pub mod format;
+pub mod positions;
 pub mod psycopg;
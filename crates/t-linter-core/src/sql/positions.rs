@@ -0,0 +1,386 @@
+use std::path::Path;
+
+use tree_sitter::{Node, Parser, Tree};
+
+use super::dialect;
+use crate::lint::{DiagnosticEdit, DiagnosticEditRange, LintDiagnostic, LintSeverity};
+use crate::parser::InterpolationInfo;
+use crate::project_config::SqlDialect;
+use crate::{TemplatePart, TemplateStringInfo};
+
+const RULE_INJECTION_POSITION: &str = "sql-injection-position";
+const VALUE_PLACEHOLDER: &str = "1";
+const IDENTIFIER_PLACEHOLDER: &str = "t_linter_expr";
+
+/// Where an interpolation sits in the parsed SQL statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlPosition {
+    Value,
+    Identifier(&'static str),
+    Keyword(&'static str),
+}
+
+impl SqlPosition {
+    pub fn is_identifier(self) -> bool {
+        matches!(self, Self::Identifier(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClassifiedInterpolation<'a> {
+    pub interpolation: &'a InterpolationInfo,
+    pub position: SqlPosition,
+}
+
+/// Classifies interpolations left to right. Each slot is parsed as a value
+/// first; if that does not yield a literal it is re-parsed as an identifier,
+/// and later slots see the placeholders already chosen for earlier ones.
+pub fn classify_interpolations(
+    template: &TemplateStringInfo,
+    dialect: SqlDialect,
+) -> Vec<ClassifiedInterpolation<'_>> {
+    let interpolations = template
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            TemplatePart::Interpolation(interpolation) => Some(interpolation),
+            TemplatePart::Static(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let mut placeholders = vec![VALUE_PLACEHOLDER; interpolations.len()];
+    let mut classified = Vec::with_capacity(interpolations.len());
+
+    for (index, interpolation) in interpolations.iter().enumerate() {
+        let (content, ranges) = placeholder_content(template, &placeholders);
+        let normalized = dialect::normalize(&content, dialect);
+        let Some(tree) = parse_sql(&normalized.content) else {
+            return Vec::new();
+        };
+        let (start, end) = ranges[index];
+        // Dialect-only constructs are blanked before parsing, so
+        // interpolations inside them are classified from their tokens.
+        let position = match value_position(&tree, start, end) {
+            _ if in_comment(&tree, start, end) => SqlPosition::Value,
+            Some(position) => position,
+            None if normalized.is_blanked(&(start..end)) => {
+                dialect::blanked_position(&content, &normalized, start..end)
+                    .unwrap_or(SqlPosition::Value)
+            }
+            None => {
+                placeholders[index] = IDENTIFIER_PLACEHOLDER;
+                let (content, ranges) = placeholder_content(template, &placeholders);
+                let (start, end) = ranges[index];
+                let position = parse_sql(&dialect::normalize(&content, dialect).content)
+                    .map(|tree| non_value_position(&tree, start, end))
+                    .unwrap_or(SqlPosition::Keyword("keyword"));
+                if !position.is_identifier() {
+                    placeholders[index] = VALUE_PLACEHOLDER;
+                }
+                position
+            }
+        };
+        classified.push(ClassifiedInterpolation {
+            interpolation,
+            position,
+        });
+    }
+    classified
+}
+
+/// Reports interpolations in table/column, `ORDER BY`, `LIMIT` or keyword
+/// positions. Driver parameters can only bind values, so anything else must
+/// be spliced into the statement text unless a psycopg spec composes it.
+pub fn lint_rules(
+    path: &Path,
+    classified: &[ClassifiedInterpolation<'_>],
+    psycopg: bool,
+) -> Vec<LintDiagnostic> {
+    classified
+        .iter()
+        .filter(|entry| entry.position != SqlPosition::Value)
+        .filter(|entry| !(psycopg && is_safe_spec(entry.interpolation.format_spec.trim())))
+        .map(|entry| injection_position(path, entry.interpolation, entry.position, psycopg))
+        .collect()
+}
+
+fn is_safe_spec(spec: &str) -> bool {
+    matches!(spec, "i" | "q" | "l")
+}
+
+fn placeholder_content(
+    template: &TemplateStringInfo,
+    placeholders: &[&str],
+) -> (String, Vec<(usize, usize)>) {
+    let mut content = String::new();
+    let mut ranges = Vec::new();
+    for part in &template.parts {
+        match part {
+            TemplatePart::Static(part) => content.push_str(&part.text),
+            TemplatePart::Interpolation(interpolation) => {
+                if let Some(debug_prefix) = &interpolation.debug_prefix {
+                    content.push_str(debug_prefix);
+                }
+                let start = content.len();
+                content.push_str(placeholders.get(ranges.len()).unwrap_or(&VALUE_PLACEHOLDER));
+                ranges.push((start, content.len()));
+            }
+        }
+    }
+    (content, ranges)
+}
+
+fn parse_sql(content: &str) -> Option<Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_sequel::LANGUAGE.into())
+        .ok()?;
+    parser.parse(content, None)
+}
+
+fn value_position(tree: &Tree, start: usize, end: usize) -> Option<SqlPosition> {
+    let node = tree
+        .root_node()
+        .named_descendant_for_byte_range(start, end)?;
+    if node.kind() != "literal" || has_error_ancestor(node) {
+        return None;
+    }
+    if node.start_byte() != start || node.end_byte() != end {
+        return Some(SqlPosition::Value);
+    }
+    Some(match node.parent().map(|parent| parent.kind()) {
+        Some("limit") => SqlPosition::Keyword("LIMIT"),
+        Some("offset") => SqlPosition::Keyword("OFFSET"),
+        Some("order_target") => SqlPosition::Identifier("ORDER BY column"),
+        _ => SqlPosition::Value,
+    })
+}
+
+fn non_value_position(tree: &Tree, start: usize, end: usize) -> SqlPosition {
+    let Some(node) = tree.root_node().named_descendant_for_byte_range(start, end) else {
+        return SqlPosition::Keyword("keyword");
+    };
+    if node.kind() != "identifier" || has_error_ancestor(node) {
+        return if follows_order_target(tree, start) {
+            SqlPosition::Keyword("ORDER BY direction")
+        } else {
+            SqlPosition::Keyword("keyword")
+        };
+    }
+
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        match ancestor.kind() {
+            "object_reference" => return SqlPosition::Identifier("table name"),
+            "field" | "column" | "column_definition" | "order_target" => {
+                return SqlPosition::Identifier("column name");
+            }
+            _ => current = ancestor.parent(),
+        }
+    }
+    SqlPosition::Identifier("identifier")
+}
+
+fn follows_order_target(tree: &Tree, start: usize) -> bool {
+    let mut found = false;
+    visit(tree.root_node(), &mut |node| {
+        if node.kind() == "order_by" && node.start_byte() < start {
+            found = true;
+        }
+    });
+    found
+}
+
+fn visit<'tree>(node: Node<'tree>, callback: &mut impl FnMut(Node<'tree>)) {
+    callback(node);
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        visit(child, callback);
+    }
+}
+
+/// Whether the range lies in a `--` or `/* */` comment, where an
+/// interpolation is not part of the statement.
+fn in_comment(tree: &Tree, start: usize, end: usize) -> bool {
+    let mut current = tree.root_node().descendant_for_byte_range(start, end);
+    while let Some(node) = current {
+        if matches!(node.kind(), "comment" | "marginalia") {
+            return true;
+        }
+        current = node.parent();
+    }
+    false
+}
+
+fn has_error_ancestor(node: Node<'_>) -> bool {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if node.is_error() {
+            return true;
+        }
+        current = node.parent();
+    }
+    false
+}
+
+fn injection_position(
+    path: &Path,
+    interpolation: &InterpolationInfo,
+    position: SqlPosition,
+    psycopg: bool,
+) -> LintDiagnostic {
+    let (kind, place) = match position {
+        SqlPosition::Identifier(place) => ("identifier", place),
+        SqlPosition::Keyword(place) => ("keyword", place),
+        SqlPosition::Value => ("value", "value"),
+    };
+    let mut message = format!(
+        "interpolation in {kind} position ({place}) can change the statement structure and is open to SQL injection"
+    );
+    let mut suggested_edits = Vec::new();
+    if psycopg && position.is_identifier() {
+        message.push_str("; use ':i' to compose it as a psycopg Identifier");
+        if interpolation.format_spec.is_empty()
+            && let Some(close) = interpolation.raw_source.rfind('}')
+        {
+            let column = interpolation.location.start_column.saturating_sub(1) + close;
+            suggested_edits.push(DiagnosticEdit {
+                range: DiagnosticEditRange {
+                    start_line: interpolation.location.start_line,
+                    start_column: column,
+                    end_line: interpolation.location.start_line,
+                    end_column: column,
+                },
+                new_text: ":i".to_string(),
+            });
+        }
+    } else {
+        message.push_str("; validate it against an allowlist before interpolating");
+    }
+
+    LintDiagnostic {
+        rule: RULE_INJECTION_POSITION.to_string(),
+        severity: LintSeverity::Error,
+        language: Some("sql".to_string()),
+        message,
+        file: path.to_path_buf(),
+        start_line: interpolation.location.start_line,
+        start_column: interpolation.location.start_column,
+        end_line: interpolation.location.end_line,
+        end_column: interpolation.location.end_column,
+        expected_type: None,
+        found_type: Some(place.to_string()),
+        schema_pointer: None,
+        source_of_truth: None,
+        suggested_edits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TemplateStringParser;
+
+    fn positions(query: &str) -> Vec<SqlPosition> {
+        dialect_positions(query, SqlDialect::Generic)
+    }
+
+    fn dialect_positions(query: &str, dialect: SqlDialect) -> Vec<SqlPosition> {
+        let source = format!(
+            "from typing import Annotated\nfrom string.templatelib import Template\n\nquery: Annotated[Template, \"sql\"] = t\"{query}\"\n"
+        );
+        let mut parser = TemplateStringParser::new().unwrap();
+        let templates = parser.find_template_strings(&source).unwrap();
+        classify_interpolations(&templates[0], dialect)
+            .into_iter()
+            .map(|entry| entry.position)
+            .collect()
+    }
+
+    #[test]
+    fn classifies_value_and_identifier_positions() {
+        assert_eq!(
+            positions("SELECT * FROM {table} WHERE id = {user_id} AND name LIKE {pattern}"),
+            vec![
+                SqlPosition::Identifier("table name"),
+                SqlPosition::Value,
+                SqlPosition::Value,
+            ]
+        );
+        assert_eq!(
+            positions("UPDATE users SET {column} = {value} WHERE id IN ({first}, {second})"),
+            vec![
+                SqlPosition::Identifier("column name"),
+                SqlPosition::Value,
+                SqlPosition::Value,
+                SqlPosition::Value,
+            ]
+        );
+    }
+
+    #[test]
+    fn treats_interpolations_in_comments_as_values() {
+        assert_eq!(
+            positions(
+                "SELECT * FROM users /* {note} */ WHERE id = {user_id} -- ORDER BY {column} {direction}"
+            ),
+            vec![
+                SqlPosition::Value,
+                SqlPosition::Value,
+                SqlPosition::Value,
+                SqlPosition::Value,
+            ]
+        );
+    }
+
+    #[test]
+    fn classifies_order_by_and_limit_positions() {
+        assert_eq!(
+            positions("SELECT * FROM users ORDER BY {column} {direction} LIMIT {limit}"),
+            vec![
+                SqlPosition::Identifier("ORDER BY column"),
+                SqlPosition::Keyword("ORDER BY direction"),
+                SqlPosition::Keyword("LIMIT"),
+            ]
+        );
+    }
+
+    #[test]
+    fn classifies_positions_inside_dialect_constructs() {
+        assert_eq!(
+            dialect_positions(
+                "INSERT INTO users (name) VALUES ({name}) ON CONFLICT ({column}) DO UPDATE SET {column} = excluded.{column}, seen = {seen} WHERE users.id > {floor}",
+                SqlDialect::Postgres,
+            ),
+            vec![
+                SqlPosition::Value,
+                SqlPosition::Identifier("column name"),
+                SqlPosition::Identifier("column name"),
+                SqlPosition::Identifier("column name"),
+                SqlPosition::Value,
+                SqlPosition::Value,
+            ]
+        );
+        assert_eq!(
+            dialect_positions(
+                "INSERT INTO users (name) VALUES ({name}) ON CONFLICT ON CONSTRAINT {constraint} DO NOTHING",
+                SqlDialect::Postgres,
+            ),
+            vec![
+                SqlPosition::Value,
+                SqlPosition::Identifier("constraint name"),
+            ]
+        );
+        assert_eq!(
+            dialect_positions(
+                "PRAGMA {pragma} = {value}; SELECT * FROM users LIMIT {offset}, {limit}",
+                SqlDialect::Sqlite,
+            ),
+            vec![
+                SqlPosition::Identifier("pragma name"),
+                SqlPosition::Value,
+                SqlPosition::Keyword("LIMIT"),
+                SqlPosition::Keyword("LIMIT"),
+            ]
+        );
+    }
+}
@@ -92,6 +92,8 @@ mod tests {
             template_profile: None,
             value_types,
             accepts_none: false,
+            accepts_template: false,
+            string_choices: None,
             required: true,
             allows_keyword: true,
             keyword_only: true,
@@ -0,0 +1,137 @@
+//! Template text helpers shared by the language formatters, which lay out a
+//! template's source with each interpolation kept as an opaque atom.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use tstring_syntax::{
+    BackendError, BackendResult, TemplateInput, TemplateInterpolation, TemplateSegment,
+};
+
+/// The interpolation's source text, which the formatters keep byte-for-byte.
+pub(crate) fn raw_source<'a>(
+    interpolation: &'a TemplateInterpolation,
+    language: &str,
+) -> BackendResult<&'a str> {
+    interpolation.raw_source.as_deref().ok_or_else(|| {
+        BackendError::semantic(format!(
+            "Cannot format {language} interpolation {:?} without raw source text.",
+            interpolation.expression_label()
+        ))
+    })
+}
+
+/// Checks that every interpolation carries its source text.
+pub(crate) fn check_raw_sources(input: &TemplateInput, language: &str) -> BackendResult<()> {
+    for segment in &input.segments {
+        if let TemplateSegment::Interpolation(interpolation) = segment {
+            raw_source(interpolation, language)?;
+        }
+    }
+    Ok(())
+}
+
+/// The template text with each interpolation's raw source in place, and a map
+/// from the start of each interpolation to its end.
+pub(crate) fn template_source(
+    input: &TemplateInput,
+    language: &str,
+) -> BackendResult<(String, BTreeMap<usize, usize>)> {
+    let mut source = String::new();
+    let mut atoms = BTreeMap::new();
+    for segment in &input.segments {
+        match segment {
+            TemplateSegment::StaticText(text) => source.push_str(text),
+            TemplateSegment::Interpolation(interpolation) => {
+                let start = source.len();
+                source.push_str(raw_source(interpolation, language)?);
+                atoms.insert(start, source.len());
+            }
+        }
+    }
+    Ok((source, atoms))
+}
+
+/// Source text as it must appear in the template literal: interpolations
+/// verbatim and literal braces, such as in `'{}'::jsonb`, doubled.
+pub(crate) fn template_text(
+    source: &str,
+    atoms: &BTreeMap<usize, usize>,
+    range: Range<usize>,
+) -> String {
+    let mut text = String::with_capacity(range.len());
+    let mut offset = range.start;
+    while offset < range.end {
+        if let Some(&end) = atoms.get(&offset) {
+            text.push_str(&source[offset..end]);
+            offset = end;
+            continue;
+        }
+        let Some(character) = source[offset..range.end].chars().next() else {
+            break;
+        };
+        match character {
+            '{' => text.push_str("{{"),
+            '}' => text.push_str("}}"),
+            _ => text.push(character),
+        }
+        offset += character.len_utf8();
+    }
+    text
+}
+
+/// Whether any interpolation overlaps `range`.
+pub(crate) fn has_atom(atoms: &BTreeMap<usize, usize>, range: &Range<usize>) -> bool {
+    atoms
+        .range(..range.end)
+        .next_back()
+        .is_some_and(|(_, &end)| end > range.start)
+}
+
+/// Block-style templates keep their opening and closing line breaks.
+pub(crate) fn keep_block_breaks(source: &str, mut formatted: String) -> String {
+    let leading = &source[..source.len() - source.trim_start().len()];
+    let trailing = &source[source.trim_end().len()..];
+    if leading.contains('\n') {
+        formatted.insert(0, '\n');
+    }
+    if trailing.contains('\n') {
+        formatted.push('\n');
+    }
+    formatted
+}
+
+/// Bytes every formatter treats as part of a word; languages add their own
+/// punctuation on top.
+pub(crate) fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || !byte.is_ascii()
+}
+
+pub(crate) fn unterminated(what: &str, language: &str) -> BackendError {
+    BackendError::parse(format!("Unterminated {what} in {language} template."))
+}
+
+pub(crate) fn unbalanced(language: &str) -> BackendError {
+    BackendError::parse(format!("Unbalanced brackets in {language} template."))
+}
+
+/// The index of the token that closes the bracket at `open`, where `kinds`
+/// are the token kinds and `brackets` the opening and closing kinds.
+pub(crate) fn matching_close<K: PartialEq>(
+    kinds: impl IntoIterator<Item = K>,
+    open: usize,
+    brackets: (K, K),
+) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, kind) in kinds.into_iter().enumerate().skip(open) {
+        if kind == brackets.0 {
+            depth += 1;
+        } else if kind == brackets.1 {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(index);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,306 @@
+use tstring_format_doc::{Doc, RenderOptions, render};
+use tstring_syntax::{BackendResult, TemplateInput};
+
+use super::syntax::{self, Element, Node, StartTag, XmlDialect, XmlSource};
+use crate::template_text::{check_raw_sources, keep_block_breaks};
+
+const INDENT_WIDTH: usize = 2;
+
+/// Formats an `xml` template: elements that hold only other elements put one
+/// child per line, indented, and start tags that do not fit put one attribute
+/// per line. Elements with text, and any element marked
+/// `xml:space="preserve"`, keep their content exactly as written, since
+/// whitespace there is data.
+///
+/// Interpolations are kept byte-for-byte. Templates that are not well-formed
+/// are refused with an error pointing at the problem.
+pub fn format_template(
+    input: &TemplateInput,
+    dialect: XmlDialect,
+    line_length: usize,
+) -> BackendResult<String> {
+    check_raw_sources(input, "xml")?;
+    let source = XmlSource::new(input);
+    let document = syntax::parse(&source);
+    syntax::validate(&source, &document, dialect)?;
+
+    let formatter = XmlFormatter { source: &source };
+    let Some(doc) = formatter.block(&document.nodes) else {
+        return Ok(source.template_text(0..source.text.len()));
+    };
+    let formatted = render(
+        &doc,
+        RenderOptions {
+            line_length,
+            indent_width: INDENT_WIDTH,
+        },
+    );
+    Ok(keep_block_breaks(&source.text, formatted))
+}
+
+struct XmlFormatter<'a> {
+    source: &'a XmlSource,
+}
+
+impl XmlFormatter<'_> {
+    /// Lays out content made only of markup one node per line, keeping a
+    /// single blank line where the source had one. Returns `None` for mixed
+    /// content, including CDATA sections, and for interpolations not already
+    /// set apart by whitespace, which may render as text.
+    fn block(&self, nodes: &[Node]) -> Option<Doc> {
+        let mut has_whitespace = false;
+        let mut has_interpolation = false;
+        for node in nodes {
+            match node {
+                Node::Text(range) if self.source.text[range.clone()].trim().is_empty() => {
+                    has_whitespace = true;
+                }
+                Node::Text(_) | Node::CData(_) => return None,
+                Node::Interpolation(_) => has_interpolation = true,
+                _ => {}
+            }
+        }
+        if has_interpolation && !has_whitespace {
+            return None;
+        }
+
+        let mut docs = Vec::new();
+        let mut previous_end = None;
+        for node in nodes {
+            if matches!(node, Node::Text(_)) {
+                continue;
+            }
+            let range = node.range();
+            if let Some(previous_end) = previous_end {
+                let gap = &self.source.text[previous_end..range.start];
+                if gap.matches('\n').count() > 1 {
+                    docs.push(Doc::text("\n"));
+                }
+                docs.push(Doc::hard_line());
+            }
+            docs.push(self.node(node));
+            previous_end = Some(range.end);
+        }
+        Some(Doc::concat(docs))
+    }
+
+    fn node(&self, node: &Node) -> Doc {
+        match node {
+            Node::Element(element) => self.element(element),
+            node => Doc::text(self.source.template_text(node.range())),
+        }
+    }
+
+    fn element(&self, element: &Element) -> Doc {
+        let open = self.start_tag(&element.start);
+        let Some(end) = element.end.clone() else {
+            return open;
+        };
+        let name = &self.source.text[element.start.name.clone()];
+        let close = Doc::text(format!("</{name}>"));
+        let content = element.start.range.end..end.start;
+        if self.source.text[content.clone()].trim().is_empty() {
+            return Doc::concat(vec![open, close]);
+        }
+        if !self.preserves_space(&element.start)
+            && let Some(body) = self.block(&element.children)
+        {
+            return Doc::concat(vec![
+                open,
+                Doc::concat(vec![Doc::hard_line(), body]).indent(),
+                Doc::hard_line(),
+                close,
+            ]);
+        }
+        Doc::concat(vec![
+            open,
+            Doc::text(self.source.template_text(content)),
+            close,
+        ])
+    }
+
+    fn start_tag(&self, tag: &StartTag) -> Doc {
+        let name = &self.source.text[tag.name.clone()];
+        let closing = if tag.self_closing { "/>" } else { ">" };
+        if tag.attributes.is_empty() {
+            return Doc::text(format!("<{name}{closing}"));
+        }
+        let attributes = tag
+            .attributes
+            .iter()
+            .flat_map(|attribute| {
+                [
+                    Doc::line(),
+                    Doc::text(format!(
+                        "{}={}",
+                        &self.source.text[attribute.name.clone()],
+                        self.source.template_text(attribute.value.clone())
+                    )),
+                ]
+            })
+            .collect();
+        Doc::concat(vec![
+            Doc::text(format!("<{name}")),
+            Doc::concat(attributes).indent(),
+            Doc::soft_line(),
+            Doc::text(closing),
+        ])
+        .group()
+    }
+
+    fn preserves_space(&self, tag: &StartTag) -> bool {
+        tag.attributes.iter().any(|attribute| {
+            &self.source.text[attribute.name.clone()] == "xml:space"
+                && self.source.text[attribute.value.start + 1..attribute.value.end - 1]
+                    == *"preserve"
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tstring_syntax::{TemplateInterpolation, TemplateSegment};
+
+    use super::*;
+
+    fn template(segments: &[&str]) -> TemplateInput {
+        let segments = segments
+            .iter()
+            .enumerate()
+            .map(|(index, text)| match text.strip_prefix('{') {
+                Some(expression) => TemplateSegment::Interpolation(TemplateInterpolation {
+                    expression: expression.trim_end_matches('}').to_string(),
+                    conversion: None,
+                    format_spec: String::new(),
+                    interpolation_index: index,
+                    raw_source: Some(text.to_string()),
+                }),
+                None => TemplateSegment::StaticText(text.to_string()),
+            })
+            .collect();
+        TemplateInput::from_segments(segments)
+    }
+
+    fn format_xml(segments: &[&str], line_length: usize) -> String {
+        format_template(&template(segments), XmlDialect::Xml, line_length)
+            .expect("xml template formats")
+    }
+
+    #[test]
+    fn formats_element_content_and_keeps_text_as_written() {
+        assert_eq!(
+            format_xml(
+                &[
+                    "\n<?xml version=\"1.0\"?>\n<rss version=\"2.0\"><channel>\n<title>  ",
+                    "{title}",
+                    " &amp; more </title>\n\n\n<!-- items -->\n   ",
+                    "{items}",
+                    "<link/>\n<pre xml:space=\"preserve\">\n  <b/>\n</pre><empty>\n</empty></channel></rss>\n",
+                ],
+                80,
+            ),
+            "
+<?xml version=\"1.0\"?>
+<rss version=\"2.0\">
+  <channel>
+    <title>  {title} &amp; more </title>
+
+    <!-- items -->
+    {items}
+    <link/>
+    <pre xml:space=\"preserve\">
+  <b/>
+</pre>
+    <empty></empty>
+  </channel>
+</rss>
+"
+        );
+    }
+
+    #[test]
+    fn breaks_long_start_tags_and_keeps_unseparated_interpolations_inline() {
+        assert_eq!(
+            format_xml(
+                &[
+                    "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" a=\"",
+                    "{a}",
+                    "\"><loc>",
+                    "{loc}",
+                    "</loc></urlset>",
+                ],
+                40,
+            ),
+            "<urlset
+  xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"
+  a=\"{a}\"
+>
+  <loc>{loc}</loc>
+</urlset>"
+        );
+        assert_eq!(format_xml(&["<a>", "{body}", "</a>"], 80), "<a>{body}</a>");
+        assert_eq!(
+            format_xml(
+                &["<item><description><![CDATA[ a ]]></description></item>"],
+                80
+            ),
+            "<item>\n  <description><![CDATA[ a ]]></description>\n</item>"
+        );
+    }
+
+    #[test]
+    fn reports_well_formedness_and_namespace_errors_at_their_span() {
+        let cases: &[(&[&str], &str, usize)] = &[
+            (
+                &["<a><b></a>"],
+                "Closing tag `</a>` does not match `<b>`.",
+                6,
+            ),
+            (
+                &["<a x=1/>"],
+                "The value of attribute `x` must be quoted.",
+                3,
+            ),
+            (&["<a>&nbsp;</a>"], "Unknown entity `&nbsp;`", 3),
+            (
+                &["<soap:Envelope/>"],
+                "Namespace prefix `soap` is not declared.",
+                1,
+            ),
+            (
+                &["<a xmlns:p=\"urn:x\" xmlns:q=\"urn:x\" p:id=\"1\" q:id=\"2\"/>"],
+                "Attributes `p:id` and `q:id` are the same attribute `id` in namespace `urn:x`.",
+                44,
+            ),
+            (
+                &["<?xml version=\"1.0\"?><a/><b/>"],
+                "XML documents must have a single root element.",
+                25,
+            ),
+        ];
+        for (segments, message, offset) in cases {
+            let error = syntax::check_template(&template(segments), XmlDialect::Xml)
+                .expect_err("template is refused");
+            assert!(
+                error.message.starts_with(message),
+                "{segments:?}: {}",
+                error.message
+            );
+            let span = error.diagnostics[0].span.as_ref().expect("error span");
+            assert_eq!(span.start.offset, *offset, "{segments:?}");
+        }
+
+        let error =
+            syntax::check_template(&template(&["<item ", "{attrs}", "/>"]), XmlDialect::Xml)
+                .expect_err("attribute interpolation is refused");
+        let span = error.diagnostics[0].span.as_ref().expect("error span");
+        assert_eq!((span.start.token_index, span.end.token_index), (1, 1));
+    }
+
+    #[test]
+    fn svg_templates_may_use_xlink_without_declaring_it() {
+        let input = template(&["<svg><use xlink:href=\"#", "{icon}", "\"/></svg>"]);
+        assert!(syntax::check_template(&input, XmlDialect::Svg).is_ok());
+        assert!(syntax::check_template(&input, XmlDialect::Xml).is_err());
+    }
+}
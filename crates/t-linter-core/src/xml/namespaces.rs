@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::syntax::{Element, Node, SyntaxError, XmlDialect, XmlSource};
+
+pub(crate) const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+pub(crate) const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+pub(crate) const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+/// Checks qualified names against the namespace declarations in scope:
+/// every prefix is declared, reserved prefixes keep their namespaces, and no
+/// element repeats an attribute, either by name or by namespace and local
+/// name. Interpolated namespace URIs are not known, so attributes bound to
+/// them are never reported as repeats.
+pub(crate) fn validate(
+    source: &XmlSource,
+    nodes: &[Node],
+    dialect: XmlDialect,
+) -> Vec<SyntaxError> {
+    let mut implicit = HashMap::from([("xml", Some(XML_NAMESPACE))]);
+    if dialect == XmlDialect::Svg {
+        implicit.insert("xlink", Some(XLINK_NAMESPACE));
+    }
+    let mut validator = Validator {
+        source,
+        scopes: vec![implicit],
+        errors: Vec::new(),
+    };
+    validator.nodes(nodes);
+    validator.errors
+}
+
+/// A namespace URI, unknown when interpolated, and a local name.
+type ExpandedName<'a> = (Option<&'a str>, &'a str);
+
+struct Validator<'a> {
+    source: &'a XmlSource,
+    /// Prefix bindings per open element; `None` for an interpolated URI and
+    /// the empty prefix for the default namespace.
+    scopes: Vec<HashMap<&'a str, Option<&'a str>>>,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> Validator<'a> {
+    fn nodes(&mut self, nodes: &'a [Node]) {
+        for node in nodes {
+            if let Node::Element(element) = node {
+                self.element(element);
+            }
+        }
+    }
+
+    fn element(&mut self, element: &'a Element) {
+        let mut bindings = HashMap::new();
+        for attribute in &element.start.attributes {
+            let name = self.text(&attribute.name);
+            let value_range = attribute.value.start + 1..attribute.value.end - 1;
+            let value = (!self.source.has_atom(&value_range)).then(|| self.text(&value_range));
+            let prefix = if name == "xmlns" {
+                ""
+            } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+                prefix
+            } else {
+                continue;
+            };
+            if let Some(message) = declaration_error(prefix, value) {
+                self.error(message, &attribute.name);
+            }
+            bindings.insert(prefix, value);
+        }
+        self.scopes.push(bindings);
+
+        if let Some((Some(prefix), _)) = self.qualified_name(&element.start.name) {
+            self.resolve(prefix, &element.start.name);
+        }
+
+        let mut seen: Vec<(&str, Option<ExpandedName<'_>>)> = Vec::new();
+        for attribute in &element.start.attributes {
+            let name = self.text(&attribute.name);
+            if seen.iter().any(|(other, _)| *other == name) {
+                self.error(format!("Attribute `{name}` is repeated."), &attribute.name);
+                continue;
+            }
+            let is_declaration = name == "xmlns" || name.starts_with("xmlns:");
+            let expanded = match self.qualified_name(&attribute.name) {
+                Some((Some(prefix), local)) if !is_declaration => self
+                    .resolve(prefix, &attribute.name)
+                    .map(|namespace| (namespace, local)),
+                _ => None,
+            };
+            if let Some((Some(namespace), local)) = expanded
+                && let Some((other, _)) = seen
+                    .iter()
+                    .find(|(_, other)| *other == Some((Some(namespace), local)))
+            {
+                self.error(
+                    format!(
+                        "Attributes `{other}` and `{name}` are the same attribute `{local}` in namespace `{namespace}`."
+                    ),
+                    &attribute.name,
+                );
+            }
+            seen.push((name, expanded));
+        }
+
+        self.nodes(&element.children);
+        self.scopes.pop();
+    }
+
+    /// Splits a name into prefix and local name, reporting names with more
+    /// than one colon or an empty part.
+    fn qualified_name(&mut self, range: &Range<usize>) -> Option<(Option<&'a str>, &'a str)> {
+        let name = self.text(range);
+        let mut parts = name.split(':');
+        let qualified = match (parts.next(), parts.next(), parts.next()) {
+            (Some(local), None, _) if !local.is_empty() => Some((None, local)),
+            (Some(prefix), Some(local), None) if !prefix.is_empty() && !local.is_empty() => {
+                Some((Some(prefix), local))
+            }
+            _ => None,
+        };
+        if qualified.is_none() {
+            self.error(format!("`{name}` is not a valid qualified name."), range);
+        }
+        qualified
+    }
+
+    /// The namespace bound to `prefix`, reporting undeclared prefixes.
+    fn resolve(&mut self, prefix: &str, range: &Range<usize>) -> Option<Option<&'a str>> {
+        if prefix == "xmlns" {
+            self.error(
+                "The `xmlns` prefix is reserved for namespace declarations.",
+                range,
+            );
+            return None;
+        }
+        let namespace = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(prefix).copied());
+        if namespace.is_none() {
+            self.error(
+                format!("Namespace prefix `{prefix}` is not declared."),
+                range,
+            );
+        }
+        namespace
+    }
+
+    fn text(&self, range: &Range<usize>) -> &'a str {
+        &self.source.text[range.clone()]
+    }
+
+    fn error(&mut self, message: impl Into<String>, range: &Range<usize>) {
+        self.errors.push(SyntaxError {
+            message: message.into(),
+            range: range.clone(),
+        });
+    }
+}
+
+fn declaration_error(prefix: &str, namespace: Option<&str>) -> Option<String> {
+    match (prefix, namespace) {
+        ("xmlns", _) => Some("The `xmlns` prefix cannot be declared.".to_string()),
+        ("xml", Some(namespace)) if namespace != XML_NAMESPACE => Some(format!(
+            "The `xml` prefix is reserved for `{XML_NAMESPACE}`."
+        )),
+        ("xml", _) => None,
+        (_, Some(namespace)) if namespace == XML_NAMESPACE || namespace == XMLNS_NAMESPACE => Some(
+            format!("Namespace `{namespace}` is reserved and cannot be bound to another prefix."),
+        ),
+        ("", _) => None,
+        (prefix, Some("")) => Some(format!(
+            "Namespace prefix `{prefix}` cannot be bound to an empty URI."
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,838 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use tstring_syntax::{
+    BackendError, BackendResult, SourcePosition, SourceSpan, TemplateInput, TemplateSegment,
+};
+
+use crate::parser::{TemplatePart, TemplateStringInfo};
+use crate::template_text;
+
+/// Entities every xml document may reference without a DOCTYPE.
+const PREDEFINED_ENTITIES: &[&str] = &["amp", "apos", "gt", "lt", "quot"];
+
+/// Which flavour of xml a template holds. SVG templates may use the `xlink`
+/// prefix without declaring it, as browsers allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmlDialect {
+    Xml,
+    Svg,
+}
+
+/// Checks that the template is well-formed xml with every namespace prefix
+/// declared, reporting the first problem at its span.
+pub fn check_template(input: &TemplateInput, dialect: XmlDialect) -> BackendResult<()> {
+    let source = XmlSource::new(input);
+    let document = parse(&source);
+    validate(&source, &document, dialect)
+}
+
+/// Reports the first well-formedness or namespace error in the document.
+pub(crate) fn validate(
+    source: &XmlSource,
+    document: &Document,
+    dialect: XmlDialect,
+) -> BackendResult<()> {
+    if let Some(error) = document.errors.first() {
+        return Err(BackendError::parse_at(
+            "xml.parse",
+            error.message.clone(),
+            source.span(error.range.clone()),
+        ));
+    }
+    if let Some(error) = super::namespaces::validate(source, &document.nodes, dialect).first() {
+        return Err(BackendError::semantic_at(
+            "xml.namespace",
+            error.message.clone(),
+            source.span(error.range.clone()),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) struct XmlSource {
+    /// The template with each interpolation's source text in place.
+    pub(crate) text: String,
+    /// The start of each interpolation mapped to its end.
+    pub(crate) atoms: BTreeMap<usize, usize>,
+    /// The byte range of each template segment and whether it is an
+    /// interpolation, for mapping offsets back to backend spans.
+    segments: Vec<(Range<usize>, bool)>,
+}
+
+impl XmlSource {
+    pub(crate) fn new(input: &TemplateInput) -> Self {
+        let mut text = String::new();
+        let mut atoms = BTreeMap::new();
+        let mut segments = Vec::new();
+        for segment in &input.segments {
+            let start = text.len();
+            match segment {
+                TemplateSegment::StaticText(static_text) => {
+                    text.push_str(static_text);
+                    segments.push((start..text.len(), false));
+                }
+                TemplateSegment::Interpolation(interpolation) => {
+                    text.push_str(interpolation.raw_source.as_deref().unwrap_or("{}"));
+                    atoms.insert(start, text.len());
+                    segments.push((start..text.len(), true));
+                }
+            }
+        }
+        Self {
+            text,
+            atoms,
+            segments,
+        }
+    }
+
+    /// The template content as the highlighter sees it, where every
+    /// interpolation takes two bytes.
+    pub(crate) fn from_template(template: &TemplateStringInfo) -> Self {
+        let mut atoms = BTreeMap::new();
+        let mut offset = 0;
+        for part in &template.parts {
+            match part {
+                TemplatePart::Static(part) => offset += part.text.len(),
+                TemplatePart::Interpolation(_) => {
+                    atoms.insert(offset, offset + 2);
+                    offset += 2;
+                }
+            }
+        }
+        Self {
+            text: template.content.clone(),
+            atoms,
+            segments: Vec::new(),
+        }
+    }
+
+    pub(crate) fn span(&self, range: Range<usize>) -> SourceSpan {
+        SourceSpan::between(
+            self.position(range.start, false),
+            self.position(range.end, true),
+        )
+    }
+
+    /// The template position of a byte offset. An interpolation spans
+    /// offsets `0..2` of its own token.
+    fn position(&self, offset: usize, is_end: bool) -> SourcePosition {
+        for (token_index, (range, is_interpolation)) in self.segments.iter().enumerate() {
+            let within = if is_end {
+                range.start < offset && offset <= range.end
+            } else {
+                range.start <= offset && offset < range.end
+            };
+            if !within {
+                continue;
+            }
+            let offset = if *is_interpolation {
+                if is_end { 2 } else { 0 }
+            } else {
+                self.text[range.start..offset].chars().count()
+            };
+            return SourcePosition {
+                token_index,
+                offset,
+            };
+        }
+        SourcePosition {
+            token_index: self.segments.len().saturating_sub(1),
+            offset: 0,
+        }
+    }
+
+    pub(crate) fn template_text(&self, range: Range<usize>) -> String {
+        template_text::template_text(&self.text, &self.atoms, range)
+    }
+
+    pub(crate) fn has_atom(&self, range: &Range<usize>) -> bool {
+        template_text::has_atom(&self.atoms, range)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SyntaxError {
+    pub(crate) message: String,
+    pub(crate) range: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Token {
+    Text(Range<usize>),
+    Interpolation(Range<usize>),
+    StartTag(StartTag),
+    EndTag {
+        range: Range<usize>,
+        name: Range<usize>,
+    },
+    Comment(Range<usize>),
+    CData(Range<usize>),
+    Instruction {
+        range: Range<usize>,
+        target: Range<usize>,
+    },
+    Doctype(Range<usize>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct StartTag {
+    pub(crate) range: Range<usize>,
+    pub(crate) name: Range<usize>,
+    pub(crate) attributes: Vec<Attribute>,
+    pub(crate) self_closing: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Attribute {
+    pub(crate) name: Range<usize>,
+    /// The value including its quotes.
+    pub(crate) value: Range<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Node {
+    Element(Element),
+    Text(Range<usize>),
+    Interpolation(Range<usize>),
+    Comment(Range<usize>),
+    CData(Range<usize>),
+    Instruction(Range<usize>),
+    Doctype(Range<usize>),
+}
+
+impl Node {
+    pub(crate) fn range(&self) -> Range<usize> {
+        match self {
+            Self::Element(element) => element.range(),
+            Self::Text(range)
+            | Self::Interpolation(range)
+            | Self::Comment(range)
+            | Self::CData(range)
+            | Self::Instruction(range)
+            | Self::Doctype(range) => range.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Element {
+    pub(crate) start: StartTag,
+    pub(crate) children: Vec<Node>,
+    /// The closing tag, missing for self-closing and unclosed elements.
+    pub(crate) end: Option<Range<usize>>,
+}
+
+impl Element {
+    pub(crate) fn range(&self) -> Range<usize> {
+        let end = self
+            .end
+            .as_ref()
+            .map(|end| end.end)
+            .or_else(|| self.children.last().map(|child| child.range().end))
+            .unwrap_or(self.start.range.end);
+        self.start.range.start..end
+    }
+}
+
+pub(crate) struct Document {
+    pub(crate) nodes: Vec<Node>,
+    /// Well-formedness errors in source order.
+    pub(crate) errors: Vec<SyntaxError>,
+}
+
+/// Splits the template into tokens, recovering from malformed markup so the
+/// highlighter can still colour the rest.
+pub(crate) fn tokenize(source: &XmlSource) -> (Vec<Token>, Vec<SyntaxError>) {
+    let mut lexer = Lexer {
+        source,
+        offset: 0,
+        has_doctype: false,
+        tokens: Vec::new(),
+        errors: Vec::new(),
+    };
+    lexer.run();
+    (lexer.tokens, lexer.errors)
+}
+
+/// Builds the element tree, reporting unbalanced tags and, for templates
+/// with an XML declaration or DOCTYPE, content outside a single root.
+pub(crate) fn parse(source: &XmlSource) -> Document {
+    let (tokens, mut errors) = tokenize(source);
+    let is_document = tokens.iter().any(|token| match token {
+        Token::Doctype(_) => true,
+        Token::Instruction { target, .. } => &source.text[target.clone()] == "xml",
+        _ => false,
+    });
+
+    let mut nodes = Vec::new();
+    let mut stack: Vec<Element> = Vec::new();
+    for token in tokens {
+        let node = match token {
+            Token::Text(range) => Node::Text(range),
+            Token::Interpolation(range) => Node::Interpolation(range),
+            Token::Comment(range) => Node::Comment(range),
+            Token::CData(range) => Node::CData(range),
+            Token::Instruction { range, .. } => Node::Instruction(range),
+            Token::Doctype(range) => Node::Doctype(range),
+            Token::StartTag(start) if start.self_closing => Node::Element(Element {
+                start,
+                children: Vec::new(),
+                end: None,
+            }),
+            Token::StartTag(start) => {
+                stack.push(Element {
+                    start,
+                    children: Vec::new(),
+                    end: None,
+                });
+                continue;
+            }
+            Token::EndTag { range, name } => {
+                let name = &source.text[name];
+                let Some(open) = stack
+                    .iter()
+                    .rposition(|element| &source.text[element.start.name.clone()] == name)
+                else {
+                    errors.push(SyntaxError {
+                        message: format!("Closing tag `</{name}>` has no matching opening tag."),
+                        range,
+                    });
+                    continue;
+                };
+                if open + 1 < stack.len() {
+                    let innermost = &stack[stack.len() - 1];
+                    errors.push(SyntaxError {
+                        message: format!(
+                            "Closing tag `</{name}>` does not match `<{}>`.",
+                            &source.text[innermost.start.name.clone()]
+                        ),
+                        range: range.clone(),
+                    });
+                }
+                while stack.len() > open + 1 {
+                    let unclosed = stack.pop().expect("stack holds the unclosed element");
+                    attach(&mut stack, &mut nodes, Node::Element(unclosed));
+                }
+                let mut element = stack.pop().expect("stack holds the matched element");
+                element.end = Some(range);
+                Node::Element(element)
+            }
+        };
+        attach(&mut stack, &mut nodes, node);
+    }
+    while let Some(element) = stack.pop() {
+        errors.push(SyntaxError {
+            message: format!(
+                "Element `<{}>` is never closed.",
+                &source.text[element.start.name.clone()]
+            ),
+            range: element.start.range.clone(),
+        });
+        attach(&mut stack, &mut nodes, Node::Element(element));
+    }
+
+    if is_document {
+        let mut roots = 0;
+        for node in &nodes {
+            match node {
+                Node::Element(element) => {
+                    roots += 1;
+                    if roots == 2 {
+                        errors.push(SyntaxError {
+                            message: "XML documents must have a single root element.".to_string(),
+                            range: element.start.range.clone(),
+                        });
+                    }
+                }
+                Node::Text(range) if !source.text[range.clone()].trim().is_empty() => {
+                    errors.push(SyntaxError {
+                        message: "Text is not allowed outside the root element of an xml document."
+                            .to_string(),
+                        range: range.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    errors.sort_by_key(|error| error.range.start);
+    Document { nodes, errors }
+}
+
+fn attach(stack: &mut [Element], nodes: &mut Vec<Node>, node: Node) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => nodes.push(node),
+    }
+}
+
+struct Lexer<'a> {
+    source: &'a XmlSource,
+    offset: usize,
+    has_doctype: bool,
+    tokens: Vec<Token>,
+    errors: Vec<SyntaxError>,
+}
+
+impl<'a> Lexer<'a> {
+    fn run(&mut self) {
+        while self.offset < self.text().len() {
+            if let Some(end) = self.atom_at(self.offset) {
+                self.tokens.push(Token::Interpolation(self.offset..end));
+                self.offset = end;
+            } else if self.rest().starts_with('<') {
+                self.markup();
+            } else {
+                self.text_run();
+            }
+        }
+    }
+
+    fn text(&self) -> &'a str {
+        &self.source.text
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source.text[self.offset..]
+    }
+
+    fn atom_at(&self, offset: usize) -> Option<usize> {
+        self.source.atoms.get(&offset).copied()
+    }
+
+    fn error(&mut self, message: impl Into<String>, range: Range<usize>) {
+        self.errors.push(SyntaxError {
+            message: message.into(),
+            range,
+        });
+    }
+
+    fn text_run(&mut self) {
+        let start = self.offset;
+        while self.offset < self.text().len()
+            && self.atom_at(self.offset).is_none()
+            && !self.rest().starts_with('<')
+        {
+            self.advance();
+        }
+        let range = start..self.offset;
+        if let Some(position) = self.text()[range.clone()].find("]]>") {
+            self.error(
+                "`]]>` is not allowed in xml text; write `]]&gt;`.",
+                start + position..start + position + 3,
+            );
+        }
+        self.check_references(range.clone());
+        self.tokens.push(Token::Text(range));
+    }
+
+    fn markup(&mut self) {
+        let start = self.offset;
+        let rest = self.rest();
+        if rest.starts_with("<!--") {
+            let Some(end) = self.delimited(start + 4, "-->", "comment") else {
+                return;
+            };
+            if let Some(position) = self.static_find(start + 4..end - 3, "--") {
+                self.error(
+                    "`--` is not allowed inside xml comments.",
+                    position..position + 2,
+                );
+            }
+            self.tokens.push(Token::Comment(start..end));
+        } else if rest.starts_with("<![CDATA[") {
+            if let Some(end) = self.delimited(start + 9, "]]>", "CDATA section") {
+                self.tokens.push(Token::CData(start..end));
+            }
+        } else if rest.starts_with("<!DOCTYPE") {
+            self.doctype(start);
+        } else if rest.starts_with("<?") {
+            self.instruction(start);
+        } else if rest.starts_with("</") {
+            self.end_tag(start);
+        } else if self.name_end(start + 1).is_some() {
+            self.start_tag(start);
+        } else if let Some(end) = self.atom_at(start + 1) {
+            self.error(
+                "Interpolations are not allowed in xml element names.",
+                start + 1..end,
+            );
+            self.skip_tag();
+        } else {
+            self.error("Unescaped `<` in xml text; write `&lt;`.", start..start + 1);
+            self.tokens.push(Token::Text(start..start + 1));
+            self.offset += 1;
+        }
+    }
+
+    /// Consumes up to and including `terminator`, returning the end offset,
+    /// or reports the construct as unterminated.
+    fn delimited(&mut self, from: usize, terminator: &str, what: &str) -> Option<usize> {
+        match self.find(from, terminator) {
+            Some(position) => {
+                self.offset = position + terminator.len();
+                Some(self.offset)
+            }
+            None => {
+                let start = self.offset;
+                self.error(
+                    format!("Unterminated {what} in xml template."),
+                    start..self.text().len(),
+                );
+                self.offset = self.text().len();
+                None
+            }
+        }
+    }
+
+    fn doctype(&mut self, start: usize) {
+        self.has_doctype = true;
+        self.offset = start + 9;
+        let mut depth = 0usize;
+        let mut quote = None;
+        while self.offset < self.text().len() {
+            if let Some(end) = self.atom_at(self.offset) {
+                self.offset = end;
+                continue;
+            }
+            let character = self.advance();
+            match (quote, character) {
+                (Some(open), _) if character == open => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(character),
+                (None, '[') => depth += 1,
+                (None, ']') => depth = depth.saturating_sub(1),
+                (None, '>') if depth == 0 => {
+                    self.tokens.push(Token::Doctype(start..self.offset));
+                    return;
+                }
+                _ => {}
+            }
+        }
+        self.error(
+            "Unterminated DOCTYPE in xml template.",
+            start..self.text().len(),
+        );
+    }
+
+    fn instruction(&mut self, start: usize) {
+        let Some(target_end) = self.name_end(start + 2) else {
+            self.error(
+                "Processing instruction has no target name.",
+                start..start + 2,
+            );
+            self.skip_tag();
+            return;
+        };
+        let target = start + 2..target_end;
+        let Some(end) = self.delimited(target_end, "?>", "processing instruction") else {
+            return;
+        };
+        let name = &self.text()[target.clone()];
+        if name.eq_ignore_ascii_case("xml") {
+            if name != "xml" {
+                self.error(
+                    format!("The XML declaration must be written `<?xml`, not `<?{name}`."),
+                    target.clone(),
+                );
+            } else if !self.text()[..start].trim().is_empty() {
+                self.error(
+                    "The XML declaration must come first in the document.",
+                    start..end,
+                );
+            }
+        }
+        self.tokens.push(Token::Instruction {
+            range: start..end,
+            target,
+        });
+    }
+
+    fn end_tag(&mut self, start: usize) {
+        let name_start = start + 2;
+        let Some(name_end) = self.name_end(name_start) else {
+            match self.atom_at(name_start) {
+                Some(end) => self.error(
+                    "Interpolations are not allowed in xml element names.",
+                    name_start..end,
+                ),
+                None => self.error("Expected an element name after `</`.", start..name_start),
+            }
+            self.skip_tag();
+            return;
+        };
+        if let Some(end) = self.atom_at(name_end) {
+            self.error(
+                "Interpolations are not allowed in xml element names.",
+                name_end..end,
+            );
+            self.skip_tag();
+            return;
+        }
+        self.offset = name_end;
+        self.skip_whitespace();
+        if !self.rest().starts_with('>') {
+            let name = self.text()[name_start..name_end].to_string();
+            self.error(
+                format!("Expected `>` to close `</{name}`."),
+                start..name_end,
+            );
+            self.skip_tag();
+            return;
+        }
+        self.offset += 1;
+        self.tokens.push(Token::EndTag {
+            range: start..self.offset,
+            name: name_start..name_end,
+        });
+    }
+
+    fn start_tag(&mut self, start: usize) {
+        let name_end = self.name_end(start + 1).expect("start tag has a name");
+        if let Some(end) = self.atom_at(name_end) {
+            self.error(
+                "Interpolations are not allowed in xml element names.",
+                name_end..end,
+            );
+            self.skip_tag();
+            return;
+        }
+        let name = start + 1..name_end;
+        self.offset = name_end;
+        let mut attributes = Vec::new();
+        let mut self_closing = false;
+        loop {
+            let separated = self.skip_whitespace();
+            if self.offset >= self.text().len() {
+                let tag = self.text()[name.clone()].to_string();
+                self.error(format!("Unterminated tag `<{tag}`."), start..name_end);
+                return;
+            }
+            if self.rest().starts_with("/>") {
+                self.offset += 2;
+                self_closing = true;
+                break;
+            }
+            if self.rest().starts_with('>') {
+                self.offset += 1;
+                break;
+            }
+            if let Some(end) = self.atom_at(self.offset) {
+                self.error(
+                    "Interpolations cannot stand in for attributes in xml templates; interpolate a quoted attribute value instead.",
+                    self.offset..end,
+                );
+                self.offset = end;
+                continue;
+            }
+            let Some(attribute_end) = self.name_end(self.offset) else {
+                let position = self.offset;
+                let character = self.rest().chars().next().unwrap_or_default();
+                self.error(
+                    format!("Unexpected `{character}` in xml tag."),
+                    position..position + character.len_utf8(),
+                );
+                self.skip_tag();
+                return;
+            };
+            let attribute = self.offset..attribute_end;
+            if !separated {
+                self.error(
+                    "Attributes must be separated by whitespace.",
+                    attribute.clone(),
+                );
+            }
+            if let Some(end) = self.atom_at(attribute_end) {
+                self.error(
+                    "Interpolations are not allowed in xml attribute names.",
+                    attribute_end..end,
+                );
+                self.skip_tag();
+                return;
+            }
+            self.offset = attribute_end;
+            if let Some(value) = self.attribute_value(attribute.clone()) {
+                attributes.push(Attribute {
+                    name: attribute,
+                    value,
+                });
+            }
+        }
+        self.tokens.push(Token::StartTag(StartTag {
+            range: start..self.offset,
+            name,
+            attributes,
+            self_closing,
+        }));
+    }
+
+    fn attribute_value(&mut self, attribute: Range<usize>) -> Option<Range<usize>> {
+        let name = self.text()[attribute.clone()].to_string();
+        self.skip_whitespace();
+        if !self.rest().starts_with('=') {
+            self.error(
+                format!("Attribute `{name}` has no value; xml attributes need `=\"...\"`."),
+                attribute,
+            );
+            return None;
+        }
+        self.offset += 1;
+        self.skip_whitespace();
+        let start = self.offset;
+        if let Some(end) = self.atom_at(start) {
+            self.error(
+                format!("The value of attribute `{name}` must be quoted in xml templates."),
+                start..end,
+            );
+            self.offset = end;
+            return None;
+        }
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => {
+                self.error(
+                    format!("The value of attribute `{name}` must be quoted."),
+                    attribute,
+                );
+                while self.offset < self.text().len()
+                    && !self.rest().starts_with("/>")
+                    && !self.rest().starts_with(|character: char| {
+                        character.is_whitespace() || character == '>'
+                    })
+                {
+                    self.advance();
+                }
+                return None;
+            }
+        };
+        let Some(close) = self.find(start + 1, quote.encode_utf8(&mut [0; 4])) else {
+            self.error(
+                format!("Unterminated value for attribute `{name}`."),
+                start..self.text().len(),
+            );
+            self.offset = self.text().len();
+            return None;
+        };
+        if let Some(position) = self.static_find(start + 1..close, "<") {
+            self.error(
+                "Unescaped `<` in xml attribute value; write `&lt;`.",
+                position..position + 1,
+            );
+        }
+        self.check_references(start + 1..close);
+        self.offset = close + 1;
+        Some(start..self.offset)
+    }
+
+    /// Reports `&` that does not start a valid character or entity reference.
+    fn check_references(&mut self, range: Range<usize>) {
+        let mut offset = range.start;
+        while offset < range.end {
+            if let Some(end) = self.atom_at(offset) {
+                offset = end;
+                continue;
+            }
+            let Some(character) = self.text()[offset..range.end].chars().next() else {
+                break;
+            };
+            if character == '&' {
+                let body_start = offset + 1;
+                let body_end = self.text()[body_start..range.end]
+                    .find(|character: char| {
+                        !(character.is_alphanumeric() || matches!(character, '#' | '_' | '-' | '.'))
+                    })
+                    .map_or(range.end, |position| body_start + position);
+                let body = &self.text()[body_start..body_end];
+                let terminated = self.text()[body_end..range.end].starts_with(';');
+                let valid = if let Some(number) = body.strip_prefix("#x") {
+                    !number.is_empty() && number.chars().all(|digit| digit.is_ascii_hexdigit())
+                } else if let Some(number) = body.strip_prefix('#') {
+                    !number.is_empty() && number.chars().all(|digit| digit.is_ascii_digit())
+                } else {
+                    !body.is_empty()
+                };
+                if !terminated || !valid {
+                    self.error("Unescaped `&` in xml; write `&amp;`.", offset..offset + 1);
+                } else if !body.starts_with('#')
+                    && !self.has_doctype
+                    && !PREDEFINED_ENTITIES.contains(&body)
+                {
+                    self.error(
+                        format!("Unknown entity `&{body};`; only `&amp;`, `&lt;`, `&gt;`, `&quot;`, and `&apos;` are predefined."),
+                        offset..body_end + 1,
+                    );
+                }
+            }
+            offset += character.len_utf8();
+        }
+    }
+
+    /// Skips past the next `>`, for recovering from a malformed tag.
+    fn skip_tag(&mut self) {
+        match self.find(self.offset, ">") {
+            Some(position) => self.offset = position + 1,
+            None => self.offset = self.text().len(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.offset;
+        while self.atom_at(self.offset).is_none() && self.rest().starts_with(char::is_whitespace) {
+            self.advance();
+        }
+        self.offset > start
+    }
+
+    fn advance(&mut self) -> char {
+        let character = self.rest().chars().next().unwrap_or_default();
+        self.offset += character.len_utf8().max(1);
+        character
+    }
+
+    /// The end of the xml name starting at `offset`, if one starts there.
+    fn name_end(&self, offset: usize) -> Option<usize> {
+        if self.atom_at(offset).is_some() {
+            return None;
+        }
+        let rest = &self.text()[offset.min(self.text().len())..];
+        let first = rest.chars().next()?;
+        if !(first.is_alphabetic() || first == '_' || first == ':') {
+            return None;
+        }
+        let mut end = offset + first.len_utf8();
+        for character in rest[first.len_utf8()..].chars() {
+            if self.atom_at(end).is_some()
+                || !(character.is_alphanumeric() || matches!(character, '_' | ':' | '-' | '.'))
+            {
+                break;
+            }
+            end += character.len_utf8();
+        }
+        Some(end)
+    }
+
+    /// The next occurrence of `pattern` at or after `from`, skipping over
+    /// interpolations.
+    fn find(&self, from: usize, pattern: &str) -> Option<usize> {
+        let mut offset = from;
+        while offset < self.text().len() {
+            if let Some(end) = self.atom_at(offset) {
+                offset = end;
+                continue;
+            }
+            if self.text()[offset..].starts_with(pattern) {
+                return Some(offset);
+            }
+            offset += self.text()[offset..].chars().next()?.len_utf8();
+        }
+        None
+    }
+
+    /// The first occurrence of `pattern` in the static text of `range`.
+    fn static_find(&self, range: Range<usize>, pattern: &str) -> Option<usize> {
+        self.find(range.start, pattern)
+            .filter(|position| position + pattern.len() <= range.end)
+    }
+}
@@ -1,15 +1,16 @@
 use anyhow::Result;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use t_linter_core::{
-    DiagnosticData, DiagnosticEdit, FormatOptions as CoreFormatOptions, LintDiagnostic,
-    LintSeverity, ShadowDocument, SqlConfig, TemplateHighlighter, TemplateStringInfo,
-    TemplateStringParser, format_document_range_with_options, format_document_with_options,
-    lint_source_with_config, load_project_config_for_path, synthesize_for_type_check_with_config,
+    CustomGrammars, DiagnosticData, DiagnosticEdit, FormatOptions as CoreFormatOptions,
+    HighlightedRange, LanguagePlugins, LintDiagnostic, LintSeverity, ProjectConfig, ShadowDocument,
+    SqlConfig, TemplateHighlighter, TemplateStringInfo, TemplateStringParser,
+    format_document_range_with_options, format_document_with_options, lint_source_with_config,
+    load_project_config_for_path, synthesize_for_type_check_with_config,
 };
 use tower_lsp::jsonrpc::Result as JsonRpcResult;
 use tower_lsp::lsp_types::*;
@@ -878,7 +879,8 @@ impl TLinterLanguageServer {
                 return;
             };
 
-            let mut project_config = match load_project_config_for_path(&path) {
+            let settings = config.read().await.clone();
+            let mut project_config = match load_trusted_project_config(&path, &settings) {
                 Ok(config) => config,
                 Err(err) => {
                     client
@@ -895,9 +897,15 @@ impl TLinterLanguageServer {
                     return;
                 }
             };
-            project_config.sql = merge_sql_config(project_config.sql, &config.read().await.sql);
+            project_config.sql = merge_sql_config(project_config.sql, &settings.sql);
 
-            let diagnostics = match lint_source_with_config(&path, &text, &project_config) {
+            let lint_path = path.clone();
+            let lint_text = text.clone();
+            let lint_config = project_config.clone();
+            let linted =
+                run_blocking(move || lint_source_with_config(&lint_path, &lint_text, &lint_config))
+                    .await;
+            let diagnostics = match linted {
                 Ok(result) => result
                     .diagnostics
                     .iter()
@@ -1036,8 +1044,9 @@ impl TLinterLanguageServer {
             ensure_cached_document_version(&self.document_cache, uri, expected_version)?;
             return Ok(Vec::new());
         }
-        let options =
-            resolve_lsp_format_options(uri, formatting_options).map_err(internal_error)?;
+        let settings = self.config.read().await.clone();
+        let options = resolve_lsp_format_options(uri, formatting_options, &settings)
+            .map_err(internal_error)?;
         let ruff_text = if let Some(ruff) = ruff {
             self.apply_ruff_pipeline(&ruff, uri, &text, state.version, formatting_options)
                 .await
@@ -1045,7 +1054,10 @@ impl TLinterLanguageServer {
         } else {
             text.clone()
         };
-        let edits = format_document_with_options(&ruff_text, &options).map_err(internal_error)?;
+        let format_text = ruff_text.clone();
+        let edits = run_blocking(move || format_document_with_options(&format_text, &options))
+            .await
+            .map_err(internal_error)?;
         let final_text =
             t_linter_core::apply_template_edits(&ruff_text, &edits).map_err(internal_error)?;
 
@@ -1147,8 +1159,9 @@ impl TLinterLanguageServer {
         if !might_contain_template_string(&source) {
             return Ok(SelectionFormatEdits::NoTemplate);
         }
-        let options =
-            resolve_lsp_format_options(uri, formatting_options).map_err(internal_error)?;
+        let settings = self.config.read().await.clone();
+        let options = resolve_lsp_format_options(uri, formatting_options, &settings)
+            .map_err(internal_error)?;
         let location = lsp_range_to_location(range, &source).map_err(internal_error)?;
         let mut parser = self.parser.lock().await;
         let templates = parser
@@ -1169,8 +1182,13 @@ impl TLinterLanguageServer {
             return Ok(SelectionFormatEdits::MultipleTemplates);
         }
 
-        let edits = format_document_range_with_options(&source, &location, &options)
-            .map_err(internal_error)?;
+        let format_source = source.clone();
+        let format_location = location.clone();
+        let edits = run_blocking(move || {
+            format_document_range_with_options(&format_source, &format_location, &options)
+        })
+        .await
+        .map_err(internal_error)?;
         ensure_cached_document_version(&self.document_cache, uri, expected_version)?;
         Ok(SelectionFormatEdits::Edits(
             template_edits_to_lsp(edits, &source).map_err(internal_error)?,
@@ -1303,9 +1321,17 @@ impl TLinterLanguageServer {
 
         debug!("Generating semantic tokens for: {}", uri);
 
-        let mut parser = self.parser.lock().await;
-        let templates = parser.find_template_strings(&text)?;
-        let highlight_untyped_templates = self.config.read().await.highlight_untyped_templates;
+        let templates = self.parser.lock().await.find_template_strings(&text)?;
+        let settings = self.config.read().await.clone();
+        let highlight_untyped_templates = settings.highlight_untyped_templates;
+        let project_config = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| load_trusted_project_config(&path, &settings).ok())
+            .unwrap_or_default();
+        let sql_config = project_config.sql.clone();
+        let plugins = LanguagePlugins::from_config(&project_config);
+        let grammars = CustomGrammars::from_config(&project_config);
 
         let mut all_tokens = Vec::new();
 
@@ -1329,43 +1355,55 @@ impl TLinterLanguageServer {
             if let Some(lang) = &template.language {
                 info!("Attempting to highlight {} template", lang);
 
-                let mut highlighter = self.highlighter.lock().await;
-                if highlighter.supports_language(lang) {
-                    match highlighter.highlight_template(template) {
-                        Ok(ranges) => {
-                            info!("Successfully highlighted {} ranges", ranges.len());
-
-                            for (i, range) in ranges.iter().take(5).enumerate() {
-                                info!(
-                                    "  Range {}: {}..{} type={}",
-                                    i, range.start_byte, range.end_byte, range.highlight_name
-                                );
-                            }
+                let highlighted = if plugins.supports_language(lang) {
+                    Some(self.highlight_plugin_template(&plugins, template).await)
+                } else {
+                    let mut highlighter = self.highlighter.lock().await;
+                    highlighter.set_sql_config(sql_config.clone());
+                    highlighter.set_grammars(grammars.clone());
+                    highlighter
+                        .supports_language(lang)
+                        .then(|| highlighter.highlight_template(template))
+                };
+                match highlighted {
+                    Some(Ok(ranges)) => {
+                        info!("Successfully highlighted {} ranges", ranges.len());
+
+                        for (i, range) in ranges.iter().take(5).enumerate() {
+                            info!(
+                                "  Range {}: {}..{} type={}",
+                                i, range.start_byte, range.end_byte, range.highlight_name
+                            );
+                        }
 
-                            let tokens = highlighter.to_lsp_tokens(ranges, template);
-                            info!("Converted to {} LSP tokens", tokens.len());
+                        let tokens = self
+                            .highlighter
+                            .lock()
+                            .await
+                            .to_lsp_tokens(ranges, template);
+                        info!("Converted to {} LSP tokens", tokens.len());
 
-                            all_tokens.extend(tokens);
-                        }
-                        Err(e) => {
-                            self.client
-                                .log_message(
-                                    MessageType::ERROR,
-                                    format!("Failed to highlight {} template: {}", lang, e),
-                                )
-                                .await;
-
-                            let tokens = self.generate_fallback_tokens(template, &text);
-                            all_tokens.extend(tokens);
-                        }
+                        all_tokens.extend(tokens);
+                    }
+                    Some(Err(e)) => {
+                        self.client
+                            .log_message(
+                                MessageType::ERROR,
+                                format!("Failed to highlight {} template: {}", lang, e),
+                            )
+                            .await;
+
+                        let tokens = self.generate_fallback_tokens(template, &text);
+                        all_tokens.extend(tokens);
+                    }
+                    None => {
+                        info!(
+                            "Unsupported highlight language {}, using fallback tokens",
+                            lang
+                        );
+                        let tokens = self.generate_fallback_tokens(template, &text);
+                        all_tokens.extend(tokens);
                     }
-                } else {
-                    info!(
-                        "Unsupported highlight language {}, using fallback tokens",
-                        lang
-                    );
-                    let tokens = self.generate_fallback_tokens(template, &text);
-                    all_tokens.extend(tokens);
                 }
             } else {
                 if !highlight_untyped_templates {
@@ -1401,6 +1439,29 @@ impl TLinterLanguageServer {
             data,
         })
     }
+
+    /// Highlights a template with its language plugin on the blocking pool,
+    /// so waiting for the plugin does not hold the shared highlighter.
+    async fn highlight_plugin_template(
+        &self,
+        plugins: &LanguagePlugins,
+        template: &TemplateStringInfo,
+    ) -> Result<Vec<HighlightedRange>> {
+        let request_plugins = plugins.clone();
+        let request_template = template.clone();
+        let spans = run_blocking(move || {
+            request_plugins
+                .highlight_template(&request_template)
+                .unwrap_or_else(|| Ok(Vec::new()))
+        })
+        .await?;
+        Ok(self
+            .highlighter
+            .lock()
+            .await
+            .highlight_plugin_template(template, spans))
+    }
+
     fn convert_to_semantic_tokens(
         &self,
         tokens: Vec<(u32, u32, u32, u32, u32)>,
@@ -1436,19 +1497,37 @@ impl TLinterLanguageServer {
 fn resolve_lsp_format_options(
     uri: &Url,
     formatting_options: Option<&FormattingOptions>,
+    settings: &TLinterConfig,
 ) -> Result<CoreFormatOptions> {
+    let project_config = uri
+        .to_file_path()
+        .ok()
+        .and_then(|path| load_trusted_project_config(&path, settings).ok());
     let line_length = formatting_options
         .and_then(extract_line_length_from_lsp_options)
         .or_else(|| {
-            uri.to_file_path()
-                .ok()
-                .and_then(|path| load_project_config_for_path(&path).ok())
+            project_config
+                .as_ref()
                 .and_then(|config| config.line_length)
         })
         .unwrap_or(80)
         .max(1);
 
-    Ok(CoreFormatOptions { line_length })
+    let plugins = project_config
+        .as_ref()
+        .map(LanguagePlugins::from_config)
+        .unwrap_or_default();
+    let format = project_config
+        .map(|config| config.format)
+        .unwrap_or_default();
+
+    Ok(CoreFormatOptions {
+        line_length,
+        css: format.css,
+        javascript: format.javascript,
+        sql: format.sql,
+        plugins,
+    })
 }
 
 fn extract_line_length_from_lsp_options(options: &FormattingOptions) -> Option<usize> {
@@ -2266,6 +2345,10 @@ struct TLinterInitializationOptions {
     type_checking: Option<TypeCheckerConfig>,
     #[serde(default)]
     sql: Option<SqlConfig>,
+    #[serde(default)]
+    allowed_plugins: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(default)]
+    allowed_grammars: Option<BTreeMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -2276,6 +2359,13 @@ pub struct TLinterConfig {
     pub ruff_pipeline: RuffPipelineConfig,
     pub type_checking: TypeCheckerConfig,
     pub sql: SqlConfig,
+    /// The `[tool.t-linter.plugins]` command the server may run for each
+    /// language. A workspace plugin runs only when its resolved command is
+    /// the one allowed for its language.
+    pub allowed_plugins: BTreeMap<String, Vec<String>>,
+    /// The `[tool.t-linter.grammars]` library the server may load for each
+    /// language, compared by canonical path.
+    pub allowed_grammars: BTreeMap<String, String>,
 }
 
 impl Default for TLinterConfig {
@@ -2287,6 +2377,8 @@ impl Default for TLinterConfig {
             ruff_pipeline: RuffPipelineConfig::default(),
             type_checking: TypeCheckerConfig::default(),
             sql: SqlConfig::default(),
+            allowed_plugins: BTreeMap::new(),
+            allowed_grammars: BTreeMap::new(),
         }
     }
 }
@@ -2313,9 +2405,96 @@ fn parse_initialization_config(
         ruff_pipeline: options.ruff_pipeline.unwrap_or(defaults.ruff_pipeline),
         type_checking: options.type_checking.unwrap_or(defaults.type_checking),
         sql: merge_sql_config(defaults.sql, &options.sql.unwrap_or_default()),
+        allowed_plugins: options
+            .allowed_plugins
+            .map(normalize_language_keys)
+            .unwrap_or(defaults.allowed_plugins),
+        allowed_grammars: options
+            .allowed_grammars
+            .map(normalize_language_keys)
+            .unwrap_or(defaults.allowed_grammars),
     }
 }
 
+fn normalize_language_keys<T>(entries: BTreeMap<String, T>) -> BTreeMap<String, T> {
+    entries
+        .into_iter()
+        .map(|(language, value)| (language.trim().to_ascii_lowercase(), value))
+        .collect()
+}
+
+/// Loads the project config for a document, keeping only the language
+/// plugins and grammars the user allowed in the editor settings. A plugin
+/// runs a command and a grammar loads a native library named by the
+/// workspace's `pyproject.toml`, so opening a cloned project must not do
+/// either on its own.
+fn load_trusted_project_config(path: &Path, config: &TLinterConfig) -> Result<ProjectConfig> {
+    let mut project_config = load_project_config_for_path(path)?;
+    let root = project_config.root.clone();
+    project_config.plugins.retain(|language, plugin| {
+        config.allowed_plugins.get(language).is_some_and(|allowed| {
+            resolved_command(&root, &plugin.command) == canonical_absolute_paths(allowed)
+        })
+    });
+    project_config.grammars.retain(|language, grammar| {
+        let library = grammar
+            .library
+            .as_deref()
+            .and_then(|library| canonical_path(&root.join(library)));
+        let allowed = config
+            .allowed_grammars
+            .get(language)
+            .and_then(|allowed| canonical_path(Path::new(allowed)));
+        library.is_some() && library == allowed
+    });
+    Ok(project_config)
+}
+
+/// A plugin command as it runs from the project root: a program given as a
+/// path and each argument naming an existing file are replaced by their
+/// canonical paths, so the same command text in another workspace does not
+/// match. A bare program name is looked up on `PATH` and kept as written.
+fn resolved_command(root: &Path, command: &[String]) -> Vec<String> {
+    command
+        .iter()
+        .enumerate()
+        .map(|(index, part)| {
+            if index == 0 && Path::new(part).components().count() == 1 {
+                return part.clone();
+            }
+            canonical_path(&root.join(part)).unwrap_or_else(|| part.clone())
+        })
+        .collect()
+}
+
+/// An allowed command with its absolute paths canonicalized. Relative paths
+/// are kept, so they never match a workspace file.
+fn canonical_absolute_paths(command: &[String]) -> Vec<String> {
+    command
+        .iter()
+        .map(|part| {
+            Some(Path::new(part))
+                .filter(|path| path.is_absolute())
+                .and_then(canonical_path)
+                .unwrap_or_else(|| part.clone())
+        })
+        .collect()
+}
+
+fn canonical_path(path: &Path) -> Option<String> {
+    path.canonicalize()
+        .ok()
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Runs work that may call a language plugin on the blocking thread pool,
+/// since a plugin can take up to its timeout to answer.
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(work).await?
+}
+
 fn merge_sql_config(mut base: SqlConfig, override_config: &SqlConfig) -> SqlConfig {
     if override_config.library.is_some() {
         base.library = override_config.library.clone();
@@ -2659,7 +2838,8 @@ mod tests {
         assert_eq!(extract_line_length_from_lsp_options(&options), None);
 
         let uri = Url::parse("untitled:example.py").expect("uri");
-        let resolved = resolve_lsp_format_options(&uri, None).expect("default options");
+        let resolved = resolve_lsp_format_options(&uri, None, &TLinterConfig::default())
+            .expect("default options");
         assert_eq!(resolved.line_length, 80);
     }
 
@@ -2673,10 +2853,104 @@ mod tests {
         .expect("write pyproject");
         let uri = Url::from_file_path(temp.path().join("example.py")).expect("file url");
 
-        let options = resolve_lsp_format_options(&uri, None).expect("resolve options");
+        let options = resolve_lsp_format_options(&uri, None, &TLinterConfig::default())
+            .expect("resolve options");
         assert_eq!(options.line_length, 88);
     }
 
+    #[test]
+    fn lsp_runs_only_the_language_plugins_the_user_allowed() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.plugins.ruledsl]\ncommand = [\"python\", \"ruledsl.py\"]\n",
+        )
+        .expect("write pyproject");
+        std::fs::write(temp.path().join("ruledsl.py"), "").expect("write plugin");
+        let uri = Url::from_file_path(temp.path().join("example.py")).expect("file url");
+
+        let options = resolve_lsp_format_options(&uri, None, &TLinterConfig::default())
+            .expect("resolve options");
+        assert!(!options.plugins.supports_language("ruledsl"));
+
+        let plugin = temp
+            .path()
+            .join("ruledsl.py")
+            .to_string_lossy()
+            .into_owned();
+        let settings = parse_initialization_config(
+            Some(serde_json::json!({"allowedPlugins": {"RuleDSL": ["python", plugin]}})),
+            TLinterConfig::default(),
+        );
+        assert_eq!(
+            settings.allowed_plugins,
+            BTreeMap::from([("ruledsl".to_string(), vec!["python".to_string(), plugin])])
+        );
+        let options = resolve_lsp_format_options(&uri, None, &settings).expect("resolve options");
+        assert!(options.plugins.supports_language("ruledsl"));
+    }
+
+    #[test]
+    fn lsp_skips_an_allowed_plugin_language_that_runs_a_different_command() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.plugins.ruledsl]\ncommand = [\"python\", \"other.py\"]\n",
+        )
+        .expect("write pyproject");
+        std::fs::write(temp.path().join("ruledsl.py"), "").expect("write plugin");
+        std::fs::write(temp.path().join("other.py"), "").expect("write plugin");
+        let uri = Url::from_file_path(temp.path().join("example.py")).expect("file url");
+
+        let plugin = temp
+            .path()
+            .join("ruledsl.py")
+            .to_string_lossy()
+            .into_owned();
+        for allowed in [
+            vec!["python".to_string(), plugin],
+            vec!["python".to_string(), "other.py".to_string()],
+        ] {
+            let settings = parse_initialization_config(
+                Some(serde_json::json!({"allowedPlugins": {"ruledsl": allowed}})),
+                TLinterConfig::default(),
+            );
+            let options =
+                resolve_lsp_format_options(&uri, None, &settings).expect("resolve options");
+            assert!(!options.plugins.supports_language("ruledsl"));
+        }
+    }
+
+    #[test]
+    fn lsp_loads_only_the_grammars_the_user_allowed() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.t-linter.grammars.lua]\nlibrary = \"lua.so\"\n",
+        )
+        .expect("write pyproject");
+        std::fs::write(temp.path().join("lua.so"), "").expect("write library");
+        std::fs::write(temp.path().join("other.so"), "").expect("write library");
+        let path = temp.path().join("example.py");
+
+        let config =
+            load_trusted_project_config(&path, &TLinterConfig::default()).expect("load config");
+        assert!(!CustomGrammars::from_config(&config).supports_language("lua"));
+
+        for (library, allowed) in [("lua.so", true), ("other.so", false)] {
+            let library = temp.path().join(".").join(library);
+            let settings = parse_initialization_config(
+                Some(serde_json::json!({"allowedGrammars": {"Lua": library}})),
+                TLinterConfig::default(),
+            );
+            let config = load_trusted_project_config(&path, &settings).expect("load config");
+            assert_eq!(
+                CustomGrammars::from_config(&config).supports_language("lua"),
+                allowed
+            );
+        }
+    }
+
     #[test]
     fn requested_code_action_kinds_match_specific_and_parent_kinds() {
         assert!(code_action_kind_matches(
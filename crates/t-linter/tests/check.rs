@@ -244,6 +244,317 @@ payload: Annotated[Template, Json(schema=Order)] = t'{{"id": "abc", "nme": "Ada"
     let _ = fs::remove_dir_all(dir);
 }
 
+#[test]
+fn check_reports_nested_json_schema_binding_diagnostics() {
+    let dir = test_dir("json-schema-nested");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from enum import Enum
+from typing import Annotated, Literal, Optional, TypedDict
+from annotated_types import Ge
+from string.templatelib import Template
+from json_tstring import Json
+
+class Color(str, Enum):
+    RED = "red"
+    GREEN = "green"
+
+class Item(TypedDict):
+    sku: str
+    quantity: Annotated[int, Ge(1)]
+
+class Order(TypedDict):
+    items: list[Item]
+    tags: dict[str, int]
+    status: Literal["draft", "paid"]
+    color: Color
+    note: Optional[str]
+
+payload: Annotated[Template, Json(schema=Order)] = t'{{"items": [{{"sku": "a", "quantity": 0}}, {{"sku": 1, "quantity": {count}}}], "tags": {{"x": "y"}}, "status": "payd", "color": "blue", "note": null}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let diagnostics = json["diagnostics"].as_array().unwrap();
+    let pointers = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["schema_pointer"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 5);
+    assert!(pointers.contains(&("template-schema-invalid-value", "/items/0/quantity")));
+    assert!(pointers.contains(&("template-schema-type-shape", "/items/1/sku")));
+    assert!(pointers.contains(&("template-schema-type-shape", "/tags/x")));
+    assert!(pointers.contains(&("template-schema-invalid-value", "/status")));
+    assert!(pointers.contains(&("template-schema-invalid-value", "/color")));
+    let status = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["schema_pointer"] == "/status")
+        .unwrap();
+    assert_eq!(status["expected_type"], "\"draft\" | \"paid\"");
+    assert_eq!(status["suggested_edits"][0]["new_text"], "\"paid\"");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_nested_yaml_schema_binding_diagnostics() {
+    let dir = test_dir("yaml-schema-nested");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Literal, TypedDict
+from yaml_tstring import Yaml
+
+class Service(TypedDict):
+    image: str
+    mode: Literal["replicated", "global"]
+
+class Compose(TypedDict):
+    services: dict[str, Service]
+
+bad: Yaml[Compose] = t"services:\n  web:\n    image: nginx\n    mode: globl\n    ports: []\n"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let diagnostics = json["diagnostics"].as_array().unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 2);
+    let invalid = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["rule"] == "template-schema-invalid-value")
+        .unwrap();
+    assert_eq!(invalid["schema_pointer"], "/services/web/mode");
+    assert_eq!(invalid["suggested_edits"][0]["new_text"], "global");
+    let unknown = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["rule"] == "template-schema-unknown-key")
+        .unwrap();
+    assert_eq!(unknown["schema_pointer"], "/services/web/ports");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_pydantic_schema_binding_diagnostics() {
+    let dir = test_dir("pydantic-schema-binding");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Optional
+from pydantic import BaseModel, ConfigDict, Field
+from json_tstring import Json
+
+class Order(BaseModel):
+    model_config = ConfigDict(extra="forbid", populate_by_name=True)
+
+    order_id: int = Field(alias="orderId")
+    quantity: int = Field(
+        default=1,
+        ge=1,
+    )
+    note: Optional[str] = None
+    _secret: str
+
+class Event(BaseModel):
+    name: str
+
+class Strict(BaseModel):
+    model_config = {"extra": "forbid"}
+    name: str
+
+class Commented(BaseModel):  # extra="forbid"
+    name: str
+
+bad: Json[Order] = t'{{"quantity": 0, "nots": "x"}}'
+by_name: Json[Order] = t'{{"order_id": 1}}'
+by_alias: Json[Order] = t'{{"orderId": 1, "note": null}}'
+extra_ignored: Json[Event] = t'{{"name": "x", "other": 1}}'
+extra_forbidden_by_dict: Json[Strict] = t'{{"name": "x", "strict": 1}}'
+extra_in_comment: Json[Commented] = t'{{"name": "x", "other": 1}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let pointers = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["schema_pointer"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 4);
+    assert!(pointers.contains(&("template-schema-missing-key", "/orderId")));
+    assert!(pointers.contains(&("template-schema-invalid-value", "/quantity")));
+    assert!(pointers.contains(&("template-schema-unknown-key", "/nots")));
+    assert!(pointers.contains(&("template-schema-unknown-key", "/strict")));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_attrs_schema_binding_diagnostics() {
+    let dir = test_dir("attrs-schema-binding");
+    write_file(
+        &dir.join("broken.py"),
+        r#"import attr
+from attrs import define, field
+from json_tstring import Json
+
+@define
+class User:
+    name: str
+    tags: list[str] = field(factory=list)
+    role: str = field()
+
+@attr.s
+class Legacy:
+    id = attr.ib()
+    label = attr.ib(default="x")
+
+user: Json[User] = t'{{"name": 1, "tags": []}}'
+legacy: Json[Legacy] = t'{{"label": "y", "extra": true}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let pointers = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["schema_pointer"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 4);
+    assert!(pointers.contains(&("template-schema-type-shape", "/name")));
+    assert!(pointers.contains(&("template-schema-missing-key", "/role")));
+    assert!(pointers.contains(&("template-schema-missing-key", "/id")));
+    assert!(pointers.contains(&("template-schema-unknown-key", "/extra")));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_json_schema_file_binding_diagnostics() {
+    let dir = test_dir("json-schema-file-binding");
+    write_file(
+        &dir.join("schemas/order.schema.json"),
+        r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "properties": {
+    "id": {"type": "integer", "minimum": 1},
+    "status": {"enum": ["draft", "paid"]},
+    "items": {"type": "array", "items": {"$ref": "#/$defs/item"}}
+  },
+  "required": ["id", "items"],
+  "additionalProperties": false,
+  "$defs": {
+    "item": {
+      "type": "object",
+      "properties": {"sku": {"type": "string"}},
+      "required": ["sku"]
+    }
+  }
+}
+"##,
+    );
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.schema-files]\nOrder = \"schemas/order.schema.json\"\n",
+    );
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+from json_tstring import Json
+
+bad: Annotated[Template, Json(schema_file="schemas/order.schema.json")] = t'{{"id": 0, "status": "payd", "items": [{{"sku": 1}}], "extra": true}}'
+mapped: Json[Order] = t'{{"items": []}}'
+missing: Annotated[Template, Json(schema_file="schemas/missing.json")] = t'{{}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let diagnostics = json["diagnostics"].as_array().unwrap();
+    let found = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["schema_pointer"].as_str().unwrap_or_default(),
+                diagnostic["source_of_truth"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 6);
+    assert!(found.contains(&(
+        "template-schema-invalid-value",
+        "/id",
+        "schemas/order.schema.json#/properties/id"
+    )));
+    assert!(found.contains(&(
+        "template-schema-invalid-value",
+        "/status",
+        "schemas/order.schema.json#/properties/status"
+    )));
+    assert!(found.contains(&(
+        "template-schema-type-shape",
+        "/items/0/sku",
+        "schemas/order.schema.json#/$defs/item/properties/sku"
+    )));
+    assert!(found.contains(&(
+        "template-schema-unknown-key",
+        "/extra",
+        "schemas/order.schema.json#"
+    )));
+    assert!(found.contains(&(
+        "template-schema-missing-key",
+        "/id",
+        "schemas/order.schema.json#"
+    )));
+    let unresolved = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["rule"] == "binding-unresolved")
+        .unwrap();
+    assert_eq!(
+        unresolved["message"],
+        "Could not find JSON Schema file 'schemas/missing.json'."
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
 #[test]
 fn check_json_marker_metadata_enables_json_parse_diagnostics() {
     let dir = test_dir("json-marker-language");
@@ -367,128 +678,344 @@ payload: Annotated[Template, Json, YamlTemplate] = t'{{"id": 1}}'
 }
 
 #[test]
-fn check_resolves_json_schema_binding_marker_alias() {
-    let dir = test_dir("json-schema-marker-alias");
+fn check_reports_strings_passed_where_template_expected() {
+    let dir = test_dir("template-string-expected");
+    write_file(
+        &dir.join("views.py"),
+        r#"from string.templatelib import Template
+
+def render(body: Template) -> str:
+    return ""
+"#,
+    );
     write_file(
         &dir.join("broken.py"),
-        r#"from typing import Annotated, TypedDict
+        r#"from typing import Annotated, Optional
 from string.templatelib import Template
-from json_tstring import Json as JsonMarker
+from views import render
 
-class Order(TypedDict):
-    id: int
+def page(body: Template, *, title: Annotated[Template, "html"] | None = None) -> str:
+    return ""
 
-payload: Annotated[Template, JsonMarker(schema=Order)] = t'{{"id": "abc"}}'
+def show(body: str | Template, footer: Optional[str | Template] = None) -> str:
+    return ""
+
+name = "Ada"
+render(f"<p>{name}</p>")
+page(t"<p>{name}</p>", title="<h1>%s</h1>" % name)
+page("<p>{}</p>".format(name))
+header: Template = rf"<h1>{name}</h1>"
+label: str = f"{name}"
+page(t"<p>{name}</p>")
+show(f"<p>{name}</p>", footer=f"<p>{name}</p>")
+either: str | Template = f"{name}"
 "#,
     );
 
     let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let diagnostics = json["diagnostics"].as_array().unwrap();
 
     assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 1);
-    assert_eq!(json["diagnostics"][0]["rule"], "template-schema-type-shape");
-    assert_eq!(json["diagnostics"][0]["language"], "json");
-    assert_eq!(json["diagnostics"][0]["schema_pointer"], "/id");
-    assert_eq!(json["diagnostics"][0]["source_of_truth"], "Order");
-    assert_eq!(json["diagnostics"][0]["expected_type"], "integer");
-    assert_eq!(json["diagnostics"][0]["found_type"], "string");
+    assert_eq!(json["summary"]["diagnostics"], 4);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic["rule"] == "template-string-expected")
+    );
+    assert_eq!(diagnostics[0]["start_line"], 12);
+    assert!(
+        diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Parameter 'body' of 'render' expects a Template, but an f-string")
+    );
+    assert_eq!(diagnostics[0]["suggested_edits"][0]["new_text"], "t");
+    assert!(
+        diagnostics[1]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Parameter 'title' of 'page' expects a Template, but a %-formatted string")
+    );
+    assert_eq!(diagnostics[1]["suggested_edits"], serde_json::json!([]));
+    assert!(
+        diagnostics[2]["message"]
+            .as_str()
+            .unwrap()
+            .contains("a .format() string")
+    );
+    assert!(
+        diagnostics[3]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Variable 'header' is annotated as a Template")
+    );
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_reports_json_generic_binding_and_unresolved_binding() {
-    let dir = test_dir("json-generic-binding");
+fn check_fix_converts_f_string_to_template_string() {
+    let dir = test_dir("template-string-expected-fix");
+    let path = dir.join("broken.py");
     write_file(
-        &dir.join("broken.py"),
-        r#"from typing import TypedDict
-from json_tstring import Json
+        &path,
+        r#"from string.templatelib import Template
 
-class Order(TypedDict):
-    id: int
+def render(body: Template) -> str:
+    return ""
 
-ok: Json[Order] = t'{{"id": 1}}'
-bad: Json[Order] = t'{{"id": "abc"}}'
-missing: Json[MissingOrder] = t'{{"id": 1}}'
+name = "Ada"
+render(Rf"<p>{name}</p>")
 "#,
     );
 
-    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let output = run_check(&dir, &["check", "broken.py", "--fix", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    let rules = json["diagnostics"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .map(|diagnostic| diagnostic["rule"].as_str().unwrap())
-        .collect::<Vec<_>>();
+    let fixed = fs::read_to_string(&path).unwrap();
 
     assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 2);
-    assert!(rules.contains(&"template-schema-type-shape"));
-    assert!(rules.contains(&"binding-unresolved"));
-    let shape = json["diagnostics"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .find(|diagnostic| diagnostic["rule"] == "template-schema-type-shape")
-        .unwrap();
-    assert_eq!(shape["schema_pointer"], "/id");
-    assert_eq!(shape["source_of_truth"], "Order");
+    assert_eq!(json["summary"]["diagnostics"], 0);
+    assert!(fixed.contains(r#"render(Rt"<p>{name}</p>")"#));
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_resolves_json_schema_binding_type_alias() {
-    let dir = test_dir("json-schema-type-alias");
-    write_file(
-        &dir.join("broken.py"),
-        r#"from typing import Annotated, TypedDict
-from string.templatelib import Template
-from json_tstring import Json
-
-class Order(TypedDict):
-    id: int
+fn check_fix_leaves_f_strings_concatenated_with_plain_strings() {
+    let dir = test_dir("template-string-expected-mixed-fix");
+    let path = dir.join("broken.py");
+    let source = r#"from string.templatelib import Template
 
-type OrderPayload = Annotated[Template, Json(schema=Order)]
+def render(body: Template) -> str:
+    return ""
 
-payload: OrderPayload = t'{{"id": "abc"}}'
-"#,
-    );
+name = "Ada"
+render(f"<p>{name}</p>" "<b>static</b>")
+"#;
+    write_file(&path, source);
 
-    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let output = run_check(&dir, &["check", "broken.py", "--fix", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
 
     assert_eq!(output.status.code(), Some(0));
     assert_eq!(json["summary"]["diagnostics"], 1);
-    assert_eq!(json["diagnostics"][0]["rule"], "template-schema-type-shape");
-    assert_eq!(json["diagnostics"][0]["language"], "json");
-    assert_eq!(json["diagnostics"][0]["schema_pointer"], "/id");
-    assert_eq!(json["diagnostics"][0]["source_of_truth"], "Order");
+    assert_eq!(json["diagnostics"][0]["rule"], "template-string-expected");
+    assert_eq!(fs::read_to_string(&path).unwrap(), source);
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_ignores_non_module_json_schema_type_alias() {
-    let dir = test_dir("json-schema-local-type-alias");
+fn check_reports_repr_conversions_and_debug_specifiers_in_markup_and_data() {
+    let dir = test_dir("interpolation-formatting");
+    let path = dir.join("broken.py");
     write_file(
-        &dir.join("broken.py"),
-        r#"from typing import Annotated, TypedDict
+        &path,
+        r#"from typing import Annotated
 from string.templatelib import Template
-from json_tstring import Json
-
-class Order(TypedDict):
-    id: int
-
-def configure() -> None:
-    type Payload = Annotated[Template, Json(schema=Order)]
 
-payload: Payload = t'{{"id": "abc"}}'
+name = "Ada"
+page: Annotated[Template, "html"] = t"<p>{name!r} {name = } {name!s} {name:>10} {name:upper}</p>"
+data: Annotated[Template, "json"] = t'{{"name": {name!a}}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let rules = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["language"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        rules,
+        vec![
+            ("interpolation-repr-conversion", "html"),
+            ("interpolation-debug-specifier", "html"),
+            ("interpolation-format-spec-unknown", "html"),
+            ("interpolation-repr-conversion", "json"),
+        ]
+    );
+    assert_eq!(json["diagnostics"][0]["severity"], "warning");
+
+    let source = fs::read_to_string(&path).unwrap();
+    let output = run_check(&dir, &["check", "broken.py", "--fix"]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), source);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_honors_interpolation_allowlist_per_language() {
+    let dir = test_dir("interpolation-formatting-allowlist");
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.interpolation.html]\nallowed-conversions = [\"r\"]\nallow-debug = true\nallowed-format-specs = [\"upper\"]\n",
+    );
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+name = "Ada"
+page: Annotated[Template, "html"] = t"<p>{name!r} {name=} {name:upper}</p>"
+data: Annotated[Template, "yaml"] = t"name: {name!r}\n"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(
+        json["diagnostics"][0]["rule"],
+        "interpolation-repr-conversion"
+    );
+    assert_eq!(json["diagnostics"][0]["language"], "yaml");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_resolves_json_schema_binding_marker_alias() {
+    let dir = test_dir("json-schema-marker-alias");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated, TypedDict
+from string.templatelib import Template
+from json_tstring import Json as JsonMarker
+
+class Order(TypedDict):
+    id: int
+
+payload: Annotated[Template, JsonMarker(schema=Order)] = t'{{"id": "abc"}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(json["diagnostics"][0]["rule"], "template-schema-type-shape");
+    assert_eq!(json["diagnostics"][0]["language"], "json");
+    assert_eq!(json["diagnostics"][0]["schema_pointer"], "/id");
+    assert_eq!(json["diagnostics"][0]["source_of_truth"], "Order");
+    assert_eq!(json["diagnostics"][0]["expected_type"], "integer");
+    assert_eq!(json["diagnostics"][0]["found_type"], "string");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_json_generic_binding_and_unresolved_binding() {
+    let dir = test_dir("json-generic-binding");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import TypedDict
+from json_tstring import Json
+
+class Order(TypedDict):
+    id: int
+
+ok: Json[Order] = t'{{"id": 1}}'
+bad: Json[Order] = t'{{"id": "abc"}}'
+missing: Json[MissingOrder] = t'{{"id": 1}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let rules = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| diagnostic["rule"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 2);
+    assert!(rules.contains(&"template-schema-type-shape"));
+    assert!(rules.contains(&"binding-unresolved"));
+    let shape = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|diagnostic| diagnostic["rule"] == "template-schema-type-shape")
+        .unwrap();
+    assert_eq!(shape["schema_pointer"], "/id");
+    assert_eq!(shape["source_of_truth"], "Order");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_resolves_json_schema_binding_type_alias() {
+    let dir = test_dir("json-schema-type-alias");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated, TypedDict
+from string.templatelib import Template
+from json_tstring import Json
+
+class Order(TypedDict):
+    id: int
+
+type OrderPayload = Annotated[Template, Json(schema=Order)]
+
+payload: OrderPayload = t'{{"id": "abc"}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(json["diagnostics"][0]["rule"], "template-schema-type-shape");
+    assert_eq!(json["diagnostics"][0]["language"], "json");
+    assert_eq!(json["diagnostics"][0]["schema_pointer"], "/id");
+    assert_eq!(json["diagnostics"][0]["source_of_truth"], "Order");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_ignores_non_module_json_schema_type_alias() {
+    let dir = test_dir("json-schema-local-type-alias");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated, TypedDict
+from string.templatelib import Template
+from json_tstring import Json
+
+class Order(TypedDict):
+    id: int
+
+def configure() -> None:
+    type Payload = Annotated[Template, Json(schema=Order)]
+
+payload: Payload = t'{{"id": "abc"}}'
 "#,
     );
 
@@ -557,182 +1084,962 @@ payload: Json[Order] = t'{{"note": "ok"}}'
 "#,
     );
 
-    let output = run_check(&dir, &["check", "app.py", "--format", "json"]);
+    let output = run_check(&dir, &["check", "app.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(
+        json["diagnostics"][0]["rule"],
+        "template-schema-missing-key"
+    );
+    assert!(
+        json["diagnostics"][0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("'id'")
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_suppresses_json_schema_missing_key_when_key_is_interpolated() {
+    let dir = test_dir("json-schema-interpolated-key");
+    write_file(
+        &dir.join("ok.py"),
+        r#"from typing import Annotated, TypedDict
+from string.templatelib import Template
+from json_tstring import Json
+
+class Order(TypedDict):
+    id: int
+
+payload: Annotated[Template, Json(schema=Order)] = t'{{{key}: 1}}'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "ok.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 0);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_yaml_schema_binding_inward_diagnostics() {
+    let dir = test_dir("yaml-schema-binding");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated, NotRequired, TypedDict
+from string.templatelib import Template
+from yaml_tstring import Yaml
+
+class Order(TypedDict):
+    id: int
+    name: str
+    note: NotRequired[str]
+
+payload: Annotated[Template, Yaml(schema=Order)] = t"id: abc\nnme: Ada\n"
+ok: Yaml[Order] = t"id: {order_id}\nname: {name}\n"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let diagnostics = json["diagnostics"].as_array().unwrap();
+    let rules = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic["rule"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 3);
+    assert!(rules.contains(&"template-schema-missing-key"));
+    assert!(rules.contains(&"template-schema-unknown-key"));
+    assert!(rules.contains(&"template-schema-type-shape"));
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic["language"] == "yaml")
+    );
+    let unknown = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["rule"] == "template-schema-unknown-key")
+        .unwrap();
+    assert_eq!(unknown["schema_pointer"], "/nme");
+    assert_eq!(unknown["source_of_truth"], "Order");
+    assert_eq!(unknown["suggested_edits"][0]["new_text"], "name");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_toml_schema_binding_inward_diagnostics() {
+    let dir = test_dir("toml-schema-binding");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import TypedDict
+from toml_tstring import Toml
+
+class Server(TypedDict):
+    host: str
+    port: int
+
+class Config(TypedDict):
+    name: str
+    server: Server
+
+bad: Toml[Config] = t'name = 1\n"nmae" = "x"\n[server]\nport = 80\n'
+ok: Toml[Config] = t'name = {name}\n[server]\nhost = "db"\nport = 5432\n'
+missing: Toml[MissingConfig] = t'name = "x"'
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let diagnostics = json["diagnostics"].as_array().unwrap();
+    let rules = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic["rule"].as_str().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 4);
+    assert!(rules.contains(&"template-schema-type-shape"));
+    assert!(rules.contains(&"template-schema-unknown-key"));
+    assert!(rules.contains(&"binding-unresolved"));
+    let missing = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["rule"] == "template-schema-missing-key")
+        .unwrap();
+    assert_eq!(missing["schema_pointer"], "/server/host");
+    let unknown = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["rule"] == "template-schema-unknown-key")
+        .unwrap();
+    assert_eq!(unknown["language"], "toml");
+    assert_eq!(unknown["schema_pointer"], "/nmae");
+    assert_eq!(unknown["suggested_edits"][0]["new_text"], "\"name\"");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_yaml_plain_scalars_via_imported_function_annotation() {
+    let dir = test_dir("yaml-imported-function");
+    write_file(
+        &dir.join("typed_api.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+def render_data(template: Annotated[Template, "yaml"]) -> object:
+    return {"ok": True}
+"#,
+    );
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typed_api import render_data as render_yaml_data
+
+name = "api"
+replicas = 3
+
+yaml_template = t"""
+service:
+  name: {name}
+  replicas: fdsa fff {replicas}
+"""
+
+render_yaml_data(yaml_template)
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(
+        json["diagnostics"][0]["message"],
+        "Quote YAML plain scalars that mix whitespace and interpolations."
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_yaml_plain_scalars_via_keyword_function_annotation() {
+    let dir = test_dir("yaml-keyword-function");
+    write_file(
+        &dir.join("typed_api.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+def render_data(template: Annotated[Template, "yaml"]) -> object:
+    return {"ok": True}
+"#,
+    );
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typed_api import render_data as render_yaml_data
+
+name = "api"
+replicas = 3
+
+yaml_template = t"""
+service:
+  name: {name}
+  replicas: fdsa fff {replicas}
+"""
+
+render_yaml_data(template=yaml_template)
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(
+        json["diagnostics"][0]["message"],
+        "Quote YAML plain scalars that mix whitespace and interpolations."
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_psycopg_sql_rules_are_silent_without_sql_config() {
+    let dir = test_dir("psycopg-silent-without-config");
+    write_file(
+        &dir.join("query.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+user_id = 1
+query: Annotated[Template, "sql"] = t"SELECT * FROM users WHERE id = {user_id!r}"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "query.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 0);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_psycopg_sql_rules_run_for_inferred_execute_templates() {
+    let dir = test_dir("psycopg-inferred-execute");
+    write_file(
+        &dir.join("query.py"),
+        r#"import psycopg
+
+conn = psycopg.connect("dbname=app")
+cur = conn.cursor()
+cur.execute(t"SELECT * FROM users WHERE id = {user_id!r}")
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "query.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(json["diagnostics"][0]["rule"], "sql-conversion-unsupported");
+    assert_eq!(json["diagnostics"][0]["language"], "sql");
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_constant_duplicated_and_mismatched_interpolations() {
+    let dir = test_dir("interpolation-usage");
+    write_file(
+        &dir.join("page.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+name = "Ada"
+query: Annotated[Template, "sql"] = t"SELECT * FROM users WHERE name = {name}"
+inner: Annotated[Template, "html"] = t"<b>{name}</b>"
+page: Annotated[Template, "html"] = t"<p>{'<b>Hello</b>'} {42} {name.title()} {name.title()} {name} {name} {f'{name}'}</p>"
+body: Annotated[Template, "html"] = t"<div>{inner}{query}</div>"
+note: Annotated[Template, "markdown"] = t'# {"Hello"} {42}'
+by_id: Annotated[Template, "sql"] = t"SELECT * FROM users WHERE id = {42}"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "page.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            ("interpolation-duplicate-expression", 7, 80),
+            ("interpolation-language-mismatch", 8, 52),
+            ("interpolation-constant-literal", 9, 46),
+            ("interpolation-constant-literal", 9, 56),
+        ]
+    );
+    assert_eq!(
+        json["diagnostics"][1]["message"],
+        "Interpolation 'query' is a sql template but is interpolated into text content of a html template"
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_nested_templates_by_markup_position() {
+    let dir = test_dir("nested-template-positions");
+    write_file(
+        &dir.join("page.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+name = "Ada"
+inner: Annotated[Template, "html"] = t"<b>{name}</b>"
+styles: Annotated[Template, "css"] = t"color: {name};"
+handler: Annotated[Template, "javascript"] = t"alert({name})"
+data: Annotated[Template, "json"] = t'{{"name": {name}}}'
+page: Annotated[Template, "html"] = t"""<main title="{inner}">{inner}{styles}<p style="{styles}" onclick="{handler}"></p></main>"""
+doc: Annotated[Template, "tdom"] = t"""<main>{inner}<script>{handler}</script><script type="application/json">{data}</script><style>{handler}</style></main>"""
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "page.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["expected_type"].as_str().unwrap(),
+                diagnostic["found_type"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            ("interpolation-language-mismatch", "str", "html"),
+            ("interpolation-language-mismatch", "html", "css"),
+            ("interpolation-language-mismatch", "css", "javascript"),
+        ]
+    );
+    assert_eq!(
+        json["diagnostics"][0]["message"],
+        "Interpolation 'inner' is a html template but is interpolated into the 'title' attribute of a html template"
+    );
+    assert_eq!(
+        json["diagnostics"][2]["message"],
+        "Interpolation 'handler' is a javascript template but is interpolated into a <style> element of a tdom template"
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_css_semantic_rules_with_interpolations_as_wildcards() {
+    let dir = test_dir("css-semantic-rules");
+    write_file(
+        &dir.join("styles.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+size = 4
+styles: Annotated[Template, "css"] = t"""
+.card {{ colr: red; color: red; display: flexx; width: {size}px; margin: 0 auto; color: #fff; }}
+.empty {{ }}
+@tailwind base;
+"""
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "styles.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["severity"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            ("css-unknown-property", "error", 6, 10),
+            ("css-invalid-value", "error", 6, 42),
+            ("css-duplicate-property", "warning", 6, 82),
+            ("css-empty-ruleset", "warning", 7, 1),
+            ("css-unknown-at-rule", "error", 8, 1),
+        ]
+    );
+    assert_eq!(
+        json["diagnostics"][0]["message"],
+        "Unknown CSS property 'colr'. Did you mean 'color'?"
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_javascript_semantic_rules_and_honours_ignores() {
+    let dir = test_dir("javascript-semantic-rules");
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter]\nignore = [\"javascript-debugger\"]\n",
+    );
+    write_file(
+        &dir.join("script.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+user = "ada"
+script: Annotated[Template, "javascript"] = t"""
+const greeting = "Hello {user}";
+if (greeting == other) {{ debugger; }}
+eval(greeting);
+total = 1;
+"""
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "script.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["severity"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            ("javascript-interpolation-in-string", "error", 6, 26),
+            ("javascript-loose-equality", "warning", 7, 14),
+            ("javascript-eval", "error", 8, 1),
+            ("javascript-undeclared-assignment", "warning", 9, 1),
+        ]
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_sql_interpolations_in_identifier_and_keyword_positions() {
+    let dir = test_dir("sql-injection-position");
+    write_file(
+        &dir.join("query.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+table = "users"
+direction = "DESC"
+user_id = 1
+query: Annotated[Template, "sql"] = t"SELECT * FROM {table} WHERE id = {user_id} ORDER BY name {direction} LIMIT {user_id}"
+safe: Annotated[Template, "sql"] = t"SELECT * FROM users WHERE id IN ({user_id}, {user_id})"
+spec_without_psycopg: Annotated[Template, "sql"] = t"SELECT * FROM users ORDER BY {table:i}"
+commented: Annotated[Template, "sql"] = t"SELECT * FROM users /* {table} */ -- ORDER BY name {direction}"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "query.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["found_type"].as_str().unwrap_or_default(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            ("sql-injection-position", "table name"),
+            ("sql-injection-position", "ORDER BY direction"),
+            ("sql-injection-position", "LIMIT"),
+            ("sql-injection-position", "ORDER BY column"),
+        ]
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_parses_sql_templates_with_configured_and_profile_dialects() {
+    let dir = test_dir("sql-dialect");
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.sql]\ndialect = \"sqlite\"\n",
+    );
+    write_file(
+        &dir.join("query.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+name = "ada"
+sqlite: Annotated[Template, "sql"] = t"INSERT OR REPLACE INTO users (name) VALUES (:name), ({name})"
+page: Annotated[Template, "sql"] = t"SELECT * FROM users WHERE name = ?1 LIMIT 5, 10"
+upsert: Annotated[Template, "sql", "postgres"] = t"INSERT INTO users (name) VALUES ({name}) ON CONFLICT (name) DO UPDATE SET name = excluded.name RETURNING id"
+generic: Annotated[Template, "sql", "generic"] = t"INSERT INTO users (name) VALUES ({name}) ON CONFLICT (name) DO NOTHING"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "query.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(found, vec![("embedded-parse-error", 8)]);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_xml_well_formedness_and_namespace_errors() {
+    let dir = test_dir("xml-check");
+    write_file(
+        &dir.join("feeds.py"),
+        r##"from typing import Annotated
+from string.templatelib import Template
+
+url = "https://example.com"
+sitemap: Annotated[Template, "xml"] = t'<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>{url}</loc></url></urlset>'
+soap: Annotated[Template, "xml"] = t"<soap:Envelope><soap:Body/></soap:Envelope>"
+feed: Annotated[Template, "xml"] = t"<rss><channel></rss>"
+icon: Annotated[Template, "svg"] = t'<svg><use xlink:href="#{url}"/></svg>'
+"##,
+    );
+
+    let output = run_check(&dir, &["check", "feeds.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["message"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        found,
+        vec![
+            ("Namespace prefix `soap` is not declared.", 6, 39),
+            ("Closing tag `</rss>` does not match `<channel>`.", 7, 52),
+        ]
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_graphql_errors_inline_values_and_unknown_schema_fields() {
+    let dir = test_dir("graphql-check");
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.graphql]\nschema = \"schema.graphql\"\n",
+    );
+    write_file(
+        &dir.join("schema.graphql"),
+        "type Query { user(id: ID!): User }\ntype User { id: ID! email: String }\n",
+    );
+    write_file(
+        &dir.join("api.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+user_id = 1
+query: Annotated[Template, "graphql"] = t"query User($id: ID!) {{ user(id: $id) {{ emial }} other: user(id: {user_id}) {{ id }} }}"
+broken: Annotated[Template, "gql"] = t"{{ user(id: $id) {{ id }} }}"
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "api.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        found,
+        vec![
+            ("graphql-unknown-field", 5, 84),
+            ("graphql-interpolated-value", 5, 110),
+            ("embedded-parse-error", 6, 52),
+        ]
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_markdown_structure_and_unescaped_link_urls() {
+    let dir = test_dir("markdown-check");
+    write_file(
+        &dir.join("notify.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+from urllib.parse import quote
+
+name = "x"
+url = "https://example.com"
+note: Annotated[Template, "md"] = t"""
+# Release {name}
+
+Read [the guide] (https://example.com/guide) and **bold text.
+
+| a | b |
+|---|---|
+| 1 | 2 | 3 |
+
+[home]({url}) [profile](https://example.com/u/{quote(name)})
+"""
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "notify.py", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
 
-    assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 1);
     assert_eq!(
-        json["diagnostics"][0]["rule"],
-        "template-schema-missing-key"
-    );
-    assert!(
-        json["diagnostics"][0]["message"]
-            .as_str()
-            .unwrap()
-            .contains("'id'")
+        found,
+        vec![
+            ("markdown-broken-link", 10, 6),
+            ("markdown-unbalanced-emphasis", 10, 50),
+            ("markdown-table-row", 14, 1),
+            ("markdown-interpolated-url", 16, 9),
+        ]
     );
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_suppresses_json_schema_missing_key_when_key_is_interpolated() {
-    let dir = test_dir("json-schema-interpolated-key");
+fn check_reports_shell_interpolations_by_position() {
+    let dir = test_dir("shell-check");
     write_file(
-        &dir.join("ok.py"),
-        r#"from typing import Annotated, TypedDict
+        &dir.join("deploy.py"),
+        r#"import shlex
+from typing import Annotated
 from string.templatelib import Template
-from json_tstring import Json
-
-class Order(TypedDict):
-    id: int
 
-payload: Annotated[Template, Json(schema=Order)] = t'{{{key}: 1}}'
+host = branch = tool = "x"
+deploy: Annotated[Template, "sh"] = t"""
+git checkout {shlex.quote(branch)}
+scp "dist/{branch}.tar" {host}:/srv/ && {tool} restart
+"""
+broken: Annotated[Template, "bash"] = t"if true; then echo hi"
 "#,
     );
 
-    let output = run_check(&dir, &["check", "ok.py", "--format", "json"]);
+    let output = run_check(&dir, &["check", "deploy.py", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
 
-    assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 0);
+    assert_eq!(
+        found,
+        vec![
+            ("shell-quoted-interpolation", 8, 12),
+            ("shell-unquoted-interpolation", 8, 26),
+            ("shell-interpolated-command", 8, 42),
+            ("embedded-parse-error", 10, 41),
+        ]
+    );
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_reports_yaml_plain_scalars_via_imported_function_annotation() {
-    let dir = test_dir("yaml-imported-function");
+fn check_reports_regex_syntax_errors_and_unescaped_interpolations() {
+    let dir = test_dir("regex-check");
     write_file(
-        &dir.join("typed_api.py"),
-        r#"from typing import Annotated
+        &dir.join("patterns.py"),
+        r#"import re
+from typing import Annotated
 from string.templatelib import Template
 
-def render_data(template: Annotated[Template, "yaml"]) -> object:
-    return {"ok": True}
-"#,
-    );
-    write_file(
-        &dir.join("broken.py"),
-        r#"from typed_api import render_data as render_yaml_data
-
-name = "api"
-replicas = 3
-
-yaml_template = t"""
-service:
-  name: {name}
-  replicas: fdsa fff {replicas}
-"""
-
-render_yaml_data(yaml_template)
+prefix = suffix = "x"
+ticket: Annotated[Template, "regex"] = rt"""^{prefix}-(?P<id>\d+)\.{re.escape(suffix)}$"""
+broken: Annotated[Template, "re"] = rt"(?P<id>\d+)(?P=name)"
 "#,
     );
 
-    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let output = run_check(&dir, &["check", "patterns.py", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["message"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
 
-    assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(found.len(), 2);
     assert_eq!(
-        json["diagnostics"][0]["message"],
-        "Quote YAML plain scalars that mix whitespace and interpolations."
+        (found[0].0, found[0].2, found[0].3),
+        ("regex-unescaped-interpolation", 6, 47)
+    );
+    assert_eq!(
+        found[1],
+        (
+            "embedded-parse-error",
+            "Invalid regex syntax in template string: unknown group name 'name'",
+            7,
+            55
+        )
     );
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_reports_yaml_plain_scalars_via_keyword_function_annotation() {
-    let dir = test_dir("yaml-keyword-function");
+fn check_runs_configured_language_plugin() {
+    let dir = test_dir("language-plugin");
     write_file(
-        &dir.join("typed_api.py"),
-        r#"from typing import Annotated
-from string.templatelib import Template
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.plugins.rules]\ncommand = [\"python3\", \"rules_plugin.py\"]\n\n[tool.t-linter.plugins.policy]\ncommand = [\"./missing-plugin\"]\n",
+    );
+    write_file(
+        &dir.join("rules_plugin.py"),
+        r#"import json, re, sys
 
-def render_data(template: Annotated[Template, "yaml"]) -> object:
-    return {"ok": True}
+for line in sys.stdin:
+    request = json.loads(line)
+    text = "".join(
+        s["text"] if s["type"] == "text" else "{}" for s in request["segments"]
+    ).encode()
+    diagnostics = [
+        {"rule": "rules-unknown-action", "message": "Unknown action " + m.group(1).decode(),
+         "start": m.start(1), "end": m.end(1)}
+        for m in re.finditer(rb"^ *(?!allow\b|deny\b)(\w+)", text, re.M)
+    ]
+    print(json.dumps({"id": request["id"], "diagnostics": diagnostics}), flush=True)
 "#,
     );
     write_file(
-        &dir.join("broken.py"),
-        r#"from typed_api import render_data as render_yaml_data
-
-name = "api"
-replicas = 3
+        &dir.join("acl.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
 
-yaml_template = t"""
-service:
-  name: {name}
-  replicas: fdsa fff {replicas}
+user = "ada"
+acl: Annotated[Template, "rules"] = t"""allow {user} read
+grant {user}
 """
-
-render_yaml_data(template=yaml_template)
+audit: Annotated[Template, "policy"] = t"log {user}"
 "#,
     );
 
-    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let output = run_check(&dir, &["check", "acl.py", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["severity"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+                diagnostic["start_column"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
 
-    assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 1);
     assert_eq!(
-        json["diagnostics"][0]["message"],
-        "Quote YAML plain scalars that mix whitespace and interpolations."
+        found,
+        vec![
+            ("rules-unknown-action", "error", 6, 1),
+            ("plugin-error", "error", 8, 40),
+        ]
+    );
+    assert!(
+        json["diagnostics"][1]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Language plugin for 'policy' failed")
     );
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_psycopg_sql_rules_are_silent_without_sql_config() {
-    let dir = test_dir("psycopg-silent-without-config");
+fn check_reports_unsupported_sql_profiles() {
+    let dir = test_dir("sql-dialect-unsupported-profile");
     write_file(
         &dir.join("query.py"),
         r#"from typing import Annotated
 from string.templatelib import Template
 
-user_id = 1
-query: Annotated[Template, "sql"] = t"SELECT * FROM users WHERE id = {user_id!r}"
+oracle: Annotated[Template, "sql", "profile:oracle"] = t"SELECT * FROM users"
+lite: Annotated[Template, "sql", "profile:sqlite"] = t"SELECT * FROM users"
 "#,
     );
 
     let output = run_check(&dir, &["check", "query.py", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let diagnostics = json["diagnostics"].as_array().unwrap();
 
-    assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 0);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["rule"], "embedded-parse-error");
+    assert_eq!(diagnostics[0]["language"], "sql");
+    assert_eq!(diagnostics[0]["start_line"], 4);
+    assert!(
+        diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Unsupported SQL profile \"oracle\"")
+    );
 
     let _ = fs::remove_dir_all(dir);
 }
 
 #[test]
-fn check_psycopg_sql_rules_run_for_inferred_execute_templates() {
-    let dir = test_dir("psycopg-inferred-execute");
+fn check_fix_adds_psycopg_identifier_spec_for_table_interpolation() {
+    let dir = test_dir("sql-injection-position-fix");
     write_file(
-        &dir.join("query.py"),
-        r#"import psycopg
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.sql]\nlibrary = \"psycopg\"\n",
+    );
+    let path = dir.join("query.py");
+    write_file(
+        &path,
+        r#"from typing import Annotated
+from string.templatelib import Template
 
-conn = psycopg.connect("dbname=app")
-cur = conn.cursor()
-cur.execute(t"SELECT * FROM users WHERE id = {user_id!r}")
+table = "users"
+user_id = 1
+query: Annotated[Template, "sql"] = t"SELECT * FROM {table} WHERE id = {user_id}"
 "#,
     );
 
-    let output = run_check(&dir, &["check", "query.py", "--format", "json"]);
+    let output = run_check(&dir, &["check", "query.py", "--fix", "--format", "json"]);
     let stdout = String::from_utf8(output.stdout).unwrap();
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let fixed = fs::read_to_string(&path).unwrap();
 
     assert_eq!(output.status.code(), Some(0));
-    assert_eq!(json["summary"]["diagnostics"], 1);
-    assert_eq!(json["diagnostics"][0]["rule"], "sql-conversion-unsupported");
-    assert_eq!(json["diagnostics"][0]["language"], "sql");
+    assert_eq!(json["summary"]["diagnostics"], 0);
+    assert!(fixed.contains("SELECT * FROM {table:i} WHERE id = {user_id}"));
 
     let _ = fs::remove_dir_all(dir);
 }
@@ -2505,6 +3812,229 @@ other: Annotated[Template, "html"] = t"<div><"
     let _ = fs::remove_dir_all(dir);
 }
 
+#[test]
+fn check_reports_thtml_component_children_and_tag_form() {
+    let dir = test_dir("thtml-component-children");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+def Icon(*, name: str) -> object:
+    return None
+
+def Card(*, title: str, children: str) -> Template:
+    return t"<section>{children}</section>"
+
+def Badge(*, tone: str) -> None:
+    return None
+
+template: Annotated[Template, "thtml"] = t"""
+<Icon name="x">label</Icon>
+<Icon name="y"></Icon>
+<Card title="Empty" />
+<Card title="Full"><Icon name="z" /></Card>
+<Badge tone="info" />
+"""
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["severity"].as_str().unwrap(),
+                diagnostic["start_line"].as_u64().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            ("component-unexpected-children", "error", 14),
+            ("component-self-closing", "warning", 15),
+            ("component-missing-children", "error", 16),
+            ("component-return-type", "warning", 18),
+        ]
+    );
+    assert_eq!(
+        json["diagnostics"][3]["message"],
+        "Component 'Badge' returns 'None', which is not a Template or Node the renderer can insert."
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_tdom_children_for_component_without_children_parameter() {
+    let dir = test_dir("tdom-component-children");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from tdom import html
+
+def Icon(*, name: str) -> object:
+    return None
+
+def Layout(**props: object) -> object:
+    return None
+
+page = html(t"<{Icon} name='x'><span>label</span></{Icon}><{Layout}><p>ok</p></{Layout}>")
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(json["summary"]["diagnostics"], 1);
+    assert_eq!(json["diagnostics"][0]["language"], "tdom");
+    assert_eq!(
+        json["diagnostics"][0]["rule"],
+        "component-unexpected-children"
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_component_props_outside_literal_and_enum_choices() {
+    let dir = test_dir("component-prop-choices");
+    write_file(
+        &dir.join("broken.py"),
+        r#"from enum import StrEnum
+from typing import Annotated, Literal
+from string.templatelib import Template
+from tdom import html
+
+class Size(StrEnum):
+    SMALL = "small"
+    LARGE = "large"
+
+def Button(*, tone: Literal["primary", "secondary"], size: Size = Size.SMALL) -> Template:
+    return t""
+
+template: Annotated[Template, "thtml"] = t'<Button tone="primery" size="large" />'
+page = html(t"<{Button} tone='primary' size='tiny' />")
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["language"].as_str().unwrap(),
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["message"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            (
+                "thtml",
+                "component-prop-type-error",
+                "Component 'Button' prop 'tone' expects 'primary' | 'secondary', but got 'primery'. Did you mean 'primary'?",
+            ),
+            (
+                "tdom",
+                "component-prop-type-error",
+                "Component 'Button' prop 'size' expects 'small' | 'large', but got 'tiny'.",
+            ),
+        ]
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn check_reports_component_props_from_unpacked_typed_dict() {
+    let dir = test_dir("component-unpack-typed-dict");
+    write_file(
+        &dir.join("props.py"),
+        r#"from typing import NotRequired, TypedDict
+
+class ButtonProps(TypedDict):
+    label: str
+    tone: NotRequired[str]
+"#,
+    );
+    write_file(
+        &dir.join("broken.py"),
+        r#"from typing import Annotated, TypedDict, Unpack
+from string.templatelib import Template
+from tdom import html
+
+from props import ButtonProps
+
+class CardProps(TypedDict, total=False):
+    title: str
+    count: int
+
+def Card(**props: Unpack[CardProps]) -> Template:
+    return t""
+
+def Button(**props: Unpack[ButtonProps]) -> Template:
+    return t""
+
+template: Annotated[Template, "thtml"] = t"<Card title='x' bogus='y' /><Button tone='info' />"
+page = html(t"<{Card} count='3' />")
+"#,
+    );
+
+    let output = run_check(&dir, &["check", "broken.py", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let found = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic["rule"].as_str().unwrap(),
+                diagnostic["message"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        found,
+        vec![
+            (
+                "component-unexpected-prop",
+                "Component 'Card' does not accept prop 'bogus'."
+            ),
+            (
+                "component-missing-prop",
+                "Component 'Button' is missing required prop 'label'."
+            ),
+            (
+                "component-prop-type-error",
+                "Component 'Card' prop 'count' expects int, but this tdom attribute resolves to a string-like value."
+            ),
+        ]
+    );
+
+    let _ = fs::remove_dir_all(dir);
+}
+
 #[test]
 fn check_reports_thtml_component_prop_type_errors() {
     let dir = test_dir("thtml-prop-type");
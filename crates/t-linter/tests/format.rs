@@ -379,6 +379,186 @@ markup: Annotated[Template, "html"] = t'<div data-a="12345" data-b="67890"></div
     let _ = fs::remove_dir_all(dir);
 }
 
+#[test]
+fn format_sql_templates_with_pyproject_sql_settings() {
+    let dir = test_dir("sql-format");
+    let path = dir.join("example.py");
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter]\nline-length = 50\n\n[tool.t-linter.format.sql]\nkeyword-case = \"lower\"\nindent-width = 2\n",
+    );
+    write_file(
+        &path,
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+user_id = 1
+short: Annotated[Template, "sql"] = t"SELECT id FROM users WHERE id={user_id}"
+query: Annotated[Template, "sql", "postgres"] = t"""
+SELECT id, name, email, created_at, updated_at, last_login FROM users WHERE id = {user_id} AND name = %s ORDER BY name
+"""
+"#,
+    );
+
+    let first = run_t_linter(&dir, &["format", "example.py"], None);
+    let content = fs::read_to_string(&path).unwrap();
+    let second = run_t_linter(&dir, &["format", "--check", "example.py"], None);
+
+    assert_eq!(first.status.code(), Some(0));
+    assert_eq!(second.status.code(), Some(0));
+    assert!(content.contains(r#"t"select id from users where id = {user_id}""#));
+    assert!(content.contains(
+        "t\"\"\"\nselect\n  id,\n  name,\n  email,\n  created_at,\n  updated_at,\n  last_login\nfrom users\nwhere id = {user_id} and name = %s\norder by name\n\"\"\""
+    ));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn format_css_templates_with_pyproject_css_settings() {
+    let dir = test_dir("css-format");
+    let path = dir.join("example.py");
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.format.css]\nindent-width = 4\nquote-style = \"single\"\ndeclaration-order = \"alphabetical\"\n",
+    );
+    write_file(
+        &path,
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+name = "card"
+color = "red"
+inline: Annotated[Template, "css"] = t"color:{color};margin : 0"
+styles: Annotated[Template, "css"] = t"""
+.{name}>h1{{margin:0;font-family:"Inter";color:{color}}}
+"""
+"#,
+    );
+
+    let first = run_t_linter(&dir, &["format", "example.py"], None);
+    let content = fs::read_to_string(&path).unwrap();
+    let second = run_t_linter(&dir, &["format", "--check", "example.py"], None);
+
+    assert_eq!(first.status.code(), Some(0));
+    assert_eq!(second.status.code(), Some(0));
+    assert!(content.contains(r#"t"color: {color}; margin: 0""#));
+    assert!(content.contains(
+        "t\"\"\"\n.{name} > h1 {{\n    color: {color};\n    font-family: 'Inter';\n    margin: 0;\n}}\n\"\"\""
+    ));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn format_javascript_templates_and_refuse_standalone_interpolations() {
+    let dir = test_dir("javascript-format");
+    let path = dir.join("example.py");
+    let refused = dir.join("refused.py");
+    write_file(
+        &dir.join("pyproject.toml"),
+        "[tool.t-linter.format.javascript]\nsemicolons = \"as-needed\"\nquote-style = \"single\"\n",
+    );
+    write_file(
+        &path,
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+user = "ada"
+script: Annotated[Template, "javascript"] = t"""
+if(ready){{greet("hi",{user});}}
+"""
+"#,
+    );
+    let refused_source = r#"from typing import Annotated
+from string.templatelib import Template
+
+body = "run()"
+script: Annotated[Template, "javascript"] = t"""
+init();
+{body}
+"""
+"#;
+    write_file(&refused, refused_source);
+
+    let first = run_t_linter(&dir, &["format", "example.py"], None);
+    let content = fs::read_to_string(&path).unwrap();
+    let second = run_t_linter(&dir, &["format", "--check", "example.py"], None);
+    let failed = run_t_linter(&dir, &["format", "refused.py"], None);
+    let failed_stderr = String::from_utf8(failed.stderr).unwrap();
+
+    assert_eq!(first.status.code(), Some(0));
+    assert_eq!(second.status.code(), Some(0));
+    assert!(content.contains("t\"\"\"\nif (ready) {{\n  greet('hi', {user})\n}}\n\"\"\""));
+    assert_ne!(failed.status.code(), Some(0));
+    assert!(failed_stderr.contains("refused.py:7:1: Interpolation '{body}' stands alone"));
+    assert_eq!(fs::read_to_string(&refused).unwrap(), refused_source);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn format_xml_and_svg_templates() {
+    let dir = test_dir("xml-format");
+    let path = dir.join("example.py");
+    write_file(
+        &path,
+        r##"from typing import Annotated
+from string.templatelib import Template
+
+title = "News"
+icon = "star"
+feed: Annotated[Template, "xml"] = t"""
+<?xml version="1.0"?>
+<rss version="2.0"><channel><title>{title}</title><description>Daily  news</description></channel></rss>
+"""
+badge: Annotated[Template, "svg"] = t'<svg viewBox="0 0 10 10"><use xlink:href="#{icon}"/></svg>'
+"##,
+    );
+
+    let first = run_t_linter(&dir, &["format", "example.py"], None);
+    let content = fs::read_to_string(&path).unwrap();
+    let second = run_t_linter(&dir, &["format", "--check", "example.py"], None);
+
+    assert_eq!(first.status.code(), Some(0));
+    assert_eq!(second.status.code(), Some(0));
+    assert!(content.contains(
+        "t\"\"\"\n<?xml version=\"1.0\"?>\n<rss version=\"2.0\">\n  <channel>\n    <title>{title}</title>\n    <description>Daily  news</description>\n  </channel>\n</rss>\n\"\"\""
+    ));
+    assert!(content.contains(
+        "t\"\"\"<svg viewBox=\"0 0 10 10\">\n  <use xlink:href=\"#{icon}\"/>\n</svg>\"\"\""
+    ));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn format_graphql_templates() {
+    let dir = test_dir("graphql-format");
+    let path = dir.join("example.py");
+    write_file(
+        &path,
+        r#"from typing import Annotated
+from string.templatelib import Template
+
+fields = "id"
+query: Annotated[Template, "graphql"] = t"query User($id:ID!){{user(id:$id){{name,...on Admin{{level}} {fields}}}}}"
+"#,
+    );
+
+    let first = run_t_linter(&dir, &["format", "example.py"], None);
+    let content = fs::read_to_string(&path).unwrap();
+    let second = run_t_linter(&dir, &["format", "--check", "example.py"], None);
+
+    assert_eq!(first.status.code(), Some(0));
+    assert_eq!(second.status.code(), Some(0));
+    assert!(content.contains(
+        "t\"\"\"query User($id: ID!) {{\n  user(id: $id) {{\n    name\n    ... on Admin {{\n      level\n    }}\n    {fields}\n  }}\n}}\"\"\""
+    ));
+
+    let _ = fs::remove_dir_all(dir);
+}
+
 #[test]
 fn format_cli_line_length_overrides_pyproject() {
     let dir = test_dir("cli-line-length");